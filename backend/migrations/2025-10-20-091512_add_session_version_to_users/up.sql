@@ -0,0 +1,2 @@
+-- Incremented whenever existing sessions for a user must be invalidated
+ALTER TABLE users ADD COLUMN session_version INTEGER NOT NULL DEFAULT 0;
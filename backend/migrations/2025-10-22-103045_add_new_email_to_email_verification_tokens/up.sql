@@ -0,0 +1,2 @@
+-- Pending address for email-change tokens; NULL for signup verification tokens
+ALTER TABLE email_verification_tokens ADD COLUMN new_email TEXT;
@@ -0,0 +1,7 @@
+ALTER TABLE users ADD COLUMN auth_provider VARCHAR(50) NOT NULL DEFAULT 'email';
+
+UPDATE users
+SET auth_provider = CASE WHEN password_hash IS NULL THEN 'google' ELSE 'both' END
+WHERE id IN (SELECT user_id FROM user_identities WHERE provider = 'google');
+
+DROP TABLE user_identities;
@@ -0,0 +1,2 @@
+-- Remove passwordless login links
+DROP TABLE magic_link_tokens;
@@ -0,0 +1,4 @@
+-- Remove the OAuth2 authorization server tables
+DROP TABLE oauth_tokens;
+DROP TABLE oauth_authorization_codes;
+DROP TABLE oauth_clients;
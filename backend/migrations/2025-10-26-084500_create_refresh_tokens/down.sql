@@ -0,0 +1,2 @@
+-- Remove rotating refresh tokens
+DROP TABLE refresh_tokens;
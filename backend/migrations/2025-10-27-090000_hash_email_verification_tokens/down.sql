@@ -0,0 +1,5 @@
+-- Hashed tokens cannot be turned back into links, so outstanding ones are dropped
+DELETE FROM email_verification_tokens;
+ALTER INDEX idx_email_verification_tokens_token_hash RENAME TO idx_email_verification_tokens_token;
+ALTER TABLE email_verification_tokens ALTER COLUMN token_hash TYPE VARCHAR(255);
+ALTER TABLE email_verification_tokens RENAME COLUMN token_hash TO token;
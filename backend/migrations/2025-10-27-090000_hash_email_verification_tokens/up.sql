@@ -0,0 +1,6 @@
+-- Store only SHA-256 hashes of verification and email change tokens. Existing
+-- rows are hashed in place so links already sent keep working.
+UPDATE email_verification_tokens SET token = encode(sha256(convert_to(token, 'UTF8')), 'hex');
+ALTER TABLE email_verification_tokens RENAME COLUMN token TO token_hash;
+ALTER TABLE email_verification_tokens ALTER COLUMN token_hash TYPE VARCHAR(64);
+ALTER INDEX idx_email_verification_tokens_token RENAME TO idx_email_verification_tokens_token_hash;
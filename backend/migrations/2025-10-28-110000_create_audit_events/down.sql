@@ -0,0 +1,3 @@
+-- Remove the security audit log
+DROP TABLE audit_events;
+DROP FUNCTION reject_audit_event_changes();
@@ -0,0 +1,26 @@
+-- Append-only security audit log. user_id is deliberately not a foreign key so
+-- history survives account deletion.
+CREATE TABLE audit_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID,
+    actor_id UUID,
+    event_type VARCHAR(64) NOT NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- id breaks ties between events logged in the same instant when paging
+CREATE INDEX idx_audit_events_user_id_created_at ON audit_events(user_id, created_at DESC, id DESC);
+CREATE INDEX idx_audit_events_event_type_created_at ON audit_events(event_type, created_at DESC, id DESC);
+
+CREATE FUNCTION reject_audit_event_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+    BEFORE UPDATE OR DELETE ON audit_events
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_event_changes();
@@ -0,0 +1,2 @@
+-- Drop the known devices used for new-device login alerts
+DROP TABLE known_devices;
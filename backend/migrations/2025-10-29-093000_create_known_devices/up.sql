@@ -0,0 +1,16 @@
+-- Devices each user has signed in from, identified by a hash of the user agent
+-- and the client network (IPv4 /24, IPv6 /64). A sign-in from a device not in
+-- this table triggers an alert email whose "this wasn't me" link is stored
+-- here as a hash.
+CREATE TABLE known_devices (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    fingerprint VARCHAR(64) NOT NULL,
+    user_agent TEXT,
+    ip_address TEXT,
+    deny_token_hash VARCHAR(64) UNIQUE,
+    deny_token_expires_at TIMESTAMP,
+    first_seen_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, fingerprint)
+);
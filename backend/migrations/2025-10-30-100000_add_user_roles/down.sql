@@ -0,0 +1,5 @@
+-- Remove account roles and suspension
+DROP INDEX idx_users_role;
+ALTER TABLE users
+    DROP COLUMN suspended_at,
+    DROP COLUMN role;
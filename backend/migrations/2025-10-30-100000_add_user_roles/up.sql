@@ -0,0 +1,13 @@
+-- Account roles and suspension. Promote the first admin by hand with
+--   UPDATE users SET role = 'admin' WHERE email = '...';
+-- after which admins manage roles through /api/admin.
+ALTER TABLE users
+    ADD COLUMN role VARCHAR(16) NOT NULL DEFAULT 'user'
+        CHECK (role IN ('user', 'creator', 'admin')),
+    ADD COLUMN suspended_at TIMESTAMP;
+
+-- Everyone who already runs a series is a creator
+UPDATE users SET role = 'creator'
+WHERE id IN (SELECT user_id FROM series WHERE deleted_at IS NULL);
+
+CREATE INDEX idx_users_role ON users(role) WHERE role <> 'user';
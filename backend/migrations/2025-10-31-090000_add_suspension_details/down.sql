@@ -0,0 +1,5 @@
+-- Remove suspension reasons and expiry
+ALTER TABLE users
+    DROP CONSTRAINT users_suspension_details_check,
+    DROP COLUMN suspended_until,
+    DROP COLUMN suspension_reason;
@@ -0,0 +1,7 @@
+-- Why an account was suspended and, for temporary suspensions, when it ends.
+-- A suspension whose suspended_until has passed no longer applies.
+ALTER TABLE users
+    ADD COLUMN suspension_reason TEXT,
+    ADD COLUMN suspended_until TIMESTAMP,
+    ADD CONSTRAINT users_suspension_details_check
+        CHECK (suspended_at IS NOT NULL OR (suspension_reason IS NULL AND suspended_until IS NULL));
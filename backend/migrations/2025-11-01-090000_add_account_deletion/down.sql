@@ -0,0 +1,9 @@
+-- Remove scheduled account deletion
+CREATE OR REPLACE FUNCTION reject_audit_event_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP INDEX idx_users_deletion_scheduled_for;
+ALTER TABLE users DROP COLUMN deletion_scheduled_for;
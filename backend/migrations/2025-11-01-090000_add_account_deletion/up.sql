@@ -0,0 +1,29 @@
+-- Self-service account deletion. The account is purged once
+-- deletion_scheduled_for has passed unless the user cancels before then.
+ALTER TABLE users ADD COLUMN deletion_scheduled_for TIMESTAMP;
+
+CREATE INDEX idx_users_deletion_scheduled_for ON users(deletion_scheduled_for)
+    WHERE deletion_scheduled_for IS NOT NULL;
+
+-- Purging an account pseudonymizes its audit history instead of deleting it.
+-- Only a transaction that sets audit.pseudonymize may update audit rows, and
+-- only to clear the client address and user agent and to drop parts of the
+-- metadata. Rows still cannot be deleted.
+CREATE OR REPLACE FUNCTION reject_audit_event_changes() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND current_setting('audit.pseudonymize', true) = 'on'
+        AND NEW.id = OLD.id
+        AND NEW.user_id IS NOT DISTINCT FROM OLD.user_id
+        AND NEW.actor_id IS NOT DISTINCT FROM OLD.actor_id
+        AND NEW.event_type = OLD.event_type
+        AND NEW.created_at = OLD.created_at
+        AND NEW.ip_address IS NULL
+        AND NEW.user_agent IS NULL
+        AND OLD.metadata @> NEW.metadata
+    THEN
+        RETURN NEW;
+    END IF;
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
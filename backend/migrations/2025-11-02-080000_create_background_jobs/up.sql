@@ -0,0 +1,25 @@
+-- Queue of long-running work such as data exports, run by the server's job worker.
+-- output_key names a temporary object in the bucket that is removed once
+-- output_expires_at has passed.
+CREATE TABLE background_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'completed', 'failed')),
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    result JSONB,
+    error TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    output_key TEXT,
+    output_expires_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP,
+    finished_at TIMESTAMP
+);
+
+CREATE INDEX idx_background_jobs_user_id_created_at ON background_jobs(user_id, created_at DESC);
+CREATE INDEX idx_background_jobs_queue ON background_jobs(created_at)
+    WHERE status IN ('pending', 'running');
+CREATE INDEX idx_background_jobs_output_expires_at ON background_jobs(output_expires_at)
+    WHERE output_key IS NOT NULL;
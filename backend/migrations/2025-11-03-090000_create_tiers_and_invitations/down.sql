@@ -0,0 +1,4 @@
+-- Drop invitations, tier locks and tiers
+DROP TABLE IF EXISTS invitations;
+ALTER TABLE posts DROP COLUMN IF EXISTS min_tier_id;
+DROP TABLE IF EXISTS tiers;
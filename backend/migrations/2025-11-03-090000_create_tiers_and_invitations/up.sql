@@ -0,0 +1,39 @@
+-- Membership tiers a creator offers, tier locks on posts, and invitations
+-- for members brought over from other platforms. Tiers are ordered by
+-- amount_cents: a post locked to a tier is open to that tier and dearer ones.
+CREATE TABLE tiers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    amount_cents INTEGER NOT NULL DEFAULT 0 CHECK (amount_cents >= 0),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT unique_user_tier_name UNIQUE(user_id, name)
+);
+
+CREATE INDEX idx_tiers_user_id_amount ON tiers(user_id, amount_cents);
+
+-- Deleting a tier must not quietly make the posts locked to it public, so
+-- locks are moved or removed explicitly before a tier can go.
+ALTER TABLE posts ADD COLUMN min_tier_id UUID REFERENCES tiers(id) ON DELETE RESTRICT;
+
+CREATE INDEX idx_posts_min_tier_id ON posts(min_tier_id) WHERE min_tier_id IS NOT NULL;
+
+CREATE TABLE invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    creator_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tier_id UUID REFERENCES tiers(id) ON DELETE SET NULL,
+    email VARCHAR(255) NOT NULL,
+    name VARCHAR(255),
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'revoked')),
+    source VARCHAR(32) NOT NULL,
+    external_id VARCHAR(64),
+    metadata JSONB,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT unique_creator_invitation_email UNIQUE(creator_id, email)
+);
+
+CREATE INDEX idx_invitations_creator_id_status ON invitations(creator_id, status);
@@ -0,0 +1,2 @@
+-- Drop post pages
+DROP TABLE IF EXISTS post_pages;
@@ -0,0 +1,13 @@
+-- Ordered page images of multi-page posts, such as the pages of a webcomic
+-- episode. Pages are replaced as a whole, so positions run from 1 without gaps.
+CREATE TABLE post_pages (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    file_id UUID NOT NULL REFERENCES user_files(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL CHECK (position >= 1),
+    alt_text TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT unique_post_page_position UNIQUE(post_id, position)
+);
+
+CREATE INDEX idx_post_pages_file_id ON post_pages(file_id);
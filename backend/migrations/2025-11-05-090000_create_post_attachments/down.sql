@@ -0,0 +1,2 @@
+-- Drop post attachments
+DROP TABLE IF EXISTS post_attachments;
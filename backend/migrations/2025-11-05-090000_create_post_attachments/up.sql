@@ -0,0 +1,20 @@
+-- Files attached to posts beyond their audio and video, such as PDFs, bonus
+-- art, source files and stems. An attachment without min_tier_id is public;
+-- otherwise it is open to that tier and dearer ones, like a locked post, and
+-- its tier cannot be deleted while the lock is in place.
+-- Attachments are moved one at a time, so positions run from 1 without gaps
+-- but are not unique while they shift.
+CREATE TABLE post_attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    file_id UUID NOT NULL REFERENCES user_files(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL CHECK (position >= 1),
+    caption TEXT,
+    min_tier_id UUID REFERENCES tiers(id) ON DELETE RESTRICT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_post_attachments_post_id_position ON post_attachments(post_id, position);
+CREATE INDEX idx_post_attachments_file_id ON post_attachments(file_id);
+CREATE INDEX idx_post_attachments_min_tier_id ON post_attachments(min_tier_id) WHERE min_tier_id IS NOT NULL;
@@ -0,0 +1,171 @@
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::{header, Method},
+    middleware::Next,
+    web, Error, HttpResponse,
+};
+use shared::{errors::ErrorResponse, services::config::ConfigService};
+
+/// Name of the session cookie set by `SessionMiddleware`
+pub const SESSION_COOKIE_NAME: &str = "session_id";
+
+/// Reject cross-site state-changing requests authenticated by the session cookie
+///
+/// Browsers attach the session cookie to requests from any site, so unsafe
+/// methods carrying it must come from an allowed origin, judged by the
+/// `Origin` header or, failing that, the `Referer`. Requests without the
+/// cookie (bearer tokens, API keys, webhooks) are not affected since a
+/// foreign page cannot make the browser add those credentials.
+///
+/// # Errors
+/// Returns an error if the wrapped service fails.
+pub async fn csrf_protection<B: MessageBody + 'static>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    if is_safe_method(req.method()) || req.cookie(SESSION_COOKIE_NAME).is_none() {
+        return next
+            .call(req)
+            .await
+            .map(ServiceResponse::map_into_boxed_body);
+    }
+
+    let allowed = req
+        .app_data::<web::Data<ConfigService>>()
+        .is_some_and(|config| is_allowed_origin(&req, &config.cors_allowed_origins));
+
+    if allowed {
+        return next
+            .call(req)
+            .await
+            .map(ServiceResponse::map_into_boxed_body);
+    }
+
+    tracing::warn!(
+        "Rejected cross-site {} {} from origin {:?}",
+        req.method(),
+        req.path(),
+        request_origin(&req)
+    );
+    let response = HttpResponse::Forbidden().json(ErrorResponse {
+        error: "Cross-site request rejected".to_owned(),
+        code: Some("CSRF_REJECTED".to_owned()),
+    });
+    Ok(req.into_response(response))
+}
+
+/// Whether a method is defined as safe, i.e. must not change state
+const fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Whether the request was made from one of the allowed origins or the API's own
+///
+/// Same-origin requests come from the few pages the API serves itself, such as
+/// the confirmation page of a "this wasn't me" link. Origins are compared
+/// exactly, so a different scheme or port is another origin.
+fn is_allowed_origin(req: &ServiceRequest, allowed_origins: &[String]) -> bool {
+    let Some(origin) = request_origin(req) else {
+        return false;
+    };
+    let connection = req.connection_info();
+    allowed_origins.contains(&origin)
+        || origin == format!("{}://{}", connection.scheme(), connection.host())
+}
+
+/// Origin the request was made from, from `Origin` or else `Referer`
+fn request_origin(req: &ServiceRequest) -> Option<String> {
+    let headers = req.headers();
+    if let Some(origin) = headers
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+        .filter(|origin| *origin != "null")
+    {
+        return Some(origin.trim_end_matches('/').to_owned());
+    }
+
+    let referer = headers.get(header::REFERER)?.to_str().ok()?;
+    let url = reqwest::Url::parse(referer).ok()?;
+    Some(url.origin().ascii_serialization())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn allowed_origins() -> Vec<String> {
+        vec![
+            "https://app.example.com".to_owned(),
+            "http://localhost:5173".to_owned(),
+        ]
+    }
+
+    fn is_allowed(headers: &[(header::HeaderName, &str)]) -> bool {
+        let req = headers
+            .iter()
+            .fold(TestRequest::post(), |req, &(ref name, value)| {
+                req.insert_header((name.clone(), value))
+            })
+            .to_srv_request();
+        is_allowed_origin(&req, &allowed_origins())
+    }
+
+    #[test]
+    fn test_allowed_origin_is_accepted() {
+        assert!(is_allowed(&[(header::ORIGIN, "https://app.example.com")]));
+        assert!(is_allowed(&[(header::ORIGIN, "http://localhost:5173")]));
+    }
+
+    #[test]
+    fn test_missing_origin_and_referer_is_rejected() {
+        assert!(!is_allowed(&[]));
+        assert!(!is_allowed(&[(header::ORIGIN, "null")]));
+    }
+
+    #[test]
+    fn test_referer_is_used_without_origin() {
+        assert!(is_allowed(&[(
+            header::REFERER,
+            "https://app.example.com/settings/account?tab=security"
+        )]));
+        assert!(!is_allowed(&[(
+            header::REFERER,
+            "https://evil.example.net/https://app.example.com/"
+        )]));
+        assert!(!is_allowed(&[(header::REFERER, "not a url")]));
+    }
+
+    #[test]
+    fn test_origin_takes_precedence_over_referer() {
+        assert!(!is_allowed(&[
+            (header::ORIGIN, "https://evil.example.net"),
+            (header::REFERER, "https://app.example.com/"),
+        ]));
+    }
+
+    #[test]
+    fn test_same_origin_is_accepted() {
+        let req = TestRequest::post()
+            .insert_header((header::HOST, "api.example.com"))
+            .insert_header((header::ORIGIN, "http://api.example.com"))
+            .to_srv_request();
+        assert!(is_allowed_origin(&req, &allowed_origins()));
+    }
+
+    #[test]
+    fn test_scheme_and_port_must_match() {
+        assert!(!is_allowed(&[(header::ORIGIN, "http://app.example.com")]));
+        assert!(!is_allowed(&[(
+            header::ORIGIN,
+            "https://app.example.com:8443"
+        )]));
+        assert!(!is_allowed(&[(header::ORIGIN, "http://localhost:3000")]));
+        assert!(!is_allowed(&[(header::REFERER, "https://localhost:5173/")]));
+        assert!(!is_allowed(&[(
+            header::ORIGIN,
+            "https://app.example.com.evil.example.net"
+        )]));
+    }
+}
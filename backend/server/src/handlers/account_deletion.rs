@@ -0,0 +1,225 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        account_deletion::{AccountDeletionResponse, DeleteAccountRequest},
+        audit::AuditEventType,
+        auth::{load_password_hash, User},
+    },
+    services::{
+        account_deletion::ACCOUNT_DELETION_GRACE_DAYS,
+        audit::record_event,
+        auth::GoogleOAuthService,
+        db::DbService,
+        email::{EmailService, HtmlEmailContent},
+        user_cache::UserCacheService,
+    },
+};
+
+use super::auth::{json_error, verify_password};
+
+/// How recently an account without a password must have signed in to delete itself
+const REAUTH_WINDOW_MINUTES: i64 = 10;
+
+/// Helper function to tell the user when their account will be deleted and how to keep it
+async fn send_deletion_scheduled_email(
+    user: &User,
+    scheduled_for: NaiveDateTime,
+    email_service: &EmailService,
+    google_oauth_service: &GoogleOAuthService,
+) {
+    let deletion_date = scheduled_for.format("%Y-%m-%d %H:%M UTC");
+    let settings_link = format!("{}/settings", google_oauth_service.frontend_url);
+
+    let email_body = format!(
+        "<h1>Your account is scheduled for deletion</h1>
+        <p>You asked us to delete your Patron account. It will be deleted on {deletion_date}, together with its series, posts, files and API keys.</p>
+        <p>Changed your mind? <a href=\"{settings_link}\">Sign in and cancel the deletion</a> before then to keep your account.</p>
+        <p>If you did not ask for this, cancel the deletion and change your password immediately.</p>"
+    );
+
+    if let Err(e) = email_service
+        .send_html_email(HtmlEmailContent {
+            to: &user.email,
+            subject: "Your Patron account is scheduled for deletion",
+            html_body: &email_body,
+            text_body: None,
+            from: None,
+        })
+        .await
+    {
+        tracing::warn!("Failed to send account deletion notice: {}", e);
+    }
+}
+
+/// Helper function to build the response for a pending deletion
+fn deletion_scheduled_response(scheduled_for: NaiveDateTime) -> HttpResponse {
+    HttpResponse::Ok().json(AccountDeletionResponse {
+        message: format!(
+            "Your account will be deleted on {}. Cancel before then to keep it.",
+            scheduled_for.format("%Y-%m-%d")
+        ),
+        deletion_scheduled_for: Some(scheduled_for),
+    })
+}
+
+/// Request deletion of the signed-in account
+///
+/// Schedules the account to be purged after a grace period during which the
+/// user can cancel. The request is re-authenticated with the current password,
+/// or for accounts without one, by a sign-in within the last few minutes.
+/// Requesting again while a deletion is pending keeps the original date.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/auth/account/deletion",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    request_body(content = DeleteAccountRequest, description = "Current password"),
+    responses(
+        (status = 200, description = "Account scheduled for deletion", body = AccountDeletionResponse),
+        (status = 400, description = "Wrong password, or no recent sign-in for accounts without one", body = ErrorResponse,
+            example = json!({
+                "error": "Current password is incorrect"
+            })
+        ),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn request_account_deletion(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+    user_cache: web::Data<UserCacheService>,
+    email_service: web::Data<EmailService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    body: web::Json<DeleteAccountRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    let now = Utc::now().naive_utc();
+
+    if let Some(scheduled_for) = user.deletion_scheduled_for {
+        return Ok(deletion_scheduled_response(scheduled_for));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    if let Some(ref password_hash) = load_password_hash(&mut conn, user.id).await? {
+        let current_password = body.current_password.as_deref().unwrap_or_default();
+        if !verify_password(current_password, password_hash)? {
+            return Ok(json_error("Current password is incorrect"));
+        }
+    } else {
+        let signed_in_recently = user.last_login.is_some_and(|last_login| {
+            now.signed_duration_since(last_login) <= Duration::minutes(REAUTH_WINDOW_MINUTES)
+        });
+        if !signed_in_recently {
+            return Ok(json_error(
+                "Sign in again to confirm that you want to delete your account.",
+            ));
+        }
+    }
+
+    let scheduled_for = now
+        .checked_add_signed(Duration::days(ACCOUNT_DELETION_GRACE_DAYS))
+        .ok_or_else(|| ServiceError::Unknown("Failed to compute deletion date".to_owned()))?;
+
+    let _ = diesel::update(users_dsl::users.find(user.id))
+        .set(users_dsl::deletion_scheduled_for.eq(Some(scheduled_for)))
+        .execute(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    user_cache.invalidate(user.id).await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::AccountDeletionRequested,
+        json!({ "scheduledFor": scheduled_for.and_utc() }),
+    )
+    .await;
+
+    send_deletion_scheduled_email(&user, scheduled_for, &email_service, &google_oauth_service)
+        .await;
+
+    Ok(deletion_scheduled_response(scheduled_for))
+}
+
+/// Cancel a pending deletion of the signed-in account
+///
+/// # Errors
+/// Returns an error if the user is not authenticated, no deletion is pending,
+/// or database operations fail.
+#[utoipa::path(
+    delete,
+    path = "/auth/account/deletion",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Deletion canceled", body = AccountDeletionResponse),
+        (status = 400, description = "No deletion is pending", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn cancel_account_deletion(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+    user_cache: web::Data<UserCacheService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let canceled = diesel::update(
+        users_dsl::users
+            .filter(users_dsl::id.eq(user.id))
+            .filter(users_dsl::deletion_scheduled_for.is_not_null()),
+    )
+    .set(users_dsl::deletion_scheduled_for.eq(None::<NaiveDateTime>))
+    .execute(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    if canceled == 0 {
+        return Ok(json_error("Your account is not scheduled for deletion"));
+    }
+
+    user_cache.invalidate(user.id).await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::AccountDeletionCanceled,
+        json!({}),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(AccountDeletionResponse {
+        message: "Your account will not be deleted".to_owned(),
+        deletion_scheduled_for: None,
+    }))
+}
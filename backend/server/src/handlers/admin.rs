@@ -0,0 +1,958 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use redis::aio::ConnectionManager;
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        admin::{
+            AdminUpdateUserRequest, AdminUserDetailResponse, AdminUserResponse,
+            AdminUserSearchQuery, AdminUsersListResponse, ModerationQuery, SuspendUserRequest,
+        },
+        audit::{AuditEvent, AuditEventType, AuditEventsListResponse, AuditQuery},
+        auth::{User, UserRole},
+        posts::Post,
+        series::{Series, SeriesResponse},
+        user_files::{UserFile, UserFileInfo, UserFilesResponse},
+    },
+    services::{
+        audit::record_admin_action,
+        auth::GoogleOAuthService,
+        config::ConfigService,
+        db::DbService,
+        email::{EmailService, HtmlEmailContent},
+        series_length::refresh_series_length,
+        sessions::SessionService,
+        user_cache::UserCacheService,
+    },
+};
+use uuid::Uuid;
+
+use super::audit::{audit_page, DEFAULT_AUDIT_PAGE_SIZE, MAX_AUDIT_PAGE_SIZE};
+use super::auth::{hash_password, send_password_reset_email, store_password_reset_token};
+use super::sessions::sign_out_everywhere;
+use super::user_files::ListFilesQuery;
+
+/// Helper function to reject users who are not admins
+fn require_admin(user: &User) -> Result<(), ServiceError> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(ServiceError::Forbidden("Admin access required".to_owned()))
+    }
+}
+
+/// Helper function to load the account an admin action targets
+async fn find_user(conn: &mut AsyncPgConnection, user_id: Uuid) -> Result<User, ServiceError> {
+    use shared::schema::users::dsl as users_dsl;
+
+    users_dsl::users
+        .find(user_id)
+        .first::<User>(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| ServiceError::NotFound("User not found".to_owned()))
+}
+
+/// Helper function to turn a search term into a `LIKE` pattern matching it anywhere
+fn contains_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Search accounts
+///
+/// Matches the search term against email addresses and display names, newest
+/// accounts first.
+///
+/// # Errors
+/// Returns an error if the user is not an admin or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/admin/users",
+    context_path = "/api",
+    tag = "Admin",
+    params(AdminUserSearchQuery),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Matching accounts", body = AdminUsersListResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn search_users(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    query: web::Query<AdminUserSearchQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    require_admin(&admin)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let mut users_query = users_dsl::users
+        .order(users_dsl::created_at.desc())
+        .limit(limit)
+        .into_boxed();
+
+    if let Some(term) = query.q.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        let pattern = contains_pattern(term);
+        users_query = users_query.filter(
+            users_dsl::email
+                .ilike(pattern.clone())
+                .or(users_dsl::display_name.ilike(pattern)),
+        );
+    }
+    if let Some(role) = query.role {
+        users_query = users_query.filter(users_dsl::role.eq(role.as_str()));
+    }
+    if let Some(suspended) = query.suspended {
+        let now = Utc::now().naive_utc();
+        users_query = if suspended {
+            users_query.filter(
+                users_dsl::suspended_at.is_not_null().and(
+                    users_dsl::suspended_until
+                        .is_null()
+                        .or(users_dsl::suspended_until.gt(now)),
+                ),
+            )
+        } else {
+            users_query.filter(
+                users_dsl::suspended_at
+                    .is_null()
+                    .or(users_dsl::suspended_until.le(now)),
+            )
+        };
+    }
+    if let Some(offset_id) = query.offset {
+        let offset_created_at: chrono::NaiveDateTime = users_dsl::users
+            .find(offset_id)
+            .select(users_dsl::created_at)
+            .first::<Option<chrono::NaiveDateTime>>(&mut conn)
+            .await
+            .optional()
+            .map_err(ServiceError::from)?
+            .flatten()
+            .unwrap_or_else(|| Utc::now().naive_utc());
+
+        users_query = users_query.filter(users_dsl::created_at.lt(offset_created_at));
+    }
+
+    let users: Vec<User> = users_query
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        None,
+        AuditEventType::AdminUsersSearched,
+        json!({
+            "q": query.q,
+            "role": query.role,
+            "suspended": query.suspended,
+        }),
+    )
+    .await;
+
+    let response: Vec<AdminUserResponse> = users.into_iter().map(AdminUserResponse::from).collect();
+    Ok(HttpResponse::Ok().json(AdminUsersListResponse::from(response)))
+}
+
+/// View an account
+///
+/// Returns the account with the series it runs.
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the account does not exist,
+/// or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/admin/users/{user_id}",
+    context_path = "/api",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "ID of the account")),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Account details", body = AdminUserDetailResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn get_user(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::series::dsl as series_dsl;
+
+    require_admin(&admin)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let user = find_user(&mut conn, path.into_inner()).await?;
+
+    let series: Vec<Series> = series_dsl::series
+        .filter(series_dsl::user_id.eq(user.id))
+        .filter(series_dsl::deleted_at.is_null())
+        .order(series_dsl::created_at.desc())
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        Some(user.id),
+        AuditEventType::AdminUserViewed,
+        json!({}),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(AdminUserDetailResponse {
+        user: AdminUserResponse::from(user),
+        series: series.into_iter().map(SeriesResponse::from).collect(),
+    }))
+}
+
+/// Edit an account
+///
+/// Changes the display name, email address, verification status or role.
+/// Admins cannot change their own role.
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the account does not exist,
+/// the email address is taken, or database operations fail.
+#[utoipa::path(
+    put,
+    path = "/admin/users/{user_id}",
+    context_path = "/api",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "ID of the account")),
+    request_body(content = AdminUpdateUserRequest, description = "Fields to change"),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Account updated", body = AdminUserResponse),
+        (status = 400, description = "Invalid email address or own role change", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "Email address already in use", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn update_user(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    user_cache: web::Data<UserCacheService>,
+    path: web::Path<Uuid>,
+    body: web::Json<AdminUpdateUserRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    require_admin(&admin)?;
+
+    let user_id = path.into_inner();
+    if user_id == admin.id && body.role.is_some_and(|role| role != UserRole::Admin) {
+        return Err(ServiceError::Config("You cannot change your own role".to_owned()).into());
+    }
+
+    let email = body.email.as_deref().map(str::trim);
+    if email.is_some_and(|address| address.is_empty() || !address.contains('@')) {
+        return Err(ServiceError::Config("Invalid email address".to_owned()).into());
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let previous = find_user(&mut conn, user_id).await?;
+
+    let updated: User = diesel::update(users_dsl::users.find(user_id))
+        .set((
+            body.display_name
+                .as_ref()
+                .map(|v| users_dsl::display_name.eq(v)),
+            email.map(|v| users_dsl::email.eq(v)),
+            body.email_verified.map(|v| users_dsl::email_verified.eq(v)),
+            body.role.map(|v| users_dsl::role.eq(v.as_str())),
+            users_dsl::updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result(&mut conn)
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ) => ServiceError::Conflict("Email address already in use".to_owned()),
+            _ => ServiceError::Database(e.to_string()),
+        })?;
+
+    user_cache.invalidate(user_id).await?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        Some(user_id),
+        AuditEventType::AdminUserUpdated,
+        json!({
+            "before": {
+                "displayName": previous.display_name,
+                "email": previous.email,
+                "emailVerified": previous.email_verified,
+                "role": previous.role,
+            },
+            "after": {
+                "displayName": updated.display_name,
+                "email": updated.email,
+                "emailVerified": updated.email_verified,
+                "role": updated.role,
+            },
+        }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(AdminUserResponse::from(updated)))
+}
+
+/// Helper function to tell a user their account was suspended and how to appeal
+///
+/// Failures are logged rather than returned since the suspension already applies.
+async fn send_suspension_email(
+    user: &User,
+    email_service: &EmailService,
+    appeals_email: Option<&str>,
+) {
+    let reason = user.suspension_reason.as_deref().unwrap_or_default();
+    let duration = user.suspended_until.map_or_else(
+        || "until our team lifts it".to_owned(),
+        |until| format!("until {}", until.format("%Y-%m-%d %H:%M UTC")),
+    );
+    let appeal = appeals_email.map_or_else(
+        || "reply to this email".to_owned(),
+        |address| format!("write to <a href=\"mailto:{address}\">{address}</a>"),
+    );
+
+    let email_body = format!(
+        "<h1>Your account has been suspended</h1>
+        <p>Your Patron account has been suspended {duration}. While it is suspended you cannot sign in, use your API keys, or publish, and your series and files are hidden from readers.</p>
+        <p>Reason: {reason}</p>
+        <p>If you believe this is a mistake, {appeal} with any details that help us review the decision.</p>"
+    );
+
+    if let Err(e) = email_service
+        .send_html_email(HtmlEmailContent {
+            to: &user.email,
+            subject: "Your Patron account has been suspended",
+            html_body: &email_body,
+            text_body: None,
+            from: None,
+        })
+        .await
+    {
+        tracing::warn!("Failed to send suspension email: {}", e);
+    }
+}
+
+/// Suspend an account
+///
+/// Signs the account out everywhere, refuses its sessions, access tokens and
+/// API keys, and hides its files from the public CDN until the suspension ends
+/// or is lifted. The user is emailed the reason and how to appeal. Suspending
+/// an account that is already suspended replaces the reason and end.
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the account does not exist
+/// or is the admin's own, the request is invalid, or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/suspend",
+    context_path = "/api",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "ID of the account")),
+    request_body(content = SuspendUserRequest, description = "Reason and optional end of the suspension"),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Account suspended", body = AdminUserResponse),
+        (status = 400, description = "Missing reason, end in the past, or own account", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Database or session store error", body = ErrorResponse)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn suspend_user(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    session_service: web::Data<SessionService>,
+    user_cache: web::Data<UserCacheService>,
+    email_service: web::Data<EmailService>,
+    config: web::Data<ConfigService>,
+    path: web::Path<Uuid>,
+    body: web::Json<SuspendUserRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    require_admin(&admin)?;
+
+    let user_id = path.into_inner();
+    if user_id == admin.id {
+        return Err(ServiceError::Config("You cannot suspend yourself".to_owned()).into());
+    }
+
+    let reason = body.reason.trim();
+    if reason.is_empty() {
+        return Err(ServiceError::Config("A reason is required".to_owned()).into());
+    }
+
+    let now = Utc::now();
+    if body.until.is_some_and(|until| until <= now) {
+        return Err(
+            ServiceError::Config("The suspension must end in the future".to_owned()).into(),
+        );
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let previous = find_user(&mut conn, user_id).await?;
+    let suspended_at = previous
+        .suspended_at
+        .filter(|_| previous.is_suspended())
+        .unwrap_or_else(|| now.naive_utc());
+
+    let user: User = diesel::update(users_dsl::users.find(user_id))
+        .set((
+            users_dsl::suspended_at.eq(Some(suspended_at)),
+            users_dsl::suspension_reason.eq(Some(reason)),
+            users_dsl::suspended_until.eq(body.until.map(|until| until.naive_utc())),
+        ))
+        .get_result(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    sign_out_everywhere(&mut conn, &session_service, &user_cache, user_id).await?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        Some(user_id),
+        AuditEventType::AdminUserSuspended,
+        json!({
+            "reason": reason,
+            "until": body.until,
+        }),
+    )
+    .await;
+
+    send_suspension_email(&user, &email_service, config.appeals_email.as_deref()).await;
+
+    Ok(HttpResponse::Ok().json(AdminUserResponse::from(user)))
+}
+
+/// Lift a suspension
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the account does not exist,
+/// or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/unsuspend",
+    context_path = "/api",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "ID of the account")),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Suspension lifted", body = AdminUserResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn unsuspend_user(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    user_cache: web::Data<UserCacheService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    require_admin(&admin)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let user: User = diesel::update(users_dsl::users.find(path.into_inner()))
+        .set((
+            users_dsl::suspended_at.eq(None::<chrono::NaiveDateTime>),
+            users_dsl::suspension_reason.eq(None::<String>),
+            users_dsl::suspended_until.eq(None::<chrono::NaiveDateTime>),
+        ))
+        .get_result(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?
+        .ok_or_else(|| ServiceError::NotFound("User not found".to_owned()))?;
+
+    user_cache.invalidate(user.id).await?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        Some(user.id),
+        AuditEventType::AdminUserUnsuspended,
+        json!({}),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(AdminUserResponse::from(user)))
+}
+
+/// Force a password reset
+///
+/// Replaces the password with one nobody knows, signs the account out
+/// everywhere and emails the user a reset link.
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the account does not exist,
+/// or database, session store or email operations fail.
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/force-password-reset",
+    context_path = "/api",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "ID of the account")),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 204, description = "Password reset and link sent"),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Database, session store or email error", body = ErrorResponse)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn force_password_reset(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    session_service: web::Data<SessionService>,
+    user_cache: web::Data<UserCacheService>,
+    redis_manager: web::Data<ConnectionManager>,
+    email_service: web::Data<EmailService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    require_admin(&admin)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let locked_password = hash_password(&Uuid::new_v4().to_string())?;
+    let user: User = diesel::update(users_dsl::users.find(path.into_inner()))
+        .set(users_dsl::password_hash.eq(Some(locked_password)))
+        .get_result(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?
+        .ok_or_else(|| ServiceError::NotFound("User not found".to_owned()))?;
+
+    sign_out_everywhere(&mut conn, &session_service, &user_cache, user.id).await?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        Some(user.id),
+        AuditEventType::AdminPasswordResetForced,
+        json!({}),
+    )
+    .await;
+
+    let reset_token = store_password_reset_token(&redis_manager, user.id).await?;
+    send_password_reset_email(
+        &user,
+        &reset_token,
+        &email_service,
+        &google_oauth_service,
+        Some("For your security, our support team has reset your password and signed you out of every device. Choose a new password to sign in again."),
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// List an account's files
+///
+/// Includes deleted files, newest first.
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the account does not exist,
+/// or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/admin/users/{user_id}/files",
+    context_path = "/api",
+    tag = "Admin",
+    params(
+        ("user_id" = Uuid, Path, description = "ID of the account"),
+        ListFilesQuery
+    ),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "The account's files", body = UserFilesResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn list_user_files(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    query: web::Query<ListFilesQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::user_files::dsl as files_dsl;
+
+    require_admin(&admin)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let user = find_user(&mut conn, path.into_inner()).await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let mut files_query = files_dsl::user_files
+        .filter(files_dsl::user_id.eq(user.id))
+        .order(files_dsl::created_at.desc())
+        .limit(limit)
+        .into_boxed();
+
+    if let Some(offset_id) = query.offset {
+        let offset_created_at: chrono::NaiveDateTime = files_dsl::user_files
+            .find(offset_id)
+            .select(files_dsl::created_at)
+            .first::<Option<chrono::NaiveDateTime>>(&mut conn)
+            .await
+            .optional()
+            .map_err(ServiceError::from)?
+            .flatten()
+            .unwrap_or_else(|| Utc::now().naive_utc());
+
+        files_query = files_query.filter(files_dsl::created_at.lt(offset_created_at));
+    }
+
+    let files: Vec<UserFile> = files_query
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        Some(user.id),
+        AuditEventType::AdminFilesViewed,
+        json!({}),
+    )
+    .await;
+
+    let file_infos: UserFilesResponse = files.into_iter().map(UserFileInfo::from).collect();
+    Ok(HttpResponse::Ok().json(file_infos))
+}
+
+/// Remove a series
+///
+/// Soft-deletes the series, hiding it and its posts like a deletion by its
+/// creator would.
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the series does not exist,
+/// or database operations fail.
+#[utoipa::path(
+    delete,
+    path = "/admin/series/{series_id}",
+    context_path = "/api",
+    tag = "Admin",
+    params(
+        ("series_id" = Uuid, Path, description = "ID of the series to remove"),
+        ModerationQuery
+    ),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 204, description = "Series removed"),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "Series not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn delete_series(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    query: web::Query<ModerationQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::series::dsl as series_dsl;
+
+    require_admin(&admin)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let now = Utc::now().naive_utc();
+    let series: Series = diesel::update(
+        series_dsl::series
+            .filter(series_dsl::id.eq(path.into_inner()))
+            .filter(series_dsl::deleted_at.is_null()),
+    )
+    .set((
+        series_dsl::deleted_at.eq(now),
+        series_dsl::updated_at.eq(now),
+    ))
+    .get_result(&mut conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?
+    .ok_or_else(|| ServiceError::NotFound("Series not found".to_owned()))?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        Some(series.user_id),
+        AuditEventType::AdminSeriesDeleted,
+        json!({
+            "seriesId": series.id,
+            "title": series.title,
+            "reason": query.reason,
+        }),
+    )
+    .await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Remove a post
+///
+/// Soft-deletes the post like a deletion by its creator would.
+///
+/// # Errors
+/// Returns an error if the user is not an admin, the post does not exist,
+/// or database operations fail.
+#[utoipa::path(
+    delete,
+    path = "/admin/posts/{post_id}",
+    context_path = "/api",
+    tag = "Admin",
+    params(
+        ("post_id" = Uuid, Path, description = "ID of the post to remove"),
+        ModerationQuery
+    ),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 204, description = "Post removed"),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 404, description = "Post not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn delete_post(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    query: web::Query<ModerationQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::posts::dsl as posts_dsl;
+    use shared::schema::series::dsl as series_dsl;
+
+    require_admin(&admin)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let now = Utc::now().naive_utc();
+    let post: Post = diesel::update(
+        posts_dsl::posts
+            .filter(posts_dsl::id.eq(path.into_inner()))
+            .filter(posts_dsl::deleted_at.is_null()),
+    )
+    .set((posts_dsl::deleted_at.eq(now), posts_dsl::updated_at.eq(now)))
+    .get_result(&mut conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?
+    .ok_or_else(|| ServiceError::NotFound("Post not found".to_owned()))?;
+
+    refresh_series_length(&mut conn, post.series_id).await?;
+
+    let owner_id = series_dsl::series
+        .find(post.series_id)
+        .select(series_dsl::user_id)
+        .first::<Uuid>(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?;
+
+    record_admin_action(
+        &db_service,
+        &req,
+        admin.id,
+        owner_id,
+        AuditEventType::AdminPostDeleted,
+        json!({
+            "postId": post.id,
+            "seriesId": post.series_id,
+            "title": post.title,
+            "reason": query.reason,
+        }),
+    )
+    .await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Search the security audit log
+///
+/// Returns audit events across all users, newest first, optionally filtered
+/// by the user they concern or were caused by, event type or IP address.
+///
+/// # Errors
+/// Returns an error if the user is not an admin or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/admin/audit-events",
+    context_path = "/api",
+    tag = "Admin",
+    params(AuditQuery),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Matching audit events", body = AuditEventsListResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Admin access required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn list_audit_events(
+    req: HttpRequest,
+    admin: User,
+    db_service: web::Data<DbService>,
+    query: web::Query<AuditQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::audit_events::dsl as audit_dsl;
+
+    require_admin(&admin)?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_PAGE_SIZE)
+        .clamp(1, MAX_AUDIT_PAGE_SIZE);
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let mut events_query = audit_dsl::audit_events.into_boxed();
+    if let Some(subject_id) = query.user_id {
+        events_query = events_query.filter(
+            audit_dsl::user_id
+                .eq(subject_id)
+                .or(audit_dsl::actor_id.eq(subject_id)),
+        );
+    }
+    if let Some(ref event_type) = query.event_type {
+        events_query = events_query.filter(audit_dsl::event_type.eq(event_type));
+    }
+    if let Some(ref ip_address) = query.ip_address {
+        events_query = events_query.filter(audit_dsl::ip_address.eq(ip_address));
+    }
+    if let Some(before) = query.before {
+        let before_at = before.naive_utc();
+        events_query = match query.before_id {
+            Some(before_id) => events_query.filter(
+                audit_dsl::created_at.lt(before_at).or(audit_dsl::created_at
+                    .eq(before_at)
+                    .and(audit_dsl::id.lt(before_id))),
+            ),
+            None => events_query.filter(audit_dsl::created_at.lt(before_at)),
+        };
+    }
+
+    let events = events_query
+        .order((audit_dsl::created_at.desc(), audit_dsl::id.desc()))
+        .limit(limit.saturating_add(1))
+        .load::<AuditEvent>(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    // Only the first page is recorded so paging through results stays one entry
+    if query.before.is_none() {
+        record_admin_action(
+            &db_service,
+            &req,
+            admin.id,
+            query.user_id,
+            AuditEventType::AdminAuditSearched,
+            json!({
+                "eventType": query.event_type,
+                "ipAddress": query.ip_address,
+            }),
+        )
+        .await;
+    }
+
+    Ok(HttpResponse::Ok().json(audit_page(events, limit)))
+}
@@ -1,11 +1,12 @@
 #![allow(clippy::unused_async)]
 
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpRequest, HttpResponse, Result};
 use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use rand::Rng;
 use serde::Deserialize;
+use serde_json::json;
 use sha2::{Digest, Sha256};
 use shared::{
     errors::{ErrorResponse, ServiceError},
@@ -14,8 +15,10 @@ use shared::{
             ApiKey, ApiKeyResponse, ApiKeysListResponse, CreateApiKeyRequest, CreateApiKeyResponse,
             UpdateApiKeyRequest,
         },
+        audit::AuditEventType,
         auth::User,
     },
+    services::audit::record_event,
 };
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -77,6 +80,7 @@ fn hash_api_key(key: &str) -> String {
     security(("cookieAuth" = [], "bearerAuth" = []))
 )]
 pub async fn create_api_key(
+    req: HttpRequest,
     user: User,
     db_service: web::Data<shared::services::db::DbService>,
     body: web::Json<CreateApiKeyRequest>,
@@ -123,6 +127,15 @@ pub async fn create_api_key(
             _ => ServiceError::Database(e.to_string()),
         })?;
 
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::ApiKeyCreated,
+        json!({ "apiKeyId": inserted_api_key.id, "name": inserted_api_key.name }),
+    )
+    .await;
+
     let response = CreateApiKeyResponse {
         id: inserted_api_key.id,
         name: inserted_api_key.name,
@@ -270,6 +283,7 @@ pub async fn get_api_key(
     security(("cookieAuth" = [], "bearerAuth" = []))
 )]
 pub async fn update_api_key(
+    req: HttpRequest,
     user: User,
     db_service: web::Data<shared::services::db::DbService>,
     path: web::Path<Uuid>,
@@ -321,6 +335,15 @@ pub async fn update_api_key(
             .await
             .map_err(|e| ServiceError::Database(e.to_string()))?;
 
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::ApiKeyUpdated,
+        json!({ "apiKeyId": updated_api_key.id, "isActive": updated_api_key.is_active }),
+    )
+    .await;
+
     Ok(HttpResponse::Ok().json(ApiKeyResponse::from(updated_api_key)))
 }
 
@@ -343,6 +366,7 @@ pub async fn update_api_key(
     security(("cookieAuth" = [], "bearerAuth" = []))
 )]
 pub async fn delete_api_key(
+    req: HttpRequest,
     user: User,
     db_service: web::Data<shared::services::db::DbService>,
     path: web::Path<Uuid>,
@@ -353,7 +377,7 @@ pub async fn delete_api_key(
     let pool = db_service.pool();
     let mut conn = pool.get().await.map_err(ServiceError::from)?;
 
-    let _existing_api_key: ApiKey = api_keys_dsl::api_keys
+    let existing_api_key: ApiKey = api_keys_dsl::api_keys
         .filter(api_keys_dsl::id.eq(api_key_id))
         .filter(api_keys_dsl::user_id.eq(user.id))
         .first(&mut conn)
@@ -370,6 +394,15 @@ pub async fn delete_api_key(
         .await
         .map_err(|e| ServiceError::Database(e.to_string()))?;
 
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::ApiKeyDeleted,
+        json!({ "apiKeyId": existing_api_key.id, "name": existing_api_key.name }),
+    )
+    .await;
+
     Ok(HttpResponse::NoContent().finish())
 }
 
@@ -0,0 +1,105 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::{
+            ActivityQuery, AuditCursor, AuditEvent, AuditEventResponse, AuditEventsListResponse,
+        },
+        auth::User,
+    },
+    services::db::DbService,
+};
+
+/// Default number of audit events per page
+pub(crate) const DEFAULT_AUDIT_PAGE_SIZE: i64 = 50;
+/// Largest number of audit events a single page may hold
+pub(crate) const MAX_AUDIT_PAGE_SIZE: i64 = 200;
+
+/// Helper function to build a page of audit events, fetched with one extra row
+///
+/// The extra row only tells whether another page exists and is dropped.
+pub(crate) fn audit_page(mut events: Vec<AuditEvent>, limit: i64) -> AuditEventsListResponse {
+    let page_size = usize::try_from(limit).unwrap_or(0);
+    let has_more = events.len() > page_size;
+    events.truncate(page_size);
+
+    let next_before = if has_more {
+        events.last().map(|event| AuditCursor {
+            created_at: event.created_at.and_utc(),
+            id: event.id,
+        })
+    } else {
+        None
+    };
+
+    AuditEventsListResponse {
+        events: events.into_iter().map(AuditEventResponse::from).collect(),
+        next_before,
+    }
+}
+
+/// List the signed-in user's security activity
+///
+/// Returns sign-ins, failed sign-in attempts, password and email changes,
+/// API key changes and other security events on the account, newest first.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    get,
+    path = "/auth/activity",
+    context_path = "/api",
+    tag = "Auth",
+    params(ActivityQuery),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Security events for the authenticated user", body = AuditEventsListResponse),
+        (status = 401, description = "Authentication required to view activity", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn list_activity(
+    user: User,
+    db_service: web::Data<DbService>,
+    query: web::Query<ActivityQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::audit_events::dsl as audit_dsl;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_PAGE_SIZE)
+        .clamp(1, MAX_AUDIT_PAGE_SIZE);
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let mut events_query = audit_dsl::audit_events
+        .filter(audit_dsl::user_id.eq(user.id))
+        .into_boxed();
+    if let Some(before) = query.before {
+        let before_at = before.naive_utc();
+        events_query = match query.before_id {
+            Some(before_id) => events_query.filter(
+                audit_dsl::created_at.lt(before_at).or(audit_dsl::created_at
+                    .eq(before_at)
+                    .and(audit_dsl::id.lt(before_id))),
+            ),
+            None => events_query.filter(audit_dsl::created_at.lt(before_at)),
+        };
+    }
+
+    let events = events_query
+        .order((audit_dsl::created_at.desc(), audit_dsl::id.desc()))
+        .limit(limit.saturating_add(1))
+        .load::<AuditEvent>(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    Ok(HttpResponse::Ok().json(audit_page(events, limit)))
+}
@@ -1,17 +1,35 @@
 #![allow(clippy::unused_async)]
 
 use actix_session::Session;
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpRequest, HttpResponse, Result};
 use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use shared::{
     errors::{ErrorResponse, ServiceError},
-    models::auth::{AuthCallbackQuery, EmailVerificationToken, User, UserInfo, UserInfoResponse},
-    services::{auth::GoogleOAuthService, email::EmailService},
+    models::{
+        audit::AuditEventType,
+        auth::{
+            load_password_hash, AuthCallbackQuery, EmailVerificationToken, User, UserInfo,
+            UserInfoResponse, UserRole, UserSession,
+        },
+    },
+    services::{
+        audit::{email_domain, record_event},
+        auth::GoogleOAuthService,
+        config::ConfigService,
+        email::{escape_html, EmailService},
+        oidc::OidcService,
+        rate_limit::{RateLimitService, RateLimitedAction},
+        sessions::{client_ip, user_agent, SessionService},
+        tokens::{hash_token, token_matches_hash},
+        user_cache::UserCacheService,
+    },
 };
 use std::collections::HashMap;
+use std::ops::Add;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -23,6 +41,12 @@ use argon2::{
 use md5;
 use redis::{aio::ConnectionManager, AsyncCommands};
 
+use super::login_alerts::alert_if_new_device;
+use super::oidc::{begin_authorization, complete_authorization};
+
+/// How long a password reset link stays valid
+pub(crate) const PASSWORD_RESET_TTL_MINUTES: u64 = 60;
+
 /// Request body for user registration.
 #[derive(Debug, Deserialize, ToSchema)]
 #[schema(example = json!({
@@ -32,7 +56,7 @@ use redis::{aio::ConnectionManager, AsyncCommands};
 pub struct RegisterRequest {
     /// Email address for new user registration
     pub email: String,
-    /// User's password (minimum 8 characters)
+    /// User's password (minimum 8 characters, checked for strength)
     pub password: String,
     /// Optional display name for the user
     #[serde(rename = "displayName")]
@@ -47,7 +71,7 @@ pub struct RegisterRequest {
         "id": "d290f1ee-6c54-4b01-90e6-d701748f0851",
         "email": "user@example.com",
         "displayName": "John Doe",
-        "authProvider": "email",
+        "hasPassword": true,
         "emailVerified": false
     }
 }))]
@@ -79,7 +103,7 @@ pub struct LoginRequest {
         "id": "d290f1ee-6c54-4b01-90e6-d701748f0851",
         "email": "user@example.com",
         "displayName": "John Doe",
-        "authProvider": "email",
+        "hasPassword": true,
         "emailVerified": true
     }
 }))]
@@ -150,7 +174,7 @@ pub struct UpdateUserInfoRequest {
         "id": "d290f1ee-6c54-4b01-90e6-d701748f0851",
         "email": "user@example.com",
         "displayName": "New Display Name",
-        "authProvider": "email",
+        "hasPassword": true,
         "emailVerified": true
     }
 }))]
@@ -195,7 +219,7 @@ pub struct ResetPasswordRequest {
     /// User ID associated with the reset token
     #[serde(rename = "userId")]
     pub user_id: Uuid,
-    /// New password (minimum 8 characters)
+    /// New password (minimum 8 characters, checked for strength)
     #[serde(rename = "newPassword")]
     pub new_password: String,
 }
@@ -210,16 +234,163 @@ pub struct ResetPasswordResponse {
     pub message: String,
 }
 
-/// Helper function to validate password (simple 8+ character check)
-const fn validate_password(password: &str) -> Result<(), &'static str> {
-    if password.len() < 8 {
-        return Err("Password must be at least 8 characters long");
+/// Request body for changing the password of the logged-in user
+#[derive(Deserialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "currentPassword": "oldpassword123",
+    "newPassword": "correct horse battery staple"
+}))]
+pub struct ChangePasswordRequest {
+    /// The user's current password
+    #[serde(rename = "currentPassword")]
+    pub current_password: String,
+    /// New password (minimum 8 characters, checked for strength)
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+}
+
+/// Request body for adding a password to an account that only signs in with an external provider
+#[derive(Deserialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "newPassword": "correct horse battery staple"
+}))]
+pub struct SetPasswordRequest {
+    /// New password (minimum 8 characters, checked for strength)
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+}
+
+/// Response for a successful password change or addition
+#[derive(Serialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "message": "Password changed successfully"
+}))]
+pub struct PasswordUpdatedResponse {
+    /// Confirmation message
+    pub message: String,
+}
+
+/// Request body for changing the email address of the logged-in user
+#[derive(Deserialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "newEmail": "new-address@example.com",
+    "currentPassword": "password123"
+}))]
+pub struct ChangeEmailRequest {
+    /// Address to change to; it must be confirmed before the change applies
+    #[serde(rename = "newEmail")]
+    pub new_email: String,
+    /// Current password, required when the account has one
+    #[serde(rename = "currentPassword")]
+    pub current_password: Option<String>,
+}
+
+/// Response for an email change request
+#[derive(Serialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "message": "Check new-address@example.com for a link to confirm the change."
+}))]
+pub struct ChangeEmailResponse {
+    /// Status message
+    pub message: String,
+}
+
+/// Structured feedback from the password strength estimator
+#[derive(Serialize, ToSchema, Debug)]
+pub struct PasswordFeedback {
+    /// Explanation of what makes the password weak, if any
+    #[schema(example = "This is similar to a commonly used password.")]
+    pub warning: Option<String>,
+    /// Suggestions for choosing a stronger password
+    #[schema(example = json!(["Add another word or two. Uncommon words are better."]))]
+    pub suggestions: Vec<String>,
+}
+
+/// Error response for a password rejected by the strength check
+#[derive(Serialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "error": "Password is too weak",
+    "code": "AUTH_WEAK_PASSWORD",
+    "score": 1,
+    "minScore": 3,
+    "feedback": {
+        "warning": "This is similar to a commonly used password.",
+        "suggestions": ["Add another word or two. Uncommon words are better."]
     }
-    Ok(())
+}))]
+pub struct WeakPasswordResponse {
+    /// Error message
+    pub error: String,
+    /// Error code for programmatic handling
+    pub code: String,
+    /// Strength score of the submitted password, from 0 (weakest) to 4
+    pub score: u8,
+    /// Minimum score a password must reach
+    #[serde(rename = "minScore")]
+    pub min_score: u8,
+    /// Warning and suggestions to show the user
+    pub feedback: PasswordFeedback,
+}
+
+/// Only this many characters are scored; longer passwords are strong by length
+/// alone and scoring cost grows with input size.
+const MAX_SCORED_PASSWORD_CHARS: usize = 128;
+
+/// Helper function to validate password length and zxcvbn strength
+///
+/// `user_inputs` are words specific to the user (email, display name) that
+/// make a password easier to guess when it contains them.
+fn validate_password(
+    password: &str,
+    user_inputs: &[&str],
+    min_score: u8,
+) -> Result<(), HttpResponse> {
+    if password.chars().count() < 8 {
+        return Err(json_error("Password must be at least 8 characters long"));
+    }
+
+    let scored_password: String = password.chars().take(MAX_SCORED_PASSWORD_CHARS).collect();
+    let entropy = zxcvbn::zxcvbn(&scored_password, user_inputs);
+    let score = u8::from(entropy.score());
+    if score >= min_score {
+        return Ok(());
+    }
+
+    let feedback = entropy.feedback().map_or_else(
+        || PasswordFeedback {
+            warning: None,
+            suggestions: Vec::new(),
+        },
+        |feedback| PasswordFeedback {
+            warning: feedback.warning().map(|warning| warning.to_string()),
+            suggestions: feedback
+                .suggestions()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        },
+    );
+
+    Err(HttpResponse::BadRequest().json(WeakPasswordResponse {
+        error: "Password is too weak".to_owned(),
+        code: "AUTH_WEAK_PASSWORD".to_owned(),
+        score,
+        min_score,
+        feedback,
+    }))
+}
+
+/// Helper function to collect the user-specific words passed to the strength check
+fn password_user_inputs(user: &User) -> Vec<&str> {
+    let mut user_inputs = vec![user.email.as_str()];
+    if let Some(ref display_name) = user.display_name {
+        user_inputs.push(display_name);
+    }
+    user_inputs
 }
 
 /// Helper function to hash password using Argon2
-fn hash_password(password: &str) -> Result<String, ServiceError> {
+pub(crate) fn hash_password(password: &str) -> Result<String, ServiceError> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
     let password_hash = argon2
@@ -229,7 +400,7 @@ fn hash_password(password: &str) -> Result<String, ServiceError> {
 }
 
 /// Helper function to verify password using Argon2
-fn verify_password(password: &str, hash: &str) -> Result<bool, ServiceError> {
+pub(crate) fn verify_password(password: &str, hash: &str) -> Result<bool, ServiceError> {
     let parsed_hash = PasswordHash::new(hash)
         .map_err(|e| ServiceError::Unknown(format!("Invalid password hash: {e}")))?;
     let argon2 = Argon2::default();
@@ -243,7 +414,7 @@ fn verify_password(password: &str, hash: &str) -> Result<bool, ServiceError> {
 }
 
 /// Helper function to create a JSON error response
-fn json_error(message: &str) -> HttpResponse {
+pub(crate) fn json_error(message: &str) -> HttpResponse {
     HttpResponse::BadRequest().json(ErrorResponse {
         error: message.to_owned(),
         code: None,
@@ -251,7 +422,7 @@ fn json_error(message: &str) -> HttpResponse {
 }
 
 /// Helper function to generate Gravatar URL from email
-fn generate_gravatar_url(email: &str) -> String {
+pub(crate) fn generate_gravatar_url(email: &str) -> String {
     let email_hash = format!("{:x}", md5::compute(email.trim().to_lowercase().as_bytes()));
     format!("https://www.gravatar.com/avatar/{email_hash}?d=identicon&s=200")
 }
@@ -274,9 +445,10 @@ async fn send_email_verification(
     let new_verification_token = EmailVerificationToken {
         id: Uuid::new_v4(),
         user_id: user.id,
-        token: verification_token.clone(),
+        token_hash: hash_token(&verification_token),
         expires_at,
         created_at: Utc::now().naive_utc(),
+        new_email: None,
     };
 
     let _ = diesel::insert_into(tokens_dsl::email_verification_tokens)
@@ -310,100 +482,194 @@ async fn send_email_verification(
     Ok(())
 }
 
-/// Helper function to verify `OAuth` state
-fn verify_oauth_state(session: &Session, received_state: &str) -> Result<(), HttpResponse> {
-    let stored_state: Option<String> = session
-        .get("oauth_state")
-        .map_err(|e| json_error(&format!("Failed to get OAuth state from session: {e}")))
-        .map_err(|_session_err| json_error("Session error"))?;
-
-    let Some(stored_oauth_state) = stored_state else {
-        return Err(json_error("Invalid OAuth state"));
+/// Helper function to notify a user that the password on their account changed
+///
+/// Failures are logged rather than returned since the change itself already succeeded.
+async fn send_password_security_notice(user: &User, email_service: &EmailService, added: bool) {
+    let (subject, summary) = if added {
+        (
+            "A password was added to your account",
+            "A password was just added to your Patron account. You can now sign in with your email address and password as well as with your linked sign-in providers.",
+        )
+    } else {
+        (
+            "Your password was changed",
+            "The password for your Patron account was just changed. Other devices have been signed out.",
+        )
     };
 
-    if stored_oauth_state != received_state {
-        return Err(json_error("OAuth state mismatch"));
-    }
+    let email_body = format!(
+        "<h1>Security notice</h1>
+        <p>{summary}</p>
+        <p>If you did not make this change, reset your password immediately and contact support.</p>"
+    );
 
-    Ok(())
+    if let Err(e) = email_service
+        .send_html_email(shared::services::email::HtmlEmailContent {
+            to: &user.email,
+            subject,
+            html_body: &email_body,
+            text_body: None,
+            from: None,
+        })
+        .await
+    {
+        tracing::warn!("Failed to send password security notice: {}", e);
+    }
 }
 
-/// Helper function to update existing user with Google info
-async fn update_existing_user_with_google_info(
-    mut existing_user: User,
-    google_user_info: &shared::services::auth::GoogleUserInfo,
-    conn: &mut diesel_async::AsyncPgConnection,
-) -> Result<User, ServiceError> {
-    use shared::schema::users::dsl as users_dsl;
+/// Helper function to send the confirmation link to the new address and a notice to the old one
+async fn send_email_change_emails(
+    user: &User,
+    new_email: &str,
+    change_token: &str,
+    email_service: &EmailService,
+    google_oauth_service: &GoogleOAuthService,
+) -> Result<(), ServiceError> {
+    let confirmation_link = format!(
+        "{}/api/auth/confirm-email-change?token={}",
+        google_oauth_service.backend_url, change_token
+    );
+    let confirmation_body = format!(
+        "<h1>Confirm your new email address</h1>
+        <p>Click the link below to use this address for your Patron account:</p>
+        <p><a href=\"{confirmation_link}\">Confirm Email Change</a></p>
+        <p>This link will expire in 24 hours.</p>"
+    );
+    email_service
+        .send_html_email(shared::services::email::HtmlEmailContent {
+            to: new_email,
+            subject: "Confirm your new email address",
+            html_body: &confirmation_body,
+            text_body: None,
+            from: None,
+        })
+        .await?;
 
-    if existing_user.display_name.is_none() && !google_user_info.name.is_empty() {
-        existing_user.display_name = Some(google_user_info.name.clone());
-    }
-    if existing_user.avatar_url.is_none() {
-        if google_user_info.picture.is_empty() {
-            existing_user.avatar_url = Some(generate_gravatar_url(&existing_user.email));
-        } else {
-            existing_user.avatar_url = Some(google_user_info.picture.clone());
-        }
+    let notice_body = format!(
+        "<h1>Email change requested</h1>
+        <p>Someone asked to change the email address of your Patron account to {}. The change applies once the new address is confirmed.</p>
+        <p>If you did not request this, change your password immediately.</p>",
+        escape_html(new_email)
+    );
+    if let Err(e) = email_service
+        .send_html_email(shared::services::email::HtmlEmailContent {
+            to: &user.email,
+            subject: "Email change requested for your account",
+            html_body: &notice_body,
+            text_body: None,
+            from: None,
+        })
+        .await
+    {
+        tracing::warn!("Failed to send email change notice to old address: {}", e);
     }
 
-    let new_auth_provider = match existing_user.auth_provider.as_str() {
-        "email" => "both".to_owned(),
-        _ => existing_user.auth_provider.clone(),
-    };
+    Ok(())
+}
 
-    let _ = diesel::update(users_dsl::users.filter(users_dsl::id.eq(&existing_user.id)))
-        .set((
-            users_dsl::display_name.eq(&existing_user.display_name),
-            users_dsl::avatar_url.eq(&existing_user.avatar_url),
-            users_dsl::auth_provider.eq(&new_auth_provider),
-            users_dsl::email_verified.eq(true),
-            users_dsl::last_login.eq(Some(Utc::now().naive_utc())),
-        ))
-        .execute(conn)
+/// Helper function to create a password reset token for a user
+///
+/// Only the hash is stored, and a new token replaces any earlier one.
+pub(crate) async fn store_password_reset_token(
+    redis_manager: &ConnectionManager,
+    user_id: Uuid,
+) -> Result<String, ServiceError> {
+    let reset_token = Uuid::new_v4().to_string();
+
+    let mut con = redis_manager.clone();
+    let key = format!("password-reset:{user_id}");
+    let _: () = con
+        .set_ex(
+            &key,
+            hash_token(&reset_token),
+            PASSWORD_RESET_TTL_MINUTES.saturating_mul(60),
+        )
         .await
-        .map_err(ServiceError::from)?;
+        .map_err(|e| ServiceError::Unknown(format!("Failed to store token in Redis: {e}")))?;
 
-    existing_user.auth_provider = new_auth_provider;
-    existing_user.email_verified = true;
-    existing_user.last_login = Some(Utc::now().naive_utc());
-
-    Ok(existing_user)
+    Ok(reset_token)
 }
 
-/// Helper function to create new user from Google info
-async fn create_new_user_from_google_info(
-    google_user_info: &shared::services::auth::GoogleUserInfo,
-    conn: &mut diesel_async::AsyncPgConnection,
-) -> Result<User, ServiceError> {
-    use shared::schema::users::dsl as users_dsl;
+/// Helper function to email a password reset link
+///
+/// `notice` is shown above the link to explain why a reset was sent when the
+/// user did not ask for it.
+pub(crate) async fn send_password_reset_email(
+    user: &User,
+    reset_token: &str,
+    email_service: &EmailService,
+    google_oauth_service: &GoogleOAuthService,
+    notice: Option<&str>,
+) -> Result<(), ServiceError> {
+    let reset_link = format!(
+        "{}/reset-password?token={}&user_id={}",
+        google_oauth_service.frontend_url, reset_token, user.id
+    );
+    let notice_html = notice
+        .map(|text| format!("<p>{text}</p>\n            "))
+        .unwrap_or_default();
 
-    let new_user = User {
-        id: Uuid::new_v4(),
-        email: google_user_info.email.clone(),
-        display_name: Some(google_user_info.name.clone()),
-        avatar_url: Some(if google_user_info.picture.is_empty() {
-            generate_gravatar_url(&google_user_info.email)
-        } else {
-            google_user_info.picture.clone()
-        }),
-        auth_provider: "google".to_owned(),
-        email_verified: true,
-        password_hash: None,
-        created_at: None,
-        updated_at: None,
-        last_login: Some(Utc::now().naive_utc()),
-        description: None,
-        banner: None,
-    };
+    let email_body = format!(
+        "<h1>Password Reset Request</h1>
+            {notice_html}<p>Click the link below to reset your password:</p>
+            <p><a href=\"{reset_link}\">Reset Password</a></p>
+            <p>This link can be used once and will expire in {PASSWORD_RESET_TTL_MINUTES} minutes.</p>"
+    );
 
-    let _ = diesel::insert_into(users_dsl::users)
-        .values(&new_user)
-        .execute(conn)
+    email_service
+        .send_html_email(shared::services::email::HtmlEmailContent {
+            to: &user.email,
+            subject: "Password Reset Request",
+            html_body: &email_body,
+            text_body: None,
+            from: None,
+        })
         .await
-        .map_err(ServiceError::from)?;
+}
+
+/// Helper function to log a user into the session and register it in the session inventory
+///
+/// Any inventory record attached to the previous session is revoked so the
+/// same cookie never maps to two records. The cached copy of the user is dropped
+/// since callers usually just updated the row. Suspended accounts are refused
+/// before any session is created. Returns the new session record ID.
+pub(crate) async fn start_session(
+    session: &Session,
+    session_service: &SessionService,
+    req: &HttpRequest,
+    user: &User,
+) -> Result<String, ServiceError> {
+    if user.is_suspended() {
+        return Err(ServiceError::Forbidden(user.suspension_message()));
+    }
+
+    if let Ok(Some(previous_id)) = session.get::<String>(SessionService::SESSION_ID_KEY) {
+        if let Ok(Some(previous)) = session.get::<UserSession>(UserSession::KEY) {
+            let _ = session_service
+                .revoke(previous.user_id, &previous_id)
+                .await?;
+        }
+    }
+
+    if let Some(user_cache) = req.app_data::<web::Data<UserCacheService>>() {
+        user_cache.invalidate(user.id).await?;
+    }
+
+    let record = session_service
+        .create(user.id, client_ip(req), user_agent(req))
+        .await?;
+
+    session.renew();
+    let _ = session.remove("user");
+    session
+        .insert(UserSession::KEY, UserSession::for_user(user))
+        .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
+    session
+        .insert(SessionService::SESSION_ID_KEY, &record.id)
+        .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
 
-    Ok(new_user)
+    Ok(record.id)
 }
 
 /// Google `OAuth` redirect
@@ -427,21 +693,17 @@ async fn create_new_user_from_google_info(
 )]
 pub async fn google_auth_redirect(
     session: Session,
+    oidc_service: web::Data<OidcService>,
     google_oauth_service: web::Data<GoogleOAuthService>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let state = Uuid::new_v4().to_string();
-    let auth_url = google_oauth_service.get_authorization_url(Some(&state));
-
-    session
-        .insert("oauth_state", &state)
-        .map_err(|e| ServiceError::Unknown(format!("Failed to store OAuth state: {e}")))?;
-    session
-        .insert("oauth_redirect_uri", &google_oauth_service.frontend_url)
-        .map_err(|e| ServiceError::Unknown(format!("Failed to store redirect URI: {e}")))?;
-
-    Ok(HttpResponse::Found()
-        .append_header(("Location", auth_url))
-        .finish())
+    Ok(begin_authorization(
+        &session,
+        &oidc_service,
+        "google",
+        &google_oauth_service.frontend_url,
+        None,
+    )
+    .await?)
 }
 
 /// Google `OAuth` callback
@@ -473,60 +735,29 @@ pub async fn google_auth_redirect(
         ),
     )
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn google_auth_callback(
+    req: HttpRequest,
     session: Session,
+    session_service: web::Data<SessionService>,
+    user_cache: web::Data<UserCacheService>,
     db_service: web::Data<shared::services::db::DbService>,
+    oidc_service: web::Data<OidcService>,
     google_oauth_service: web::Data<GoogleOAuthService>,
     query: web::Query<AuthCallbackQuery>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    use shared::schema::users::dsl as users_dsl;
-
-    if let Err(response) = verify_oauth_state(&session, &query.state) {
-        return Ok(response);
-    }
-
-    let redirect_uri: String = session
-        .get("oauth_redirect_uri")
-        .map_err(|e| {
-            ServiceError::Unknown(format!("Failed to get redirect URI from session: {e}"))
-        })?
-        .unwrap_or_else(|| google_oauth_service.frontend_url.clone());
-
-    let token_response = google_oauth_service
-        .exchange_code_for_token(&query.code)
-        .await?;
-    let google_user_info = google_oauth_service
-        .get_user_info(&token_response.access_token)
-        .await?;
-
-    let pool = db_service.pool();
-    let mut conn = pool.get().await.map_err(ServiceError::from)?;
-
-    let user = match users_dsl::users
-        .filter(users_dsl::email.eq(&google_user_info.email))
-        .first::<User>(&mut conn)
-        .await
-    {
-        Ok(existing_user) => {
-            update_existing_user_with_google_info(existing_user, &google_user_info, &mut conn)
-                .await?
-        }
-        Err(diesel::result::Error::NotFound) => {
-            create_new_user_from_google_info(&google_user_info, &mut conn).await?
-        }
-        Err(e) => return Err(ServiceError::from(e).into()),
-    };
-
-    session
-        .insert("user", user)
-        .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
-
-    let _ = session.remove("oauth_state");
-    let _ = session.remove("oauth_redirect_uri");
-
-    Ok(HttpResponse::Found()
-        .append_header(("Location", redirect_uri.clone()))
-        .finish())
+    complete_authorization(
+        &req,
+        &session,
+        &session_service,
+        &user_cache,
+        &db_service,
+        &oidc_service,
+        &google_oauth_service,
+        "google",
+        &query,
+    )
+    .await
 }
 
 /// User registration
@@ -541,7 +772,7 @@ pub async fn google_auth_callback(
     request_body(content = RegisterRequest, description = "User registration data including email and password"),
     responses(
         (status = 200, description = "Registration successful", body = RegisterResponse),
-        (status = 400, description = "Invalid input or email already exists", body = ErrorResponse,
+        (status = 400, description = "Invalid input or email already exists. Weak passwords are rejected with a `WeakPasswordResponse` carrying the score and feedback", body = ErrorResponse,
             example = json!({
                 "error": "Email address already registered",
                 "code": "AUTH_EMAIL_EXISTS"
@@ -553,10 +784,21 @@ pub async fn google_auth_callback(
                 "code": "DATABASE_ERROR"
             })
         ),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
     )
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn register(
+    req: HttpRequest,
+    config: web::Data<ConfigService>,
     session: Session,
+    session_service: web::Data<SessionService>,
+    rate_limiter: web::Data<RateLimitService>,
     email_service: web::Data<EmailService>,
     db_service: web::Data<shared::services::db::DbService>,
     google_oauth_service: web::Data<GoogleOAuthService>,
@@ -564,11 +806,21 @@ pub async fn register(
 ) -> Result<HttpResponse, actix_web::Error> {
     use shared::schema::users::dsl as users_dsl;
 
+    rate_limiter
+        .check_request(RateLimitedAction::Register, &req, Some(&body.email))
+        .await?;
+
     if body.email.is_empty() {
         return Ok(json_error("Email is required"));
     }
-    if let Err(msg) = validate_password(&body.password) {
-        return Ok(json_error(msg));
+    let mut user_inputs = vec![body.email.as_str()];
+    if let Some(ref display_name) = body.display_name {
+        user_inputs.push(display_name);
+    }
+    if let Err(response) =
+        validate_password(&body.password, &user_inputs, config.min_password_score)
+    {
+        return Ok(response);
     }
 
     let pool = db_service.pool();
@@ -592,7 +844,6 @@ pub async fn register(
         email: body.email.clone(),
         display_name: body.display_name.clone(),
         avatar_url: Some(avatar_url),
-        auth_provider: "email".to_owned(),
         email_verified: false,
         password_hash: Some(password_hash),
         created_at: None,
@@ -600,6 +851,12 @@ pub async fn register(
         last_login: Some(Utc::now().naive_utc()),
         description: None,
         banner: None,
+        session_version: 0,
+        role: UserRole::User.into(),
+        suspended_at: None,
+        suspension_reason: None,
+        suspended_until: None,
+        deletion_scheduled_for: None,
     };
 
     let _ = diesel::insert_into(users_dsl::users)
@@ -610,9 +867,7 @@ pub async fn register(
 
     send_email_verification(&new_user, &email_service, &google_oauth_service, &mut conn).await?;
 
-    session
-        .insert("user", &new_user)
-        .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
+    let _ = start_session(&session, &session_service, &req, &new_user).await?;
 
     Ok(HttpResponse::Ok().json(RegisterResponse {
         message: "Registration successful. Please check your email for verification.".to_owned(),
@@ -650,7 +905,9 @@ pub async fn register(
 )]
 #[allow(clippy::implicit_hasher)]
 pub async fn verify_email(
+    req: HttpRequest,
     session: Session,
+    session_service: web::Data<SessionService>,
     db_service: web::Data<shared::services::db::DbService>,
     google_oauth_service: web::Data<GoogleOAuthService>,
     query: web::Query<HashMap<String, String>>,
@@ -665,28 +922,31 @@ pub async fn verify_email(
     let pool = db_service.pool();
     let mut conn = pool.get().await.map_err(ServiceError::from)?;
 
-    let verification_token: EmailVerificationToken = match tokens_dsl::email_verification_tokens
-        .filter(tokens_dsl::token.eq(token))
-        .first(&mut conn)
-        .await
-    {
-        Ok(token_record) => token_record,
-        Err(diesel::result::Error::NotFound) => {
-            return Ok(json_error("Invalid or expired verification token"));
-        }
-        Err(e) => return Err(ServiceError::from(e).into()),
+    // Deleting the token in the same statement that looks it up keeps it single-use
+    let Some(verification_token) = diesel::delete(
+        tokens_dsl::email_verification_tokens
+            .filter(tokens_dsl::token_hash.eq(hash_token(token)))
+            .filter(tokens_dsl::new_email.is_null())
+            .filter(tokens_dsl::expires_at.gt(Utc::now().naive_utc())),
+    )
+    .get_result::<EmailVerificationToken>(&mut conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?
+    else {
+        return Ok(json_error("Invalid or expired verification token"));
     };
 
-    if verification_token.expires_at < Utc::now().naive_utc() {
-        if let Err(e) = diesel::delete(
-            tokens_dsl::email_verification_tokens.filter(tokens_dsl::id.eq(&verification_token.id)),
-        )
-        .execute(&mut conn)
-        .await
-        {
-            tracing::warn!("Failed to clean up expired verification token: {}", e);
-        }
-        return Ok(json_error("Invalid or expired verification token"));
+    // Links from earlier verification emails are no longer needed
+    if let Err(e) = diesel::delete(
+        tokens_dsl::email_verification_tokens
+            .filter(tokens_dsl::user_id.eq(&verification_token.user_id))
+            .filter(tokens_dsl::new_email.is_null()),
+    )
+    .execute(&mut conn)
+    .await
+    {
+        tracing::warn!("Failed to clean up verification tokens: {}", e);
     }
 
     let mut user: User = users_dsl::users
@@ -713,18 +973,16 @@ pub async fn verify_email(
 
     user.email_verified = true;
 
-    session
-        .insert("user", user)
-        .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
-
-    if let Err(e) = diesel::delete(
-        tokens_dsl::email_verification_tokens.filter(tokens_dsl::id.eq(&verification_token.id)),
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::EmailVerified,
+        json!({ "email": user.email }),
     )
-    .execute(&mut conn)
-    .await
-    {
-        tracing::warn!("Failed to clean up verification token: {}", e);
-    }
+    .await;
+
+    let _ = start_session(&session, &session_service, &req, &user).await?;
 
     let redirect_url = format!("{}?verified=success", google_oauth_service.frontend_url);
     Ok(HttpResponse::Found()
@@ -756,10 +1014,21 @@ pub async fn verify_email(
                 "code": "AUTH_SERVICE_ERROR"
             })
         ),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
     )
 )]
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_lines)]
 pub async fn login(
+    req: HttpRequest,
     session: Session,
+    session_service: web::Data<SessionService>,
+    rate_limiter: web::Data<RateLimitService>,
     db_service: web::Data<shared::services::db::DbService>,
     email_service: web::Data<EmailService>,
     google_oauth_service: web::Data<GoogleOAuthService>,
@@ -767,6 +1036,10 @@ pub async fn login(
 ) -> Result<HttpResponse, actix_web::Error> {
     use shared::schema::users::dsl as users_dsl;
 
+    rate_limiter
+        .check_request(RateLimitedAction::Login, &req, Some(&body.email))
+        .await?;
+
     let pool = db_service.pool();
     let mut conn = pool.get().await.map_err(ServiceError::from)?;
 
@@ -777,18 +1050,46 @@ pub async fn login(
     {
         Ok(user) => user,
         Err(diesel::result::Error::NotFound) => {
+            record_event(
+                &db_service,
+                &req,
+                None,
+                AuditEventType::LoginFailed,
+                json!({
+                    "method": "password",
+                    "emailDomain": email_domain(&body.email),
+                    "reason": "unknown_email",
+                }),
+            )
+            .await;
             return Ok(json_error("Invalid email or password"));
         }
         Err(e) => return Err(ServiceError::from(e).into()),
     };
 
     let Some(ref password_hash) = user.password_hash else {
+        record_event(
+            &db_service,
+            &req,
+            Some(user.id),
+            AuditEventType::LoginFailed,
+            json!({ "method": "password", "reason": "no_password" }),
+        )
+        .await;
         return Ok(json_error(
-            "This account uses Google sign-in. Please use the Google login button.",
+            "This account has no password. Sign in with the provider it is linked to.",
         ));
     };
 
     if !verify_password(&body.password, password_hash)? {
+        record_event(
+            &db_service,
+            &req,
+            Some(user.id),
+            AuditEventType::LoginFailed,
+            json!({ "method": "password", "reason": "wrong_password" }),
+        )
+        .await;
         return Ok(json_error("Invalid email or password"));
     }
 
@@ -808,9 +1109,17 @@ pub async fn login(
 
     user.last_login = Some(Utc::now().naive_utc());
 
-    session
-        .insert("user", user.clone())
-        .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::Login,
+        json!({ "method": "password" }),
+    )
+    .await;
+
+    let _ = start_session(&session, &session_service, &req, &user).await?;
+    alert_if_new_device(&req, &db_service, &user).await;
 
     Ok(HttpResponse::Ok().json(LoginResponse {
         message: "Login successful".to_owned(),
@@ -835,7 +1144,7 @@ pub async fn login(
             example = json!({
                 "id": "d290f1ee-6c54-4b01-90e6-d701748f0851",
                 "email": "user@example.com",
-                "authProvider": "email",
+                "hasPassword": true,
                 "emailVerified": true,
                 "createdAt": "2023-01-01T00:00:00"
             })
@@ -848,8 +1157,20 @@ pub async fn login(
         ),
     )
 )]
-pub async fn get_me(user: User) -> Result<HttpResponse, actix_web::Error> {
-    Ok(HttpResponse::Ok().json(UserInfoResponse::from(UserInfo::from(user))))
+pub async fn get_me(
+    user: User,
+    db_service: web::Data<shared::services::db::DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    let password_hash = load_password_hash(&mut conn, user.id).await?;
+
+    Ok(
+        HttpResponse::Ok().json(UserInfoResponse::from(UserInfo::from(User {
+            password_hash,
+            ..user
+        }))),
+    )
 }
 
 /// Logout
@@ -868,13 +1189,39 @@ pub async fn get_me(user: User) -> Result<HttpResponse, actix_web::Error> {
         (status = 200, description = "Successfully logged out", body = LogoutResponse),
     )
 )]
-pub async fn logout(session: Session) -> Result<HttpResponse, actix_web::Error> {
-    session.purge();
-    Ok(HttpResponse::Ok().json(LogoutResponse {
-        message: "Logged out successfully".to_owned(),
-    }))
-}
-
+pub async fn logout(
+    req: HttpRequest,
+    session: Session,
+    session_service: web::Data<SessionService>,
+    db_service: web::Data<shared::services::db::DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if let Ok(Some(user_session)) = session.get::<UserSession>(UserSession::KEY) {
+        record_event(
+            &db_service,
+            &req,
+            Some(user_session.user_id),
+            AuditEventType::Logout,
+            json!({}),
+        )
+        .await;
+    }
+    if let (Ok(Some(user_session)), Ok(Some(session_id))) = (
+        session.get::<UserSession>(UserSession::KEY),
+        session.get::<String>(SessionService::SESSION_ID_KEY),
+    ) {
+        if let Err(e) = session_service
+            .revoke(user_session.user_id, &session_id)
+            .await
+        {
+            tracing::warn!("Failed to remove session record on logout: {}", e);
+        }
+    }
+    session.purge();
+    Ok(HttpResponse::Ok().json(LogoutResponse {
+        message: "Logged out successfully".to_owned(),
+    }))
+}
+
 /// Forgot password
 ///
 /// # Errors
@@ -893,9 +1240,18 @@ pub async fn logout(session: Session) -> Result<HttpResponse, actix_web::Error>
                 "code": "EMAIL_SERVICE_ERROR"
             })
         ),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
     )
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn forgot_password(
+    req: HttpRequest,
+    rate_limiter: web::Data<RateLimitService>,
     redis_manager: web::Data<ConnectionManager>,
     email_service: web::Data<EmailService>,
     db_service: web::Data<shared::services::db::DbService>,
@@ -904,6 +1260,10 @@ pub async fn forgot_password(
 ) -> Result<HttpResponse, actix_web::Error> {
     use shared::schema::users::dsl as users_dsl;
 
+    rate_limiter
+        .check_request(RateLimitedAction::ForgotPassword, &req, Some(&body.email))
+        .await?;
+
     let pool = db_service.pool();
     let mut conn = pool.get().await.map_err(ServiceError::from)?;
 
@@ -925,36 +1285,24 @@ pub async fn forgot_password(
     };
 
     if user.password_hash.is_some() {
-        let reset_token = Uuid::new_v4().to_string();
-
-        let mut con = redis_manager.get_ref().clone();
-        let key = format!("password-reset:{}", user.id);
-        let _: () = con
-            .set_ex(&key, &reset_token, 3600)
-            .await
-            .map_err(|e| ServiceError::Unknown(format!("Failed to store token in Redis: {e}")))?;
-
-        let reset_link = format!(
-            "{}/reset-password?token={}&user_id={}",
-            google_oauth_service.frontend_url, reset_token, user.id
-        );
-
-        let email_body = format!(
-            "<h1>Password Reset Request</h1>
-            <p>Click the link below to reset your password:</p>
-            <p><a href=\"{reset_link}\">Reset Password</a></p>
-            <p>This link will expire in 2 hours.</p>"
-        );
+        record_event(
+            &db_service,
+            &req,
+            Some(user.id),
+            AuditEventType::PasswordResetRequested,
+            json!({}),
+        )
+        .await;
 
-        email_service
-            .send_html_email(shared::services::email::HtmlEmailContent {
-                to: &body.email,
-                subject: "Password Reset Request",
-                html_body: &email_body,
-                text_body: None,
-                from: None,
-            })
-            .await?;
+        let reset_token = store_password_reset_token(&redis_manager, user.id).await?;
+        send_password_reset_email(
+            &user,
+            &reset_token,
+            &email_service,
+            &google_oauth_service,
+            None,
+        )
+        .await?;
     }
 
     Ok(HttpResponse::Ok().json(response))
@@ -972,7 +1320,7 @@ pub async fn forgot_password(
     request_body(content = ResetPasswordRequest, description = "Password reset token and new password"),
     responses(
         (status = 200, description = "Password reset successful", body = ResetPasswordResponse),
-        (status = 400, description = "Invalid token or password", body = ErrorResponse,
+        (status = 400, description = "Invalid token or password. Weak passwords are rejected with a `WeakPasswordResponse` carrying the score and feedback", body = ErrorResponse,
             example = json!({
                 "error": "Invalid or expired password reset token",
                 "code": "AUTH_INVALID_RESET_TOKEN"
@@ -986,55 +1334,595 @@ pub async fn forgot_password(
         ),
     )
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn reset_password(
+    req: HttpRequest,
+    config: web::Data<ConfigService>,
     session: Session,
+    session_service: web::Data<SessionService>,
     redis_manager: web::Data<ConnectionManager>,
     db_service: web::Data<shared::services::db::DbService>,
     body: web::Json<ResetPasswordRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
     use shared::schema::users::dsl as users_dsl;
 
-    if let Err(msg) = validate_password(&body.new_password) {
-        return Ok(json_error(msg));
-    }
-
     let pool = db_service.pool();
     let mut pg_conn = pool.get().await.map_err(ServiceError::from)?;
 
+    let reset_key = format!("password-reset:{}", body.user_id);
     let mut redis_conn = redis_manager.get_ref().clone();
-    let stored_token = redis_conn
-        .get::<_, String>(format!("password-reset:{}", body.user_id))
-        .await;
-    let Some(actual_token) = stored_token.ok() else {
+    let stored_hash = redis_conn
+        .get::<_, Option<String>>(&reset_key)
+        .await
+        .map_err(|e| ServiceError::Unknown(format!("Failed to read token from Redis: {e}")))?;
+    if !stored_hash.is_some_and(|hash| token_matches_hash(&body.token, &hash)) {
         return Ok(json_error("Invalid or expired password reset token"));
-    };
-    if actual_token != body.token {
-        return Ok(json_error("Invalid password reset token"));
+    }
+
+    let existing_user: User = users_dsl::users
+        .filter(users_dsl::id.eq(&body.user_id))
+        .first(&mut pg_conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    if let Err(response) = validate_password(
+        &body.new_password,
+        &password_user_inputs(&existing_user),
+        config.min_password_score,
+    ) {
+        return Ok(response);
     }
 
     let password_hash = hash_password(&body.new_password)?;
 
-    let _ = diesel::update(users_dsl::users.filter(users_dsl::id.eq(&body.user_id)))
-        .set(users_dsl::password_hash.eq(password_hash))
-        .execute(&mut pg_conn)
+    // The link is consumed only once the new password is accepted; if two
+    // requests race, only the one that deletes the key may continue.
+    let deleted: usize = redis_conn
+        .del(&reset_key)
         .await
-        .map_err(ServiceError::from)?;
+        .map_err(|e| ServiceError::Unknown(format!("Failed to delete token from Redis: {e}")))?;
+    if deleted == 0 {
+        return Ok(json_error("Invalid or expired password reset token"));
+    }
 
-    let user: User = users_dsl::users
-        .filter(users_dsl::id.eq(&body.user_id))
-        .first(&mut pg_conn)
+    // Bumping the session version invalidates every session of the user,
+    // including ones not tracked in the session inventory.
+    let user: User = diesel::update(users_dsl::users.filter(users_dsl::id.eq(&body.user_id)))
+        .set((
+            users_dsl::password_hash.eq(password_hash),
+            users_dsl::session_version.eq(users_dsl::session_version.add(1_i32)),
+        ))
+        .get_result(&mut pg_conn)
         .await
         .map_err(ServiceError::from)?;
 
-    session
-        .insert("user", user)
-        .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::PasswordReset,
+        json!({}),
+    )
+    .await;
+
+    let current_session_id = start_session(&session, &session_service, &req, &user).await?;
+
+    // A password reset may be in response to a compromised account, so every
+    // other device is signed out.
+    let _ = session_service
+        .revoke_all_except(user.id, Some(&current_session_id))
+        .await?;
 
     Ok(HttpResponse::Ok().json(ResetPasswordResponse {
         message: "Password has been reset successfully".to_owned(),
     }))
 }
 
+/// Change password
+///
+/// Replaces the password of the logged-in user after checking the current one.
+/// Every other session of the user is signed out.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated, the current password is wrong,
+/// the new password is too weak, or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/auth/change-password",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    request_body(content = ChangePasswordRequest, description = "Current password and new password"),
+    responses(
+        (status = 200, description = "Password changed", body = PasswordUpdatedResponse),
+        (status = 400, description = "Current password is wrong, the account has no password, or the new password is too weak. Weak passwords are rejected with a `WeakPasswordResponse` carrying the score and feedback", body = ErrorResponse,
+            example = json!({
+                "error": "Current password is incorrect",
+                "code": "AUTH_INVALID_CREDENTIALS"
+            })
+        ),
+        (status = 401, description = "User authentication required to change password", body = ErrorResponse,
+            example = json!({
+                "error": "Not authenticated",
+                "code": "AUTH_REQUIRED"
+            })
+        ),
+        (status = 500, description = "Database connection failed", body = ErrorResponse,
+            example = json!({
+                "error": "Database connection failed",
+                "code": "DATABASE_ERROR"
+            })
+        ),
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn change_password(
+    req: HttpRequest,
+    user: User,
+    session: Session,
+    session_service: web::Data<SessionService>,
+    config: web::Data<ConfigService>,
+    db_service: web::Data<shared::services::db::DbService>,
+    email_service: web::Data<EmailService>,
+    body: web::Json<ChangePasswordRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let Some(password_hash) = load_password_hash(&mut conn, user.id).await? else {
+        return Ok(json_error(
+            "This account has no password yet. Use set-password to add one.",
+        ));
+    };
+
+    if !verify_password(&body.current_password, &password_hash)? {
+        return Ok(json_error("Current password is incorrect"));
+    }
+
+    if let Err(response) = validate_password(
+        &body.new_password,
+        &password_user_inputs(&user),
+        config.min_password_score,
+    ) {
+        return Ok(response);
+    }
+
+    let new_password_hash = hash_password(&body.new_password)?;
+
+    // Bumping the session version signs out every session; the caller's cookie
+    // session is started again below so only other devices are affected.
+    let updated_user: User = diesel::update(users_dsl::users.filter(users_dsl::id.eq(&user.id)))
+        .set((
+            users_dsl::password_hash.eq(new_password_hash),
+            users_dsl::session_version.eq(users_dsl::session_version.add(1_i32)),
+            users_dsl::updated_at.eq(Some(Utc::now().naive_utc())),
+        ))
+        .get_result(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let current_session_id = if matches!(session.get::<UserSession>(UserSession::KEY), Ok(Some(_)))
+    {
+        Some(start_session(&session, &session_service, &req, &updated_user).await?)
+    } else {
+        None
+    };
+    let _ = session_service
+        .revoke_all_except(updated_user.id, current_session_id.as_deref())
+        .await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(updated_user.id),
+        AuditEventType::PasswordChanged,
+        json!({}),
+    )
+    .await;
+
+    send_password_security_notice(&updated_user, &email_service, false).await;
+
+    Ok(HttpResponse::Ok().json(PasswordUpdatedResponse {
+        message: "Password changed successfully".to_owned(),
+    }))
+}
+
+/// Set password
+///
+/// Adds a password to an account that so far only signs in with an external
+/// provider, so the user can also sign in with email and password.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated, already has a password,
+/// the new password is too weak, or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/auth/set-password",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    request_body(content = SetPasswordRequest, description = "Password to add to the account"),
+    responses(
+        (status = 200, description = "Password added", body = PasswordUpdatedResponse),
+        (status = 400, description = "The account already has a password or the new password is too weak. Weak passwords are rejected with a `WeakPasswordResponse` carrying the score and feedback", body = ErrorResponse,
+            example = json!({
+                "error": "This account already has a password. Use change-password instead.",
+                "code": "AUTH_PASSWORD_EXISTS"
+            })
+        ),
+        (status = 401, description = "User authentication required to set password", body = ErrorResponse,
+            example = json!({
+                "error": "Not authenticated",
+                "code": "AUTH_REQUIRED"
+            })
+        ),
+        (status = 500, description = "Database connection failed", body = ErrorResponse,
+            example = json!({
+                "error": "Database connection failed",
+                "code": "DATABASE_ERROR"
+            })
+        ),
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn set_password(
+    req: HttpRequest,
+    user: User,
+    config: web::Data<ConfigService>,
+    db_service: web::Data<shared::services::db::DbService>,
+    user_cache: web::Data<UserCacheService>,
+    email_service: web::Data<EmailService>,
+    body: web::Json<SetPasswordRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::users::dsl as users_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    if load_password_hash(&mut conn, user.id).await?.is_some() {
+        return Ok(json_error(
+            "This account already has a password. Use change-password instead.",
+        ));
+    }
+
+    if let Err(response) = validate_password(
+        &body.new_password,
+        &password_user_inputs(&user),
+        config.min_password_score,
+    ) {
+        return Ok(response);
+    }
+
+    let new_password_hash = hash_password(&body.new_password)?;
+
+    // The password_hash filter guards against a concurrent request adding a
+    // password between the check above and this update.
+    let updated_rows = diesel::update(
+        users_dsl::users
+            .filter(users_dsl::id.eq(&user.id))
+            .filter(users_dsl::password_hash.is_null()),
+    )
+    .set((
+        users_dsl::password_hash.eq(new_password_hash),
+        users_dsl::updated_at.eq(Some(Utc::now().naive_utc())),
+    ))
+    .execute(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    if updated_rows == 0 {
+        return Ok(json_error(
+            "This account already has a password. Use change-password instead.",
+        ));
+    }
+
+    user_cache.invalidate(user.id).await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::PasswordSet,
+        json!({}),
+    )
+    .await;
+
+    send_password_security_notice(&user, &email_service, true).await;
+
+    Ok(HttpResponse::Ok().json(PasswordUpdatedResponse {
+        message: "Password added successfully".to_owned(),
+    }))
+}
+
+/// Request email change
+///
+/// Sends a confirmation link to the new address and a notice to the current one.
+/// The address is only changed once the link is followed. Accounts without a
+/// password must add one first so the request can be re-authenticated.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated, the password is wrong,
+/// the address is taken, or email delivery fails.
+#[utoipa::path(
+    post,
+    path = "/auth/change-email",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    request_body(content = ChangeEmailRequest, description = "New email address and current password"),
+    responses(
+        (status = 200, description = "Confirmation email sent to the new address", body = ChangeEmailResponse),
+        (status = 400, description = "Invalid address, wrong password, or account has no password", body = ErrorResponse,
+            example = json!({
+                "error": "Current password is incorrect",
+                "code": "AUTH_INVALID_CREDENTIALS"
+            })
+        ),
+        (status = 401, description = "User authentication required to change email", body = ErrorResponse,
+            example = json!({
+                "error": "Not authenticated",
+                "code": "AUTH_REQUIRED"
+            })
+        ),
+        (status = 409, description = "Email address already registered", body = ErrorResponse,
+            example = json!({
+                "error": "Conflict: Email address already registered"
+            })
+        ),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
+        (status = 500, description = "Database connection failed or email delivery failed", body = ErrorResponse,
+            example = json!({
+                "error": "Database connection failed",
+                "code": "DATABASE_ERROR"
+            })
+        ),
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_lines)]
+pub async fn request_email_change(
+    req: HttpRequest,
+    user: User,
+    rate_limiter: web::Data<RateLimitService>,
+    db_service: web::Data<shared::services::db::DbService>,
+    email_service: web::Data<EmailService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    body: web::Json<ChangeEmailRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::email_verification_tokens::dsl as tokens_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    rate_limiter
+        .check_request(RateLimitedAction::ChangeEmail, &req, Some(&user.email))
+        .await?;
+
+    let new_email = body.new_email.trim();
+    if new_email.is_empty() || !new_email.contains('@') {
+        return Ok(json_error("A valid email address is required"));
+    }
+    if new_email.eq_ignore_ascii_case(&user.email) {
+        return Ok(json_error("This is already your email address"));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    match load_password_hash(&mut conn, user.id).await? {
+        Some(ref password_hash) => {
+            let current_password = body.current_password.as_deref().unwrap_or_default();
+            if !verify_password(current_password, password_hash)? {
+                return Ok(json_error("Current password is incorrect"));
+            }
+        }
+        None => {
+            return Ok(json_error(
+                "This account has no password. Add a password before changing your email address.",
+            ));
+        }
+    }
+
+    if users_dsl::users
+        .filter(users_dsl::email.eq(new_email))
+        .first::<User>(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?
+        .is_some()
+    {
+        return Err(ServiceError::Conflict("Email address already registered".to_owned()).into());
+    }
+
+    // Only the most recent request can be confirmed.
+    let _ = diesel::delete(
+        tokens_dsl::email_verification_tokens
+            .filter(tokens_dsl::user_id.eq(&user.id))
+            .filter(tokens_dsl::new_email.is_not_null()),
+    )
+    .execute(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    let change_token = Uuid::new_v4().to_string();
+    let expires_at = Utc::now()
+        .naive_utc()
+        .checked_add_signed(chrono::Duration::hours(24))
+        .ok_or_else(|| ServiceError::Unknown("Failed to compute expiration time".to_owned()))?;
+
+    let _ = diesel::insert_into(tokens_dsl::email_verification_tokens)
+        .values(&EmailVerificationToken {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            token_hash: hash_token(&change_token),
+            expires_at,
+            created_at: Utc::now().naive_utc(),
+            new_email: Some(new_email.to_owned()),
+        })
+        .execute(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    send_email_change_emails(
+        &user,
+        new_email,
+        &change_token,
+        &email_service,
+        &google_oauth_service,
+    )
+    .await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::EmailChangeRequested,
+        json!({ "newEmail": new_email }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(ChangeEmailResponse {
+        message: format!("Check {new_email} for a link to confirm the change."),
+    }))
+}
+
+/// Confirm email change
+///
+/// Applies a pending email change once the link sent to the new address is followed.
+///
+/// # Errors
+/// Returns an error if the token is invalid or expired, the address was taken in
+/// the meantime, or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/auth/confirm-email-change",
+    context_path = "/api",
+    tag = "Auth",
+    params(
+        ("token" = String, Query, description = "Email change token from the confirmation email")
+    ),
+    responses(
+        (status = 302, description = "Email changed, redirect to the frontend"),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse,
+            example = json!({
+                "error": "Invalid or expired email change token",
+                "code": "AUTH_INVALID_TOKEN"
+            })
+        ),
+        (status = 409, description = "Email address registered by another account in the meantime", body = ErrorResponse,
+            example = json!({
+                "error": "Conflict: Email address already registered"
+            })
+        ),
+        (status = 500, description = "Database connection failed", body = ErrorResponse,
+            example = json!({
+                "error": "Database connection failed",
+                "code": "DATABASE_ERROR"
+            })
+        ),
+    )
+)]
+#[allow(clippy::implicit_hasher)]
+pub async fn confirm_email_change(
+    req: HttpRequest,
+    db_service: web::Data<shared::services::db::DbService>,
+    user_cache: web::Data<UserCacheService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::email_verification_tokens::dsl as tokens_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    let Some(token) = query.get("token") else {
+        return Ok(json_error("Token is required"));
+    };
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    // Deleting the token in the same statement that looks it up keeps it single-use
+    let pending_change: Option<EmailVerificationToken> = diesel::delete(
+        tokens_dsl::email_verification_tokens
+            .filter(tokens_dsl::token_hash.eq(hash_token(token)))
+            .filter(tokens_dsl::new_email.is_not_null())
+            .filter(tokens_dsl::expires_at.gt(Utc::now().naive_utc())),
+    )
+    .get_result(&mut conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?;
+    let Some((change_token, new_email)) =
+        pending_change.and_then(|record| record.new_email.clone().map(|email| (record, email)))
+    else {
+        return Ok(json_error("Invalid or expired email change token"));
+    };
+
+    let user: User = users_dsl::users
+        .filter(users_dsl::id.eq(&change_token.user_id))
+        .first(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let update_result = diesel::update(users_dsl::users.filter(users_dsl::id.eq(&user.id)))
+        .set((
+            users_dsl::email.eq(&new_email),
+            users_dsl::email_verified.eq(true),
+            users_dsl::updated_at.eq(Some(Utc::now().naive_utc())),
+        ))
+        .execute(&mut conn)
+        .await;
+    match update_result {
+        Ok(_) => {}
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => {
+            return Err(
+                ServiceError::Conflict("Email address already registered".to_owned()).into(),
+            );
+        }
+        Err(e) => return Err(ServiceError::from(e).into()),
+    }
+
+    if let Err(e) = diesel::delete(
+        tokens_dsl::email_verification_tokens
+            .filter(tokens_dsl::user_id.eq(&change_token.user_id))
+            .filter(tokens_dsl::new_email.is_not_null()),
+    )
+    .execute(&mut conn)
+    .await
+    {
+        tracing::warn!("Failed to clean up email change tokens: {}", e);
+    }
+
+    user_cache.invalidate(user.id).await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::EmailChanged,
+        json!({ "oldEmail": user.email, "newEmail": new_email }),
+    )
+    .await;
+
+    let redirect_url = format!("{}?emailChanged=success", google_oauth_service.frontend_url);
+    Ok(HttpResponse::Found()
+        .append_header(("Location", redirect_url))
+        .finish())
+}
+
 /// Check if email exists
 ///
 /// # Errors
@@ -1054,14 +1942,26 @@ pub async fn reset_password(
                 "code": "DATABASE_ERROR"
             })
         ),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
     )
 )]
 pub async fn check_email(
+    req: HttpRequest,
+    rate_limiter: web::Data<RateLimitService>,
     db_service: web::Data<shared::services::db::DbService>,
     body: web::Json<CheckEmailRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
     use shared::schema::users::dsl as users_dsl;
 
+    rate_limiter
+        .check_request(RateLimitedAction::CheckEmail, &req, Some(&body.email))
+        .await?;
+
     let pool = db_service.pool();
     let mut conn = pool.get().await.map_err(ServiceError::from)?;
 
@@ -1108,14 +2008,30 @@ pub async fn check_email(
                 "code": "EMAIL_SERVICE_ERROR"
             })
         ),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
     )
 )]
 pub async fn resend_verification_email(
+    req: HttpRequest,
     user: User,
+    rate_limiter: web::Data<RateLimitService>,
     email_service: web::Data<EmailService>,
     db_service: web::Data<shared::services::db::DbService>,
     google_oauth_service: web::Data<GoogleOAuthService>,
 ) -> Result<HttpResponse, actix_web::Error> {
+    rate_limiter
+        .check_request(
+            RateLimitedAction::ResendVerification,
+            &req,
+            Some(&user.email),
+        )
+        .await?;
+
     if user.email_verified {
         return Ok(json_error("Email is already verified"));
     }
@@ -1162,7 +2078,7 @@ pub async fn resend_verification_email(
 pub async fn update_user_info(
     mut user: User,
     db_service: web::Data<shared::services::db::DbService>,
-    session: Session,
+    user_cache: web::Data<UserCacheService>,
     body: web::Json<UpdateUserInfoRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
     use shared::schema::users::dsl as users_dsl;
@@ -1196,9 +2112,7 @@ pub async fn update_user_info(
         .map_err(ServiceError::from)?;
 
     user.updated_at = Some(Utc::now().naive_utc());
-    session
-        .insert("user", &user)
-        .map_err(|e| ServiceError::Unknown(format!("Failed to update session: {e}")))?;
+    user_cache.invalidate(user.id).await?;
 
     Ok(HttpResponse::Ok().json(UpdateUserInfoResponse {
         message: "User information updated successfully".to_owned(),
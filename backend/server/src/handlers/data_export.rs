@@ -0,0 +1,74 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::User,
+        jobs::{JobKind, JobResponse},
+    },
+    services::{
+        audit::record_event,
+        db::DbService,
+        jobs::{enqueue_job, has_active_job},
+    },
+};
+
+/// Request an archive of everything stored about the signed-in account
+///
+/// Queues a job that builds a ZIP with the profile, series, posts as Markdown
+/// with front matter, API key metadata, a file manifest and the original
+/// files. When it is ready, a download link valid for 7 days is emailed to the
+/// account address. Progress can be followed through `GET /api/jobs/{job_id}`.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated, an export is already in
+/// progress, or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/auth/export",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 202, description = "Export queued", body = JobResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 409, description = "An export is already in progress", body = ErrorResponse,
+            example = json!({
+                "error": "A data export is already in progress"
+            })
+        ),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn request_data_export(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    if has_active_job(&mut conn, user.id, JobKind::DataExport).await? {
+        return Err(
+            ServiceError::Conflict("A data export is already in progress".to_owned()).into(),
+        );
+    }
+
+    let job = enqueue_job(&mut conn, user.id, JobKind::DataExport, json!({}), None).await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::DataExportRequested,
+        json!({ "job_id": job.id }),
+    )
+    .await;
+
+    Ok(HttpResponse::Accepted().json(JobResponse::from(job)))
+}
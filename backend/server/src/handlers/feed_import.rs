@@ -0,0 +1,202 @@
+#![allow(clippy::unused_async)]
+
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::User,
+        jobs::{JobKind, JobResponse},
+    },
+    services::{
+        audit::record_event,
+        db::DbService,
+        feed_import::FeedImportPayload,
+        jobs::{enqueue_job, has_active_job},
+        patreon_import::IMPORT_INPUT_TTL_HOURS,
+        post_import::MAX_IMPORT_ARCHIVE_SIZE,
+        s3::S3Service,
+    },
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::auth::json_error;
+
+/// Feed import request schema for multipart form data
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedImportRequest {
+    /// Substack export (ZIP), RSS or Atom feed, or a ZIP holding a feed and its images
+    #[schema(format = "binary", example = "Binary ZIP or XML content")]
+    pub file: String,
+    /// Series the posts are imported into
+    #[serde(rename = "seriesId")]
+    pub series_id: Uuid,
+}
+
+/// Helper struct for the fields of a feed import upload
+#[derive(Debug, Default)]
+struct FeedForm {
+    file: Option<Vec<u8>>,
+    series_id: Option<Uuid>,
+}
+
+/// Helper function to read the fields of a feed import upload
+async fn read_feed_form(mut payload: Multipart) -> Result<FeedForm, actix_web::Error> {
+    let mut upload = FeedForm::default();
+    let mut total_size: usize = 0;
+    while let Some(payload_field) = payload.next().await {
+        let mut field = payload_field.map_err(|e| {
+            actix_web::error::ErrorBadRequest(format!("Failed to process multipart field: {e}"))
+        })?;
+
+        let field_name = field.name().to_owned();
+        let mut bytes = Vec::new();
+        while let Some(chunk_field) = field.next().await {
+            let chunk = chunk_field.map_err(|e| {
+                actix_web::error::ErrorBadRequest(format!("Failed to read file chunk: {e}"))
+            })?;
+            total_size = total_size.saturating_add(chunk.len());
+            if total_size > MAX_IMPORT_ARCHIVE_SIZE {
+                return Err(actix_web::error::ErrorPayloadTooLarge(
+                    "Upload is too large",
+                ));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        match field_name.as_str() {
+            "file" => upload.file = Some(bytes),
+            "seriesId" => {
+                let value = String::from_utf8_lossy(&bytes);
+                upload.series_id = Some(
+                    Uuid::parse_str(value.trim())
+                        .ok()
+                        .ok_or_else(|| actix_web::error::ErrorBadRequest("Invalid seriesId"))?,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    Ok(upload)
+}
+
+/// Import posts from a Substack export or an RSS/Atom feed
+///
+/// Posts are added to the series named by `seriesId` in publish order,
+/// numbered after its last post and keeping their publish dates. Inline
+/// images are stored as files of the user and linked through the CDN; those
+/// that cannot be fetched keep their source URL. Paid Substack posts are
+/// locked to the cheapest paid tier, or imported as drafts without one. Posts
+/// already imported, with the same title and date, are skipped.
+///
+/// The import runs as a background job whose result is the mapping report.
+///
+/// # Errors
+/// Returns an error if no file is uploaded, the series does not belong to the
+/// user, an import is already in progress, or storage or database operations
+/// fail.
+#[utoipa::path(
+    post,
+    path = "/imports/feed",
+    context_path = "/api",
+    tag = "Imports",
+    request_body(
+        content = FeedImportRequest,
+        description = "Multipart form data with the export or feed and the target series",
+        content_type = "multipart/form-data"
+    ),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 202, description = "Import queued; its result is a FeedImportReport", body = JobResponse),
+        (status = 400, description = "No file uploaded, or no series", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Access denied - series does not belong to authenticated user", body = ErrorResponse),
+        (status = 409, description = "A feed import is already in progress", body = ErrorResponse),
+        (status = 413, description = "Upload is too large", body = ErrorResponse),
+        (status = 500, description = "Storage or database error", body = ErrorResponse)
+    )
+)]
+pub async fn import_feed(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+    s3_service: web::Data<S3Service>,
+    payload: Multipart,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::series::dsl as series_dsl;
+
+    let upload = read_feed_form(payload).await?;
+
+    let Some(contents) = upload.file.filter(|bytes| !bytes.is_empty()) else {
+        return Ok(json_error(
+            "Upload a Substack export or an RSS or Atom feed",
+        ));
+    };
+    let Some(target_series) = upload.series_id else {
+        return Ok(json_error("A seriesId is required"));
+    };
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let owns_series = diesel::select(diesel::dsl::exists(
+        series_dsl::series
+            .filter(series_dsl::id.eq(target_series))
+            .filter(series_dsl::user_id.eq(user.id))
+            .filter(series_dsl::deleted_at.is_null()),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+    if !owns_series {
+        return Err(ServiceError::Forbidden("Series not found or access denied".to_owned()).into());
+    }
+
+    if has_active_job(&mut conn, user.id, JobKind::FeedImport).await? {
+        return Err(
+            ServiceError::Conflict("A feed import is already in progress".to_owned()).into(),
+        );
+    }
+
+    let input_key = format!("imports/{}/{}", user.id, Uuid::new_v4());
+    let _ = s3_service.put_object(&input_key, contents).await?;
+
+    let now = Utc::now().naive_utc();
+    let expires_at = now
+        .checked_add_signed(Duration::hours(IMPORT_INPUT_TTL_HOURS))
+        .unwrap_or(now);
+    let job_payload = FeedImportPayload {
+        series_id: target_series,
+        input_key: input_key.clone(),
+    };
+    let job = enqueue_job(
+        &mut conn,
+        user.id,
+        JobKind::FeedImport,
+        serde_json::to_value(&job_payload).map_err(ServiceError::from)?,
+        Some((input_key, expires_at)),
+    )
+    .await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::FeedImportRequested,
+        json!({ "job_id": job.id, "series_id": target_series }),
+    )
+    .await;
+
+    Ok(HttpResponse::Accepted().json(JobResponse::from(job)))
+}
@@ -0,0 +1,56 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        auth::User,
+        invitations::{Invitation, InvitationResponse, InvitationsListResponse},
+    },
+    services::db::DbService,
+};
+
+/// List the signed-in creator's member invitations
+///
+/// Returns the newest invitations first.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/invitations",
+    context_path = "/api",
+    tag = "Tiers",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Member invitations, newest first", body = InvitationsListResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn list_invitations(
+    user: User,
+    db_service: web::Data<DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::invitations::dsl as invitations_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let invitations: Vec<Invitation> = invitations_dsl::invitations
+        .filter(invitations_dsl::creator_id.eq(user.id))
+        .order(invitations_dsl::created_at.desc())
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let responses: Vec<InvitationResponse> = invitations
+        .into_iter()
+        .map(InvitationResponse::from)
+        .collect();
+    Ok(HttpResponse::Ok().json(InvitationsListResponse::from(responses)))
+}
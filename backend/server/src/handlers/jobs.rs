@@ -0,0 +1,102 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        auth::User,
+        jobs::{BackgroundJob, JobResponse, JobsListResponse},
+    },
+    services::db::DbService,
+};
+use uuid::Uuid;
+
+/// How many of the most recent jobs are listed
+const JOBS_LIST_LIMIT: i64 = 50;
+
+/// List the signed-in user's background jobs
+///
+/// Returns the most recent jobs first.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    context_path = "/api",
+    tag = "Jobs",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Background jobs, newest first", body = JobsListResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn list_jobs(
+    user: User,
+    db_service: web::Data<DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::background_jobs::dsl as jobs_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let jobs: Vec<BackgroundJob> = jobs_dsl::background_jobs
+        .filter(jobs_dsl::user_id.eq(user.id))
+        .order(jobs_dsl::created_at.desc())
+        .limit(JOBS_LIST_LIMIT)
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let responses: Vec<JobResponse> = jobs.into_iter().map(JobResponse::from).collect();
+    Ok(HttpResponse::Ok().json(JobsListResponse::from(responses)))
+}
+
+/// Get one of the signed-in user's background jobs
+///
+/// # Errors
+/// Returns an error if the job does not exist, belongs to another user, or
+/// database operations fail.
+#[utoipa::path(
+    get,
+    path = "/jobs/{job_id}",
+    context_path = "/api",
+    tag = "Jobs",
+    params(("job_id" = Uuid, Path, description = "UUID of the job")),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Background job", body = JobResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn get_job(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::background_jobs::dsl as jobs_dsl;
+
+    let job_id = path.into_inner();
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let job: BackgroundJob = jobs_dsl::background_jobs
+        .filter(jobs_dsl::id.eq(job_id))
+        .filter(jobs_dsl::user_id.eq(user.id))
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?
+        .ok_or_else(|| ServiceError::NotFound("Job not found".to_owned()))?;
+
+    Ok(HttpResponse::Ok().json(JobResponse::from(job)))
+}
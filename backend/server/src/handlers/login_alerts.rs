@@ -0,0 +1,258 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use redis::aio::ConnectionManager;
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::User,
+        devices::{DenyLoginQuery, DenyLoginRequest, KnownDevice},
+    },
+    services::{
+        audit::record_event,
+        auth::GoogleOAuthService,
+        db::DbService,
+        devices::{register_login_device, DENY_LOGIN_TTL_DAYS},
+        email::{escape_html, EmailService, HtmlEmailContent},
+        sessions::{client_ip, user_agent, SessionService},
+        tokens::hash_token,
+        user_cache::UserCacheService,
+    },
+};
+use uuid::Uuid;
+
+use super::auth::{hash_password, json_error, store_password_reset_token};
+use super::sessions::sign_out_everywhere;
+
+/// Remember the device of a successful sign-in and alert the user if it is new
+///
+/// Called after every sign-in. The alert carries a "this wasn't me" link that
+/// signs every device out and forces a password reset. Failures are logged
+/// rather than returned so alerts never block signing in.
+pub(crate) async fn alert_if_new_device(req: &HttpRequest, db_service: &DbService, user: &User) {
+    let deny_token = match register_login_device(db_service, req, user.id).await {
+        Ok(Some(token)) => token,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Failed to register sign-in device: {}", e);
+            return;
+        }
+    };
+
+    record_event(
+        db_service,
+        req,
+        Some(user.id),
+        AuditEventType::NewDeviceLogin,
+        json!({}),
+    )
+    .await;
+
+    let (Some(email_service), Some(google_oauth_service)) = (
+        req.app_data::<web::Data<EmailService>>(),
+        req.app_data::<web::Data<GoogleOAuthService>>(),
+    ) else {
+        return;
+    };
+
+    let deny_link = format!(
+        "{}/api/auth/login-alerts/deny?token={}",
+        google_oauth_service.backend_url, deny_token
+    );
+    let device = user_agent(req).unwrap_or_else(|| "Unknown device".to_owned());
+    let ip_address = client_ip(req).unwrap_or_else(|| "unknown".to_owned());
+    let signed_in_at = Utc::now().format("%Y-%m-%d %H:%M UTC");
+
+    let email_body = format!(
+        "<h1>New sign-in to your account</h1>
+        <p>Your Patron account was just signed in to from a device we have not seen before.</p>
+        <p>Device: {}<br>IP address: {}<br>Time: {signed_in_at}</p>
+        <p>If this was you, you can ignore this email.</p>
+        <p>If it wasn't, <a href=\"{deny_link}\">secure your account</a>. This signs out every device and asks you to choose a new password. The link will expire in {DENY_LOGIN_TTL_DAYS} days.</p>",
+        escape_html(&device),
+        escape_html(&ip_address)
+    );
+
+    if let Err(e) = email_service
+        .send_html_email(HtmlEmailContent {
+            to: &user.email,
+            subject: "New sign-in to your Patron account",
+            html_body: &email_body,
+            text_body: None,
+            from: None,
+        })
+        .await
+    {
+        tracing::warn!("Failed to send new device alert: {}", e);
+    }
+}
+
+/// Helper function to render a minimal HTML page for a link opened from an email
+pub(crate) fn email_link_page(title: &str, body: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .insert_header((header::REFERRER_POLICY, "no-referrer"))
+        .body(format!(
+            "<!DOCTYPE html>
+<html lang=\"en\">
+<head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1\"><title>{title}</title></head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>"
+        ))
+}
+
+/// Confirm a sign-in was not made by the user
+///
+/// Target of the "this wasn't me" link in new-device alert emails. Only shows
+/// a page explaining what securing the account does, with a button that posts
+/// the token back, so mail scanners and link previews that fetch the link do
+/// not lock the user out.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    get,
+    path = "/auth/login-alerts/deny",
+    context_path = "/api",
+    tag = "Auth",
+    params(DenyLoginQuery),
+    responses(
+        (status = 200, description = "HTML page asking to confirm, or explaining that the link is invalid, used or expired", content_type = "text/html"),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn confirm_deny_login(
+    db_service: web::Data<DbService>,
+    query: web::Query<DenyLoginQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::known_devices::dsl as devices_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let is_valid = diesel::select(diesel::dsl::exists(
+        devices_dsl::known_devices
+            .filter(devices_dsl::deny_token_hash.eq(hash_token(&query.token)))
+            .filter(devices_dsl::deny_token_expires_at.gt(Utc::now().naive_utc())),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    if !is_valid {
+        return Ok(email_link_page(
+            "Invalid or expired link",
+            "<p>This link has already been used or has expired. If you still think someone else signed in to your account, reset your password.</p>",
+        ));
+    }
+
+    Ok(email_link_page(
+        "Secure your account",
+        &format!(
+            "<p>Securing your account signs out every device, including this one, and asks you to choose a new password.</p>
+<form method=\"post\" action=\"/api/auth/login-alerts/deny\">
+<input type=\"hidden\" name=\"token\" value=\"{}\">
+<button type=\"submit\">Secure my account</button>
+</form>",
+            escape_html(&query.token)
+        ),
+    ))
+}
+
+/// Report a sign-in as not made by the user
+///
+/// Posted from the page the "this wasn't me" link opens. Forgets the device, signs the account out everywhere, revokes refresh tokens and
+/// third-party app access, and replaces the password so only a reset gets the
+/// account back. Redirects to the frontend's reset page with a fresh reset token.
+///
+/// # Errors
+/// Returns an error if database or session store operations fail.
+#[utoipa::path(
+    post,
+    path = "/auth/login-alerts/deny",
+    context_path = "/api",
+    tag = "Auth",
+    request_body(content = DenyLoginRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 303, description = "Account secured, redirect to the password reset page"),
+        (status = 400, description = "Invalid, used or expired link", body = ErrorResponse,
+            example = json!({
+                "error": "Invalid or expired link"
+            })
+        ),
+        (status = 500, description = "Database error or session store unavailable", body = ErrorResponse)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn deny_login(
+    req: HttpRequest,
+    db_service: web::Data<DbService>,
+    session_service: web::Data<SessionService>,
+    user_cache: web::Data<UserCacheService>,
+    redis_manager: web::Data<ConnectionManager>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    form: web::Form<DenyLoginRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::known_devices::dsl as devices_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    let now = Utc::now().naive_utc();
+
+    // Deleting the device in the same statement that looks it up keeps the
+    // link single-use, and the device alerts again if it signs in later.
+    let Some(device) = diesel::delete(
+        devices_dsl::known_devices
+            .filter(devices_dsl::deny_token_hash.eq(hash_token(&form.token)))
+            .filter(devices_dsl::deny_token_expires_at.gt(now)),
+    )
+    .get_result::<KnownDevice>(&mut conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?
+    else {
+        return Ok(json_error("Invalid or expired link"));
+    };
+
+    // A random password nobody knows locks out whoever signed in, while
+    // leaving the account able to request password resets.
+    let locked_password = hash_password(&Uuid::new_v4().to_string())?;
+    let user: User = diesel::update(users_dsl::users.find(device.user_id))
+        .set(users_dsl::password_hash.eq(Some(locked_password)))
+        .get_result(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    sign_out_everywhere(&mut conn, &session_service, &user_cache, user.id).await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::LoginDenied,
+        json!({
+            "deviceUserAgent": device.user_agent,
+            "deviceIpAddress": device.ip_address,
+        }),
+    )
+    .await;
+
+    let reset_token = store_password_reset_token(&redis_manager, user.id).await?;
+    let redirect_url = format!(
+        "{}/reset-password?token={}&user_id={}&reason=login_denied",
+        google_oauth_service.frontend_url, reset_token, user.id
+    );
+    Ok(HttpResponse::SeeOther()
+        .append_header(("Location", redirect_url))
+        .finish())
+}
@@ -0,0 +1,393 @@
+#![allow(clippy::unused_async)]
+
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::{MagicLinkToken, User, UserRole},
+    },
+    services::{
+        audit::record_event,
+        auth::GoogleOAuthService,
+        db::DbService,
+        email::{escape_html, EmailService, HtmlEmailContent},
+        rate_limit::{RateLimitService, RateLimitedAction},
+        sessions::SessionService,
+        tokens::hash_token,
+        user_cache::UserCacheService,
+    },
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::auth::{generate_gravatar_url, json_error, start_session};
+use super::login_alerts::{alert_if_new_device, email_link_page};
+use super::sessions::discard_unverified_credentials;
+
+/// How long a login link stays valid
+const MAGIC_LINK_TTL_MINUTES: i64 = 15;
+
+/// Request body for a passwordless login link
+#[derive(Deserialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "email": "user@example.com"
+}))]
+pub struct MagicLinkRequest {
+    /// Email address to send the login link to
+    #[schema(example = "user@example.com")]
+    pub email: String,
+}
+
+/// Response for a passwordless login link request
+#[derive(Serialize, ToSchema, Debug)]
+#[schema(example = json!({
+    "message": "Check your email for a link to sign in."
+}))]
+pub struct MagicLinkResponse {
+    /// Login link request status message
+    pub message: String,
+}
+
+/// Query parameters of a login link
+#[derive(Deserialize, ToSchema, Debug)]
+pub struct MagicLinkVerifyQuery {
+    /// Token from the login link
+    #[schema(example = "3q2-7wVYo8bLrZpKcZx0dXH1n6bB2gWm")]
+    pub token: String,
+}
+
+/// Form posted from the page a login link opens
+#[derive(Deserialize, ToSchema, Debug)]
+pub struct MagicLinkVerifyRequest {
+    /// Token from the login link
+    #[schema(example = "3q2-7wVYo8bLrZpKcZx0dXH1n6bB2gWm")]
+    pub token: String,
+}
+
+/// Helper function to generate a login link token
+fn generate_magic_link_token() -> String {
+    let mut rng = rand::rng();
+    let charset = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    (0_i32..43_i32)
+        .map(|_| {
+            let idx = rng.random_range(0..62);
+            char::from(*charset.get(idx).unwrap_or(&b'0'))
+        })
+        .collect()
+}
+
+/// Request a login link
+///
+/// Emails a single-use link that signs the user in without a password. If no
+/// account exists for the address one is created when the link is used. The
+/// response is the same whether or not the address is registered.
+///
+/// # Errors
+/// Returns an error if database operations fail or the email cannot be sent.
+#[utoipa::path(
+    post,
+    path = "/auth/magic-link",
+    context_path = "/api",
+    tag = "Auth",
+    request_body(content = MagicLinkRequest, description = "Email address to send the login link to"),
+    responses(
+        (status = 200, description = "Login link sent", body = MagicLinkResponse),
+        (status = 400, description = "Invalid email address", body = ErrorResponse),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
+        (status = 500, description = "Database error or email delivery failed", body = ErrorResponse)
+    )
+)]
+pub async fn request_magic_link(
+    req: HttpRequest,
+    rate_limiter: web::Data<RateLimitService>,
+    email_service: web::Data<EmailService>,
+    db_service: web::Data<DbService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    body: web::Json<MagicLinkRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::magic_link_tokens::dsl as magic_dsl;
+
+    let email = body.email.trim();
+
+    rate_limiter
+        .check_request(RateLimitedAction::MagicLink, &req, Some(email))
+        .await?;
+
+    if email.is_empty() || !email.contains('@') {
+        return Ok(json_error("A valid email address is required"));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    // Only the most recent link for an address works
+    let _ = diesel::delete(
+        magic_dsl::magic_link_tokens
+            .filter(magic_dsl::email.eq(email))
+            .filter(magic_dsl::used_at.is_null()),
+    )
+    .execute(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    let token = generate_magic_link_token();
+    let now = Utc::now().naive_utc();
+    let expires_at = now
+        .checked_add_signed(chrono::Duration::minutes(MAGIC_LINK_TTL_MINUTES))
+        .ok_or_else(|| ServiceError::Unknown("Failed to compute expiration time".to_owned()))?;
+
+    let _ = diesel::insert_into(magic_dsl::magic_link_tokens)
+        .values(&MagicLinkToken {
+            id: Uuid::new_v4(),
+            email: email.to_owned(),
+            token_hash: hash_token(&token),
+            expires_at,
+            used_at: None,
+            created_at: now,
+        })
+        .execute(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let login_link = format!(
+        "{}/api/auth/magic-link/verify?token={}",
+        google_oauth_service.backend_url, token
+    );
+
+    let email_body = format!(
+        "<h1>Sign in to Patron</h1>
+        <p>Click the link below to sign in:</p>
+        <p><a href=\"{login_link}\">Sign in</a></p>
+        <p>This link can be used once and will expire in {MAGIC_LINK_TTL_MINUTES} minutes.</p>
+        <p>If you didn't request this link, you can safely ignore this email.</p>"
+    );
+
+    email_service
+        .send_html_email(HtmlEmailContent {
+            to: email,
+            subject: "Your Patron sign-in link",
+            html_body: &email_body,
+            text_body: None,
+            from: None,
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(MagicLinkResponse {
+        message: "Check your email for a link to sign in.".to_owned(),
+    }))
+}
+
+/// Confirm signing in with a login link
+///
+/// Target of the link in login link emails. Only shows a page with a button
+/// that posts the token back, so mail scanners and link previews that fetch
+/// the link do not use it up before the user clicks it.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    get,
+    path = "/auth/magic-link/verify",
+    context_path = "/api",
+    tag = "Auth",
+    params(
+        ("token" = String, Query, description = "Token from the login link")
+    ),
+    responses(
+        (status = 200, description = "HTML page asking to confirm, or explaining that the link is invalid, used or expired", content_type = "text/html"),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn confirm_magic_link(
+    db_service: web::Data<DbService>,
+    query: web::Query<MagicLinkVerifyQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::magic_link_tokens::dsl as magic_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let is_valid = diesel::select(diesel::dsl::exists(
+        magic_dsl::magic_link_tokens
+            .filter(magic_dsl::token_hash.eq(hash_token(&query.token)))
+            .filter(magic_dsl::used_at.is_null())
+            .filter(magic_dsl::expires_at.gt(Utc::now().naive_utc())),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    if !is_valid {
+        return Ok(email_link_page(
+            "Invalid or expired link",
+            "<p>This login link has already been used or has expired. Request a new one to sign in.</p>",
+        ));
+    }
+
+    Ok(email_link_page(
+        "Sign in to Patron",
+        &format!(
+            "<p>Continue to sign in to your Patron account.</p>
+<form method=\"post\" action=\"/api/auth/magic-link/verify\">
+<input type=\"hidden\" name=\"token\" value=\"{}\">
+<button type=\"submit\">Sign in</button>
+</form>",
+            escape_html(&query.token)
+        ),
+    ))
+}
+
+/// Sign in with a login link
+///
+/// Posted from the page the login link opens. Consumes the link, creating the
+/// account if the address is not registered yet. Following the link proves
+/// ownership of the address, so it is marked verified; an account that was
+/// not verified yet loses its password and every session, since whoever set
+/// them never proved the address.
+///
+/// # Errors
+/// Returns an error if database operations fail or the session cannot be created.
+#[utoipa::path(
+    post,
+    path = "/auth/magic-link/verify",
+    context_path = "/api",
+    tag = "Auth",
+    request_body(content = MagicLinkVerifyRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 303, description = "Signed in, redirect to frontend"),
+        (status = 400, description = "Invalid, used or expired link", body = ErrorResponse,
+            example = json!({
+                "error": "Invalid or expired login link"
+            })
+        ),
+        (status = 500, description = "Database error or session storage failure", body = ErrorResponse)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_lines)]
+pub async fn verify_magic_link(
+    req: HttpRequest,
+    session: Session,
+    session_service: web::Data<SessionService>,
+    user_cache: web::Data<UserCacheService>,
+    db_service: web::Data<DbService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    form: web::Form<MagicLinkVerifyRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::magic_link_tokens::dsl as magic_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let now = Utc::now().naive_utc();
+
+    // Marking the link used in the same statement that looks it up keeps it single-use
+    let Some(magic_link) = diesel::update(
+        magic_dsl::magic_link_tokens
+            .filter(magic_dsl::token_hash.eq(hash_token(&form.token)))
+            .filter(magic_dsl::used_at.is_null())
+            .filter(magic_dsl::expires_at.gt(now)),
+    )
+    .set(magic_dsl::used_at.eq(Some(now)))
+    .get_result::<MagicLinkToken>(&mut conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?
+    else {
+        return Ok(json_error("Invalid or expired login link"));
+    };
+
+    let existing_user = users_dsl::users
+        .filter(users_dsl::email.eq(&magic_link.email))
+        .first::<User>(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?;
+
+    let user = if let Some(existing) = existing_user {
+        if !existing.email_verified {
+            discard_unverified_credentials(&mut conn, &session_service, &user_cache, existing.id)
+                .await?;
+        }
+        diesel::update(users_dsl::users.find(existing.id))
+            .set((
+                users_dsl::email_verified.eq(true),
+                users_dsl::last_login.eq(Some(now)),
+            ))
+            .get_result::<User>(&mut conn)
+            .await
+            .map_err(ServiceError::from)?
+    } else {
+        let new_user = User {
+            id: Uuid::new_v4(),
+            email: magic_link.email.clone(),
+            display_name: None,
+            avatar_url: Some(generate_gravatar_url(&magic_link.email)),
+            email_verified: true,
+            password_hash: None,
+            created_at: None,
+            updated_at: None,
+            last_login: Some(now),
+            description: None,
+            banner: None,
+            session_version: 0,
+            role: UserRole::User.into(),
+            suspended_at: None,
+            suspension_reason: None,
+            suspended_until: None,
+            deletion_scheduled_for: None,
+        };
+
+        diesel::insert_into(users_dsl::users)
+            .values(&new_user)
+            .get_result::<User>(&mut conn)
+            .await
+            .map_err(ServiceError::from)?
+    };
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::Login,
+        json!({ "method": "magic_link" }),
+    )
+    .await;
+
+    let _ = start_session(&session, &session_service, &req, &user).await?;
+    alert_if_new_device(&req, &db_service, &user).await;
+
+    if let Err(e) = diesel::delete(
+        magic_dsl::magic_link_tokens
+            .filter(magic_dsl::email.eq(&magic_link.email))
+            .filter(
+                magic_dsl::used_at
+                    .is_not_null()
+                    .or(magic_dsl::expires_at.le(now)),
+            ),
+    )
+    .execute(&mut conn)
+    .await
+    {
+        tracing::warn!("Failed to clean up login links: {}", e);
+    }
+
+    let redirect_url = format!("{}?magicLink=success", google_oauth_service.frontend_url);
+    Ok(HttpResponse::SeeOther()
+        .append_header(("Location", redirect_url))
+        .finish())
+}
@@ -1,6 +1,39 @@
 /// Authentication-related handlers
 pub mod auth;
 
+/// Passwordless login link handlers
+pub mod magic_link;
+
+/// New-device sign-in alert handlers
+pub mod login_alerts;
+
+/// `OpenID Connect` sign-in and linked identity handlers
+pub mod oidc;
+
+/// `OAuth` authorization server handlers
+pub mod oauth;
+
+/// Self-service account deletion handlers
+pub mod account_deletion;
+
+/// Personal data export handlers
+pub mod data_export;
+
+/// Background job status handlers
+pub mod jobs;
+
+/// Session inventory handlers
+pub mod sessions;
+
+/// Security activity handlers
+pub mod audit;
+
+/// Staff-only admin handlers
+pub mod admin;
+
+/// Native client access and refresh token handlers
+pub mod tokens;
+
 /// User file management handlers
 pub mod user_files;
 
@@ -10,6 +43,30 @@ pub mod series;
 /// Posts management handlers
 pub mod posts;
 
+/// Post page handlers
+pub mod post_pages;
+
+/// Post attachment handlers
+pub mod post_attachments;
+
+/// Series book export handlers
+pub mod series_export;
+
+/// Bulk post import handlers
+pub mod post_import;
+
+/// Patreon export import handlers
+pub mod patreon_import;
+
+/// Substack export and RSS/Atom feed import handlers
+pub mod feed_import;
+
+/// Membership tier handlers
+pub mod tiers;
+
+/// Member invitation handlers
+pub mod invitations;
+
 /// API keys management handlers
 pub mod api_keys;
 
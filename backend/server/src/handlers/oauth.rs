@@ -0,0 +1,911 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{http::header, web, HttpMessage, HttpRequest, HttpResponse, ResponseError, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use rand::Rng;
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::User,
+        invitations::{
+            Invitation, InvitationStatus, MembershipResponse, MembershipsListResponse,
+            MembershipsQuery,
+        },
+        oauth::{
+            AuthorizeParams, ConsentClient, ConsentDecisionRequest, ConsentDecisionResponse,
+            ConsentResponse, CreateOAuthClientRequest, CreateOAuthClientResponse,
+            IntrospectionResponse, OAuthAuthorizationCode, OAuthClient, OAuthClientResponse,
+            OAuthClientsListResponse, OAuthErrorResponse, OAuthGrant, OAuthScope, OAuthToken,
+            OAuthUserInfoResponse, ScopeDescription, TokenLookupRequest, TokenRequest,
+            TokenResponse, ACCESS_TOKEN_PREFIX, CLIENT_SECRET_PREFIX, REFRESH_TOKEN_PREFIX,
+        },
+        tiers::Tier,
+    },
+    services::{audit::record_event, db::DbService, tokens::hash_token},
+};
+use uuid::Uuid;
+
+use super::auth::json_error;
+
+/// How long an authorization code can be exchanged for tokens
+const AUTHORIZATION_CODE_TTL_MINUTES: i64 = 10;
+/// Lifetime of access tokens
+const ACCESS_TOKEN_TTL_SECONDS: i64 = 3600;
+/// Lifetime of refresh tokens
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+/// Helper function to generate a random secret with a recognizable prefix
+fn generate_secret(prefix: &str) -> String {
+    let mut rng = rand::rng();
+    let charset = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let random_part: String = (0_i32..40_i32)
+        .map(|_| {
+            let idx = rng.random_range(0..62);
+            char::from(*charset.get(idx).unwrap_or(&b'0'))
+        })
+        .collect();
+    format!("{prefix}{random_part}")
+}
+
+/// Helper function to build an `OAuth` error response (RFC 6749 section 5.2)
+fn oauth_error(error: &str, description: &str) -> HttpResponse {
+    let body = OAuthErrorResponse {
+        error: error.to_owned(),
+        error_description: description.to_owned(),
+    };
+    if error == "invalid_client" {
+        HttpResponse::Unauthorized().json(body)
+    } else {
+        HttpResponse::BadRequest().json(body)
+    }
+}
+
+/// Helper function to check that a redirect URI is safe to register
+///
+/// Redirect URIs must be absolute, without a fragment, and use HTTPS unless
+/// they point at the local machine. Custom schemes are allowed for native apps.
+fn is_valid_redirect_uri(uri: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(uri) else {
+        return false;
+    };
+    if parsed.fragment().is_some() {
+        return false;
+    }
+    match parsed.scheme() {
+        "http" => matches!(parsed.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")),
+        "javascript" | "data" | "file" => false,
+        _ => true,
+    }
+}
+
+/// Helper function to append query parameters to a redirect URI
+fn append_query(uri: &str, params: &[(&str, &str)]) -> String {
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    let query = params
+        .iter()
+        .map(|&(key, value)| format!("{key}={}", urlencoding::encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{uri}{separator}{query}")
+}
+
+/// Validate an authorization request and resolve its client and scopes
+async fn validate_authorize_params(
+    db_service: &DbService,
+    params: &AuthorizeParams,
+) -> Result<(OAuthClient, Vec<OAuthScope>), HttpResponse> {
+    use shared::schema::oauth_clients::dsl as clients_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool
+        .get()
+        .await
+        .map_err(|e| ServiceError::from(e).error_response())?;
+
+    let client = clients_dsl::oauth_clients
+        .find(params.client_id)
+        .first::<OAuthClient>(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| ServiceError::from(e).error_response())?
+        .ok_or_else(|| json_error("Unknown client"))?;
+
+    if !client.has_redirect_uri(&params.redirect_uri) {
+        return Err(json_error("Redirect URI is not registered for this client"));
+    }
+    if params.response_type != "code" {
+        return Err(json_error("Only the authorization code flow is supported"));
+    }
+    if params.code_challenge_method != "S256" || params.code_challenge.len() != 43 {
+        return Err(json_error("A PKCE S256 code challenge is required"));
+    }
+
+    let requested = OAuthScope::parse_list(params.scope.as_deref().unwrap_or("identity"))
+        .map_err(|unknown| json_error(&format!("Unknown scope: {unknown}")))?;
+    if requested.is_empty() {
+        return Err(json_error("At least one scope is required"));
+    }
+    let allowed = OAuthScope::parse_list(&client.allowed_scopes).unwrap_or_default();
+    if let Some(scope) = requested.iter().find(|scope| !allowed.contains(scope)) {
+        return Err(json_error(&format!(
+            "Client may not request the {} scope",
+            scope.as_str()
+        )));
+    }
+
+    Ok((client, requested))
+}
+
+/// Authenticate the client calling the token, revocation or introspection endpoint
+///
+/// Credentials are read from HTTP Basic authentication or the request body.
+/// Public clients only need to send their client ID.
+async fn authenticate_client(
+    req: &HttpRequest,
+    conn: &mut diesel_async::AsyncPgConnection,
+    body_client_id: Option<Uuid>,
+    body_client_secret: Option<&str>,
+) -> Result<OAuthClient, HttpResponse> {
+    use shared::schema::oauth_clients::dsl as clients_dsl;
+
+    let basic_credentials = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|encoded| STANDARD.decode(encoded).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|credentials| {
+            let (id, secret) = credentials.split_once(':')?;
+            Some((
+                urlencoding::decode(id).ok()?.into_owned(),
+                urlencoding::decode(secret).ok()?.into_owned(),
+            ))
+        });
+
+    let (presented_id, client_secret) = match basic_credentials {
+        Some((id, secret)) => (Uuid::parse_str(&id).ok(), Some(secret)),
+        None => (body_client_id, body_client_secret.map(ToOwned::to_owned)),
+    };
+
+    let Some(client_id) = presented_id else {
+        return Err(oauth_error(
+            "invalid_client",
+            "Client authentication failed",
+        ));
+    };
+
+    let client = clients_dsl::oauth_clients
+        .find(client_id)
+        .first::<OAuthClient>(conn)
+        .await
+        .optional()
+        .map_err(|e| ServiceError::from(e).error_response())?
+        .ok_or_else(|| oauth_error("invalid_client", "Client authentication failed"))?;
+
+    if let Some(ref secret_hash) = client.client_secret_hash {
+        let presented = client_secret.as_deref().map(hash_token);
+        if presented.as_deref() != Some(secret_hash.as_str()) {
+            return Err(oauth_error(
+                "invalid_client",
+                "Client authentication failed",
+            ));
+        }
+    }
+
+    Ok(client)
+}
+
+/// Issue a new access and refresh token pair
+async fn issue_tokens(
+    conn: &mut diesel_async::AsyncPgConnection,
+    client_id: Uuid,
+    user_id: Uuid,
+    scope: &str,
+) -> Result<TokenResponse, ServiceError> {
+    use shared::schema::oauth_tokens::dsl as tokens_dsl;
+
+    let access_token = generate_secret(ACCESS_TOKEN_PREFIX);
+    let refresh_token = generate_secret(REFRESH_TOKEN_PREFIX);
+    let now = Utc::now().naive_utc();
+
+    let _ = diesel::insert_into(tokens_dsl::oauth_tokens)
+        .values(&OAuthToken {
+            id: Uuid::new_v4(),
+            client_id,
+            user_id,
+            access_token_hash: hash_token(&access_token),
+            refresh_token_hash: Some(hash_token(&refresh_token)),
+            scope: scope.to_owned(),
+            access_expires_at: now
+                .checked_add_signed(Duration::seconds(ACCESS_TOKEN_TTL_SECONDS))
+                .unwrap_or(now),
+            refresh_expires_at: now.checked_add_signed(Duration::days(REFRESH_TOKEN_TTL_DAYS)),
+            revoked_at: None,
+            created_at: now,
+            last_used_at: None,
+        })
+        .execute(conn)
+        .await?;
+
+    Ok(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_owned(),
+        expires_in: ACCESS_TOKEN_TTL_SECONDS,
+        refresh_token,
+        scope: scope.to_owned(),
+    })
+}
+
+/// Register an `OAuth` client
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    post,
+    path = "/oauth/clients",
+    context_path = "/api",
+    tag = "OAuth",
+    request_body(content = CreateOAuthClientRequest, description = "Client registration data"),
+    responses(
+        (status = 201, description = "Client registered", body = CreateOAuthClientResponse),
+        (status = 400, description = "Invalid name or redirect URIs", body = ErrorResponse),
+        (status = 401, description = "Authentication required to register clients", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn create_client(
+    user: User,
+    db_service: web::Data<DbService>,
+    body: web::Json<CreateOAuthClientRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::oauth_clients::dsl as clients_dsl;
+
+    if body.name.trim().is_empty() {
+        return Ok(json_error("Client name is required"));
+    }
+    if body.redirect_uris.is_empty() {
+        return Ok(json_error("At least one redirect URI is required"));
+    }
+    if let Some(uri) = body
+        .redirect_uris
+        .iter()
+        .find(|uri| !is_valid_redirect_uri(uri))
+    {
+        return Ok(json_error(&format!("Invalid redirect URI: {uri}")));
+    }
+
+    let scopes = body
+        .scopes
+        .clone()
+        .filter(|scopes| !scopes.is_empty())
+        .unwrap_or_else(|| OAuthScope::ALL.to_vec());
+    let client_secret = body
+        .confidential
+        .then(|| generate_secret(CLIENT_SECRET_PREFIX));
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let now = Utc::now().naive_utc();
+    let client: OAuthClient = diesel::insert_into(clients_dsl::oauth_clients)
+        .values(&OAuthClient {
+            id: Uuid::new_v4(),
+            owner_id: user.id,
+            name: body.name.trim().to_owned(),
+            client_secret_hash: client_secret.as_deref().map(hash_token),
+            redirect_uris: body.redirect_uris.iter().cloned().map(Some).collect(),
+            allowed_scopes: OAuthScope::format_list(&scopes),
+            created_at: now,
+            updated_at: now,
+        })
+        .get_result(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    Ok(HttpResponse::Created().json(CreateOAuthClientResponse {
+        client: client.into(),
+        client_secret,
+    }))
+}
+
+/// List registered `OAuth` clients
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    get,
+    path = "/oauth/clients",
+    context_path = "/api",
+    tag = "OAuth",
+    responses(
+        (status = 200, description = "Clients registered by the authenticated user", body = OAuthClientsListResponse),
+        (status = 401, description = "Authentication required to list clients", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn list_clients(
+    user: User,
+    db_service: web::Data<DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::oauth_clients::dsl as clients_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let clients: Vec<OAuthClientResponse> = clients_dsl::oauth_clients
+        .filter(clients_dsl::owner_id.eq(user.id))
+        .order(clients_dsl::created_at.desc())
+        .load::<OAuthClient>(&mut conn)
+        .await
+        .map_err(ServiceError::from)?
+        .into_iter()
+        .map(OAuthClientResponse::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(OAuthClientsListResponse::from(clients)))
+}
+
+/// Delete an `OAuth` client
+///
+/// Every token issued to the client stops working.
+///
+/// # Errors
+/// Returns an error if the client does not exist or database operations fail.
+#[utoipa::path(
+    delete,
+    path = "/oauth/clients/{client_id}",
+    context_path = "/api",
+    tag = "OAuth",
+    params(("client_id" = Uuid, Path, description = "Client ID")),
+    responses(
+        (status = 204, description = "Client deleted"),
+        (status = 401, description = "Authentication required to delete clients", body = ErrorResponse),
+        (status = 404, description = "Client not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn delete_client(
+    user: User,
+    db_service: web::Data<DbService>,
+    client_id: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::oauth_clients::dsl as clients_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let deleted = diesel::delete(
+        clients_dsl::oauth_clients
+            .filter(clients_dsl::id.eq(*client_id))
+            .filter(clients_dsl::owner_id.eq(user.id)),
+    )
+    .execute(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    if deleted == 0 {
+        return Err(ServiceError::NotFound("Client not found".to_owned()).into());
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Consent screen data
+///
+/// Validates an authorization request and returns what the frontend needs to
+/// ask the signed-in user for consent.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    get,
+    path = "/oauth/authorize",
+    context_path = "/api",
+    tag = "OAuth",
+    params(AuthorizeParams),
+    responses(
+        (status = 200, description = "Authorization request is valid", body = ConsentResponse),
+        (status = 400, description = "Unknown client, unregistered redirect URI, missing PKCE challenge or invalid scope", body = ErrorResponse),
+        (status = 401, description = "User must sign in first", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = []))
+)]
+pub async fn get_consent(
+    _user: User,
+    db_service: web::Data<DbService>,
+    params: web::Query<AuthorizeParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (client, scopes) = match validate_authorize_params(&db_service, &params).await {
+        Ok(validated) => validated,
+        Err(response) => return Ok(response),
+    };
+
+    Ok(HttpResponse::Ok().json(ConsentResponse {
+        client: ConsentClient {
+            client_id: client.id,
+            name: client.name,
+        },
+        scopes: scopes
+            .into_iter()
+            .map(|scope| ScopeDescription {
+                name: scope,
+                description: scope.description().to_owned(),
+            })
+            .collect(),
+        redirect_uri: params.redirect_uri.clone(),
+    }))
+}
+
+/// Record a consent decision
+///
+/// Returns the URI to send the user back to the client with, carrying either
+/// an authorization code or an `access_denied` error.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    post,
+    path = "/oauth/authorize",
+    context_path = "/api",
+    tag = "OAuth",
+    request_body(content = ConsentDecisionRequest, description = "Authorization request and the user's decision"),
+    responses(
+        (status = 200, description = "Decision recorded", body = ConsentDecisionResponse),
+        (status = 400, description = "Unknown client, unregistered redirect URI, missing PKCE challenge or invalid scope", body = ErrorResponse),
+        (status = 401, description = "User must sign in first", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = []))
+)]
+pub async fn decide_consent(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+    body: web::Json<ConsentDecisionRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::oauth_authorization_codes::dsl as codes_dsl;
+
+    let params = &body.params;
+    let (client, scopes) = match validate_authorize_params(&db_service, params).await {
+        Ok(validated) => validated,
+        Err(response) => return Ok(response),
+    };
+    let state = params.state.as_deref();
+
+    if !body.approve {
+        let mut query = vec![("error", "access_denied")];
+        query.extend(state.map(|value| ("state", value)));
+        return Ok(HttpResponse::Ok().json(ConsentDecisionResponse {
+            redirect_to: append_query(&params.redirect_uri, &query),
+        }));
+    }
+
+    let code = generate_secret("");
+    let now = Utc::now().naive_utc();
+    let expires_at = now
+        .checked_add_signed(Duration::minutes(AUTHORIZATION_CODE_TTL_MINUTES))
+        .ok_or_else(|| ServiceError::Unknown("Failed to compute expiration time".to_owned()))?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let _ = diesel::insert_into(codes_dsl::oauth_authorization_codes)
+        .values(&OAuthAuthorizationCode {
+            id: Uuid::new_v4(),
+            code_hash: hash_token(&code),
+            client_id: client.id,
+            user_id: user.id,
+            redirect_uri: params.redirect_uri.clone(),
+            scope: OAuthScope::format_list(&scopes),
+            code_challenge: params.code_challenge.clone(),
+            expires_at,
+            created_at: now,
+        })
+        .execute(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::OAuthAppAuthorized,
+        json!({
+            "clientId": client.id,
+            "clientName": client.name,
+            "scope": OAuthScope::format_list(&scopes),
+        }),
+    )
+    .await;
+
+    let mut query = vec![("code", code.as_str())];
+    query.extend(state.map(|value| ("state", value)));
+    Ok(HttpResponse::Ok().json(ConsentDecisionResponse {
+        redirect_to: append_query(&params.redirect_uri, &query),
+    }))
+}
+
+/// Exchange an authorization code for tokens
+async fn exchange_authorization_code(
+    conn: &mut diesel_async::AsyncPgConnection,
+    client: &OAuthClient,
+    body: &TokenRequest,
+) -> Result<HttpResponse, ServiceError> {
+    use shared::schema::oauth_authorization_codes::dsl as codes_dsl;
+
+    let (Some(code), Some(redirect_uri), Some(verifier)) = (
+        body.code.as_deref(),
+        body.redirect_uri.as_deref(),
+        body.code_verifier.as_deref(),
+    ) else {
+        return Ok(oauth_error(
+            "invalid_request",
+            "code, redirect_uri and code_verifier are required",
+        ));
+    };
+
+    // Deleting the code as it is read makes it single-use
+    let Some(authorization) = diesel::delete(
+        codes_dsl::oauth_authorization_codes.filter(codes_dsl::code_hash.eq(hash_token(code))),
+    )
+    .get_result::<OAuthAuthorizationCode>(conn)
+    .await
+    .optional()?
+    else {
+        return Ok(oauth_error(
+            "invalid_grant",
+            "Authorization code is invalid or expired",
+        ));
+    };
+
+    if !authorization.is_redeemable(client.id, redirect_uri, verifier, Utc::now().naive_utc()) {
+        return Ok(oauth_error(
+            "invalid_grant",
+            "Authorization code is invalid or expired",
+        ));
+    }
+
+    let tokens = issue_tokens(conn, client.id, authorization.user_id, &authorization.scope).await?;
+    Ok(token_response(&tokens))
+}
+
+/// Exchange a refresh token for a new token pair, revoking the old pair
+async fn exchange_refresh_token(
+    conn: &mut diesel_async::AsyncPgConnection,
+    client: &OAuthClient,
+    body: &TokenRequest,
+) -> Result<HttpResponse, ServiceError> {
+    use shared::schema::oauth_tokens::dsl as tokens_dsl;
+
+    let Some(refresh_token) = body.refresh_token.as_deref() else {
+        return Ok(oauth_error("invalid_request", "refresh_token is required"));
+    };
+
+    let now = Utc::now().naive_utc();
+    let Some(previous) = diesel::update(
+        tokens_dsl::oauth_tokens
+            .filter(tokens_dsl::refresh_token_hash.eq(hash_token(refresh_token)))
+            .filter(tokens_dsl::client_id.eq(client.id))
+            .filter(tokens_dsl::revoked_at.is_null())
+            .filter(tokens_dsl::refresh_expires_at.gt(now)),
+    )
+    .set(tokens_dsl::revoked_at.eq(Some(now)))
+    .get_result::<OAuthToken>(conn)
+    .await
+    .optional()?
+    else {
+        return Ok(oauth_error(
+            "invalid_grant",
+            "Refresh token is invalid or expired",
+        ));
+    };
+
+    let tokens = issue_tokens(conn, client.id, previous.user_id, &previous.scope).await?;
+    Ok(token_response(&tokens))
+}
+
+/// Helper function to send tokens with the caching headers RFC 6749 requires
+fn token_response(tokens: &TokenResponse) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .insert_header((header::PRAGMA, "no-cache"))
+        .json(tokens)
+}
+
+/// Token endpoint
+///
+/// Supports the `authorization_code` grant (with PKCE) and the
+/// `refresh_token` grant. Refresh tokens are rotated on every use.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    post,
+    path = "/oauth/token",
+    context_path = "/api",
+    tag = "OAuth",
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Tokens issued", body = TokenResponse),
+        (status = 400, description = "Invalid request or grant", body = OAuthErrorResponse),
+        (status = 401, description = "Client authentication failed", body = OAuthErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn token(
+    req: HttpRequest,
+    db_service: web::Data<DbService>,
+    body: web::Form<TokenRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let client = match authenticate_client(
+        &req,
+        &mut conn,
+        body.client_id,
+        body.client_secret.as_deref(),
+    )
+    .await
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    Ok(match body.grant_type.as_str() {
+        "authorization_code" => exchange_authorization_code(&mut conn, &client, &body).await?,
+        "refresh_token" => exchange_refresh_token(&mut conn, &client, &body).await?,
+        _ => oauth_error(
+            "unsupported_grant_type",
+            "Only authorization_code and refresh_token grants are supported",
+        ),
+    })
+}
+
+/// Revoke a token (RFC 7009)
+///
+/// Revokes the token pair the given access or refresh token belongs to.
+/// Unknown tokens are ignored, as the RFC requires.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    post,
+    path = "/oauth/revoke",
+    context_path = "/api",
+    tag = "OAuth",
+    request_body(content = TokenLookupRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Token revoked or unknown"),
+        (status = 401, description = "Client authentication failed", body = OAuthErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn revoke(
+    req: HttpRequest,
+    db_service: web::Data<DbService>,
+    body: web::Form<TokenLookupRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::oauth_tokens::dsl as tokens_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let client = match authenticate_client(
+        &req,
+        &mut conn,
+        body.client_id,
+        body.client_secret.as_deref(),
+    )
+    .await
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    let token_hash = hash_token(&body.token);
+    let _ = diesel::update(
+        tokens_dsl::oauth_tokens
+            .filter(tokens_dsl::client_id.eq(client.id))
+            .filter(
+                tokens_dsl::access_token_hash
+                    .eq(&token_hash)
+                    .or(tokens_dsl::refresh_token_hash.eq(&token_hash)),
+            )
+            .filter(tokens_dsl::revoked_at.is_null()),
+    )
+    .set(tokens_dsl::revoked_at.eq(Some(Utc::now().naive_utc())))
+    .execute(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Introspect a token (RFC 7662)
+///
+/// Clients can only introspect tokens issued to themselves; any other token
+/// is reported as inactive.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    post,
+    path = "/oauth/introspect",
+    context_path = "/api",
+    tag = "OAuth",
+    request_body(content = TokenLookupRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Token state", body = IntrospectionResponse),
+        (status = 401, description = "Client authentication failed", body = OAuthErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn introspect(
+    req: HttpRequest,
+    db_service: web::Data<DbService>,
+    body: web::Form<TokenLookupRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::oauth_tokens::dsl as tokens_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let client = match authenticate_client(
+        &req,
+        &mut conn,
+        body.client_id,
+        body.client_secret.as_deref(),
+    )
+    .await
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    let now = Utc::now().naive_utc();
+    let token_hash = hash_token(&body.token);
+    let found = tokens_dsl::oauth_tokens
+        .filter(tokens_dsl::client_id.eq(client.id))
+        .filter(tokens_dsl::revoked_at.is_null())
+        .filter(
+            tokens_dsl::access_token_hash
+                .eq(&token_hash)
+                .or(tokens_dsl::refresh_token_hash.eq(&token_hash)),
+        )
+        .first::<OAuthToken>(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?;
+
+    let active = found.and_then(|token| {
+        let expires_at = if token.access_token_hash == token_hash {
+            token.access_expires_at
+        } else {
+            token.refresh_expires_at?
+        };
+        (expires_at > now).then_some((token, expires_at))
+    });
+
+    let response = match active {
+        Some((token, expires_at)) => IntrospectionResponse {
+            active: true,
+            scope: Some(token.scope),
+            client_id: Some(token.client_id),
+            sub: Some(token.user_id),
+            exp: Some(expires_at.and_utc().timestamp()),
+        },
+        None => IntrospectionResponse {
+            active: false,
+            scope: None,
+            client_id: None,
+            sub: None,
+            exp: None,
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// `OAuth` user info
+///
+/// Returns the identity of the user an access token acts for. Requires the
+/// `identity` scope; the email address is only included when the token also
+/// has the `email` scope.
+#[utoipa::path(
+    get,
+    path = "/oauth/userinfo",
+    context_path = "/api",
+    tag = "OAuth",
+    responses(
+        (status = 200, description = "User identity", body = OAuthUserInfoResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Access token lacks the identity scope", body = ErrorResponse)
+    ),
+    security(("bearerAuth" = []))
+)]
+pub async fn userinfo(req: HttpRequest, user: User) -> HttpResponse {
+    // Requests signed in without an OAuth token are the user asking about themselves
+    let include_email = req
+        .extensions()
+        .get::<OAuthGrant>()
+        .map_or(true, |grant| grant.scopes.contains(&OAuthScope::Email));
+
+    HttpResponse::Ok().json(OAuthUserInfoResponse {
+        sub: user.id,
+        name: user.display_name,
+        picture: user.avatar_url,
+        email: include_email.then_some(user.email),
+        email_verified: include_email.then_some(user.email_verified),
+    })
+}
+
+/// List the signed-in reader's memberships
+///
+/// Returns the creators the user has been invited to support, newest first,
+/// with the tier they were offered. Memberships are matched by email address,
+/// so none are returned until the address is verified. Requires the
+/// `memberships:read` scope.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/oauth/memberships",
+    context_path = "/api",
+    tag = "OAuth",
+    params(MembershipsQuery),
+    responses(
+        (status = 200, description = "Memberships, newest first", body = MembershipsListResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Access token lacks the memberships:read scope", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("bearerAuth" = []))
+)]
+pub async fn list_memberships(
+    user: User,
+    db_service: web::Data<DbService>,
+    query: web::Query<MembershipsQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::invitations::dsl as invitations_dsl;
+    use shared::schema::tiers::dsl as tiers_dsl;
+
+    if !user.email_verified {
+        return Ok(HttpResponse::Ok().json(MembershipsListResponse::from(Vec::new())));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let mut memberships_query = invitations_dsl::invitations
+        .left_join(tiers_dsl::tiers)
+        .filter(invitations_dsl::email.eq(user.email.to_lowercase()))
+        .filter(invitations_dsl::status.ne(InvitationStatus::Revoked.as_str()))
+        .into_boxed();
+    if let Some(creator_id) = query.creator_id {
+        memberships_query = memberships_query.filter(invitations_dsl::creator_id.eq(creator_id));
+    }
+
+    let rows: Vec<(Invitation, Option<Tier>)> = memberships_query
+        .order(invitations_dsl::created_at.desc())
+        .select((Invitation::as_select(), Option::<Tier>::as_select()))
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let memberships: Vec<MembershipResponse> = rows
+        .into_iter()
+        .map(|(invitation, tier)| MembershipResponse::new(invitation, tier))
+        .collect();
+    Ok(HttpResponse::Ok().json(MembershipsListResponse::from(memberships)))
+}
@@ -0,0 +1,693 @@
+#![allow(clippy::unused_async)]
+
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::{load_password_hash, AuthCallbackQuery, User, UserRole, UserSession},
+        identities::{
+            OidcProviderResponse, OidcProvidersListResponse, UserIdentitiesListResponse,
+            UserIdentity, UserIdentityResponse,
+        },
+    },
+    services::{
+        audit::record_event,
+        auth::GoogleOAuthService,
+        db::DbService,
+        oidc::{OidcProvider, OidcService, OidcUserInfo},
+        sessions::SessionService,
+        user_cache::UserCacheService,
+    },
+};
+use uuid::Uuid;
+
+use super::auth::{generate_gravatar_url, json_error, start_session};
+use super::login_alerts::alert_if_new_device;
+use super::sessions::discard_unverified_credentials;
+
+/// Session key holding the `OAuth` state parameter of the pending authorization
+const OAUTH_STATE_KEY: &str = "oauth_state";
+/// Session key holding the provider the pending authorization was started with
+const OAUTH_PROVIDER_KEY: &str = "oauth_provider";
+/// Session key holding where to send the user once the authorization completes
+const OAUTH_REDIRECT_URI_KEY: &str = "oauth_redirect_uri";
+/// Session key holding the user an identity is being linked to, if any
+const OAUTH_LINK_USER_KEY: &str = "oauth_link_user_id";
+
+/// Store the pending authorization in the session and redirect to the provider
+///
+/// When `link_user_id` is set the callback links the identity to that user
+/// instead of signing in.
+///
+/// # Errors
+/// Returns an error if the provider is unknown, its discovery document cannot
+/// be fetched, or the session cannot be written.
+pub(crate) async fn begin_authorization(
+    session: &Session,
+    oidc_service: &OidcService,
+    provider_name: &str,
+    redirect_uri: &str,
+    link_user_id: Option<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let provider = oidc_service.provider(provider_name)?;
+    let state = Uuid::new_v4().to_string();
+    let auth_url = oidc_service.authorization_url(provider, &state).await?;
+
+    let store = |key: &str, value: &str| {
+        session
+            .insert(key, value)
+            .map_err(|e| ServiceError::Unknown(format!("Failed to store OAuth session: {e}")))
+    };
+    store(OAUTH_STATE_KEY, &state)?;
+    store(OAUTH_PROVIDER_KEY, provider.name())?;
+    store(OAUTH_REDIRECT_URI_KEY, redirect_uri)?;
+    match link_user_id {
+        Some(user_id) => store(OAUTH_LINK_USER_KEY, &user_id.to_string())?,
+        None => {
+            let _ = session.remove(OAUTH_LINK_USER_KEY);
+        }
+    }
+
+    Ok(HttpResponse::Found()
+        .append_header(("Location", auth_url))
+        .finish())
+}
+
+/// Helper function to verify the `OAuth` state and provider of a callback
+fn verify_oauth_state(
+    session: &Session,
+    provider_name: &str,
+    received_state: &str,
+) -> Result<(), HttpResponse> {
+    let stored_state = session
+        .get::<String>(OAUTH_STATE_KEY)
+        .map_err(|_session_err| json_error("Session error"))?;
+    let stored_provider = session
+        .get::<String>(OAUTH_PROVIDER_KEY)
+        .map_err(|_session_err| json_error("Session error"))?;
+
+    let Some(stored_oauth_state) = stored_state else {
+        return Err(json_error("Invalid OAuth state"));
+    };
+
+    if stored_oauth_state != received_state {
+        return Err(json_error("OAuth state mismatch"));
+    }
+
+    if stored_provider.as_deref() != Some(provider_name) {
+        return Err(json_error("OAuth provider mismatch"));
+    }
+
+    Ok(())
+}
+
+/// Exchange the authorization code and either sign the user in or link the identity
+///
+/// # Errors
+/// Returns an error if the state does not match, the provider rejects the code,
+/// the identity belongs to someone else, or database operations fail.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn complete_authorization(
+    req: &HttpRequest,
+    session: &Session,
+    session_service: &SessionService,
+    user_cache: &UserCacheService,
+    db_service: &DbService,
+    oidc_service: &OidcService,
+    google_oauth_service: &GoogleOAuthService,
+    provider_name: &str,
+    query: &AuthCallbackQuery,
+) -> Result<HttpResponse, actix_web::Error> {
+    if let Err(response) = verify_oauth_state(session, provider_name, &query.state) {
+        return Ok(response);
+    }
+
+    let redirect_uri = session
+        .get::<String>(OAUTH_REDIRECT_URI_KEY)
+        .map_err(|e| {
+            ServiceError::Unknown(format!("Failed to get redirect URI from session: {e}"))
+        })?
+        .unwrap_or_else(|| google_oauth_service.frontend_url.clone());
+    let link_user_id = session
+        .get::<String>(OAUTH_LINK_USER_KEY)
+        .ok()
+        .flatten()
+        .and_then(|id| Uuid::parse_str(&id).ok());
+
+    for key in [
+        OAUTH_STATE_KEY,
+        OAUTH_PROVIDER_KEY,
+        OAUTH_REDIRECT_URI_KEY,
+        OAUTH_LINK_USER_KEY,
+    ] {
+        let _ = session.remove(key);
+    }
+
+    let provider = oidc_service.provider(provider_name)?;
+    let user_info = oidc_service
+        .authenticate(provider, &query.code, &query.state)
+        .await?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    if let Some(user_id) = link_user_id {
+        // The link must finish in the same signed-in session that started it
+        let current_user_id = session
+            .get::<UserSession>(UserSession::KEY)
+            .ok()
+            .flatten()
+            .map(|user_session| user_session.user_id);
+        if current_user_id != Some(user_id) {
+            return Ok(json_error("Sign in again to link this account"));
+        }
+
+        link_identity(&mut conn, user_id, provider.name(), &user_info).await?;
+
+        record_event(
+            db_service,
+            req,
+            Some(user_id),
+            AuditEventType::IdentityLinked,
+            json!({ "provider": provider.name() }),
+        )
+        .await;
+
+        return Ok(HttpResponse::Found()
+            .append_header(("Location", redirect_uri))
+            .finish());
+    }
+
+    let user =
+        sign_in_with_identity(&mut conn, session_service, user_cache, provider, &user_info).await?;
+
+    record_event(
+        db_service,
+        req,
+        Some(user.id),
+        AuditEventType::Login,
+        json!({ "method": provider.name() }),
+    )
+    .await;
+
+    let _ = start_session(session, session_service, req, &user).await?;
+    alert_if_new_device(req, db_service, &user).await;
+
+    Ok(HttpResponse::Found()
+        .append_header(("Location", redirect_uri))
+        .finish())
+}
+
+/// Helper function to resolve the user an external identity signs in as
+///
+/// Known identities sign in as their user. Otherwise a new account is created
+/// for the verified email address, and the identity is linked for next time.
+/// An existing account with that address is only signed in to when the
+/// provider is trusted to vouch for its addresses; for any other provider
+/// the user has to sign in first and link the identity from their account.
+/// Signing in to an unverified account removes its password and signs out
+/// everyone else, since whoever set that password never proved the address.
+async fn sign_in_with_identity(
+    conn: &mut diesel_async::AsyncPgConnection,
+    session_service: &SessionService,
+    user_cache: &UserCacheService,
+    provider: &OidcProvider,
+    user_info: &OidcUserInfo,
+) -> Result<User, ServiceError> {
+    use shared::schema::user_identities::dsl as identities_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    let provider_name = provider.name();
+    let now = Utc::now().naive_utc();
+    let existing_identity = identities_dsl::user_identities
+        .filter(identities_dsl::provider.eq(provider_name))
+        .filter(identities_dsl::subject.eq(&user_info.subject))
+        .first::<UserIdentity>(conn)
+        .await
+        .optional()?;
+
+    if let Some(identity) = existing_identity {
+        let _ = diesel::update(identities_dsl::user_identities.find(identity.id))
+            .set((
+                identities_dsl::email.eq(&user_info.email),
+                identities_dsl::last_used_at.eq(Some(now)),
+            ))
+            .execute(conn)
+            .await?;
+
+        let user = diesel::update(users_dsl::users.find(identity.user_id))
+            .set(users_dsl::last_login.eq(Some(now)))
+            .get_result::<User>(conn)
+            .await?;
+        return Ok(user);
+    }
+
+    let Some(email) = user_info
+        .email
+        .as_deref()
+        .filter(|_| user_info.email_verified)
+    else {
+        return Err(ServiceError::Forbidden(format!(
+            "{} did not share a verified email address",
+            provider.display_name()
+        )));
+    };
+
+    let existing_user = users_dsl::users
+        .filter(users_dsl::email.eq(email))
+        .first::<User>(conn)
+        .await
+        .optional()?;
+
+    let user = match existing_user {
+        Some(_) if !provider.trusts_email() => {
+            return Err(ServiceError::Conflict(format!(
+                "An account with this email address already exists. Sign in to it and link your {} account from your account settings.",
+                provider.display_name()
+            )));
+        }
+        Some(existing) => {
+            // The provider vouches for the address, which the account never proved
+            if !existing.email_verified {
+                discard_unverified_credentials(conn, session_service, user_cache, existing.id)
+                    .await?;
+            }
+            update_user_from_provider(existing, user_info, conn).await?
+        }
+        None => create_user_from_provider(email, user_info, conn).await?,
+    };
+
+    let new_identity = UserIdentity {
+        id: Uuid::new_v4(),
+        user_id: user.id,
+        provider: provider_name.to_owned(),
+        subject: user_info.subject.clone(),
+        email: user_info.email.clone(),
+        created_at: now,
+        last_used_at: Some(now),
+    };
+    let _ = diesel::insert_into(identities_dsl::user_identities)
+        .values(&new_identity)
+        .execute(conn)
+        .await?;
+
+    Ok(user)
+}
+
+/// Helper function to fill in an existing user's profile from provider claims
+async fn update_user_from_provider(
+    mut existing_user: User,
+    user_info: &OidcUserInfo,
+    conn: &mut diesel_async::AsyncPgConnection,
+) -> Result<User, ServiceError> {
+    use shared::schema::users::dsl as users_dsl;
+
+    if existing_user.display_name.is_none() {
+        existing_user.display_name.clone_from(&user_info.name);
+    }
+    if existing_user.avatar_url.is_none() {
+        existing_user.avatar_url = Some(
+            user_info
+                .picture
+                .clone()
+                .unwrap_or_else(|| generate_gravatar_url(&existing_user.email)),
+        );
+    }
+
+    let user = diesel::update(users_dsl::users.find(existing_user.id))
+        .set((
+            users_dsl::display_name.eq(&existing_user.display_name),
+            users_dsl::avatar_url.eq(&existing_user.avatar_url),
+            users_dsl::email_verified.eq(true),
+            users_dsl::last_login.eq(Some(Utc::now().naive_utc())),
+        ))
+        .get_result::<User>(conn)
+        .await?;
+
+    Ok(user)
+}
+
+/// Helper function to create a new user from provider claims
+async fn create_user_from_provider(
+    email: &str,
+    user_info: &OidcUserInfo,
+    conn: &mut diesel_async::AsyncPgConnection,
+) -> Result<User, ServiceError> {
+    use shared::schema::users::dsl as users_dsl;
+
+    let new_user = User {
+        id: Uuid::new_v4(),
+        email: email.to_owned(),
+        display_name: user_info.name.clone(),
+        avatar_url: Some(
+            user_info
+                .picture
+                .clone()
+                .unwrap_or_else(|| generate_gravatar_url(email)),
+        ),
+        email_verified: true,
+        password_hash: None,
+        created_at: None,
+        updated_at: None,
+        last_login: Some(Utc::now().naive_utc()),
+        description: None,
+        banner: None,
+        session_version: 0,
+        role: UserRole::User.into(),
+        suspended_at: None,
+        suspension_reason: None,
+        suspended_until: None,
+        deletion_scheduled_for: None,
+    };
+
+    let _ = diesel::insert_into(users_dsl::users)
+        .values(&new_user)
+        .execute(conn)
+        .await?;
+
+    Ok(new_user)
+}
+
+/// Helper function to link an external identity to a signed-in user
+async fn link_identity(
+    conn: &mut diesel_async::AsyncPgConnection,
+    user_id: Uuid,
+    provider_name: &str,
+    user_info: &OidcUserInfo,
+) -> Result<(), ServiceError> {
+    use shared::schema::user_identities::dsl as identities_dsl;
+
+    let now = Utc::now().naive_utc();
+    let existing = identities_dsl::user_identities
+        .filter(identities_dsl::provider.eq(provider_name))
+        .filter(identities_dsl::subject.eq(&user_info.subject))
+        .first::<UserIdentity>(conn)
+        .await
+        .optional()?;
+
+    match existing {
+        Some(identity) if identity.user_id != user_id => Err(ServiceError::Conflict(
+            "This account is already linked to another user".to_owned(),
+        )),
+        Some(identity) => {
+            let _ = diesel::update(identities_dsl::user_identities.find(identity.id))
+                .set((
+                    identities_dsl::email.eq(&user_info.email),
+                    identities_dsl::last_used_at.eq(Some(now)),
+                ))
+                .execute(conn)
+                .await?;
+            Ok(())
+        }
+        None => {
+            let new_identity = UserIdentity {
+                id: Uuid::new_v4(),
+                user_id,
+                provider: provider_name.to_owned(),
+                subject: user_info.subject.clone(),
+                email: user_info.email.clone(),
+                created_at: now,
+                last_used_at: None,
+            };
+            let _ = diesel::insert_into(identities_dsl::user_identities)
+                .values(&new_identity)
+                .execute(conn)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/// List sign-in providers
+///
+/// Returns the external providers users can sign in with or link.
+#[utoipa::path(
+    get,
+    path = "/auth/providers",
+    context_path = "/api",
+    tag = "Auth",
+    responses(
+        (status = 200, description = "Configured sign-in providers", body = OidcProvidersListResponse)
+    )
+)]
+pub async fn list_providers(oidc_service: web::Data<OidcService>) -> HttpResponse {
+    let providers: Vec<OidcProviderResponse> = oidc_service
+        .providers()
+        .iter()
+        .map(|provider| OidcProviderResponse {
+            name: provider.name().to_owned(),
+            display_name: provider.display_name().to_owned(),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(OidcProvidersListResponse::from(providers))
+}
+
+/// Sign in with an `OpenID Connect` provider
+///
+/// # Errors
+/// Returns an error if the provider is unknown or its discovery document cannot be fetched.
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/{provider}",
+    context_path = "/api",
+    tag = "Auth",
+    params(("provider" = String, Path, description = "Name of the sign-in provider")),
+    responses(
+        (status = 302, description = "Redirect to the provider's consent screen"),
+        (status = 404, description = "Unknown sign-in provider", body = ErrorResponse),
+        (status = 500, description = "Provider discovery failed or session storage error", body = ErrorResponse)
+    )
+)]
+pub async fn oidc_redirect(
+    session: Session,
+    oidc_service: web::Data<OidcService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    provider: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    Ok(begin_authorization(
+        &session,
+        &oidc_service,
+        &provider,
+        &google_oauth_service.frontend_url,
+        None,
+    )
+    .await?)
+}
+
+/// `OpenID Connect` provider callback
+///
+/// Signs the user in, or finishes linking the identity when the flow was
+/// started from the link endpoint.
+///
+/// # Errors
+/// Returns an error if state verification fails, token exchange fails, or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/{provider}/callback",
+    context_path = "/api",
+    tag = "Auth",
+    params(
+        ("provider" = String, Path, description = "Name of the sign-in provider"),
+        ("code" = String, Query, description = "Authorization code from the provider"),
+        ("state" = String, Query, description = "State parameter for CSRF protection")
+    ),
+    responses(
+        (status = 302, description = "Redirect to frontend application"),
+        (status = 400, description = "Invalid authorization code or state", body = ErrorResponse),
+        (status = 403, description = "Provider did not share a verified email address", body = ErrorResponse),
+        (status = 409, description = "Identity is already linked to another user, or an account with its email address exists and has to link it first", body = ErrorResponse),
+        (status = 500, description = "Provider error or database connection failed", body = ErrorResponse)
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn oidc_callback(
+    req: HttpRequest,
+    session: Session,
+    session_service: web::Data<SessionService>,
+    user_cache: web::Data<UserCacheService>,
+    db_service: web::Data<DbService>,
+    oidc_service: web::Data<OidcService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    provider: web::Path<String>,
+    query: web::Query<AuthCallbackQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    complete_authorization(
+        &req,
+        &session,
+        &session_service,
+        &user_cache,
+        &db_service,
+        &oidc_service,
+        &google_oauth_service,
+        &provider,
+        &query,
+    )
+    .await
+}
+
+/// List linked identities
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    get,
+    path = "/auth/identities",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "External identities linked to the authenticated user", body = UserIdentitiesListResponse),
+        (status = 401, description = "Authentication required to list identities", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn list_identities(
+    user: User,
+    db_service: web::Data<DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::user_identities::dsl as identities_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let identities: Vec<UserIdentityResponse> = identities_dsl::user_identities
+        .filter(identities_dsl::user_id.eq(user.id))
+        .order(identities_dsl::created_at.asc())
+        .load::<UserIdentity>(&mut conn)
+        .await
+        .map_err(ServiceError::from)?
+        .into_iter()
+        .map(UserIdentityResponse::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(UserIdentitiesListResponse::from(identities)))
+}
+
+/// Link an identity
+///
+/// Redirects to the provider; on return the identity is linked to the
+/// authenticated user and the frontend is opened with `?linked={provider}`.
+///
+/// # Errors
+/// Returns an error if the provider is unknown or its discovery document cannot be fetched.
+#[utoipa::path(
+    get,
+    path = "/auth/identities/{provider}/link",
+    context_path = "/api",
+    tag = "Auth",
+    params(("provider" = String, Path, description = "Name of the sign-in provider")),
+    security(
+        ("cookieAuth" = [])
+    ),
+    responses(
+        (status = 302, description = "Redirect to the provider's consent screen"),
+        (status = 401, description = "Authentication required to link identities", body = ErrorResponse),
+        (status = 404, description = "Unknown sign-in provider", body = ErrorResponse),
+        (status = 500, description = "Provider discovery failed or session storage error", body = ErrorResponse)
+    )
+)]
+pub async fn link_identity_redirect(
+    user: User,
+    session: Session,
+    oidc_service: web::Data<OidcService>,
+    google_oauth_service: web::Data<GoogleOAuthService>,
+    provider: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let redirect_uri = format!(
+        "{}?linked={}",
+        google_oauth_service.frontend_url,
+        urlencoding::encode(&provider)
+    );
+
+    Ok(begin_authorization(
+        &session,
+        &oidc_service,
+        &provider,
+        &redirect_uri,
+        Some(user.id),
+    )
+    .await?)
+}
+
+/// Unlink an identity
+///
+/// An identity cannot be unlinked if it is the account's only way to sign in.
+///
+/// # Errors
+/// Returns an error if the identity does not exist or database operations fail.
+#[utoipa::path(
+    delete,
+    path = "/auth/identities/{identity_id}",
+    context_path = "/api",
+    tag = "Auth",
+    params(("identity_id" = Uuid, Path, description = "ID of the identity to unlink")),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 204, description = "Identity unlinked"),
+        (status = 400, description = "Identity is the only way to sign in", body = ErrorResponse),
+        (status = 401, description = "Authentication required to unlink identities", body = ErrorResponse),
+        (status = 404, description = "Identity not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn unlink_identity(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+    identity_id: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::user_identities::dsl as identities_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let identity_count: i64 = identities_dsl::user_identities
+        .filter(identities_dsl::user_id.eq(user.id))
+        .count()
+        .get_result(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let has_password = load_password_hash(&mut conn, user.id).await?.is_some();
+    if !has_password && identity_count <= 1_i64 {
+        return Ok(json_error(
+            "Add a password or link another account before unlinking your only sign-in method",
+        ));
+    }
+
+    let Some(provider) = diesel::delete(
+        identities_dsl::user_identities
+            .filter(identities_dsl::id.eq(*identity_id))
+            .filter(identities_dsl::user_id.eq(user.id)),
+    )
+    .returning(identities_dsl::provider)
+    .get_result::<String>(&mut conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?
+    else {
+        return Err(ServiceError::NotFound("Identity not found".to_owned()).into());
+    };
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::IdentityUnlinked,
+        json!({ "provider": provider }),
+    )
+    .await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,353 @@
+#![allow(clippy::unused_async)]
+
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::User,
+        jobs::{BackgroundJob, JobKind, JobResponse, JobStatus},
+    },
+    services::{
+        audit::record_event,
+        db::DbService,
+        epub_export::InMemoryZip,
+        jobs::{enqueue_job, has_active_job},
+        patreon_import::{
+            PatreonImportPayload, IMPORT_INPUT_TTL_HOURS, MEMBERS_ENTRY, POSTS_ENTRY,
+        },
+        post_import::MAX_IMPORT_ARCHIVE_SIZE,
+        s3::S3Service,
+    },
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::auth::json_error;
+
+/// Patreon import request schema for multipart form data
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PatreonImportRequest {
+    /// Patreon posts export (JSON)
+    #[schema(format = "binary", example = "Binary JSON content")]
+    pub posts: Option<String>,
+    /// Patreon members export (CSV)
+    #[schema(format = "binary", example = "Binary CSV content")]
+    pub members: Option<String>,
+    /// Series the posts are imported into; required with a posts export
+    #[serde(rename = "seriesId")]
+    pub series_id: Option<Uuid>,
+    /// Only report what would be imported (defaults to true)
+    #[serde(rename = "dryRun")]
+    pub dry_run: Option<bool>,
+}
+
+/// Helper struct for the fields of a Patreon import upload
+#[derive(Debug, Default)]
+struct PatreonUpload {
+    posts: Option<Vec<u8>>,
+    members: Option<Vec<u8>>,
+    series_id: Option<Uuid>,
+    dry_run: Option<bool>,
+}
+
+/// Helper function to read the fields of a Patreon import upload
+async fn read_patreon_upload(mut payload: Multipart) -> Result<PatreonUpload, actix_web::Error> {
+    let mut upload = PatreonUpload::default();
+    let mut total_size: usize = 0;
+
+    while let Some(payload_field) = payload.next().await {
+        let mut field = payload_field.map_err(|e| {
+            actix_web::error::ErrorBadRequest(format!("Failed to process multipart field: {e}"))
+        })?;
+
+        let field_name = field.name().to_owned();
+        let mut bytes = Vec::new();
+        while let Some(chunk_field) = field.next().await {
+            let chunk = chunk_field.map_err(|e| {
+                actix_web::error::ErrorBadRequest(format!("Failed to read file chunk: {e}"))
+            })?;
+            total_size = total_size.saturating_add(chunk.len());
+            if total_size > MAX_IMPORT_ARCHIVE_SIZE {
+                return Err(actix_web::error::ErrorPayloadTooLarge(
+                    "Patreon export is too large",
+                ));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        match field_name.as_str() {
+            "posts" => upload.posts = Some(bytes),
+            "members" => upload.members = Some(bytes),
+            "seriesId" => {
+                let value = String::from_utf8_lossy(&bytes);
+                upload.series_id = Some(
+                    Uuid::parse_str(value.trim())
+                        .ok()
+                        .ok_or_else(|| actix_web::error::ErrorBadRequest("Invalid seriesId"))?,
+                );
+            }
+            "dryRun" => {
+                let value = String::from_utf8_lossy(&bytes);
+                upload.dry_run = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .ok_or_else(|| actix_web::error::ErrorBadRequest("Invalid dryRun"))?,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    Ok(upload)
+}
+
+/// Import posts, tiers and members from a Patreon export
+///
+/// Takes the posts export (`posts`, JSON) and the members CSV (`members`),
+/// either or both. Posts go into the series named by `seriesId`, keeping their
+/// publish dates and locked to the cheapest matching tier; Patreon tiers are
+/// matched to the creator's tiers by name or created; active patrons get a
+/// pending invitation to their tier. Nobody is emailed.
+///
+/// The import runs as a background job whose result is the mapping report.
+/// A dry run, the default, only builds the report; it can then be run for
+/// real within 72 hours through `POST /api/imports/patreon/{job_id}/run`.
+///
+/// # Errors
+/// Returns an error if no export is uploaded, the series does not belong to
+/// the user, an import is already in progress, or storage or database
+/// operations fail.
+#[utoipa::path(
+    post,
+    path = "/imports/patreon",
+    context_path = "/api",
+    tag = "Imports",
+    request_body(
+        content = PatreonImportRequest,
+        description = "Multipart form data with the Patreon exports and import options",
+        content_type = "multipart/form-data"
+    ),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 202, description = "Import queued; its result is a PatreonImportReport", body = JobResponse),
+        (status = 400, description = "No export uploaded, or posts without a series", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Access denied - series does not belong to authenticated user", body = ErrorResponse),
+        (status = 409, description = "A Patreon import is already in progress", body = ErrorResponse),
+        (status = 413, description = "Export is too large", body = ErrorResponse),
+        (status = 500, description = "Storage or database error", body = ErrorResponse)
+    )
+)]
+pub async fn import_patreon(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+    s3_service: web::Data<S3Service>,
+    payload: Multipart,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::series::dsl as series_dsl;
+
+    let upload = read_patreon_upload(payload).await?;
+    if upload.posts.is_none() && upload.members.is_none() {
+        return Ok(json_error("Upload a posts export, a members CSV, or both"));
+    }
+    if upload.posts.is_some() && upload.series_id.is_none() {
+        return Ok(json_error("A seriesId is required to import posts"));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    if let Some(series_id) = upload.series_id {
+        let owns_series = diesel::select(diesel::dsl::exists(
+            series_dsl::series
+                .filter(series_dsl::id.eq(series_id))
+                .filter(series_dsl::user_id.eq(user.id))
+                .filter(series_dsl::deleted_at.is_null()),
+        ))
+        .get_result::<bool>(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+        if !owns_series {
+            return Err(
+                ServiceError::Forbidden("Series not found or access denied".to_owned()).into(),
+            );
+        }
+    }
+
+    if has_active_job(&mut conn, user.id, JobKind::PatreonImport).await? {
+        return Err(
+            ServiceError::Conflict("A Patreon import is already in progress".to_owned()).into(),
+        );
+    }
+
+    let mut writer = InMemoryZip::default();
+    if let Some(ref posts) = upload.posts {
+        writer.add_file(POSTS_ENTRY, posts)?;
+    }
+    if let Some(ref members) = upload.members {
+        writer.add_file(MEMBERS_ENTRY, members)?;
+    }
+    let input_key = format!("imports/{}/{}.zip", user.id, Uuid::new_v4());
+    let _ = s3_service.put_object(&input_key, writer.finish()?).await?;
+
+    let now = Utc::now().naive_utc();
+    let expires_at = now
+        .checked_add_signed(Duration::hours(IMPORT_INPUT_TTL_HOURS))
+        .unwrap_or(now);
+    let job_payload = PatreonImportPayload {
+        series_id: upload.series_id,
+        dry_run: upload.dry_run.unwrap_or(true),
+        input_key: input_key.clone(),
+    };
+    let job = enqueue_job(
+        &mut conn,
+        user.id,
+        JobKind::PatreonImport,
+        serde_json::to_value(&job_payload).map_err(ServiceError::from)?,
+        Some((input_key, expires_at)),
+    )
+    .await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::PatreonImportRequested,
+        json!({ "job_id": job.id, "dry_run": job_payload.dry_run }),
+    )
+    .await;
+
+    Ok(HttpResponse::Accepted().json(JobResponse::from(job)))
+}
+
+/// Run a previewed Patreon import for real
+///
+/// Queues a new import job with the export uploaded for a completed dry run,
+/// so it does not need to be uploaded again. The preview's report is only a
+/// guide: the import is mapped again against what exists when it runs.
+///
+/// # Errors
+/// Returns an error if the job is not a completed dry run of the user, its
+/// upload has expired, an import is already in progress, or database
+/// operations fail.
+#[utoipa::path(
+    post,
+    path = "/imports/patreon/{job_id}/run",
+    context_path = "/api",
+    tag = "Imports",
+    params(("job_id" = Uuid, Path, description = "UUID of the completed dry run")),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 202, description = "Import queued", body = JobResponse),
+        (status = 400, description = "Job is not a completed dry run", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 404, description = "Dry run not found, or its upload has expired", body = ErrorResponse),
+        (status = 409, description = "A Patreon import is already in progress", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn run_patreon_import(
+    req: HttpRequest,
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::background_jobs::dsl as jobs_dsl;
+
+    let preview_id = path.into_inner();
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let preview: BackgroundJob = jobs_dsl::background_jobs
+        .filter(jobs_dsl::id.eq(preview_id))
+        .filter(jobs_dsl::user_id.eq(user.id))
+        .filter(jobs_dsl::kind.eq(JobKind::PatreonImport.as_str()))
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?
+        .ok_or_else(|| ServiceError::NotFound("Import not found".to_owned()))?;
+
+    let mut job_payload: PatreonImportPayload =
+        serde_json::from_value(preview.payload).map_err(ServiceError::from)?;
+    if !job_payload.dry_run || JobStatus::from(preview.status) != JobStatus::Completed {
+        return Ok(json_error("Only a completed dry run can be run"));
+    }
+    let (Some(input_key), Some(expires_at)) = (preview.output_key, preview.output_expires_at)
+    else {
+        return Err(ServiceError::NotFound(
+            "The uploaded export has expired, upload it again".to_owned(),
+        )
+        .into());
+    };
+
+    if has_active_job(&mut conn, user.id, JobKind::PatreonImport).await? {
+        return Err(
+            ServiceError::Conflict("A Patreon import is already in progress".to_owned()).into(),
+        );
+    }
+
+    // The upload moves to the new job, so the dry run cannot be run twice
+    job_payload.dry_run = false;
+    job_payload.input_key.clone_from(&input_key);
+    let value = serde_json::to_value(&job_payload).map_err(ServiceError::from)?;
+    let user_id = user.id;
+    let job = conn
+        .transaction::<_, ServiceError, _>(|tx| {
+            async move {
+                let moved = diesel::update(
+                    jobs_dsl::background_jobs
+                        .find(preview_id)
+                        .filter(jobs_dsl::output_key.eq(&input_key)),
+                )
+                .set((
+                    jobs_dsl::output_key.eq(None::<String>),
+                    jobs_dsl::output_expires_at.eq(None::<chrono::NaiveDateTime>),
+                ))
+                .execute(tx)
+                .await?;
+                if moved == 0 {
+                    return Err(ServiceError::Conflict(
+                        "This dry run is already being imported".to_owned(),
+                    ));
+                }
+                enqueue_job(
+                    tx,
+                    user_id,
+                    JobKind::PatreonImport,
+                    value,
+                    Some((input_key, expires_at)),
+                )
+                .await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::PatreonImportRequested,
+        json!({ "job_id": job.id, "dry_run": false, "preview_job_id": preview_id }),
+    )
+    .await;
+
+    Ok(HttpResponse::Accepted().json(JobResponse::from(job)))
+}
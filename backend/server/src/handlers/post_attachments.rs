@@ -0,0 +1,466 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpResponse, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        auth::User,
+        post_attachments::{
+            CreatePostAttachmentRequest, PostAttachment, PostAttachmentResponse,
+            PostAttachmentsResponse, UpdatePostAttachmentRequest,
+        },
+        user_files::UserFile,
+    },
+    services::{db::DbService, post_attachments::load_post_attachments},
+};
+use std::cmp::Ordering;
+use uuid::Uuid;
+
+use super::auth::json_error;
+
+/// Maximum number of files that can be attached to a post
+const MAX_POST_ATTACHMENTS: i64 = 50;
+
+/// Maximum length of an attachment caption in characters
+const MAX_CAPTION_LENGTH: usize = 1000;
+
+/// Helper function to check that the user owns the post
+async fn check_post_owner(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    post_id: Uuid,
+) -> Result<(), ServiceError> {
+    use shared::schema::{posts::dsl as posts_dsl, series::dsl as series_dsl};
+
+    let owns_post = diesel::select(diesel::dsl::exists(
+        posts_dsl::posts
+            .inner_join(series_dsl::series.on(posts_dsl::series_id.eq(series_dsl::id)))
+            .filter(posts_dsl::id.eq(post_id))
+            .filter(series_dsl::user_id.eq(user_id))
+            .filter(posts_dsl::deleted_at.is_null())
+            .filter(series_dsl::deleted_at.is_null()),
+    ))
+    .get_result::<bool>(conn)
+    .await?;
+    if owns_post {
+        Ok(())
+    } else {
+        Err(ServiceError::NotFound("Post not found".to_owned()))
+    }
+}
+
+/// Helper function to check that a tier belongs to the user
+async fn owns_tier(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    tier_id: Uuid,
+) -> Result<bool, ServiceError> {
+    use shared::schema::tiers::dsl as tiers_dsl;
+
+    Ok(diesel::select(diesel::dsl::exists(
+        tiers_dsl::tiers
+            .filter(tiers_dsl::id.eq(tier_id))
+            .filter(tiers_dsl::user_id.eq(user_id)),
+    ))
+    .get_result::<bool>(conn)
+    .await?)
+}
+
+/// Helper function to trim a caption, treating a blank one as none
+fn normalize_caption(text: Option<String>) -> Result<Option<String>, &'static str> {
+    let caption = text
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty());
+    if caption
+        .as_ref()
+        .is_some_and(|value| value.chars().count() > MAX_CAPTION_LENGTH)
+    {
+        return Err("Captions can be at most 1000 characters long");
+    }
+    Ok(caption)
+}
+
+/// Helper function to lock a post row for the rest of the transaction, so
+/// concurrent changes to its attachments are counted and placed one at a time
+async fn lock_post(conn: &mut AsyncPgConnection, post_id: Uuid) -> Result<(), ServiceError> {
+    use shared::schema::posts::dsl as posts_dsl;
+
+    let _ = posts_dsl::posts
+        .filter(posts_dsl::id.eq(post_id))
+        .select(posts_dsl::id)
+        .for_update()
+        .first::<Uuid>(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| ServiceError::NotFound("Post not found".to_owned()))?;
+    Ok(())
+}
+
+/// Helper function to count the attachments of a post
+async fn count_attachments(
+    conn: &mut AsyncPgConnection,
+    post_id: Uuid,
+) -> Result<i32, ServiceError> {
+    use shared::schema::post_attachments::dsl as attachments_dsl;
+
+    let count: i64 = attachments_dsl::post_attachments
+        .filter(attachments_dsl::post_id.eq(post_id))
+        .count()
+        .get_result(conn)
+        .await?;
+    Ok(i32::try_from(count).unwrap_or(i32::MAX))
+}
+
+/// Helper function to move the other attachments of a post between two
+/// positions, inclusive, by `delta`
+#[allow(clippy::arithmetic_side_effects)] // Arithmetic on the position column runs in SQL
+async fn shift_positions(
+    conn: &mut AsyncPgConnection,
+    post_id: Uuid,
+    except_id: Uuid,
+    (from, to): (i32, i32),
+    delta: i32,
+) -> Result<(), ServiceError> {
+    use shared::schema::post_attachments::dsl as attachments_dsl;
+
+    let _ = diesel::update(
+        attachments_dsl::post_attachments
+            .filter(attachments_dsl::post_id.eq(post_id))
+            .filter(attachments_dsl::id.ne(except_id))
+            .filter(attachments_dsl::position.between(from, to)),
+    )
+    .set(attachments_dsl::position.eq(attachments_dsl::position + delta))
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Helper function to load one attachment of a post with its file
+async fn load_attachment(
+    conn: &mut AsyncPgConnection,
+    post_id: Uuid,
+    attachment_id: Uuid,
+) -> Result<PostAttachmentResponse, ServiceError> {
+    use shared::schema::{post_attachments::dsl as attachments_dsl, user_files::dsl as files_dsl};
+
+    let row: (PostAttachment, UserFile) = attachments_dsl::post_attachments
+        .inner_join(files_dsl::user_files)
+        .filter(attachments_dsl::id.eq(attachment_id))
+        .filter(attachments_dsl::post_id.eq(post_id))
+        .filter(files_dsl::deleted_at.is_null())
+        .select((PostAttachment::as_select(), UserFile::as_select()))
+        .first(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| ServiceError::NotFound("Attachment not found".to_owned()))?;
+    Ok(PostAttachmentResponse::from(row))
+}
+
+/// List the files attached to a post
+///
+/// # Errors
+/// Returns error if post not found, access denied, or database operations fail
+#[utoipa::path(
+    get,
+    path = "/posts/{post_id}/attachments",
+    context_path = "/api",
+    tag = "Posts",
+    params(("post_id" = Uuid, Path, description = "UUID of the post whose attachments to list")),
+    responses(
+        (status = 200, description = "Attachments in order", body = PostAttachmentsResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 404, description = "Post not found or access denied", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn list_post_attachments(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let post_id = path.into_inner();
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    check_post_owner(&mut conn, user.id, post_id).await?;
+
+    let attachments = load_post_attachments(&mut conn, &[post_id])
+        .await?
+        .remove(&post_id)
+        .unwrap_or_default();
+    Ok(HttpResponse::Ok().json(PostAttachmentsResponse::from(attachments)))
+}
+
+/// Attach a file to a post
+///
+/// The file must belong to the signed-in user. Without a position the
+/// attachment goes after the others; with one, the attachments from that
+/// position on move down. Without `minTierId` the attachment is public.
+///
+/// # Errors
+/// Returns error if post, file or tier not found, the post has too many
+/// attachments, the caption is too long, or database operations fail
+#[utoipa::path(
+    post,
+    path = "/posts/{post_id}/attachments",
+    context_path = "/api",
+    tag = "Posts",
+    params(("post_id" = Uuid, Path, description = "UUID of the post to attach the file to")),
+    request_body(content = CreatePostAttachmentRequest, description = "File to attach and its details"),
+    responses(
+        (status = 201, description = "File attached", body = PostAttachmentResponse),
+        (status = 400, description = "File or tier not found, invalid position or caption, or too many attachments", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 404, description = "Post not found or access denied", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn create_post_attachment(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    body: web::Json<CreatePostAttachmentRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::{post_attachments::dsl as attachments_dsl, user_files::dsl as files_dsl};
+
+    let post_id = path.into_inner();
+    let request = body.into_inner();
+    let caption = match normalize_caption(request.caption) {
+        Ok(caption) => caption,
+        Err(message) => return Ok(json_error(message)),
+    };
+    if request.position.is_some_and(|position| position < 1_i32) {
+        return Ok(json_error("Positions start at 1"));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    check_post_owner(&mut conn, user.id, post_id).await?;
+
+    let owns_file = diesel::select(diesel::dsl::exists(
+        files_dsl::user_files
+            .filter(files_dsl::id.eq(request.file_id))
+            .filter(files_dsl::user_id.eq(user.id))
+            .filter(files_dsl::deleted_at.is_null()),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+    if !owns_file {
+        return Ok(json_error("File not found"));
+    }
+    if let Some(tier_id) = request.min_tier_id {
+        if !owns_tier(&mut conn, user.id, tier_id).await? {
+            return Ok(json_error("Tier not found"));
+        }
+    }
+
+    let attachment_id = Uuid::new_v4();
+    let attached = conn
+        .transaction::<_, ServiceError, _>(|tx| {
+            async move {
+                lock_post(tx, post_id).await?;
+                let count = count_attachments(tx, post_id).await?;
+                if i64::from(count) >= MAX_POST_ATTACHMENTS {
+                    return Ok(false);
+                }
+
+                let last_position = count.saturating_add(1_i32);
+                let now = Utc::now().naive_utc();
+                let attachment = PostAttachment {
+                    id: attachment_id,
+                    post_id,
+                    file_id: request.file_id,
+                    position: request
+                        .position
+                        .map_or(last_position, |position| position.min(last_position)),
+                    caption,
+                    min_tier_id: request.min_tier_id,
+                    created_at: now,
+                    updated_at: now,
+                };
+                shift_positions(
+                    tx,
+                    post_id,
+                    attachment.id,
+                    (attachment.position, i32::MAX),
+                    1_i32,
+                )
+                .await?;
+                let _ = diesel::insert_into(attachments_dsl::post_attachments)
+                    .values(&attachment)
+                    .execute(tx)
+                    .await?;
+                Ok(true)
+            }
+            .scope_boxed()
+        })
+        .await?;
+    if !attached {
+        return Ok(json_error(&format!(
+            "A post can have at most {MAX_POST_ATTACHMENTS} attachments"
+        )));
+    }
+
+    let response = load_attachment(&mut conn, post_id, attachment_id).await?;
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// Update the caption, access or position of an attachment
+///
+/// Moving an attachment shifts the ones between its old and new position.
+///
+/// # Errors
+/// Returns error if post, attachment or tier not found, the position or
+/// caption is invalid, or database operations fail
+#[utoipa::path(
+    put,
+    path = "/posts/{post_id}/attachments/{attachment_id}",
+    context_path = "/api",
+    tag = "Posts",
+    params(
+        ("post_id" = Uuid, Path, description = "UUID of the post"),
+        ("attachment_id" = Uuid, Path, description = "UUID of the attachment to update")
+    ),
+    request_body(content = UpdatePostAttachmentRequest, description = "Attachment fields to change"),
+    responses(
+        (status = 200, description = "Attachment updated", body = PostAttachmentResponse),
+        (status = 400, description = "Tier not found, or invalid position or caption", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 404, description = "Post or attachment not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn update_post_attachment(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<(Uuid, Uuid)>,
+    body: web::Json<UpdatePostAttachmentRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::post_attachments::dsl as attachments_dsl;
+
+    let (post_id, attachment_id) = path.into_inner();
+    let request = body.into_inner();
+    let caption = match request.caption.map(|text| normalize_caption(Some(text))) {
+        Some(Ok(caption)) => Some(caption),
+        Some(Err(message)) => return Ok(json_error(message)),
+        None => None,
+    };
+    if request.position.is_some_and(|position| position < 1_i32) {
+        return Ok(json_error("Positions start at 1"));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    check_post_owner(&mut conn, user.id, post_id).await?;
+    if let Some(Some(tier_id)) = request.min_tier_id {
+        if !owns_tier(&mut conn, user.id, tier_id).await? {
+            return Ok(json_error("Tier not found"));
+        }
+    }
+
+    conn.transaction::<_, ServiceError, _>(|tx| {
+        async move {
+            lock_post(tx, post_id).await?;
+            let old_position = load_attachment(tx, post_id, attachment_id).await?.position;
+            let count = count_attachments(tx, post_id).await?;
+            let new_position = request
+                .position
+                .map_or(old_position, |position| position.min(count));
+
+            match new_position.cmp(&old_position) {
+                Ordering::Less => {
+                    let range = (new_position, old_position.saturating_sub(1_i32));
+                    shift_positions(tx, post_id, attachment_id, range, 1_i32).await?;
+                }
+                Ordering::Greater => {
+                    let range = (old_position.saturating_add(1_i32), new_position);
+                    shift_positions(tx, post_id, attachment_id, range, -1_i32).await?;
+                }
+                Ordering::Equal => {}
+            }
+            let _ = diesel::update(attachments_dsl::post_attachments.find(attachment_id))
+                .set((
+                    caption.map(|value| attachments_dsl::caption.eq(value)),
+                    request
+                        .min_tier_id
+                        .map(|value| attachments_dsl::min_tier_id.eq(value)),
+                    attachments_dsl::position.eq(new_position),
+                    attachments_dsl::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(tx)
+                .await?;
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    let response = load_attachment(&mut conn, post_id, attachment_id).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Remove an attachment from a post
+///
+/// The file itself is kept; only its attachment to the post is removed.
+///
+/// # Errors
+/// Returns error if post or attachment not found, or database operations fail
+#[utoipa::path(
+    delete,
+    path = "/posts/{post_id}/attachments/{attachment_id}",
+    context_path = "/api",
+    tag = "Posts",
+    params(
+        ("post_id" = Uuid, Path, description = "UUID of the post"),
+        ("attachment_id" = Uuid, Path, description = "UUID of the attachment to remove")
+    ),
+    responses(
+        (status = 204, description = "Attachment removed"),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 404, description = "Post or attachment not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn delete_post_attachment(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::post_attachments::dsl as attachments_dsl;
+
+    let (post_id, attachment_id) = path.into_inner();
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    check_post_owner(&mut conn, user.id, post_id).await?;
+
+    conn.transaction::<_, ServiceError, _>(|tx| {
+        async move {
+            lock_post(tx, post_id).await?;
+            let position: i32 = diesel::delete(
+                attachments_dsl::post_attachments
+                    .filter(attachments_dsl::id.eq(attachment_id))
+                    .filter(attachments_dsl::post_id.eq(post_id)),
+            )
+            .returning(attachments_dsl::position)
+            .get_result(tx)
+            .await
+            .optional()?
+            .ok_or_else(|| ServiceError::NotFound("Attachment not found".to_owned()))?;
+            let range = (position.saturating_add(1_i32), i32::MAX);
+            shift_positions(tx, post_id, attachment_id, range, -1_i32).await?;
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,176 @@
+#![allow(clippy::unused_async)]
+
+use actix_multipart::Multipart;
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        auth::User,
+        imports::{ImportFileStatus, PostImportResponse},
+    },
+    services::{
+        db::DbService,
+        post_import::{plan_markdown_import, read_zip, MAX_IMPORT_ARCHIVE_SIZE, MAX_IMPORT_POSTS},
+        series_length::refresh_series_length,
+    },
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::auth::json_error;
+
+/// Post import request schema for multipart form data
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostImportRequest {
+    /// ZIP archive of Markdown files with YAML front matter
+    #[schema(format = "binary", example = "Binary ZIP archive content")]
+    pub file: String,
+}
+
+/// Helper function to read the `file` field of a multipart upload
+pub(crate) async fn read_upload(mut payload: Multipart) -> Result<Vec<u8>, actix_web::Error> {
+    let mut file_data: Option<Vec<u8>> = None;
+
+    while let Some(payload_field) = payload.next().await {
+        let mut field = payload_field.map_err(|e| {
+            actix_web::error::ErrorBadRequest(format!("Failed to process multipart field: {e}"))
+        })?;
+
+        if field.name() == "file" {
+            let mut file_bytes = Vec::new();
+            while let Some(chunk_field) = field.next().await {
+                let chunk = chunk_field.map_err(|e| {
+                    actix_web::error::ErrorBadRequest(format!("Failed to read file chunk: {e}"))
+                })?;
+                file_bytes.extend_from_slice(&chunk);
+            }
+            file_data = Some(file_bytes);
+        }
+    }
+
+    file_data.ok_or_else(|| actix_web::error::ErrorBadRequest("No file uploaded"))
+}
+
+/// Import posts into a series from a ZIP of Markdown files
+///
+/// Every `.md` file becomes a post. Its YAML front matter sets the `title`
+/// (required), `slug` (defaults to the slugified title), `number` (defaults
+/// to the next free numbers in file name order), `published` flag (defaults
+/// to false) and publish `date` (defaults to now). Other files are skipped.
+///
+/// All files are validated before anything is written, and the posts are
+/// created in one transaction: if any file is invalid, nothing is imported
+/// and the report explains what to fix.
+///
+/// # Errors
+/// Returns an error if the series does not belong to the user, the archive
+/// cannot be read, or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/api/series/{series_id}/actions/import",
+    tag = "Posts",
+    params(("series_id" = Uuid, Path, description = "UUID of the series to import into")),
+    request_body(
+        content = PostImportRequest,
+        description = "Multipart form data with a ZIP archive in the file field",
+        content_type = "multipart/form-data"
+    ),
+    responses(
+        (status = 201, description = "Posts imported", body = PostImportResponse),
+        (status = 400, description = "Archive is not a readable ZIP or has no Markdown files", body = ErrorResponse),
+        (status = 401, description = "Authentication required to import posts", body = ErrorResponse),
+        (status = 403, description = "Access denied - series does not belong to authenticated user", body = ErrorResponse),
+        (status = 409, description = "A post was created with a conflicting slug or number during the import", body = ErrorResponse),
+        (status = 422, description = "Some files are invalid, nothing was imported", body = PostImportResponse),
+        (status = 500, description = "Server error during import", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn import_posts(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    payload: Multipart,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::{posts::dsl as posts_dsl, series::dsl as series_dsl};
+
+    let series_id = path.into_inner();
+    // Buffer the upload before checking out a connection, so slow clients
+    // cannot hold pool connections for the length of their upload
+    let archive = read_upload(payload).await?;
+    let entries = read_zip(&archive, MAX_IMPORT_ARCHIVE_SIZE)?;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let owns_series = diesel::select(diesel::dsl::exists(
+        series_dsl::series
+            .filter(series_dsl::id.eq(series_id))
+            .filter(series_dsl::user_id.eq(user.id))
+            .filter(series_dsl::deleted_at.is_null()),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+    if !owns_series {
+        return Err(ServiceError::Forbidden("Series not found or access denied".to_owned()).into());
+    }
+
+    // Deleted posts keep their slug and number, so they are taken too
+    let existing: Vec<(String, i32)> = posts_dsl::posts
+        .filter(posts_dsl::series_id.eq(series_id))
+        .select((posts_dsl::slug, posts_dsl::number))
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let mut plan = plan_markdown_import(series_id, entries, &existing);
+    let markdown_files = plan
+        .reports
+        .iter()
+        .filter(|r| r.status != ImportFileStatus::Skipped)
+        .count();
+    if markdown_files == 0 {
+        return Ok(json_error("Archive contains no Markdown files"));
+    }
+    if markdown_files > MAX_IMPORT_POSTS {
+        return Ok(json_error(&format!(
+            "An import can create at most {MAX_IMPORT_POSTS} posts"
+        )));
+    }
+    if !plan.is_valid() {
+        return Ok(HttpResponse::UnprocessableEntity()
+            .json(PostImportResponse::new(series_id, plan.reports)));
+    }
+
+    let posts = std::mem::take(&mut plan.posts);
+    conn.transaction::<_, ServiceError, _>(|tx| {
+        async move {
+            let _ = diesel::insert_into(posts_dsl::posts)
+                .values(&posts)
+                .execute(tx)
+                .await
+                .map_err(|e| match e {
+                    diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::UniqueViolation,
+                        _,
+                    ) => ServiceError::Conflict(
+                        "A post with the same slug or number was created during the import"
+                            .to_owned(),
+                    ),
+                    _ => ServiceError::Database(e.to_string()),
+                })?;
+            refresh_series_length(tx, series_id).await
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    plan.mark_imported();
+    Ok(HttpResponse::Created().json(PostImportResponse::new(series_id, plan.reports)))
+}
@@ -0,0 +1,258 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpResponse, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        auth::User,
+        post_pages::{PostPage, PostPageResponse, PostPagesResponse, UpdatePostPagesRequest},
+    },
+    services::db::DbService,
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::auth::json_error;
+
+/// Maximum number of pages a post can have
+const MAX_POST_PAGES: usize = 200;
+
+/// Helper function to load the pages of a post in reading order
+async fn load_pages(
+    conn: &mut AsyncPgConnection,
+    post_id: Uuid,
+) -> Result<Vec<PostPageResponse>, ServiceError> {
+    use shared::schema::{post_pages::dsl as pages_dsl, user_files::dsl as files_dsl};
+
+    let rows: Vec<(i32, Uuid, String, Option<String>)> = pages_dsl::post_pages
+        .inner_join(files_dsl::user_files)
+        .filter(pages_dsl::post_id.eq(post_id))
+        .filter(files_dsl::deleted_at.is_null())
+        .order(pages_dsl::position.asc())
+        .select((
+            pages_dsl::position,
+            pages_dsl::file_id,
+            files_dsl::mime_type,
+            pages_dsl::alt_text,
+        ))
+        .load(conn)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(position, file_id, mime_type, alt_text)| PostPageResponse {
+                position,
+                file_id,
+                url: format!("/api/cdn/files/{file_id}"),
+                mime_type,
+                alt_text,
+            },
+        )
+        .collect())
+}
+
+/// Helper function to explain why one of the given files cannot be a page, if any
+async fn invalid_page_file(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    file_ids: &[Uuid],
+) -> Result<Option<String>, ServiceError> {
+    use shared::schema::user_files::dsl as files_dsl;
+
+    let mime_types: HashMap<Uuid, String> = files_dsl::user_files
+        .filter(files_dsl::id.eq_any(file_ids))
+        .filter(files_dsl::user_id.eq(user_id))
+        .filter(files_dsl::deleted_at.is_null())
+        .select((files_dsl::id, files_dsl::mime_type))
+        .load::<(Uuid, String)>(conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(file_ids
+        .iter()
+        .find_map(|file_id| match mime_types.get(file_id) {
+            Some(mime_type) if mime_type.starts_with("image/") => None,
+            Some(_) => Some(format!("File {file_id} is not an image")),
+            None => Some(format!("File {file_id} not found")),
+        }))
+}
+
+/// Replace the pages of a post
+///
+/// Pages are image files of the signed-in user, shown in the order given.
+/// Comic and illustration posts use them instead of, or alongside, the
+/// post's text. An empty list removes every page.
+///
+/// # Errors
+/// Returns error if post not found, a file is not an image of the user, too
+/// many pages are given, or database operations fail
+#[utoipa::path(
+    put,
+    path = "/posts/{post_id}/pages",
+    context_path = "/api",
+    tag = "Posts",
+    params(("post_id" = Uuid, Path, description = "UUID of the post whose pages to set")),
+    request_body(content = UpdatePostPagesRequest, description = "Pages in reading order"),
+    responses(
+        (status = 200, description = "Pages updated", body = PostPagesResponse),
+        (status = 400, description = "Too many pages, or a file is not an image of the user", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 404, description = "Post not found or access denied", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security(("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn set_post_pages(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    body: web::Json<UpdatePostPagesRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::{
+        post_pages::dsl as pages_dsl, posts::dsl as posts_dsl, series::dsl as series_dsl,
+    };
+
+    let post_id = path.into_inner();
+    let request = body.into_inner();
+    if request.pages.len() > MAX_POST_PAGES {
+        return Ok(json_error(&format!(
+            "A post can have at most {MAX_POST_PAGES} pages"
+        )));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let owns_post = diesel::select(diesel::dsl::exists(
+        posts_dsl::posts
+            .inner_join(series_dsl::series.on(posts_dsl::series_id.eq(series_dsl::id)))
+            .filter(posts_dsl::id.eq(post_id))
+            .filter(series_dsl::user_id.eq(user.id))
+            .filter(posts_dsl::deleted_at.is_null())
+            .filter(series_dsl::deleted_at.is_null()),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+    if !owns_post {
+        return Err(ServiceError::NotFound("Post not found".to_owned()).into());
+    }
+
+    let file_ids: Vec<Uuid> = request.pages.iter().map(|page| page.file_id).collect();
+    if let Some(message) = invalid_page_file(&mut conn, user.id, &file_ids).await? {
+        return Ok(json_error(&message));
+    }
+
+    let now = Utc::now().naive_utc();
+    let new_pages: Vec<PostPage> = request
+        .pages
+        .into_iter()
+        .zip(1_i32..)
+        .map(|(page, position)| PostPage {
+            id: Uuid::new_v4(),
+            post_id,
+            file_id: page.file_id,
+            position,
+            alt_text: page
+                .alt_text
+                .map(|text| text.trim().to_owned())
+                .filter(|text| !text.is_empty()),
+            created_at: now,
+        })
+        .collect();
+
+    conn.transaction::<_, ServiceError, _>(|tx| {
+        async move {
+            let _ = diesel::delete(pages_dsl::post_pages.filter(pages_dsl::post_id.eq(post_id)))
+                .execute(tx)
+                .await?;
+            let _ = diesel::insert_into(pages_dsl::post_pages)
+                .values(&new_pages)
+                .execute(tx)
+                .await?;
+            let _ = diesel::update(posts_dsl::posts.filter(posts_dsl::id.eq(post_id)))
+                .set(posts_dsl::updated_at.eq(now))
+                .execute(tx)
+                .await?;
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    let pages = load_pages(&mut conn, post_id).await?;
+    Ok(HttpResponse::Ok().json(PostPagesResponse { post_id, pages }))
+}
+
+/// Get the pages of a post in reading order
+///
+/// The creator can always see the pages. Anyone else, signed in or not, can
+/// see the pages of published posts that are not locked to a tier.
+///
+/// # Errors
+/// Returns error if post not found, the post is locked to a tier, or database
+/// operations fail
+#[utoipa::path(
+    get,
+    path = "/posts/{post_id}/pages",
+    context_path = "/api",
+    tag = "Posts",
+    params(("post_id" = Uuid, Path, description = "UUID of the post whose pages to get")),
+    responses(
+        (status = 200, description = "Pages of the post", body = PostPagesResponse),
+        (status = 403, description = "The post is only available to members of a tier", body = ErrorResponse),
+        (status = 404, description = "Post not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    security((), ("cookieAuth" = [], "bearerAuth" = []))
+)]
+pub async fn get_post_pages(
+    user: Option<User>,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::{posts::dsl as posts_dsl, series::dsl as series_dsl};
+
+    let post_id = path.into_inner();
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let (owner_id, is_published, min_tier_id): (Uuid, Option<bool>, Option<Uuid>) =
+        posts_dsl::posts
+            .inner_join(series_dsl::series.on(posts_dsl::series_id.eq(series_dsl::id)))
+            .filter(posts_dsl::id.eq(post_id))
+            .filter(posts_dsl::deleted_at.is_null())
+            .filter(series_dsl::deleted_at.is_null())
+            .select((
+                series_dsl::user_id,
+                posts_dsl::is_published,
+                posts_dsl::min_tier_id,
+            ))
+            .first(&mut conn)
+            .await
+            .optional()
+            .map_err(ServiceError::from)?
+            .ok_or_else(|| ServiceError::NotFound("Post not found".to_owned()))?;
+
+    let is_owner = user.is_some_and(|viewer| viewer.id == owner_id);
+    if !is_owner {
+        if is_published != Some(true) {
+            return Err(ServiceError::NotFound("Post not found".to_owned()).into());
+        }
+        if min_tier_id.is_some() {
+            return Err(ServiceError::Forbidden(
+                "This post is only available to members of a tier".to_owned(),
+            )
+            .into());
+        }
+    }
+
+    let pages = load_pages(&mut conn, post_id).await?;
+    Ok(HttpResponse::Ok().json(PostPagesResponse { post_id, pages }))
+}
@@ -11,9 +11,8 @@ use shared::{
         auth::User,
         posts::{CreatePostRequest, Post, PostResponse, PostsListResponse, UpdatePostRequest},
         series::Series,
-        series_length::SeriesLength,
     },
-    schema::series_length::dsl as series_length_dsl,
+    services::{post_attachments::load_post_attachments, series_length::refresh_series_length},
 };
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -92,6 +91,7 @@ pub async fn create_post(
         created_at: Some(Utc::now().naive_utc()),
         updated_at: Some(Utc::now().naive_utc()),
         deleted_at: None,
+        min_tier_id: None,
     };
 
     let inserted_post: Post = diesel::insert_into(posts_dsl::posts)
@@ -108,27 +108,7 @@ pub async fn create_post(
             _ => ServiceError::Database(e.to_string()),
         })?;
 
-    // Update series length
-    let post_count: i64 = posts_dsl::posts
-        .filter(posts_dsl::series_id.eq(body.series_id))
-        .filter(posts_dsl::deleted_at.is_null())
-        .count()
-        .get_result(&mut conn)
-        .await
-        .map_err(|e| ServiceError::Database(e.to_string()))?;
-
-    #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
-    let post_count_i32 = post_count as i32;
-    let new_series_length = SeriesLength::new(body.series_id, post_count_i32);
-
-    let _rows_affected = diesel::insert_into(series_length_dsl::series_length)
-        .values(&new_series_length)
-        .on_conflict(series_length_dsl::series_id)
-        .do_update()
-        .set(series_length_dsl::length.eq(post_count_i32))
-        .execute(&mut conn)
-        .await
-        .map_err(|e| ServiceError::Database(e.to_string()))?;
+    refresh_series_length(&mut conn, body.series_id).await?;
 
     Ok(HttpResponse::Created().json(PostResponse::from(inserted_post)))
 }
@@ -193,8 +173,15 @@ pub async fn list_posts(
         .await
         .map_err(|e| ServiceError::Database(e.to_string()))?;
 
-    let posts_responses: Vec<PostResponse> =
-        posts_list.into_iter().map(PostResponse::from).collect();
+    let post_ids: Vec<Uuid> = posts_list.iter().map(|post| post.id).collect();
+    let mut attachments = load_post_attachments(&mut conn, &post_ids).await?;
+    let posts_responses: Vec<PostResponse> = posts_list
+        .into_iter()
+        .map(|post| {
+            let post_attachments = attachments.remove(&post.id).unwrap_or_default();
+            PostResponse::from(post).with_attachments(post_attachments)
+        })
+        .collect();
     let response = PostsListResponse::from(posts_responses);
 
     Ok(HttpResponse::Ok().json(response))
@@ -243,7 +230,11 @@ pub async fn get_post(
             _ => ServiceError::Database(e.to_string()),
         })?;
 
-    Ok(HttpResponse::Ok().json(PostResponse::from(post)))
+    let attachments = load_post_attachments(&mut conn, &[post.id])
+        .await?
+        .remove(&post.id)
+        .unwrap_or_default();
+    Ok(HttpResponse::Ok().json(PostResponse::from(post).with_attachments(attachments)))
 }
 
 /// Update a post
@@ -320,7 +311,11 @@ pub async fn update_post(
             _ => ServiceError::Database(e.to_string()),
         })?;
 
-    Ok(HttpResponse::Ok().json(PostResponse::from(updated_post)))
+    let attachments = load_post_attachments(&mut conn, &[post_id])
+        .await?
+        .remove(&post_id)
+        .unwrap_or_default();
+    Ok(HttpResponse::Ok().json(PostResponse::from(updated_post).with_attachments(attachments)))
 }
 
 /// Delete a post (soft delete) with series ownership validation
@@ -377,27 +372,7 @@ pub async fn delete_post(
         .await
         .map_err(|e| ServiceError::Database(e.to_string()))?;
 
-    // Update series length
-    let post_count: i64 = posts_dsl::posts
-        .filter(posts_dsl::series_id.eq(series_id_for_update))
-        .filter(posts_dsl::deleted_at.is_null())
-        .count()
-        .get_result(&mut conn)
-        .await
-        .map_err(|e| ServiceError::Database(e.to_string()))?;
-
-    #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
-    let post_count_i32 = post_count as i32;
-    let new_series_length = SeriesLength::new(series_id_for_update, post_count_i32);
-
-    let _rows_affected = diesel::insert_into(series_length_dsl::series_length)
-        .values(&new_series_length)
-        .on_conflict(series_length_dsl::series_id)
-        .do_update()
-        .set(series_length_dsl::length.eq(post_count_i32))
-        .execute(&mut conn)
-        .await
-        .map_err(|e| ServiceError::Database(e.to_string()))?;
+    refresh_series_length(&mut conn, series_id_for_update).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
@@ -8,12 +8,13 @@ use serde::Deserialize;
 use shared::{
     errors::{ErrorResponse, ServiceError},
     models::{
-        auth::User,
+        auth::{User, UserRole},
         series::{
             CreateSeriesRequest, Series, SeriesListResponse, SeriesResponse, UpdateSeriesRequest,
         },
     },
     schema::series_length::dsl as series_length_dsl,
+    services::user_cache::UserCacheService,
 };
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -54,9 +55,11 @@ pub struct ListSeriesQuery {
 pub async fn create_series(
     user: User,
     db_service: web::Data<shared::services::db::DbService>,
+    user_cache: web::Data<UserCacheService>,
     body: web::Json<CreateSeriesRequest>,
 ) -> Result<HttpResponse, actix_web::Error> {
     use shared::schema::series::dsl as series_dsl;
+    use shared::schema::users::dsl as users_dsl;
 
     let pool = db_service.pool();
     let mut conn = pool.get().await.map_err(ServiceError::from)?;
@@ -86,6 +89,20 @@ pub async fn create_series(
             _ => ServiceError::Database(e.to_string()),
         })?;
 
+    // Publishing a first series makes a regular member a creator
+    if user.role() == UserRole::User {
+        let _ = diesel::update(
+            users_dsl::users
+                .filter(users_dsl::id.eq(user.id))
+                .filter(users_dsl::role.eq(UserRole::User.as_str())),
+        )
+        .set(users_dsl::role.eq(UserRole::Creator.as_str()))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| ServiceError::Database(e.to_string()))?;
+        user_cache.invalidate(user.id).await?;
+    }
+
     // Fetch series length
     let length: Option<i32> = series_length_dsl::series_length
         .filter(series_length_dsl::series_id.eq(inserted_series.id))
@@ -0,0 +1,239 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        auth::User,
+        jobs::{JobKind, JobResponse},
+        series::SeriesExportRequest,
+    },
+    services::{
+        cbz_export::CbzExportPayload,
+        db::DbService,
+        epub_export::EpubExportPayload,
+        jobs::{enqueue_job, has_active_job},
+    },
+};
+use uuid::Uuid;
+
+use super::auth::json_error;
+
+/// Helper function to explain what is wrong with an export range, if anything
+fn invalid_range(range: SeriesExportRequest) -> Option<&'static str> {
+    if range.from_number.is_some_and(|from| from < 1_i32)
+        || range.to_number.is_some_and(|to| to < 1_i32)
+    {
+        return Some("Post numbers start at 1");
+    }
+    match (range.from_number, range.to_number) {
+        (Some(from), Some(to)) if from > to => Some("fromNumber must not be greater than toNumber"),
+        _ => None,
+    }
+}
+
+/// Helper function to check that the user owns the series being exported
+async fn check_series_owner(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    series_id: Uuid,
+) -> Result<(), ServiceError> {
+    use shared::schema::series::dsl as series_dsl;
+
+    let owns_series = diesel::select(diesel::dsl::exists(
+        series_dsl::series
+            .filter(series_dsl::id.eq(series_id))
+            .filter(series_dsl::user_id.eq(user_id))
+            .filter(series_dsl::deleted_at.is_null()),
+    ))
+    .get_result::<bool>(conn)
+    .await?;
+    if owns_series {
+        Ok(())
+    } else {
+        Err(ServiceError::Forbidden(
+            "Series not found or access denied".to_owned(),
+        ))
+    }
+}
+
+/// Compile a series, or a range of its posts, into an EPUB
+///
+/// Published posts between `fromNumber` and `toNumber`, both optional, become
+/// chapters in number order, with a table of contents from their numbers and
+/// titles. The series cover, the images of the posts and the creator's display
+/// name are included. The book is stored as a file of the user; the job's
+/// result, an `EpubExportResult`, names it.
+///
+/// # Errors
+/// Returns an error if the range is invalid or has no published posts, the
+/// series does not belong to the user, an export is already in progress, or
+/// database operations fail.
+#[utoipa::path(
+    post,
+    path = "/series/{series_id}/actions/export-epub",
+    context_path = "/api",
+    tag = "Series",
+    params(("series_id" = Uuid, Path, description = "UUID of the series to export")),
+    request_body(content = SeriesExportRequest, description = "Range of post numbers to include"),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 202, description = "Export queued; its result is an EpubExportResult", body = JobResponse),
+        (status = 400, description = "Invalid range, or no published posts in it", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Access denied - series does not belong to authenticated user", body = ErrorResponse),
+        (status = 409, description = "An EPUB export is already in progress", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn export_epub(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    body: web::Json<SeriesExportRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::posts::dsl as posts_dsl;
+
+    let series_id = path.into_inner();
+    let range = body.into_inner();
+    if let Some(message) = invalid_range(range) {
+        return Ok(json_error(message));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    check_series_owner(&mut conn, user.id, series_id).await?;
+
+    let has_posts = diesel::select(diesel::dsl::exists(
+        posts_dsl::posts
+            .filter(posts_dsl::series_id.eq(series_id))
+            .filter(posts_dsl::is_published.eq(true))
+            .filter(posts_dsl::deleted_at.is_null())
+            .filter(posts_dsl::number.ge(range.from_number.unwrap_or(1_i32)))
+            .filter(posts_dsl::number.le(range.to_number.unwrap_or(i32::MAX))),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+    if !has_posts {
+        return Ok(json_error(
+            "The series has no published posts in this range",
+        ));
+    }
+
+    if has_active_job(&mut conn, user.id, JobKind::EpubExport).await? {
+        return Err(
+            ServiceError::Conflict("An EPUB export is already in progress".to_owned()).into(),
+        );
+    }
+
+    let job_payload = EpubExportPayload {
+        series_id,
+        from_number: range.from_number,
+        to_number: range.to_number,
+    };
+    let job = enqueue_job(
+        &mut conn,
+        user.id,
+        JobKind::EpubExport,
+        serde_json::to_value(job_payload).map_err(ServiceError::from)?,
+        None,
+    )
+    .await?;
+
+    Ok(HttpResponse::Accepted().json(JobResponse::from(job)))
+}
+
+/// Package the pages of a series' posts into comic book archives
+///
+/// Each published post with pages between `fromNumber` and `toNumber`, both
+/// optional, becomes a CBZ of its page images in order with a `ComicInfo.xml`
+/// naming the series, episode number and title, creator and publish date.
+/// The archives are stored as files of the user; the job's result, a
+/// `CbzExportResult`, lists them.
+///
+/// # Errors
+/// Returns an error if the range is invalid or has no published posts with
+/// pages, the series does not belong to the user, an export is already in
+/// progress, or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/series/{series_id}/actions/export-cbz",
+    context_path = "/api",
+    tag = "Series",
+    params(("series_id" = Uuid, Path, description = "UUID of the series to export")),
+    request_body(content = SeriesExportRequest, description = "Range of post numbers to include"),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 202, description = "Export queued; its result is a CbzExportResult", body = JobResponse),
+        (status = 400, description = "Invalid range, or no published posts with pages in it", body = ErrorResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 403, description = "Access denied - series does not belong to authenticated user", body = ErrorResponse),
+        (status = 409, description = "A CBZ export is already in progress", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn export_cbz(
+    user: User,
+    db_service: web::Data<DbService>,
+    path: web::Path<Uuid>,
+    body: web::Json<SeriesExportRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::{post_pages::dsl as pages_dsl, posts::dsl as posts_dsl};
+
+    let series_id = path.into_inner();
+    let range = body.into_inner();
+    if let Some(message) = invalid_range(range) {
+        return Ok(json_error(message));
+    }
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    check_series_owner(&mut conn, user.id, series_id).await?;
+
+    let has_pages = diesel::select(diesel::dsl::exists(
+        pages_dsl::post_pages
+            .inner_join(posts_dsl::posts)
+            .filter(posts_dsl::series_id.eq(series_id))
+            .filter(posts_dsl::is_published.eq(true))
+            .filter(posts_dsl::deleted_at.is_null())
+            .filter(posts_dsl::number.ge(range.from_number.unwrap_or(1_i32)))
+            .filter(posts_dsl::number.le(range.to_number.unwrap_or(i32::MAX))),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+    if !has_pages {
+        return Ok(json_error(
+            "None of the published posts in this range has pages",
+        ));
+    }
+
+    if has_active_job(&mut conn, user.id, JobKind::CbzExport).await? {
+        return Err(
+            ServiceError::Conflict("A CBZ export is already in progress".to_owned()).into(),
+        );
+    }
+
+    let job_payload = CbzExportPayload {
+        series_id,
+        from_number: range.from_number,
+        to_number: range.to_number,
+    };
+    let job = enqueue_job(
+        &mut conn,
+        user.id,
+        JobKind::CbzExport,
+        serde_json::to_value(job_payload).map_err(ServiceError::from)?,
+        None,
+    )
+    .await?;
+
+    Ok(HttpResponse::Accepted().json(JobResponse::from(job)))
+}
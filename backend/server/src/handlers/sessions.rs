@@ -0,0 +1,184 @@
+#![allow(clippy::unused_async)]
+
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::User,
+        sessions::{SessionResponse, SessionsListResponse},
+    },
+    services::{
+        audit::record_event, db::DbService, sessions::SessionService, user_cache::UserCacheService,
+    },
+};
+use std::ops::Add;
+use uuid::Uuid;
+
+/// Sign a user out of every device and revoke every token issued to them
+///
+/// Bumping `session_version` ends cookie sessions and access tokens; refresh
+/// tokens and third-party app tokens are revoked, and the session inventory
+/// and cached user row are cleared. API keys are left alone since the user
+/// manages them explicitly.
+pub(crate) async fn sign_out_everywhere(
+    conn: &mut AsyncPgConnection,
+    session_service: &SessionService,
+    user_cache: &UserCacheService,
+    user_id: Uuid,
+) -> Result<(), ServiceError> {
+    use shared::schema::oauth_tokens::dsl as oauth_tokens_dsl;
+    use shared::schema::refresh_tokens::dsl as refresh_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    let now = Utc::now().naive_utc();
+
+    let _ = diesel::update(users_dsl::users.find(user_id))
+        .set(users_dsl::session_version.eq(users_dsl::session_version.add(1_i32)))
+        .execute(conn)
+        .await?;
+
+    let _ = diesel::update(
+        refresh_dsl::refresh_tokens
+            .filter(refresh_dsl::user_id.eq(user_id))
+            .filter(refresh_dsl::revoked_at.is_null()),
+    )
+    .set(refresh_dsl::revoked_at.eq(Some(now)))
+    .execute(conn)
+    .await?;
+
+    let _ = diesel::update(
+        oauth_tokens_dsl::oauth_tokens
+            .filter(oauth_tokens_dsl::user_id.eq(user_id))
+            .filter(oauth_tokens_dsl::revoked_at.is_null()),
+    )
+    .set(oauth_tokens_dsl::revoked_at.eq(Some(now)))
+    .execute(conn)
+    .await?;
+
+    let _ = session_service.revoke_all_except(user_id, None).await?;
+    user_cache.invalidate(user_id).await
+}
+
+/// Remove the credentials of an account whose email address was never verified
+///
+/// Anyone can register an address with a password before its owner proves
+/// they hold it. When the owner does, by a magic link or a trusted identity
+/// provider, that password is removed and everything signed in so far is
+/// signed out, so whoever registered the account loses access to it.
+pub(crate) async fn discard_unverified_credentials(
+    conn: &mut AsyncPgConnection,
+    session_service: &SessionService,
+    user_cache: &UserCacheService,
+    user_id: Uuid,
+) -> Result<(), ServiceError> {
+    use shared::schema::users::dsl as users_dsl;
+
+    let _ = diesel::update(users_dsl::users.find(user_id))
+        .set(users_dsl::password_hash.eq(None::<String>))
+        .execute(conn)
+        .await?;
+    sign_out_everywhere(conn, session_service, user_cache, user_id).await
+}
+
+/// List active sessions
+///
+/// Returns every device the authenticated user is currently signed in on,
+/// newest first. The session making the request is flagged with `current`.
+///
+/// # Errors
+/// Returns an error if the session inventory cannot be read.
+#[utoipa::path(
+    get,
+    path = "/auth/sessions",
+    context_path = "/api",
+    tag = "Auth",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Active sessions for the authenticated user", body = SessionsListResponse),
+        (status = 401, description = "Authentication required to list sessions", body = ErrorResponse),
+        (status = 500, description = "Session store unavailable", body = ErrorResponse)
+    )
+)]
+pub async fn list_sessions(
+    user: User,
+    session: Session,
+    session_service: web::Data<SessionService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let current_session_id = session
+        .get::<String>(SessionService::SESSION_ID_KEY)
+        .ok()
+        .flatten();
+
+    let records = session_service.list(user.id).await?;
+
+    let sessions: Vec<SessionResponse> = records
+        .into_iter()
+        .map(|record| SessionResponse::from_record(record, current_session_id.as_deref()))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(SessionsListResponse::from(sessions)))
+}
+
+/// Revoke a session
+///
+/// Signs the given device out. Revoking the current session is equivalent to logging out.
+///
+/// # Errors
+/// Returns an error if the session does not exist or the session inventory cannot be updated.
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions/{session_id}",
+    context_path = "/api",
+    tag = "Auth",
+    params(("session_id" = String, Path, description = "ID of the session to revoke")),
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, description = "Authentication required to revoke sessions", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Session store unavailable", body = ErrorResponse)
+    )
+)]
+pub async fn revoke_session(
+    req: HttpRequest,
+    user: User,
+    session: Session,
+    session_service: web::Data<SessionService>,
+    db_service: web::Data<DbService>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let session_id = path.into_inner();
+
+    if !session_service.revoke(user.id, &session_id).await? {
+        return Err(ServiceError::NotFound("Session not found".to_owned()).into());
+    }
+
+    record_event(
+        &db_service,
+        &req,
+        Some(user.id),
+        AuditEventType::SessionRevoked,
+        json!({ "sessionId": session_id }),
+    )
+    .await;
+
+    let current_session_id = session
+        .get::<String>(SessionService::SESSION_ID_KEY)
+        .ok()
+        .flatten();
+    if current_session_id.as_deref() == Some(session_id.as_str()) {
+        session.purge();
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
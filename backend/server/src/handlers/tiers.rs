@@ -0,0 +1,54 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        auth::User,
+        tiers::{Tier, TierResponse, TiersListResponse},
+    },
+    services::db::DbService,
+};
+
+/// List the signed-in creator's membership tiers
+///
+/// Returns the tiers cheapest first, which is also the order in which they
+/// unlock posts.
+///
+/// # Errors
+/// Returns an error if the user is not authenticated or database operations fail.
+#[utoipa::path(
+    get,
+    path = "/tiers",
+    context_path = "/api",
+    tag = "Tiers",
+    security(
+        ("cookieAuth" = [], "bearerAuth" = [])
+    ),
+    responses(
+        (status = 200, description = "Membership tiers, cheapest first", body = TiersListResponse),
+        (status = 401, description = "Authentication required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn list_tiers(
+    user: User,
+    db_service: web::Data<DbService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::tiers::dsl as tiers_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let tiers: Vec<Tier> = tiers_dsl::tiers
+        .filter(tiers_dsl::user_id.eq(user.id))
+        .order((tiers_dsl::amount_cents.asc(), tiers_dsl::name.asc()))
+        .load(&mut conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let responses: Vec<TierResponse> = tiers.into_iter().map(TierResponse::from).collect();
+    Ok(HttpResponse::Ok().json(TiersListResponse::from(responses)))
+}
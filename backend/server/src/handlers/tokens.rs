@@ -0,0 +1,394 @@
+#![allow(clippy::unused_async)]
+
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Result};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde_json::json;
+use shared::{
+    errors::{ErrorResponse, ServiceError},
+    models::{
+        audit::AuditEventType,
+        auth::User,
+        tokens::{AuthTokensResponse, RefreshToken, RevokeRefreshTokenRequest, TokenGrantRequest},
+    },
+    services::{
+        audit::{email_domain, record_event},
+        db::DbService,
+        rate_limit::{RateLimitService, RateLimitedAction},
+        tokens::{generate_refresh_token, hash_token, AccessTokenService},
+    },
+};
+use uuid::Uuid;
+
+use super::auth::{json_error, verify_password};
+use super::login_alerts::alert_if_new_device;
+
+/// Helper function to reject a refresh token without revealing why
+fn invalid_refresh_token() -> ServiceError {
+    ServiceError::Unauthorized("Invalid or expired refresh token".to_owned())
+}
+
+/// Issue an access token and a new refresh token in the given family
+async fn issue_token_pair(
+    conn: &mut AsyncPgConnection,
+    token_service: &AccessTokenService,
+    user: &User,
+    family_id: Uuid,
+    device_name: Option<String>,
+) -> Result<AuthTokensResponse, ServiceError> {
+    use shared::schema::refresh_tokens::dsl as refresh_dsl;
+
+    let refresh_token = generate_refresh_token()?;
+    let refresh_ttl = Duration::days(token_service.refresh_ttl_days());
+    let now = Utc::now().naive_utc();
+    let expires_at = now
+        .checked_add_signed(refresh_ttl)
+        .ok_or_else(|| ServiceError::Unknown("Failed to compute expiration time".to_owned()))?;
+
+    let _ = diesel::insert_into(refresh_dsl::refresh_tokens)
+        .values(&RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            family_id,
+            token_hash: hash_token(&refresh_token),
+            session_version: user.session_version,
+            device_name,
+            expires_at,
+            used_at: None,
+            revoked_at: None,
+            created_at: now,
+        })
+        .execute(conn)
+        .await?;
+
+    Ok(AuthTokensResponse {
+        access_token: token_service.issue_access_token(user, family_id)?,
+        token_type: "Bearer".to_owned(),
+        expires_in: token_service.access_ttl_seconds(),
+        refresh_token,
+        refresh_expires_in: refresh_ttl.num_seconds(),
+    })
+}
+
+/// Revoke every refresh token in a family, signing out the device it was issued to
+async fn revoke_family(conn: &mut AsyncPgConnection, family_id: Uuid) -> Result<(), ServiceError> {
+    use shared::schema::refresh_tokens::dsl as refresh_dsl;
+
+    let _ = diesel::update(
+        refresh_dsl::refresh_tokens
+            .filter(refresh_dsl::family_id.eq(family_id))
+            .filter(refresh_dsl::revoked_at.is_null()),
+    )
+    .set(refresh_dsl::revoked_at.eq(Some(Utc::now().naive_utc())))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Sign in with email and password, starting a new refresh token family
+#[allow(clippy::too_many_lines)]
+async fn password_grant(
+    req: &HttpRequest,
+    db_service: &DbService,
+    conn: &mut AsyncPgConnection,
+    rate_limiter: &RateLimitService,
+    token_service: &AccessTokenService,
+    body: TokenGrantRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::refresh_tokens::dsl as refresh_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    let (Some(email), Some(password)) = (body.email.as_deref(), body.password.as_deref()) else {
+        return Ok(json_error("Email and password are required"));
+    };
+
+    rate_limiter
+        .check_request(RateLimitedAction::Login, req, Some(email))
+        .await?;
+
+    let Some(user) = users_dsl::users
+        .filter(users_dsl::email.eq(email))
+        .first::<User>(conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?
+    else {
+        record_event(
+            db_service,
+            req,
+            None,
+            AuditEventType::LoginFailed,
+            json!({
+                "method": "token",
+                "emailDomain": email_domain(email),
+                "reason": "unknown_email",
+            }),
+        )
+        .await;
+        return Ok(json_error("Invalid email or password"));
+    };
+
+    let Some(ref password_hash) = user.password_hash else {
+        record_event(
+            db_service,
+            req,
+            Some(user.id),
+            AuditEventType::LoginFailed,
+            json!({ "method": "token", "reason": "no_password" }),
+        )
+        .await;
+        return Ok(json_error(
+            "This account has no password. Sign in with the provider it is linked to.",
+        ));
+    };
+
+    if !verify_password(password, password_hash)? {
+        record_event(
+            db_service,
+            req,
+            Some(user.id),
+            AuditEventType::LoginFailed,
+            json!({ "method": "token", "reason": "wrong_password" }),
+        )
+        .await;
+        return Ok(json_error("Invalid email or password"));
+    }
+
+    if user.is_suspended() {
+        return Err(user.suspended_error());
+    }
+
+    let now = Utc::now().naive_utc();
+    let _ = diesel::update(users_dsl::users.find(user.id))
+        .set(users_dsl::last_login.eq(Some(now)))
+        .execute(conn)
+        .await
+        .map_err(ServiceError::from)?;
+
+    // Expired tokens are no longer needed for reuse detection
+    if let Err(e) = diesel::delete(
+        refresh_dsl::refresh_tokens
+            .filter(refresh_dsl::user_id.eq(user.id))
+            .filter(refresh_dsl::expires_at.le(now)),
+    )
+    .execute(conn)
+    .await
+    {
+        tracing::warn!("Failed to clean up expired refresh tokens: {}", e);
+    }
+
+    let device_name = body
+        .device_name
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty());
+
+    record_event(
+        db_service,
+        req,
+        Some(user.id),
+        AuditEventType::Login,
+        json!({ "method": "token", "deviceName": device_name }),
+    )
+    .await;
+
+    let tokens = issue_token_pair(conn, token_service, &user, Uuid::new_v4(), device_name).await?;
+    alert_if_new_device(req, db_service, &user).await;
+    Ok(token_response(&tokens))
+}
+
+/// Exchange a refresh token for a new pair
+///
+/// The presented token is marked used in the same statement that looks it up.
+/// If a token that was already used comes back, it has been copied, so its
+/// whole family is revoked and both holders must sign in again.
+async fn refresh_grant(
+    conn: &mut AsyncPgConnection,
+    token_service: &AccessTokenService,
+    body: &TokenGrantRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::refresh_tokens::dsl as refresh_dsl;
+    use shared::schema::users::dsl as users_dsl;
+
+    let Some(refresh_token) = body.refresh_token.as_deref() else {
+        return Ok(json_error("Refresh token is required"));
+    };
+
+    let token_hash = hash_token(refresh_token);
+    let now = Utc::now().naive_utc();
+
+    let rotated = diesel::update(
+        refresh_dsl::refresh_tokens
+            .filter(refresh_dsl::token_hash.eq(&token_hash))
+            .filter(refresh_dsl::used_at.is_null())
+            .filter(refresh_dsl::revoked_at.is_null())
+            .filter(refresh_dsl::expires_at.gt(now)),
+    )
+    .set(refresh_dsl::used_at.eq(Some(now)))
+    .get_result::<RefreshToken>(conn)
+    .await
+    .optional()
+    .map_err(ServiceError::from)?;
+
+    let Some(previous) = rotated else {
+        let replayed = refresh_dsl::refresh_tokens
+            .filter(refresh_dsl::token_hash.eq(&token_hash))
+            .filter(refresh_dsl::used_at.is_not_null())
+            .filter(refresh_dsl::revoked_at.is_null())
+            .first::<RefreshToken>(conn)
+            .await
+            .optional()
+            .map_err(ServiceError::from)?;
+
+        if let Some(reused) = replayed {
+            tracing::warn!(
+                "Refresh token reuse detected for user {}, revoking token family {}",
+                reused.user_id,
+                reused.family_id
+            );
+            revoke_family(conn, reused.family_id).await?;
+        }
+        return Err(invalid_refresh_token().into());
+    };
+
+    let owner = users_dsl::users
+        .find(previous.user_id)
+        .first::<User>(conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?;
+
+    let Some(user) = owner.filter(|current| current.session_version == previous.session_version)
+    else {
+        revoke_family(conn, previous.family_id).await?;
+        return Err(invalid_refresh_token().into());
+    };
+
+    let tokens = issue_token_pair(
+        conn,
+        token_service,
+        &user,
+        previous.family_id,
+        previous.device_name,
+    )
+    .await?;
+    Ok(token_response(&tokens))
+}
+
+/// Helper function to send tokens with caching disabled
+fn token_response(tokens: &AuthTokensResponse) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(tokens)
+}
+
+/// Issue tokens for a native client
+///
+/// With the `password` grant, signs in with email and password and returns a
+/// short-lived access token plus a refresh token. With the `refresh_token`
+/// grant, exchanges a refresh token for a new pair; every refresh token can be
+/// used once. Send the access token as `Authorization: Bearer` on any endpoint
+/// that accepts a signed-in user.
+///
+/// # Errors
+/// Returns an error if the refresh token is invalid or database operations fail.
+#[utoipa::path(
+    post,
+    path = "/auth/token",
+    context_path = "/api",
+    tag = "Auth",
+    request_body(content = TokenGrantRequest, description = "Credentials or refresh token to exchange"),
+    responses(
+        (status = 200, description = "Tokens issued", body = AuthTokensResponse),
+        (status = 400, description = "Invalid credentials or unsupported grant type", body = ErrorResponse,
+            example = json!({
+                "error": "Invalid email or password"
+            })
+        ),
+        (status = 401, description = "Refresh token is invalid, expired, revoked or was already used", body = ErrorResponse,
+            example = json!({
+                "error": "Invalid or expired refresh token"
+            })
+        ),
+        (status = 429, description = "Too many attempts, retry after the number of seconds in the Retry-After header", body = ErrorResponse,
+            example = json!({
+                "error": "Too many requests, retry in 60 seconds",
+                "code": "RATE_LIMITED"
+            })
+        ),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn issue_tokens(
+    req: HttpRequest,
+    rate_limiter: web::Data<RateLimitService>,
+    db_service: web::Data<DbService>,
+    token_service: web::Data<AccessTokenService>,
+    body: web::Json<TokenGrantRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let grant = body.into_inner();
+    match grant.grant_type.as_str() {
+        "password" => {
+            password_grant(
+                &req,
+                &db_service,
+                &mut conn,
+                &rate_limiter,
+                &token_service,
+                grant,
+            )
+            .await
+        }
+        "refresh_token" => refresh_grant(&mut conn, &token_service, &grant).await,
+        _ => Ok(json_error(
+            "Unsupported grant type, use password or refresh_token",
+        )),
+    }
+}
+
+/// Revoke a refresh token
+///
+/// Signs out the device the token was issued to: the token, every token
+/// rotated from the same login, and their access tokens stop working. Unknown
+/// tokens are ignored.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+#[utoipa::path(
+    post,
+    path = "/auth/token/revoke",
+    context_path = "/api",
+    tag = "Auth",
+    request_body(content = RevokeRefreshTokenRequest, description = "Refresh token to revoke"),
+    responses(
+        (status = 204, description = "Token revoked or unknown"),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    )
+)]
+pub async fn revoke_token(
+    db_service: web::Data<DbService>,
+    body: web::Json<RevokeRefreshTokenRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::refresh_tokens::dsl as refresh_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let family_id = refresh_dsl::refresh_tokens
+        .filter(refresh_dsl::token_hash.eq(hash_token(&body.refresh_token)))
+        .select(refresh_dsl::family_id)
+        .first::<Uuid>(&mut conn)
+        .await
+        .optional()
+        .map_err(ServiceError::from)?;
+
+    if let Some(family) = family_id {
+        revoke_family(&mut conn, family).await?;
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -127,7 +127,7 @@ pub async fn upload_file(
 
         if field_name.as_str() == "file" {
             let content_disposition = field.content_disposition();
-            original_filename = content_disposition.get_filename().map(ToString::to_string);
+            original_filename = content_disposition.get_filename().map(str::to_owned);
 
             content_type = Some(field.content_type().to_string());
 
@@ -519,6 +519,8 @@ pub async fn delete_file(
 ///
 /// This endpoint is designed to be used to get file content without authentication.
 /// It returns the file content with proper cache headers for public access.
+/// Files of suspended accounts are not served. Files attached to a post for
+/// members of a tier are only served to their owner, and never cached publicly.
 /// The file content is streamed directly from S3 to minimize memory usage for large files.
 ///
 /// # Errors
@@ -533,24 +535,38 @@ pub async fn delete_file(
     responses(
         (status = 200, description = "Streaming file content with CDN-optimized headers", content_type = "application/octet-stream",
             example = "Binary file content with appropriate Content-Type and Cache-Control headers"),
+        (status = 403, description = "The file is only available to members of a tier", body = ErrorResponse),
         (status = 404, description = "CDN file not found", body = ErrorResponse),
         (status = 500, description = "CDN streaming error from storage backend", body = ErrorResponse)
-    )
+    ),
+    security((), ("cookieAuth" = [], "bearerAuth" = []))
 )]
 pub async fn serve_file_cdn(
+    user: Option<User>,
     db_service: web::Data<shared::services::db::DbService>,
     s3_service: web::Data<S3Service>,
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse, actix_web::Error> {
+    use shared::schema::post_attachments::dsl as attachments_dsl;
     use shared::schema::user_files::dsl as files_dsl;
+    use shared::schema::users::dsl as users_dsl;
 
     let file_id = path.into_inner();
     let pool = db_service.pool();
     let mut conn = pool.get().await.map_err(ServiceError::from)?;
 
+    // Files of suspended accounts are hidden until the suspension ends
+    let now = Utc::now().naive_utc();
     let file: UserFile = files_dsl::user_files
+        .inner_join(users_dsl::users)
         .filter(files_dsl::id.eq(file_id))
         .filter(files_dsl::deleted_at.is_null())
+        .filter(
+            users_dsl::suspended_at
+                .is_null()
+                .or(users_dsl::suspended_until.le(now)),
+        )
+        .select(UserFile::as_select())
         .first(&mut conn)
         .await
         .map_err(|e| match e {
@@ -558,6 +574,23 @@ pub async fn serve_file_cdn(
             _ => actix_web::error::ErrorInternalServerError(format!("Database error: {e}")),
         })?;
 
+    // Until memberships exist, no one but the owner qualifies for a tier
+    let is_locked = diesel::select(diesel::dsl::exists(
+        attachments_dsl::post_attachments
+            .filter(attachments_dsl::file_id.eq(file_id))
+            .filter(attachments_dsl::min_tier_id.is_not_null()),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(ServiceError::from)?;
+    let is_owner = user.is_some_and(|viewer| viewer.id == file.user_id);
+    if is_locked && !is_owner {
+        return Err(ServiceError::Forbidden(
+            "This file is only available to members of a tier".to_owned(),
+        )
+        .into());
+    }
+
     let file_stream = s3_service
         .get_object_stream(&file.file_path)
         .await
@@ -571,7 +604,14 @@ pub async fn serve_file_cdn(
 
     Ok(HttpResponse::Ok()
         .content_type(file.mime_type.as_str())
-        .insert_header(("Cache-Control", "public, max-age=86400, immutable"))
+        .insert_header((
+            "Cache-Control",
+            if is_locked {
+                "private, no-store"
+            } else {
+                "public, max-age=86400, immutable"
+            },
+        ))
         .insert_header(("ETag", format!("\"{}\"", file.file_hash)))
         .insert_header((
             "Content-Disposition",
@@ -1,5 +1,7 @@
 //! Backend server for Patron, providing API endpoints and authentication services.
 
+/// Cross-site request forgery protection for cookie-authenticated requests.
+pub mod csrf;
 /// Handlers module containing API endpoint implementations.
 pub mod handlers;
 /// `OpenAPI` documentation module for API specification and documentation.
@@ -9,15 +11,27 @@ use actix_cors::Cors;
 use actix_session::{config::PersistentSession, storage::RedisSessionStore, SessionMiddleware};
 use actix_web::{
     cookie::Key,
-    middleware::Logger,
+    http::header,
+    middleware::{from_fn, Logger},
     web::{self, PayloadConfig},
     App, HttpServer,
 };
 use openapi::ApiDoc;
 use redis::aio::ConnectionManager;
 use shared::services::{
-    auth::GoogleOAuthService, config::ConfigService, db::DbService, email::EmailService,
+    account_deletion::purge_due_accounts,
+    auth::GoogleOAuthService,
+    cleanup::purge_expired_tokens,
+    config::ConfigService,
+    db::DbService,
+    email::EmailService,
+    jobs::{purge_expired_job_outputs, run_next_job, JobContext},
+    oidc::OidcService,
+    rate_limit::RateLimitService,
     s3::S3Service,
+    sessions::SessionService,
+    tokens::AccessTokenService,
+    user_cache::UserCacheService,
 };
 use tracing::level_filters::LevelFilter;
 use tracing_actix_web::TracingLogger;
@@ -25,6 +39,32 @@ use tracing_subscriber::EnvFilter;
 use utoipa::OpenApi;
 use utoipa_redoc::{Redoc, Servable};
 
+/// How often expired tokens are purged from the database
+const TOKEN_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// How often accounts whose deletion grace period has passed are purged
+const ACCOUNT_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// How often the background job worker checks for queued jobs
+const JOB_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often expired job outputs are removed from the bucket
+const JOB_OUTPUT_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Build the CORS middleware from the configured origin allowlist
+///
+/// Credentials are allowed, so only the listed origins are echoed back.
+fn cors_middleware(allowed_origins: &[String]) -> Cors {
+    allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(["GET", "POST", "PUT", "PATCH", "DELETE"])
+        .allowed_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT])
+        .expose_headers([header::RETRY_AFTER, header::CONTENT_DISPOSITION])
+        .supports_credentials()
+        .max_age(3600)
+}
+
 /// Entry point for the Patron backend server, sets up services and starts the HTTP server.
 ///
 /// # Errors
@@ -86,6 +126,18 @@ pub async fn main() -> std::io::Result<()> {
     };
     let google_oauth_service = GoogleOAuthService::new(google_oauth_config);
 
+    let token_config = match config.token_config() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Failed to get token config: {e}");
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "Failed to get token config",
+            ));
+        }
+    };
+    let token_service = AccessTokenService::new(&token_config);
+
     let email_service = EmailService::from_env();
 
     let db_config = match config.database_config() {
@@ -161,6 +213,90 @@ pub async fn main() -> std::io::Result<()> {
         }
     };
 
+    let session_service = SessionService::new(redis_manager.clone(), redis_config.session_ttl_days);
+    let user_cache = UserCacheService::new(redis_manager.clone());
+    let rate_limiter = RateLimitService::new(redis_manager.clone(), &config);
+    let oidc_service = OidcService::new(config.oidc_providers.clone());
+
+    let cleanup_db_service = db_service.clone();
+    let _cleanup_task = actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(TOKEN_CLEANUP_INTERVAL);
+        loop {
+            let _ = ticker.tick().await;
+            match purge_expired_tokens(&cleanup_db_service).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired tokens", purged),
+                Err(e) => tracing::warn!("Failed to purge expired tokens: {}", e),
+            }
+        }
+    });
+
+    let purge_db_service = db_service.clone();
+    let purge_s3_service = s3_service.clone();
+    let purge_session_service = session_service.clone();
+    let purge_user_cache = user_cache.clone();
+    let purge_email_service = email_service.clone();
+    let _account_purge_task = actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(ACCOUNT_PURGE_INTERVAL);
+        loop {
+            let _ = ticker.tick().await;
+            match purge_due_accounts(
+                &purge_db_service,
+                &purge_s3_service,
+                &purge_session_service,
+                &purge_user_cache,
+                &purge_email_service,
+            )
+            .await
+            {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} deleted accounts", purged),
+                Err(e) => tracing::warn!("Failed to purge deleted accounts: {}", e),
+            }
+        }
+    });
+
+    let job_context = JobContext {
+        db_service: db_service.clone(),
+        s3_service: s3_service.clone(),
+        email_service: email_service.clone(),
+        frontend_url: google_oauth_service.frontend_url.clone(),
+    };
+    let _job_worker_task = actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(JOB_POLL_INTERVAL);
+        loop {
+            let _ = ticker.tick().await;
+            loop {
+                match run_next_job(&job_context).await {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    Err(e) => {
+                        tracing::warn!("Failed to run background job: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let outputs_db_service = db_service.clone();
+    let outputs_s3_service = s3_service.clone();
+    let _job_output_purge_task = actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(JOB_OUTPUT_PURGE_INTERVAL);
+        loop {
+            let _ = ticker.tick().await;
+            match purge_expired_job_outputs(&outputs_db_service, &outputs_s3_service).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired job outputs", purged),
+                Err(e) => tracing::warn!("Failed to purge expired job outputs: {}", e),
+            }
+        }
+    });
+
+    if config.cors_allowed_origins.is_empty() {
+        tracing::warn!("CORS_ALLOWED_ORIGINS is empty, browsers cannot call the API cross-origin");
+    }
+
     let session_key = Key::from(google_oauth_service.auth_secret_key.as_bytes());
 
     HttpServer::new(move || {
@@ -169,7 +305,8 @@ pub async fn main() -> std::io::Result<()> {
             .wrap(Logger::new(
                 "%a \"%r\" %s %b \"%{Referer}i\" \"%{User-Agent}i\" %T ms",
             ))
-            .wrap(Cors::permissive())
+            .wrap(from_fn(csrf::csrf_protection))
+            .wrap(cors_middleware(&config.cors_allowed_origins))
             .app_data(PayloadConfig::new(0x4000_0000))
             .wrap(
                 SessionMiddleware::builder(redis_store.clone(), session_key.clone())
@@ -195,6 +332,11 @@ pub async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(google_oauth_service.clone()))
             .app_data(web::Data::new(email_service.clone()))
             .app_data(web::Data::new(redis_manager.clone()))
+            .app_data(web::Data::new(session_service.clone()))
+            .app_data(web::Data::new(user_cache.clone()))
+            .app_data(web::Data::new(rate_limiter.clone()))
+            .app_data(web::Data::new(oidc_service.clone()))
+            .app_data(web::Data::new(token_service.clone()))
             .service(Redoc::with_url("/redoc", ApiDoc::openapi()))
             .service(
                 web::scope("/api")
@@ -237,6 +379,22 @@ pub async fn main() -> std::io::Result<()> {
                                 web::resource("/reset-password")
                                     .route(web::post().to(handlers::auth::reset_password)),
                             )
+                            .service(
+                                web::resource("/change-password")
+                                    .route(web::post().to(handlers::auth::change_password)),
+                            )
+                            .service(
+                                web::resource("/set-password")
+                                    .route(web::post().to(handlers::auth::set_password)),
+                            )
+                            .service(
+                                web::resource("/change-email")
+                                    .route(web::post().to(handlers::auth::request_email_change)),
+                            )
+                            .service(
+                                web::resource("/confirm-email-change")
+                                    .route(web::get().to(handlers::auth::confirm_email_change)),
+                            )
                             .service(
                                 web::resource("/check-email")
                                     .route(web::post().to(handlers::auth::check_email)),
@@ -245,6 +403,85 @@ pub async fn main() -> std::io::Result<()> {
                                 web::resource("/resend-verification").route(
                                     web::post().to(handlers::auth::resend_verification_email),
                                 ),
+                            )
+                            .service(
+                                web::resource("/sessions")
+                                    .route(web::get().to(handlers::sessions::list_sessions)),
+                            )
+                            .service(
+                                web::resource("/sessions/{session_id}")
+                                    .route(web::delete().to(handlers::sessions::revoke_session)),
+                            )
+                            .service(
+                                web::resource("/login-alerts/deny")
+                                    .route(
+                                        web::get().to(handlers::login_alerts::confirm_deny_login),
+                                    )
+                                    .route(web::post().to(handlers::login_alerts::deny_login)),
+                            )
+                            .service(
+                                web::resource("/activity")
+                                    .route(web::get().to(handlers::audit::list_activity)),
+                            )
+                            .service(
+                                web::resource("/account/deletion")
+                                    .route(
+                                        web::post().to(
+                                            handlers::account_deletion::request_account_deletion,
+                                        ),
+                                    )
+                                    .route(
+                                        web::delete().to(
+                                            handlers::account_deletion::cancel_account_deletion,
+                                        ),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/export").route(
+                                    web::post().to(handlers::data_export::request_data_export),
+                                ),
+                            )
+                            .service(
+                                web::resource("/token")
+                                    .route(web::post().to(handlers::tokens::issue_tokens)),
+                            )
+                            .service(
+                                web::resource("/token/revoke")
+                                    .route(web::post().to(handlers::tokens::revoke_token)),
+                            )
+                            .service(
+                                web::resource("/magic-link").route(
+                                    web::post().to(handlers::magic_link::request_magic_link),
+                                ),
+                            )
+                            .service(
+                                web::resource("/magic-link/verify")
+                                    .route(web::get().to(handlers::magic_link::confirm_magic_link))
+                                    .route(web::post().to(handlers::magic_link::verify_magic_link)),
+                            )
+                            .service(
+                                web::resource("/providers")
+                                    .route(web::get().to(handlers::oidc::list_providers)),
+                            )
+                            .service(
+                                web::resource("/oidc/{provider}")
+                                    .route(web::get().to(handlers::oidc::oidc_redirect)),
+                            )
+                            .service(
+                                web::resource("/oidc/{provider}/callback")
+                                    .route(web::get().to(handlers::oidc::oidc_callback)),
+                            )
+                            .service(
+                                web::resource("/identities")
+                                    .route(web::get().to(handlers::oidc::list_identities)),
+                            )
+                            .service(
+                                web::resource("/identities/{provider}/link")
+                                    .route(web::get().to(handlers::oidc::link_identity_redirect)),
+                            )
+                            .service(
+                                web::resource("/identities/{identity_id}")
+                                    .route(web::delete().to(handlers::oidc::unlink_identity)),
                             ),
                     )
                     .service(
@@ -264,6 +501,38 @@ pub async fn main() -> std::io::Result<()> {
                                     .route(web::delete().to(handlers::user_files::delete_file)),
                             ),
                     )
+                    .service(
+                        web::scope("/jobs")
+                            .service(
+                                web::resource("").route(web::get().to(handlers::jobs::list_jobs)),
+                            )
+                            .service(
+                                web::resource("/{job_id}")
+                                    .route(web::get().to(handlers::jobs::get_job)),
+                            ),
+                    )
+                    .service(
+                        web::scope("/imports")
+                            .service(
+                                web::resource("/patreon").route(
+                                    web::post().to(handlers::patreon_import::import_patreon),
+                                ),
+                            )
+                            .service(web::resource("/patreon/{job_id}/run").route(
+                                web::post().to(handlers::patreon_import::run_patreon_import),
+                            ))
+                            .service(
+                                web::resource("/feed")
+                                    .route(web::post().to(handlers::feed_import::import_feed)),
+                            ),
+                    )
+                    .service(
+                        web::resource("/tiers").route(web::get().to(handlers::tiers::list_tiers)),
+                    )
+                    .service(
+                        web::resource("/invitations")
+                            .route(web::get().to(handlers::invitations::list_invitations)),
+                    )
                     .service(
                         web::scope("/cdn").service(
                             web::resource("/files/{file_id}")
@@ -277,6 +546,18 @@ pub async fn main() -> std::io::Result<()> {
                                     .route(web::post().to(handlers::series::create_series))
                                     .route(web::get().to(handlers::series::list_series)),
                             )
+                            .service(
+                                web::resource("/{series_id}/actions/import")
+                                    .route(web::post().to(handlers::post_import::import_posts)),
+                            )
+                            .service(
+                                web::resource("/{series_id}/actions/export-epub")
+                                    .route(web::post().to(handlers::series_export::export_epub)),
+                            )
+                            .service(
+                                web::resource("/{series_id}/actions/export-cbz")
+                                    .route(web::post().to(handlers::series_export::export_cbz)),
+                            )
                             .service(
                                 web::resource("/{series_id}")
                                     .route(web::get().to(handlers::series::get_series))
@@ -296,6 +577,33 @@ pub async fn main() -> std::io::Result<()> {
                                     .route(web::get().to(handlers::posts::get_post))
                                     .route(web::put().to(handlers::posts::update_post))
                                     .route(web::delete().to(handlers::posts::delete_post)),
+                            )
+                            .service(
+                                web::resource("/{post_id}/pages")
+                                    .route(web::get().to(handlers::post_pages::get_post_pages))
+                                    .route(web::put().to(handlers::post_pages::set_post_pages)),
+                            )
+                            .service(
+                                web::resource("/{post_id}/attachments")
+                                    .route(
+                                        web::get()
+                                            .to(handlers::post_attachments::list_post_attachments),
+                                    )
+                                    .route(
+                                        web::post()
+                                            .to(handlers::post_attachments::create_post_attachment),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/{post_id}/attachments/{attachment_id}")
+                                    .route(
+                                        web::put()
+                                            .to(handlers::post_attachments::update_post_attachment),
+                                    )
+                                    .route(
+                                        web::delete()
+                                            .to(handlers::post_attachments::delete_post_attachment),
+                                    ),
                             ),
                     )
                     .service(
@@ -312,6 +620,83 @@ pub async fn main() -> std::io::Result<()> {
                                     .route(web::delete().to(handlers::api_keys::delete_api_key)),
                             ),
                     )
+                    .service(
+                        web::scope("/oauth")
+                            .service(
+                                web::resource("/clients")
+                                    .route(web::post().to(handlers::oauth::create_client))
+                                    .route(web::get().to(handlers::oauth::list_clients)),
+                            )
+                            .service(
+                                web::resource("/clients/{client_id}")
+                                    .route(web::delete().to(handlers::oauth::delete_client)),
+                            )
+                            .service(
+                                web::resource("/authorize")
+                                    .route(web::get().to(handlers::oauth::get_consent))
+                                    .route(web::post().to(handlers::oauth::decide_consent)),
+                            )
+                            .service(
+                                web::resource("/token")
+                                    .route(web::post().to(handlers::oauth::token)),
+                            )
+                            .service(
+                                web::resource("/revoke")
+                                    .route(web::post().to(handlers::oauth::revoke)),
+                            )
+                            .service(
+                                web::resource("/introspect")
+                                    .route(web::post().to(handlers::oauth::introspect)),
+                            )
+                            .service(
+                                web::resource("/userinfo")
+                                    .route(web::get().to(handlers::oauth::userinfo)),
+                            )
+                            .service(
+                                web::resource("/memberships")
+                                    .route(web::get().to(handlers::oauth::list_memberships)),
+                            ),
+                    )
+                    .service(
+                        web::scope("/admin")
+                            .service(
+                                web::resource("/users")
+                                    .route(web::get().to(handlers::admin::search_users)),
+                            )
+                            .service(
+                                web::resource("/users/{user_id}")
+                                    .route(web::get().to(handlers::admin::get_user))
+                                    .route(web::put().to(handlers::admin::update_user)),
+                            )
+                            .service(
+                                web::resource("/users/{user_id}/suspend")
+                                    .route(web::post().to(handlers::admin::suspend_user)),
+                            )
+                            .service(
+                                web::resource("/users/{user_id}/unsuspend")
+                                    .route(web::post().to(handlers::admin::unsuspend_user)),
+                            )
+                            .service(
+                                web::resource("/users/{user_id}/force-password-reset")
+                                    .route(web::post().to(handlers::admin::force_password_reset)),
+                            )
+                            .service(
+                                web::resource("/users/{user_id}/files")
+                                    .route(web::get().to(handlers::admin::list_user_files)),
+                            )
+                            .service(
+                                web::resource("/series/{series_id}")
+                                    .route(web::delete().to(handlers::admin::delete_series)),
+                            )
+                            .service(
+                                web::resource("/posts/{post_id}")
+                                    .route(web::delete().to(handlers::admin::delete_post)),
+                            )
+                            .service(
+                                web::resource("/audit-events")
+                                    .route(web::get().to(handlers::admin::list_audit_events)),
+                            ),
+                    )
                     .service(
                         web::scope("/outrank").service(
                             web::resource("/webhook")
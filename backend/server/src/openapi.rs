@@ -1,23 +1,67 @@
 #![allow(clippy::needless_for_each)]
 
 use crate::handlers::auth::{
-    CheckEmailRequest, ForgotPasswordRequest, ForgotPasswordResponse, LoginRequest, LoginResponse,
-    LogoutResponse, RegisterRequest, RegisterResponse, ResendVerificationResponse,
-    ResetPasswordRequest, ResetPasswordResponse, UpdateUserInfoRequest, UpdateUserInfoResponse,
+    ChangeEmailRequest, ChangeEmailResponse, ChangePasswordRequest, CheckEmailRequest,
+    ForgotPasswordRequest, ForgotPasswordResponse, LoginRequest, LoginResponse, LogoutResponse,
+    PasswordFeedback, PasswordUpdatedResponse, RegisterRequest, RegisterResponse,
+    ResendVerificationResponse, ResetPasswordRequest, ResetPasswordResponse, SetPasswordRequest,
+    UpdateUserInfoRequest, UpdateUserInfoResponse, WeakPasswordResponse,
 };
+use crate::handlers::feed_import::FeedImportRequest;
+use crate::handlers::magic_link::{MagicLinkRequest, MagicLinkResponse, MagicLinkVerifyRequest};
 use crate::handlers::outrank::{OutrankWebhookPayload, OutrankWebhookResponse};
+use crate::handlers::patreon_import::PatreonImportRequest;
+use crate::handlers::post_import::PostImportRequest;
 use crate::handlers::user_files::{FileUploadRequest, FileUploadResponse};
+use shared::models::account_deletion::{AccountDeletionResponse, DeleteAccountRequest};
+use shared::models::admin::{
+    AdminUpdateUserRequest, AdminUserDetailResponse, AdminUserResponse, AdminUsersListResponse,
+    SuspendUserRequest,
+};
 use shared::models::api_keys::{
     ApiKeyResponse, ApiKeysListResponse, CreateApiKeyRequest, CreateApiKeyResponse,
     UpdateApiKeyRequest,
 };
-use shared::models::auth::{UserInfo, UserInfoResponse};
+use shared::models::audit::{AuditCursor, AuditEventResponse, AuditEventsListResponse};
+use shared::models::auth::{UserInfo, UserInfoResponse, UserRole};
+use shared::models::devices::DenyLoginRequest;
+use shared::models::identities::{
+    OidcProviderResponse, OidcProvidersListResponse, UserIdentitiesListResponse,
+    UserIdentityResponse,
+};
+use shared::models::imports::{
+    FeedImportReport, FeedImportSummary, FeedPostMapping, FeedSource, ImportFileReport,
+    ImportFileStatus, MappingAction, MemberMapping, PatreonImportReport, PatreonImportSummary,
+    PostImportResponse, PostMapping, TierMapping,
+};
+use shared::models::invitations::{
+    InvitationResponse, InvitationStatus, InvitationsListResponse, MembershipResponse,
+    MembershipsListResponse,
+};
+use shared::models::jobs::{JobKind, JobResponse, JobStatus, JobsListResponse};
+use shared::models::oauth::{
+    ConsentClient, ConsentDecisionRequest, ConsentDecisionResponse, ConsentResponse,
+    CreateOAuthClientRequest, CreateOAuthClientResponse, IntrospectionResponse,
+    OAuthClientResponse, OAuthClientsListResponse, OAuthErrorResponse, OAuthScope,
+    OAuthUserInfoResponse, ScopeDescription, TokenLookupRequest, TokenRequest, TokenResponse,
+};
+use shared::models::post_attachments::{
+    CreatePostAttachmentRequest, PostAttachmentResponse, PostAttachmentsResponse,
+    UpdatePostAttachmentRequest,
+};
+use shared::models::post_pages::{
+    PostPageInput, PostPageResponse, PostPagesResponse, UpdatePostPagesRequest,
+};
 use shared::models::posts::{
     CreatePostRequest, PostResponse, PostsListResponse, UpdatePostRequest,
 };
 use shared::models::series::{
-    CreateSeriesRequest, SeriesListResponse, SeriesResponse, UpdateSeriesRequest,
+    CbzExportFile, CbzExportResult, CreateSeriesRequest, EpubExportResult, SeriesExportRequest,
+    SeriesListResponse, SeriesResponse, UpdateSeriesRequest,
 };
+use shared::models::sessions::{SessionResponse, SessionsListResponse};
+use shared::models::tiers::{TierResponse, TiersListResponse};
+use shared::models::tokens::{AuthTokensResponse, RevokeRefreshTokenRequest, TokenGrantRequest};
 use shared::models::user_files::{
     FileStatus, UpdateUserFileRequest, UserFileInfo, UserFileResponse, UserFilesResponse,
 };
@@ -55,9 +99,44 @@ use utoipa::{
         crate::handlers::auth::get_me,
         crate::handlers::auth::forgot_password,
         crate::handlers::auth::reset_password,
+        crate::handlers::auth::change_password,
+        crate::handlers::auth::set_password,
+        crate::handlers::auth::request_email_change,
+        crate::handlers::auth::confirm_email_change,
         crate::handlers::auth::check_email,
         crate::handlers::auth::resend_verification_email,
         crate::handlers::auth::update_user_info,
+        crate::handlers::sessions::list_sessions,
+        crate::handlers::sessions::revoke_session,
+        crate::handlers::audit::list_activity,
+        crate::handlers::account_deletion::request_account_deletion,
+        crate::handlers::account_deletion::cancel_account_deletion,
+        crate::handlers::data_export::request_data_export,
+        crate::handlers::jobs::list_jobs,
+        crate::handlers::jobs::get_job,
+        crate::handlers::login_alerts::confirm_deny_login,
+        crate::handlers::login_alerts::deny_login,
+        crate::handlers::tokens::issue_tokens,
+        crate::handlers::tokens::revoke_token,
+        crate::handlers::magic_link::request_magic_link,
+        crate::handlers::magic_link::confirm_magic_link,
+        crate::handlers::magic_link::verify_magic_link,
+        crate::handlers::oidc::list_providers,
+        crate::handlers::oidc::oidc_redirect,
+        crate::handlers::oidc::oidc_callback,
+        crate::handlers::oidc::list_identities,
+        crate::handlers::oidc::link_identity_redirect,
+        crate::handlers::oidc::unlink_identity,
+        crate::handlers::oauth::create_client,
+        crate::handlers::oauth::list_clients,
+        crate::handlers::oauth::delete_client,
+        crate::handlers::oauth::get_consent,
+        crate::handlers::oauth::decide_consent,
+        crate::handlers::oauth::token,
+        crate::handlers::oauth::revoke,
+        crate::handlers::oauth::introspect,
+        crate::handlers::oauth::userinfo,
+        crate::handlers::oauth::list_memberships,
         crate::handlers::user_files::upload_file,
         crate::handlers::user_files::list_files,
         crate::handlers::user_files::get_file,
@@ -69,16 +148,40 @@ use utoipa::{
         crate::handlers::series::get_series,
         crate::handlers::series::update_series,
         crate::handlers::series::delete_series,
+        crate::handlers::series_export::export_epub,
+        crate::handlers::series_export::export_cbz,
         crate::handlers::posts::create_post,
         crate::handlers::posts::list_posts,
         crate::handlers::posts::get_post,
         crate::handlers::posts::update_post,
         crate::handlers::posts::delete_post,
+        crate::handlers::post_pages::set_post_pages,
+        crate::handlers::post_pages::get_post_pages,
+        crate::handlers::post_attachments::list_post_attachments,
+        crate::handlers::post_attachments::create_post_attachment,
+        crate::handlers::post_attachments::update_post_attachment,
+        crate::handlers::post_attachments::delete_post_attachment,
+        crate::handlers::post_import::import_posts,
+        crate::handlers::patreon_import::import_patreon,
+        crate::handlers::patreon_import::run_patreon_import,
+        crate::handlers::feed_import::import_feed,
+        crate::handlers::tiers::list_tiers,
+        crate::handlers::invitations::list_invitations,
         crate::handlers::api_keys::create_api_key,
         crate::handlers::api_keys::list_api_keys,
         crate::handlers::api_keys::get_api_key,
         crate::handlers::api_keys::update_api_key,
         crate::handlers::api_keys::delete_api_key,
+        crate::handlers::admin::search_users,
+        crate::handlers::admin::get_user,
+        crate::handlers::admin::update_user,
+        crate::handlers::admin::suspend_user,
+        crate::handlers::admin::unsuspend_user,
+        crate::handlers::admin::force_password_reset,
+        crate::handlers::admin::list_user_files,
+        crate::handlers::admin::delete_series,
+        crate::handlers::admin::delete_post,
+        crate::handlers::admin::list_audit_events,
         crate::handlers::outrank::process_webhook,
     ),
     components(
@@ -90,14 +193,60 @@ use utoipa::{
             LoginResponse,
             UserInfo,
             UserInfoResponse,
+            UserRole,
             ForgotPasswordRequest,
             ForgotPasswordResponse,
             ResetPasswordRequest,
             ResetPasswordResponse,
+            WeakPasswordResponse,
+            PasswordFeedback,
+            ChangePasswordRequest,
+            SetPasswordRequest,
+            PasswordUpdatedResponse,
+            ChangeEmailRequest,
+            ChangeEmailResponse,
             CheckEmailRequest,
             ResendVerificationResponse,
             UpdateUserInfoRequest,
             UpdateUserInfoResponse,
+            SessionResponse,
+            SessionsListResponse,
+            DenyLoginRequest,
+            AuditCursor,
+            AuditEventResponse,
+            AuditEventsListResponse,
+            DeleteAccountRequest,
+            AccountDeletionResponse,
+            JobKind,
+            JobStatus,
+            JobResponse,
+            JobsListResponse,
+            TokenGrantRequest,
+            AuthTokensResponse,
+            RevokeRefreshTokenRequest,
+            MagicLinkRequest,
+            MagicLinkResponse,
+            MagicLinkVerifyRequest,
+            OidcProviderResponse,
+            OidcProvidersListResponse,
+            UserIdentityResponse,
+            UserIdentitiesListResponse,
+            OAuthScope,
+            CreateOAuthClientRequest,
+            OAuthClientResponse,
+            CreateOAuthClientResponse,
+            OAuthClientsListResponse,
+            ScopeDescription,
+            ConsentClient,
+            ConsentResponse,
+            ConsentDecisionRequest,
+            ConsentDecisionResponse,
+            TokenRequest,
+            TokenResponse,
+            OAuthErrorResponse,
+            TokenLookupRequest,
+            IntrospectionResponse,
+            OAuthUserInfoResponse,
             FileStatus,
             UserFileInfo,
             UserFileResponse,
@@ -108,16 +257,56 @@ use utoipa::{
             SeriesResponse,
             SeriesListResponse,
             CreateSeriesRequest,
+            SeriesExportRequest,
+            EpubExportResult,
+            CbzExportResult,
+            CbzExportFile,
             UpdateSeriesRequest,
             PostResponse,
             PostsListResponse,
             CreatePostRequest,
             UpdatePostRequest,
+            PostPageInput,
+            UpdatePostPagesRequest,
+            PostPageResponse,
+            PostPagesResponse,
+            CreatePostAttachmentRequest,
+            UpdatePostAttachmentRequest,
+            PostAttachmentResponse,
+            PostAttachmentsResponse,
+            PostImportRequest,
+            PostImportResponse,
+            ImportFileReport,
+            ImportFileStatus,
+            PatreonImportRequest,
+            PatreonImportReport,
+            PatreonImportSummary,
+            MappingAction,
+            FeedImportRequest,
+            FeedImportReport,
+            FeedImportSummary,
+            FeedPostMapping,
+            FeedSource,
+            TierMapping,
+            PostMapping,
+            MemberMapping,
+            TierResponse,
+            TiersListResponse,
+            InvitationStatus,
+            InvitationResponse,
+            InvitationsListResponse,
+            MembershipResponse,
+            MembershipsListResponse,
             ApiKeyResponse,
             ApiKeysListResponse,
             CreateApiKeyRequest,
             CreateApiKeyResponse,
             UpdateApiKeyRequest,
+            AdminUserResponse,
+            AdminUsersListResponse,
+            AdminUserDetailResponse,
+            AdminUpdateUserRequest,
+            SuspendUserRequest,
             OutrankWebhookPayload,
             OutrankWebhookResponse,
         )
@@ -128,7 +317,12 @@ use utoipa::{
         (name = "Files", description = "File upload, download, and management endpoints"),
         (name = "Series", description = "Series creation and management endpoints"),
         (name = "Posts", description = "Post creation and management endpoints"),
+        (name = "Imports", description = "Background imports from other platforms"),
+        (name = "Tiers", description = "Membership tiers and member invitation endpoints"),
         (name = "API Keys", description = "API key creation and management endpoints"),
+        (name = "Jobs", description = "Background job progress endpoints"),
+        (name = "OAuth", description = "OAuth2 authorization server endpoints for third-party apps"),
+        (name = "Admin", description = "Staff-only moderation and audit endpoints"),
         (name = "Outrank", description = "Outrank SEO integration webhook endpoints"),
     ),
     servers(
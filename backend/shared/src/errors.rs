@@ -1,4 +1,4 @@
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{http::header, HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -60,6 +60,18 @@ pub enum ServiceError {
     /// Represents a forbidden/access denied error with a message.
     #[error("Forbidden: {0}")]
     Forbidden(String),
+
+    /// Represents missing or invalid credentials with a message.
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// Represents a Redis error with a message.
+    #[error("Redis error: {0}")]
+    Redis(String),
+
+    /// Represents a request rejected by rate limiting, with the number of seconds until a retry may succeed.
+    #[error("Too many requests, retry in {0} seconds")]
+    RateLimited(u64),
 }
 
 impl From<aws_sdk_s3::Error> for ServiceError {
@@ -93,12 +105,27 @@ impl From<diesel_async::pooled_connection::bb8::RunError> for ServiceError {
     }
 }
 
+impl From<redis::RedisError> for ServiceError {
+    fn from(e: redis::RedisError) -> Self {
+        Self::Redis(e.to_string())
+    }
+}
+
 impl From<diesel::result::Error> for ServiceError {
     fn from(e: diesel::result::Error) -> Self {
         Self::Database(e.to_string())
     }
 }
 
+impl From<zip::result::ZipError> for ServiceError {
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::Io(io_error) => Self::Io(io_error),
+            other => Self::Config(format!("Invalid ZIP archive: {other}")),
+        }
+    }
+}
+
 impl ResponseError for ServiceError {
     /// Convert `ServiceError` into an HTTP response
     ///
@@ -107,20 +134,25 @@ impl ResponseError for ServiceError {
     fn error_response(&self) -> HttpResponse {
         let error_response = ErrorResponse {
             error: self.to_string(),
-            code: None,
+            code: matches!(*self, Self::RateLimited(_)).then(|| "RATE_LIMITED".to_owned()),
         };
 
         match *self {
+            Self::RateLimited(retry_after) => HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                .json(error_response),
             Self::NotFound(_) => HttpResponse::NotFound().json(error_response),
             Self::Config(_) => HttpResponse::BadRequest().json(error_response),
             Self::Conflict(_) => HttpResponse::Conflict().json(error_response),
             Self::Forbidden(_) => HttpResponse::Forbidden().json(error_response),
+            Self::Unauthorized(_) => HttpResponse::Unauthorized().json(error_response),
             Self::AwsSdk(_)
             | Self::Http(_)
             | Self::Unknown(_)
             | Self::SerdeJson(_)
             | Self::Io(_)
-            | Self::Database(_) => HttpResponse::InternalServerError().json(error_response),
+            | Self::Database(_)
+            | Self::Redis(_) => HttpResponse::InternalServerError().json(error_response),
         }
     }
 }
@@ -0,0 +1,29 @@
+use crate::models::auth::optional_datetime_format;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Request body for deleting the signed-in account
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "currentPassword": "password123"
+}))]
+pub struct DeleteAccountRequest {
+    /// Current password, required when the account has one
+    #[serde(rename = "currentPassword")]
+    pub current_password: Option<String>,
+}
+
+/// State of a requested account deletion
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "message": "Your account will be deleted on 2025-11-15. Sign in and cancel before then to keep it.",
+    "deletionScheduledFor": "2025-11-15T09:00:00+00:00"
+}))]
+pub struct AccountDeletionResponse {
+    /// Status message
+    pub message: String,
+    /// When the account will be purged; `None` once deletion is canceled
+    #[serde(with = "optional_datetime_format", rename = "deletionScheduledFor")]
+    pub deletion_scheduled_for: Option<NaiveDateTime>,
+}
@@ -0,0 +1,157 @@
+use crate::models::auth::{User, UserRole};
+use crate::models::series::SeriesResponse;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Account details as seen by an admin
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "d290f1ee-6c54-4b01-90e6-d701748f0851",
+    "email": "user@example.com",
+    "displayName": "John Doe",
+    "avatarUrl": "https://example.com/avatar.jpg",
+    "role": "creator",
+    "emailVerified": true,
+    "hasPassword": true,
+    "suspendedAt": null,
+    "suspensionReason": null,
+    "suspendedUntil": null,
+    "createdAt": "2023-01-01T00:00:00Z",
+    "lastLogin": "2023-01-02T12:00:00Z"
+}))]
+pub struct AdminUserResponse {
+    /// User identifier
+    pub id: Uuid,
+    /// Email address
+    pub email: String,
+    /// Display name
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    /// Avatar URL
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+    /// Account role
+    pub role: UserRole,
+    /// Whether the email address is verified
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    /// Whether the account can sign in with a password
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+    /// When the account was suspended, if it is
+    #[serde(rename = "suspendedAt")]
+    pub suspended_at: Option<DateTime<Utc>>,
+    /// Why the account was suspended
+    #[serde(rename = "suspensionReason")]
+    pub suspension_reason: Option<String>,
+    /// When a temporary suspension ends
+    #[serde(rename = "suspendedUntil")]
+    pub suspended_until: Option<DateTime<Utc>>,
+    /// When the account was created
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Last sign-in
+    #[serde(rename = "lastLogin")]
+    pub last_login: Option<DateTime<Utc>>,
+}
+
+impl From<User> for AdminUserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            role: user.role(),
+            email: user.email,
+            display_name: user.display_name,
+            avatar_url: user.avatar_url,
+            email_verified: user.email_verified,
+            has_password: user.password_hash.is_some(),
+            suspended_at: user.suspended_at.map(|dt| dt.and_utc()),
+            suspension_reason: user.suspension_reason,
+            suspended_until: user.suspended_until.map(|dt| dt.and_utc()),
+            created_at: user.created_at.map(|dt| dt.and_utc()),
+            last_login: user.last_login.map(|dt| dt.and_utc()),
+        }
+    }
+}
+
+/// List of accounts matching an admin search, newest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminUsersListResponse(
+    /// Matching accounts
+    pub Vec<AdminUserResponse>,
+);
+
+impl From<Vec<AdminUserResponse>> for AdminUsersListResponse {
+    fn from(users: Vec<AdminUserResponse>) -> Self {
+        Self(users)
+    }
+}
+
+/// Account details with the series it runs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminUserDetailResponse {
+    /// The account
+    pub user: AdminUserResponse,
+    /// Series the account runs, excluding deleted ones
+    pub series: Vec<SeriesResponse>,
+}
+
+/// Query parameters for searching accounts
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct AdminUserSearchQuery {
+    /// Case-insensitive substring of the email address or display name
+    pub q: Option<String>,
+    /// Only return accounts with this role
+    pub role: Option<UserRole>,
+    /// Only return suspended (`true`) or active (`false`) accounts
+    pub suspended: Option<bool>,
+    /// UUID offset for cursor-based pagination
+    pub offset: Option<Uuid>,
+    /// Maximum number of accounts to return (default: 50, max: 100)
+    pub limit: Option<i64>,
+}
+
+/// Request body for editing an account as an admin
+///
+/// Omitted fields are left unchanged.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "displayName": "Jane Doe",
+    "email": "jane@example.com",
+    "emailVerified": true,
+    "role": "creator"
+}))]
+pub struct AdminUpdateUserRequest {
+    /// New display name
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    /// New email address
+    pub email: Option<String>,
+    /// Mark the email address verified or unverified
+    #[serde(rename = "emailVerified")]
+    pub email_verified: Option<bool>,
+    /// New role
+    pub role: Option<UserRole>,
+}
+
+/// Request body for suspending an account
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "reason": "Repeated copyright infringement reports",
+    "until": "2025-12-01T00:00:00Z"
+}))]
+pub struct SuspendUserRequest {
+    /// Why the account is suspended, included in the email to the user
+    pub reason: String,
+    /// When the suspension ends; omit to suspend until lifted by an admin
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Query parameters for removing content as an admin
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct ModerationQuery {
+    /// Why the content was removed, kept in the audit log
+    pub reason: Option<String>,
+}
@@ -0,0 +1,257 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Kind of security-relevant event recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventType {
+    /// Successful sign-in by any method
+    Login,
+    /// Sign-in from a device the account was not used on before
+    NewDeviceLogin,
+    /// Sign-in reported as not made by the user through a new-device alert
+    LoginDenied,
+    /// Rejected sign-in attempt
+    LoginFailed,
+    /// Sign-out of the current session
+    Logout,
+    /// Another session was signed out by the user
+    SessionRevoked,
+    /// Password reset email requested
+    PasswordResetRequested,
+    /// Password replaced through a reset link
+    PasswordReset,
+    /// Password changed by the signed-in user
+    PasswordChanged,
+    /// Password added to an account that had none
+    PasswordSet,
+    /// Email address verified
+    EmailVerified,
+    /// Change of email address requested
+    EmailChangeRequested,
+    /// Email address changed
+    EmailChanged,
+    /// API key created
+    ApiKeyCreated,
+    /// API key name, permissions, expiry or status changed
+    ApiKeyUpdated,
+    /// API key deleted
+    ApiKeyDeleted,
+    /// External sign-in provider linked to the account
+    IdentityLinked,
+    /// External sign-in provider unlinked from the account
+    IdentityUnlinked,
+    /// Third-party app granted access through `OAuth`
+    OAuthAppAuthorized,
+    /// Account deletion requested, starting the grace period
+    AccountDeletionRequested,
+    /// Pending account deletion canceled during the grace period
+    AccountDeletionCanceled,
+    /// Account and its content purged after the grace period
+    AccountDeleted,
+    /// Archive of the account's data requested
+    DataExportRequested,
+    /// Import of a Patreon export requested, as a dry run or for real
+    PatreonImportRequested,
+    /// Import of a Substack export or RSS/Atom feed requested
+    FeedImportRequested,
+    /// Admin searched the user directory
+    AdminUsersSearched,
+    /// Admin viewed an account
+    AdminUserViewed,
+    /// Admin edited an account
+    AdminUserUpdated,
+    /// Admin suspended an account
+    AdminUserSuspended,
+    /// Admin lifted a suspension
+    AdminUserUnsuspended,
+    /// Admin forced a password reset
+    AdminPasswordResetForced,
+    /// Admin removed a series
+    AdminSeriesDeleted,
+    /// Admin removed a post
+    AdminPostDeleted,
+    /// Admin listed an account's files
+    AdminFilesViewed,
+    /// Admin searched the audit log
+    AdminAuditSearched,
+}
+
+impl AuditEventType {
+    /// Name stored in `audit_events.event_type`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::NewDeviceLogin => "new_device_login",
+            Self::LoginDenied => "login_denied",
+            Self::LoginFailed => "login_failed",
+            Self::Logout => "logout",
+            Self::SessionRevoked => "session_revoked",
+            Self::PasswordResetRequested => "password_reset_requested",
+            Self::PasswordReset => "password_reset",
+            Self::PasswordChanged => "password_changed",
+            Self::PasswordSet => "password_set",
+            Self::EmailVerified => "email_verified",
+            Self::EmailChangeRequested => "email_change_requested",
+            Self::EmailChanged => "email_changed",
+            Self::ApiKeyCreated => "api_key_created",
+            Self::ApiKeyUpdated => "api_key_updated",
+            Self::ApiKeyDeleted => "api_key_deleted",
+            Self::IdentityLinked => "identity_linked",
+            Self::IdentityUnlinked => "identity_unlinked",
+            Self::OAuthAppAuthorized => "oauth_app_authorized",
+            Self::AccountDeletionRequested => "account_deletion_requested",
+            Self::AccountDeletionCanceled => "account_deletion_canceled",
+            Self::AccountDeleted => "account_deleted",
+            Self::DataExportRequested => "data_export_requested",
+            Self::PatreonImportRequested => "patreon_import_requested",
+            Self::FeedImportRequested => "feed_import_requested",
+            Self::AdminUsersSearched => "admin_users_searched",
+            Self::AdminUserViewed => "admin_user_viewed",
+            Self::AdminUserUpdated => "admin_user_updated",
+            Self::AdminUserSuspended => "admin_user_suspended",
+            Self::AdminUserUnsuspended => "admin_user_unsuspended",
+            Self::AdminPasswordResetForced => "admin_password_reset_forced",
+            Self::AdminSeriesDeleted => "admin_series_deleted",
+            Self::AdminPostDeleted => "admin_post_deleted",
+            Self::AdminFilesViewed => "admin_files_viewed",
+            Self::AdminAuditSearched => "admin_audit_searched",
+        }
+    }
+}
+
+/// Database model for `audit_events` table
+///
+/// Rows are never updated or deleted; the table rejects both.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditEvent {
+    /// Unique event identifier
+    pub id: Uuid,
+    /// User the event concerns, if known
+    pub user_id: Option<Uuid>,
+    /// User who caused the event when it is not the subject, e.g. an admin
+    pub actor_id: Option<Uuid>,
+    /// Event name, see [`AuditEventType::as_str`]
+    pub event_type: String,
+    /// Client IP address of the request
+    pub ip_address: Option<String>,
+    /// User agent of the request
+    pub user_agent: Option<String>,
+    /// Event-specific details
+    pub metadata: serde_json::Value,
+    /// When the event happened
+    pub created_at: NaiveDateTime,
+}
+
+/// API response model for an audit log entry
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "3f8a3a5e-1b2c-4d5e-8f90-123456789abc",
+    "userId": "d290f1ee-6c54-4b01-90e6-d701748f0851",
+    "actorId": null,
+    "eventType": "login",
+    "ipAddress": "203.0.113.42",
+    "userAgent": "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/605.1.15",
+    "metadata": { "method": "password" },
+    "createdAt": "2023-01-01T12:00:00Z"
+}))]
+pub struct AuditEventResponse {
+    /// Event identifier
+    pub id: Uuid,
+    /// User the event concerns
+    #[serde(rename = "userId")]
+    pub user_id: Option<Uuid>,
+    /// User who caused the event when it is not the subject
+    #[serde(rename = "actorId")]
+    pub actor_id: Option<Uuid>,
+    /// Event name
+    #[schema(example = "login")]
+    #[serde(rename = "eventType")]
+    pub event_type: String,
+    /// Client IP address of the request
+    #[serde(rename = "ipAddress")]
+    pub ip_address: Option<String>,
+    /// User agent of the request
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    /// Event-specific details
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+    /// When the event happened
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEvent> for AuditEventResponse {
+    fn from(event: AuditEvent) -> Self {
+        Self {
+            id: event.id,
+            user_id: event.user_id,
+            actor_id: event.actor_id,
+            event_type: event.event_type,
+            ip_address: event.ip_address,
+            user_agent: event.user_agent,
+            metadata: event.metadata,
+            created_at: event.created_at.and_utc(),
+        }
+    }
+}
+
+/// Last entry of a page of the audit log, where the next page starts
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+pub struct AuditCursor {
+    /// Pass as `before`
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// Pass as `beforeId`, which separates events logged in the same instant
+    pub id: Uuid,
+}
+
+/// List of audit log entries, newest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditEventsListResponse {
+    /// Entries on this page
+    pub events: Vec<AuditEventResponse>,
+    /// Where the next page starts; absent on the last page
+    #[serde(rename = "nextBefore")]
+    pub next_before: Option<AuditCursor>,
+}
+
+/// Query parameters for the signed-in user's security activity
+#[derive(Debug, Clone, Copy, Deserialize, IntoParams)]
+pub struct ActivityQuery {
+    /// Only return events older than this timestamp, `nextBefore.createdAt` of the previous page
+    pub before: Option<DateTime<Utc>>,
+    /// With `before`, also return events at that exact timestamp with a lower ID, `nextBefore.id` of the previous page
+    #[serde(rename = "beforeId")]
+    pub before_id: Option<Uuid>,
+    /// Number of events to return (default: 50, max: 200)
+    pub limit: Option<i64>,
+}
+
+/// Query parameters for searching the audit log as an admin
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct AuditQuery {
+    /// Only return events concerning or caused by this user
+    #[serde(rename = "userId")]
+    pub user_id: Option<Uuid>,
+    /// Only return events of this type, e.g. `login_failed`
+    #[serde(rename = "eventType")]
+    pub event_type: Option<String>,
+    /// Only return events from this IP address
+    #[serde(rename = "ipAddress")]
+    pub ip_address: Option<String>,
+    /// Only return events older than this timestamp, `nextBefore.createdAt` of the previous page
+    pub before: Option<DateTime<Utc>>,
+    /// With `before`, also return events at that exact timestamp with a lower ID, `nextBefore.id` of the previous page
+    #[serde(rename = "beforeId")]
+    pub before_id: Option<Uuid>,
+    /// Number of events to return (default: 50, max: 200)
+    pub limit: Option<i64>,
+}
@@ -1,11 +1,15 @@
-use crate::errors::ServiceError;
-use crate::schema::{email_verification_tokens, users};
+use crate::errors::{ErrorResponse, ServiceError};
+use crate::models::oauth::{OAuthGrant, OAuthScope, OAuthToken, ACCESS_TOKEN_PREFIX};
+use crate::schema::{email_verification_tokens, magic_link_tokens, users};
 use crate::services::db::DbService;
+use crate::services::sessions::{client_ip, user_agent, SessionService};
+use crate::services::tokens::{hash_token, is_jwt, AccessTokenService};
+use crate::services::user_cache::UserCacheService;
 use actix_session::Session;
-use actix_web::{dev::Payload, error::Error, web, FromRequest, HttpRequest};
+use actix_web::{dev::Payload, error::Error, web, FromRequest, HttpMessage, HttpRequest};
 use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::pin::Pin;
@@ -73,47 +77,70 @@ pub struct AuthCallbackQuery {
 }
 
 /// User session data for maintaining authentication state
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Only the user ID is kept in the session; the user row itself is loaded on
+/// each request so profile changes and bans take effect immediately.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct UserSession {
-    /// The authenticated user
-    pub user: User,
-    /// Session expiration timestamp
-    pub exp: i64,
+    /// ID of the authenticated user
+    pub user_id: uuid::Uuid,
+    /// Value of `users.session_version` when the session was created
+    pub session_version: i32,
+}
+
+impl UserSession {
+    /// Session key under which the `UserSession` is stored
+    pub const KEY: &'static str = "auth";
+
+    /// Create session data for a freshly authenticated user
+    #[must_use]
+    pub const fn for_user(user: &User) -> Self {
+        Self {
+            user_id: user.id,
+            session_version: user.session_version,
+        }
+    }
 }
 
-/// Authentication provider types supported by the system
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
-pub enum AuthProvider {
-    /// Google `OAuth` authentication
-    #[serde(rename = "google")]
-    Google,
-    /// Email/password authentication
-    #[serde(rename = "email")]
-    Email,
-    /// Both Google and email authentication enabled
-    #[serde(rename = "both")]
-    Both,
+/// Role of an account, stored in `users.role`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Regular member
+    User,
+    /// Member who publishes series
+    Creator,
+    /// Staff member with access to `/api/admin`
+    Admin,
 }
 
-impl From<String> for AuthProvider {
+impl UserRole {
+    /// Name stored in `users.role`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Creator => "creator",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl From<String> for UserRole {
     #[inline]
     fn from(s: String) -> Self {
         match s.as_str() {
-            "google" => Self::Google,
-            "both" => Self::Both,
-            _ => Self::Email,
+            "creator" => Self::Creator,
+            "admin" => Self::Admin,
+            _ => Self::User,
         }
     }
 }
 
-impl From<AuthProvider> for String {
+impl From<UserRole> for String {
     #[inline]
-    fn from(provider: AuthProvider) -> Self {
-        match provider {
-            AuthProvider::Google => "google".to_owned(),
-            AuthProvider::Email => "email".to_owned(),
-            AuthProvider::Both => "both".to_owned(),
-        }
+    fn from(role: UserRole) -> Self {
+        role.as_str().to_owned()
     }
 }
 
@@ -126,6 +153,8 @@ pub struct User {
     /// User's email address
     pub email: String,
     /// Hashed password (None for OAuth-only users)
+    ///
+    /// Always `None` on users from the request extractors; use [`load_password_hash`] to check or verify a password.
     pub password_hash: Option<String>,
     /// Timestamp when user was created
     pub created_at: Option<NaiveDateTime>,
@@ -135,8 +164,6 @@ pub struct User {
     pub display_name: Option<String>,
     /// URL to user's avatar image
     pub avatar_url: Option<String>,
-    /// Authentication provider used by the user
-    pub auth_provider: String,
     /// Whether the user's email has been verified
     pub email_verified: bool,
     /// Timestamp of user's last login
@@ -145,6 +172,73 @@ pub struct User {
     pub description: Option<String>,
     /// URL to user's banner image
     pub banner: Option<String>,
+    /// Incremented to invalidate every existing session of the user
+    pub session_version: i32,
+    /// Account role, see [`UserRole`]
+    pub role: String,
+    /// When an admin suspended the account, if it is suspended
+    pub suspended_at: Option<NaiveDateTime>,
+    /// Why the account was suspended, shown to the user
+    pub suspension_reason: Option<String>,
+    /// When a temporary suspension ends; `None` suspends until lifted by an admin
+    pub suspended_until: Option<NaiveDateTime>,
+    /// When the account will be purged, if the user asked for it to be deleted
+    pub deletion_scheduled_for: Option<NaiveDateTime>,
+}
+
+impl User {
+    /// Role of the account
+    #[must_use]
+    pub fn role(&self) -> UserRole {
+        UserRole::from(self.role.clone())
+    }
+
+    /// Whether the account may use the admin endpoints
+    #[must_use]
+    pub fn is_admin(&self) -> bool {
+        self.role() == UserRole::Admin
+    }
+
+    /// Whether the account is suspended and must be refused
+    ///
+    /// Temporary suspensions stop applying once `suspended_until` has passed.
+    #[must_use]
+    pub fn is_suspended(&self) -> bool {
+        self.suspended_at.is_some()
+            && self
+                .suspended_until
+                .map_or(true, |until| until > Utc::now().naive_utc())
+    }
+
+    /// Message explaining a suspension with its reason and end, if any
+    #[must_use]
+    pub fn suspension_message(&self) -> String {
+        let reason = self
+            .suspension_reason
+            .as_deref()
+            .map(|reason| format!(": {reason}"))
+            .unwrap_or_default();
+        let until = self
+            .suspended_until
+            .map(|until| format!(" (until {})", until.and_utc().to_rfc3339()))
+            .unwrap_or_default();
+        format!("Account suspended{reason}{until}")
+    }
+
+    /// Error refusing a request made by a suspended account
+    ///
+    /// Carries the reason and end of the suspension so clients can explain
+    /// why they were signed out.
+    #[must_use]
+    pub fn suspended_error(&self) -> Error {
+        let message = self.suspension_message();
+
+        let response = actix_web::HttpResponse::Forbidden().json(ErrorResponse {
+            error: message.clone(),
+            code: Some("ACCOUNT_SUSPENDED".to_owned()),
+        });
+        actix_web::error::InternalError::from_response(message, response).into()
+    }
 }
 
 /// User information for API responses and internal use
@@ -154,12 +248,14 @@ pub struct User {
     "email": "user@example.com",
     "displayName": "John Doe",
     "avatarUrl": "https://example.com/avatar.jpg",
-    "authProvider": "email",
+    "hasPassword": true,
     "emailVerified": true,
     "createdAt": "2023-01-01T00:00:00",
     "lastLogin": "2023-01-02T12:00:00",
     "description": "A brief bio about myself",
-    "banner": "https://example.com/banner.jpg"
+    "banner": "https://example.com/banner.jpg",
+    "role": "user",
+    "deletionScheduledFor": null
 }))]
 pub struct UserInfo {
     /// User's unique identifier
@@ -176,10 +272,11 @@ pub struct UserInfo {
     #[schema(example = "https://example.com/avatar.jpg")]
     #[serde(rename = "avatarUrl")]
     pub avatar_url: Option<String>,
-    /// Authentication provider used by the user
-    #[schema(example = "email")]
-    #[serde(rename = "authProvider")]
-    pub auth_provider: AuthProvider,
+    /// Whether the user can sign in with a password; linked external
+    /// identities are listed by `GET /api/auth/identities`
+    #[schema(example = true)]
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
     /// Whether the user's email has been verified
     #[schema(example = true)]
     #[serde(rename = "emailVerified")]
@@ -198,6 +295,13 @@ pub struct UserInfo {
     /// URL to user's banner image
     #[schema(example = "https://example.com/banner.jpg")]
     pub banner: Option<String>,
+    /// Account role
+    #[schema(example = "user")]
+    pub role: UserRole,
+    /// When the account will be deleted, if deletion was requested and not canceled
+    #[schema(example = json!(null))]
+    #[serde(with = "optional_datetime_format", rename = "deletionScheduledFor")]
+    pub deletion_scheduled_for: Option<NaiveDateTime>,
 }
 
 impl FromRequest for User {
@@ -209,37 +313,53 @@ impl FromRequest for User {
         let session_result = Session::from_request(&req_clone, payload).into_inner();
 
         Box::pin(async move {
-            if let Ok(session) = session_result {
-                if let Ok(Some(user)) = session.get::<Self>("user") {
-                    return Ok(user);
-                }
+            let user = authenticate_request(&req_clone, session_result).await?;
+            if user.is_suspended() {
+                return Err(user.suspended_error());
             }
+            Ok(user)
+        })
+    }
+}
 
-            if let Some(auth_header) = req_clone.headers().get("authorization") {
-                if let Ok(auth_str) = auth_header.to_str() {
-                    if let Some(token) = auth_str.strip_prefix("Bearer ") {
-                        if let Some(db_service) = req_clone.app_data::<web::Data<DbService>>() {
-                            match verify_api_key_auth(db_service, token).await {
-                                Ok(Some(user)) => return Ok(user),
-                                Ok(None) => {
-                                    return Err(actix_web::error::ErrorUnauthorized(
-                                        "Invalid API key",
-                                    ))
-                                }
-                                Err(_) => {
-                                    return Err(actix_web::error::ErrorUnauthorized(
-                                        "API key verification failed",
-                                    ))
-                                }
-                            }
+/// Resolve the user behind a request from its session, access token or API key
+async fn authenticate_request(
+    req: &HttpRequest,
+    session_result: Result<Session, Error>,
+) -> Result<User, Error> {
+    if let Ok(session) = session_result {
+        if let Some(user) = authenticate_session(&session, req).await? {
+            return Ok(user);
+        }
+    }
+
+    if let Some(auth_header) = req.headers().get("authorization") {
+        if let Ok(auth_str) = auth_header.to_str() {
+            if let Some(token) = auth_str.strip_prefix("Bearer ") {
+                if is_jwt(token) {
+                    return authenticate_access_token(req, token).await;
+                }
+                if let Some(db_service) = req.app_data::<web::Data<DbService>>() {
+                    if token.starts_with(ACCESS_TOKEN_PREFIX) {
+                        return authenticate_oauth_token(db_service, req, token).await;
+                    }
+                    match verify_api_key_auth(db_service, token).await {
+                        Ok(Some(user)) => return Ok(user),
+                        Ok(None) => {
+                            return Err(actix_web::error::ErrorUnauthorized("Invalid API key"))
+                        }
+                        Err(_) => {
+                            return Err(actix_web::error::ErrorUnauthorized(
+                                "API key verification failed",
+                            ))
                         }
                     }
                 }
             }
-
-            Err(actix_web::error::ErrorUnauthorized("Not authenticated"))
-        })
+        }
     }
+
+    Err(actix_web::error::ErrorUnauthorized("Not authenticated"))
 }
 
 impl From<User> for UserInfo {
@@ -250,16 +370,37 @@ impl From<User> for UserInfo {
             email: user.email,
             display_name: user.display_name,
             avatar_url: user.avatar_url,
-            auth_provider: user.auth_provider.into(),
+            has_password: user.password_hash.is_some(),
             email_verified: user.email_verified,
             created_at: user.created_at,
             last_login: user.last_login,
             description: user.description,
             banner: user.banner,
+            role: UserRole::from(user.role),
+            deletion_scheduled_for: user.deletion_scheduled_for,
         }
     }
 }
 
+/// Load the password hash of a user from the database
+///
+/// # Errors
+/// Returns a `ServiceError` if the query fails or the user no longer exists
+pub async fn load_password_hash(
+    conn: &mut AsyncPgConnection,
+    user_id: uuid::Uuid,
+) -> Result<Option<String>, ServiceError> {
+    use crate::schema::users::dsl as users_dsl;
+
+    users_dsl::users
+        .find(user_id)
+        .select(users_dsl::password_hash)
+        .first(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| ServiceError::NotFound("User not found".to_owned()))
+}
+
 /// Email verification token for validating user email addresses
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable, Queryable, Selectable)]
 #[diesel(table_name = email_verification_tokens)]
@@ -268,12 +409,143 @@ pub struct EmailVerificationToken {
     pub id: uuid::Uuid,
     /// User ID this token belongs to
     pub user_id: uuid::Uuid,
-    /// The verification token string
-    pub token: String,
+    /// SHA-256 hash of the token sent in the link
+    pub token_hash: String,
     /// When this token expires
     pub expires_at: NaiveDateTime,
     /// When this token was created
     pub created_at: NaiveDateTime,
+    /// Address the user is changing to, set only for email-change tokens
+    pub new_email: Option<String>,
+}
+
+/// Single-use passwordless login link
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, Queryable, Selectable)]
+#[diesel(table_name = magic_link_tokens)]
+pub struct MagicLinkToken {
+    /// Unique token identifier
+    pub id: uuid::Uuid,
+    /// Email address the link signs in as; the account is created on first use
+    pub email: String,
+    /// SHA-256 hash of the token sent in the link
+    pub token_hash: String,
+    /// When this link expires
+    pub expires_at: NaiveDateTime,
+    /// When this link was used, if it has been
+    pub used_at: Option<NaiveDateTime>,
+    /// When this link was created
+    pub created_at: NaiveDateTime,
+}
+
+/// Resolve the user behind a cookie session
+///
+/// Returns `Ok(None)` when the session carries no login so other authentication
+/// methods can be tried. Sessions whose user no longer exists, whose
+/// `session_version` is stale, or whose inventory record was revoked are purged.
+async fn authenticate_session(session: &Session, req: &HttpRequest) -> Result<Option<User>, Error> {
+    let Some(user_session) = read_user_session(session) else {
+        return Ok(None);
+    };
+
+    let (Some(db_service), Some(user_cache)) = (
+        req.app_data::<web::Data<DbService>>(),
+        req.app_data::<web::Data<UserCacheService>>(),
+    ) else {
+        return Err(actix_web::error::ErrorInternalServerError(
+            "Session authentication is not configured",
+        ));
+    };
+
+    let user = match user_cache.get_user(db_service, user_session.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            session.purge();
+            return Err(actix_web::error::ErrorUnauthorized("Not authenticated"));
+        }
+        Err(_) => {
+            return Err(actix_web::error::ErrorUnauthorized(
+                "Session verification failed",
+            ))
+        }
+    };
+
+    if user.session_version != user_session.session_version {
+        session.purge();
+        return Err(actix_web::error::ErrorUnauthorized("Session has expired"));
+    }
+
+    if let Some(session_service) = req.app_data::<web::Data<SessionService>>() {
+        match verify_session_record(session, session_service, req, user.id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                session.purge();
+                return Err(actix_web::error::ErrorUnauthorized(
+                    "Session has been revoked",
+                ));
+            }
+            Err(_) => {
+                return Err(actix_web::error::ErrorUnauthorized(
+                    "Session verification failed",
+                ))
+            }
+        }
+    }
+
+    Ok(Some(user))
+}
+
+/// Read the login stored in a session, upgrading sessions that still carry a full `User`
+fn read_user_session(session: &Session) -> Option<UserSession> {
+    if let Ok(Some(user_session)) = session.get::<UserSession>(UserSession::KEY) {
+        return Some(user_session);
+    }
+
+    // Sessions created before only the user ID was stored hold the whole row
+    // under "user". They predate session versions, so they adopt version 0.
+    let legacy_user = session.get::<serde_json::Value>("user").ok().flatten()?;
+    let user_id = legacy_user
+        .get("id")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|id| uuid::Uuid::parse_str(id).ok())?;
+    let user_session = UserSession {
+        user_id,
+        session_version: 0,
+    };
+    let _ = session.remove("user");
+    session.insert(UserSession::KEY, user_session).ok()?;
+    Some(user_session)
+}
+
+/// Check that a cookie session is still present in the session inventory
+///
+/// Sessions created before the inventory existed carry no `sid` and are registered on first use.
+async fn verify_session_record(
+    session: &Session,
+    session_service: &SessionService,
+    req: &HttpRequest,
+    user_id: uuid::Uuid,
+) -> Result<bool, ServiceError> {
+    let stored_session_id = session
+        .get::<String>(SessionService::SESSION_ID_KEY)
+        .map_err(|e| ServiceError::Unknown(format!("Failed to read session: {e}")))?;
+
+    let Some(session_id) = stored_session_id else {
+        let record = session_service
+            .create(user_id, client_ip(req), user_agent(req))
+            .await?;
+        session
+            .insert(SessionService::SESSION_ID_KEY, &record.id)
+            .map_err(|e| ServiceError::Unknown(format!("Failed to set session: {e}")))?;
+        return Ok(true);
+    };
+
+    match session_service.get(&session_id).await? {
+        Some(record) if record.user_id == user_id => {
+            session_service.touch(record).await?;
+            Ok(true)
+        }
+        Some(_) | None => Ok(false),
+    }
 }
 
 /// Hash an API key for secure storage and verification
@@ -313,13 +585,161 @@ async fn verify_api_key_auth(
             if let Err(e) = update_api_key_last_used_background(db_service, &key_hash).await {
                 eprintln!("Failed to update API key last used timestamp: {e}");
             }
-            Ok(Some(user))
+            Ok(Some(User {
+                password_hash: None,
+                ..user
+            }))
         }
         Err(diesel::result::Error::NotFound) => Ok(None),
         Err(e) => Err(ServiceError::Database(e.to_string())),
     }
 }
 
+/// Authenticate a request made with a native client access token
+///
+/// Besides the signature and expiry, the token is rejected once the user's
+/// `session_version` changes or its refresh token family is revoked, so
+/// signing out a device takes effect before the token expires.
+async fn authenticate_access_token(req: &HttpRequest, token: &str) -> Result<User, Error> {
+    let (Some(token_service), Some(db_service), Some(user_cache)) = (
+        req.app_data::<web::Data<AccessTokenService>>(),
+        req.app_data::<web::Data<DbService>>(),
+        req.app_data::<web::Data<UserCacheService>>(),
+    ) else {
+        return Err(actix_web::error::ErrorInternalServerError(
+            "Access token authentication is not configured",
+        ));
+    };
+
+    let claims = token_service.verify_access_token(token)?;
+
+    let user = match user_cache.get_user(db_service, claims.sub).await {
+        Ok(Some(user)) if claims.is_current_for(&user) => user,
+        Ok(_) => {
+            return Err(actix_web::error::ErrorUnauthorized(
+                "Access token has been revoked",
+            ))
+        }
+        Err(_) => {
+            return Err(actix_web::error::ErrorUnauthorized(
+                "Access token verification failed",
+            ))
+        }
+    };
+
+    match is_token_family_active(db_service, claims.sid).await {
+        Ok(true) => Ok(user),
+        Ok(false) => Err(actix_web::error::ErrorUnauthorized(
+            "Access token has been revoked",
+        )),
+        Err(_) => Err(actix_web::error::ErrorUnauthorized(
+            "Access token verification failed",
+        )),
+    }
+}
+
+/// Whether a refresh token family still has an unrevoked token
+async fn is_token_family_active(
+    db_service: &DbService,
+    family_id: uuid::Uuid,
+) -> Result<bool, ServiceError> {
+    use crate::schema::refresh_tokens::dsl as refresh_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let active = diesel::select(diesel::dsl::exists(
+        refresh_dsl::refresh_tokens
+            .filter(refresh_dsl::family_id.eq(family_id))
+            .filter(refresh_dsl::revoked_at.is_null()),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await?;
+
+    Ok(active)
+}
+
+/// Authenticate a request made with an `OAuth` access token
+///
+/// The token must carry the scope the endpoint requires; its grant is stored in
+/// the request extensions for handlers that need the client or scopes.
+async fn authenticate_oauth_token(
+    db_service: &DbService,
+    req: &HttpRequest,
+    token: &str,
+) -> Result<User, Error> {
+    let (user, grant) = match verify_oauth_access_token(db_service, token).await {
+        Ok(Some(authenticated)) => authenticated,
+        Ok(None) => return Err(actix_web::error::ErrorUnauthorized("Invalid access token")),
+        Err(_) => {
+            return Err(actix_web::error::ErrorUnauthorized(
+                "Access token verification failed",
+            ))
+        }
+    };
+
+    let Some(required_scope) = OAuthScope::required_for(req.method(), req.path()) else {
+        return Err(actix_web::error::ErrorForbidden(
+            "This endpoint cannot be used with an OAuth access token",
+        ));
+    };
+    if !grant.scopes.contains(&required_scope) {
+        return Err(actix_web::error::ErrorForbidden(format!(
+            "Access token is missing the {} scope",
+            required_scope.as_str()
+        )));
+    }
+
+    let _ = req.extensions_mut().insert(grant);
+    Ok(user)
+}
+
+/// Verify an `OAuth` access token and return the user and grant it was issued for
+async fn verify_oauth_access_token(
+    db_service: &DbService,
+    access_token: &str,
+) -> Result<Option<(User, OAuthGrant)>, ServiceError> {
+    use crate::schema::{oauth_tokens::dsl as tokens_dsl, users::dsl as users_dsl};
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let now = Utc::now().naive_utc();
+    let result = tokens_dsl::oauth_tokens
+        .inner_join(users_dsl::users.on(tokens_dsl::user_id.eq(users_dsl::id)))
+        .filter(tokens_dsl::access_token_hash.eq(hash_token(access_token)))
+        .filter(tokens_dsl::revoked_at.is_null())
+        .filter(tokens_dsl::access_expires_at.gt(now))
+        .select((OAuthToken::as_select(), User::as_select()))
+        .first::<(OAuthToken, User)>(&mut conn)
+        .await
+        .optional()?;
+
+    let Some((token, user)) = result else {
+        return Ok(None);
+    };
+
+    if let Err(e) = diesel::update(tokens_dsl::oauth_tokens.find(token.id))
+        .set(tokens_dsl::last_used_at.eq(Some(now)))
+        .execute(&mut conn)
+        .await
+    {
+        tracing::warn!("Failed to update OAuth token last used timestamp: {}", e);
+    }
+
+    let grant = OAuthGrant {
+        client_id: token.client_id,
+        scopes: OAuthScope::parse_list(&token.scope).unwrap_or_default(),
+    };
+    Ok(Some((
+        User {
+            password_hash: None,
+            ..user
+        },
+        grant,
+    )))
+}
+
 /// Update last used timestamp for an API key (background operation)
 async fn update_api_key_last_used_background(
     db_service: &DbService,
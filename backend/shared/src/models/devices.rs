@@ -0,0 +1,44 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Database model for `known_devices` table
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::known_devices)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct KnownDevice {
+    /// Unique device identifier
+    pub id: Uuid,
+    /// User who signed in from the device
+    pub user_id: Uuid,
+    /// Hash of the user agent and client network, see `device_fingerprint`
+    pub fingerprint: String,
+    /// User agent of the first sign-in from the device
+    pub user_agent: Option<String>,
+    /// Client IP address of the first sign-in from the device
+    pub ip_address: Option<String>,
+    /// Hash of the "this wasn't me" token sent in the new-device alert
+    pub deny_token_hash: Option<String>,
+    /// When the "this wasn't me" link stops working
+    pub deny_token_expires_at: Option<NaiveDateTime>,
+    /// First sign-in from the device
+    pub first_seen_at: NaiveDateTime,
+    /// Latest sign-in from the device
+    pub last_seen_at: NaiveDateTime,
+}
+
+/// Query parameters of the "this wasn't me" link in a new-device alert
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct DenyLoginQuery {
+    /// Token from the alert email
+    pub token: String,
+}
+
+/// Form posted from the confirmation page of a "this wasn't me" link (`application/x-www-form-urlencoded`)
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct DenyLoginRequest {
+    /// Token from the alert email
+    pub token: String,
+}
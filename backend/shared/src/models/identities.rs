@@ -0,0 +1,120 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Database model for `user_identities` table
+#[derive(Debug, Serialize, Deserialize, Clone, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::user_identities)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserIdentity {
+    /// Unique identity identifier
+    pub id: Uuid,
+    /// ID of the user this identity signs in as
+    pub user_id: Uuid,
+    /// Name of the sign-in provider (e.g. `google`)
+    pub provider: String,
+    /// Stable identifier of the user at the provider (`sub` claim)
+    pub subject: String,
+    /// Email address reported by the provider when the identity was last used
+    pub email: Option<String>,
+    /// Timestamp when the identity was linked
+    pub created_at: NaiveDateTime,
+    /// Timestamp of the last sign-in with this identity
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+/// API response model for a linked identity
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "5f1c1c9e-0b7a-4d6e-9a57-2b6c1b8f4e21",
+    "provider": "google",
+    "email": "user@gmail.com",
+    "createdAt": "2023-01-01T00:00:00Z",
+    "lastUsedAt": "2023-01-02T12:00:00Z"
+}))]
+pub struct UserIdentityResponse {
+    /// Identity identifier used to unlink it
+    #[schema(example = "5f1c1c9e-0b7a-4d6e-9a57-2b6c1b8f4e21")]
+    pub id: Uuid,
+    /// Name of the sign-in provider
+    #[schema(example = "google")]
+    pub provider: String,
+    /// Email address reported by the provider
+    #[schema(example = "user@gmail.com")]
+    pub email: Option<String>,
+    /// When the identity was linked
+    #[schema(example = "2023-01-01T00:00:00Z")]
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the identity was last used to sign in
+    #[schema(example = "2023-01-02T12:00:00Z")]
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<UserIdentity> for UserIdentityResponse {
+    fn from(identity: UserIdentity) -> Self {
+        Self {
+            id: identity.id,
+            provider: identity.provider,
+            email: identity.email,
+            created_at: identity.created_at.and_utc(),
+            last_used_at: identity.last_used_at.map(|dt| dt.and_utc()),
+        }
+    }
+}
+
+/// Response type for the identity listing endpoint
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(description = "External identities linked to the authenticated user", example = json!([{
+    "id": "5f1c1c9e-0b7a-4d6e-9a57-2b6c1b8f4e21",
+    "provider": "google",
+    "email": "user@gmail.com",
+    "createdAt": "2023-01-01T00:00:00Z",
+    "lastUsedAt": "2023-01-02T12:00:00Z"
+}]))]
+pub struct UserIdentitiesListResponse(
+    /// List of linked identities
+    pub Vec<UserIdentityResponse>,
+);
+
+impl From<Vec<UserIdentityResponse>> for UserIdentitiesListResponse {
+    fn from(identities: Vec<UserIdentityResponse>) -> Self {
+        Self(identities)
+    }
+}
+
+/// API response model for a configured sign-in provider
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "name": "google",
+    "displayName": "Google"
+}))]
+pub struct OidcProviderResponse {
+    /// Provider identifier used in login and link URLs
+    #[schema(example = "google")]
+    pub name: String,
+    /// Human readable provider name
+    #[schema(example = "Google")]
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+}
+
+/// Response type for the provider listing endpoint
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(description = "Sign-in providers configured on this server", example = json!([{
+    "name": "google",
+    "displayName": "Google"
+}]))]
+pub struct OidcProvidersListResponse(
+    /// List of providers
+    pub Vec<OidcProviderResponse>,
+);
+
+impl From<Vec<OidcProviderResponse>> for OidcProvidersListResponse {
+    fn from(providers: Vec<OidcProviderResponse>) -> Self {
+        Self(providers)
+    }
+}
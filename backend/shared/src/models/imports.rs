@@ -0,0 +1,347 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// What happened to one file of an import archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFileStatus {
+    /// Post created
+    Imported,
+    /// Valid, but not imported because other files were invalid
+    Ready,
+    /// Rejected, see the file's errors
+    Invalid,
+    /// Not a post, e.g. an image or an operating system metadata file
+    Skipped,
+}
+
+/// Outcome of importing one file of an archive
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "file": "chapters/0001-the-beginning.md",
+    "status": "imported",
+    "title": "The Beginning",
+    "slug": "the-beginning",
+    "postNumber": 1,
+    "postId": "d290f1ee-6c54-4b01-90e6-d701748f0851",
+    "errors": []
+}))]
+pub struct ImportFileReport {
+    /// Path of the file inside the archive
+    pub file: String,
+    /// What happened to the file
+    pub status: ImportFileStatus,
+    /// Title read from the front matter
+    pub title: Option<String>,
+    /// Slug read from the front matter or derived from the title
+    pub slug: Option<String>,
+    /// Number read from the front matter or assigned after the highest one
+    #[serde(rename = "postNumber")]
+    pub number: Option<i32>,
+    /// Post created from the file
+    #[serde(rename = "postId")]
+    pub post_id: Option<Uuid>,
+    /// Why the file was rejected or skipped
+    pub errors: Vec<String>,
+}
+
+impl ImportFileReport {
+    /// Report for a file that is not a post
+    #[must_use]
+    pub fn skipped(file: &str, reason: &str) -> Self {
+        Self {
+            file: file.to_owned(),
+            status: ImportFileStatus::Skipped,
+            title: None,
+            slug: None,
+            number: None,
+            post_id: None,
+            errors: vec![reason.to_owned()],
+        }
+    }
+}
+
+/// Per-file report of a post import
+///
+/// Posts are only created when every file is valid, so either all
+/// Markdown files are `imported` or none are.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "seriesId": "b2c3d4e5-6f78-9012-bcde-f12345678901",
+    "imported": 1,
+    "invalid": 0,
+    "skipped": 1,
+    "files": [{
+        "file": "chapters/0001-the-beginning.md",
+        "status": "imported",
+        "title": "The Beginning",
+        "slug": "the-beginning",
+        "postNumber": 1,
+        "postId": "d290f1ee-6c54-4b01-90e6-d701748f0851",
+        "errors": []
+    }, {
+        "file": "chapters/cover.png",
+        "status": "skipped",
+        "title": null,
+        "slug": null,
+        "postNumber": null,
+        "postId": null,
+        "errors": ["Not a Markdown file"]
+    }]
+}))]
+pub struct PostImportResponse {
+    /// Series the posts were imported into
+    #[serde(rename = "seriesId")]
+    pub series_id: Uuid,
+    /// Number of posts created
+    pub imported: usize,
+    /// Number of files rejected
+    pub invalid: usize,
+    /// Number of files that are not posts
+    pub skipped: usize,
+    /// Outcome of each file, in archive order
+    pub files: Vec<ImportFileReport>,
+}
+
+impl PostImportResponse {
+    /// Summarize the file reports of an import into a series
+    #[must_use]
+    pub fn new(series_id: Uuid, files: Vec<ImportFileReport>) -> Self {
+        let count = |status: ImportFileStatus| files.iter().filter(|f| f.status == status).count();
+        Self {
+            series_id,
+            imported: count(ImportFileStatus::Imported),
+            invalid: count(ImportFileStatus::Invalid),
+            skipped: count(ImportFileStatus::Skipped),
+            files,
+        }
+    }
+}
+
+/// What an import does, or would do in a dry run, with one source record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MappingAction {
+    /// A new row is created
+    Create,
+    /// An existing row is reused
+    Match,
+    /// Nothing is created, see the reason
+    Skip,
+}
+
+/// How a Patreon tier maps to a tier of the creator
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TierMapping {
+    /// Patreon tier ID, absent for tiers only named in the members CSV
+    #[serde(rename = "patreonId")]
+    pub patreon_id: Option<String>,
+    /// Tier name
+    pub name: String,
+    /// Monthly price in cents
+    #[serde(rename = "amountCents")]
+    pub amount_cents: i32,
+    /// Whether the tier is created or matched by name to an existing one
+    pub action: MappingAction,
+    /// Tier the Patreon tier maps to
+    #[serde(rename = "tierId")]
+    pub tier_id: Uuid,
+}
+
+/// How a Patreon post maps to a post of the series
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PostMapping {
+    /// Patreon post ID
+    #[serde(rename = "patreonId")]
+    pub patreon_id: String,
+    /// Post title
+    pub title: String,
+    /// Slug the post gets
+    pub slug: Option<String>,
+    /// Number the post gets
+    #[serde(rename = "postNumber")]
+    pub number: Option<i32>,
+    /// Original publish date, kept as the post's creation date
+    #[serde(rename = "publishedAt")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// Name of the cheapest tier that can read the post, absent for public posts
+    pub tier: Option<String>,
+    /// Whether the post is created or skipped
+    pub action: MappingAction,
+    /// Why the post is skipped
+    pub reason: Option<String>,
+    /// Post created, or that will be created
+    #[serde(rename = "postId")]
+    pub post_id: Option<Uuid>,
+}
+
+/// How a Patreon member maps to an invitation
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MemberMapping {
+    /// Member's email address, as written in the CSV
+    pub email: String,
+    /// Member's name
+    pub name: Option<String>,
+    /// Name of the tier the member is invited to
+    pub tier: Option<String>,
+    /// Whether an invitation is created or the member skipped
+    pub action: MappingAction,
+    /// Why the member is skipped
+    pub reason: Option<String>,
+}
+
+/// Counts of a Patreon import report
+#[derive(Debug, Clone, Copy, Default, Serialize, ToSchema)]
+pub struct PatreonImportSummary {
+    /// Tiers created
+    #[serde(rename = "tiersCreated")]
+    pub tiers_created: usize,
+    /// Tiers matched to existing ones
+    #[serde(rename = "tiersMatched")]
+    pub tiers_matched: usize,
+    /// Posts created
+    #[serde(rename = "postsCreated")]
+    pub posts_created: usize,
+    /// Posts skipped
+    #[serde(rename = "postsSkipped")]
+    pub posts_skipped: usize,
+    /// Invitations created
+    #[serde(rename = "invitationsCreated")]
+    pub invitations_created: usize,
+    /// Members skipped
+    #[serde(rename = "membersSkipped")]
+    pub members_skipped: usize,
+}
+
+/// Mapping report of a Patreon import, stored as the job result
+///
+/// A dry run reports what the import would do without writing anything.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "dryRun": true,
+    "seriesId": "b2c3d4e5-6f78-9012-bcde-f12345678901",
+    "summary": {
+        "tiersCreated": 1, "tiersMatched": 0, "postsCreated": 1,
+        "postsSkipped": 0, "invitationsCreated": 1, "membersSkipped": 1
+    },
+    "tiers": [{
+        "patreonId": "8812345", "name": "Gold", "amountCents": 500,
+        "action": "create", "tierId": "3f1c2b7e-9a4d-4e8b-b6a1-0c2d4e6f8a9b"
+    }],
+    "posts": [{
+        "patreonId": "77012345", "title": "Chapter 1", "slug": "chapter-1",
+        "postNumber": 1, "publishedAt": "2023-04-01T18:00:00Z", "tier": "Gold",
+        "action": "create", "reason": null, "postId": "d290f1ee-6c54-4b01-90e6-d701748f0851"
+    }],
+    "members": [{
+        "email": "reader@example.com", "name": "Jane Reader", "tier": "Gold",
+        "action": "create", "reason": null
+    }, {
+        "email": "former@example.com", "name": "Sam Former", "tier": "Gold",
+        "action": "skip", "reason": "Not an active patron (Former patron)"
+    }]
+}))]
+pub struct PatreonImportReport {
+    /// Whether nothing was written
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+    /// Series the posts are imported into
+    #[serde(rename = "seriesId")]
+    pub series_id: Option<Uuid>,
+    /// Counts of the mappings below
+    pub summary: PatreonImportSummary,
+    /// Tier mappings
+    pub tiers: Vec<TierMapping>,
+    /// Post mappings, oldest first
+    pub posts: Vec<PostMapping>,
+    /// Member mappings, in CSV order
+    pub members: Vec<MemberMapping>,
+}
+
+/// Format of an archive or feed brought in by a feed import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedSource {
+    /// Substack export: `posts.csv` with one HTML file per post
+    Substack,
+    /// RSS feed
+    Rss,
+    /// Atom feed
+    Atom,
+    /// JSON Feed
+    Json,
+}
+
+/// How a post of a Substack export or feed maps to a post of the series
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FeedPostMapping {
+    /// Substack post ID or feed entry ID
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    /// Post title
+    pub title: String,
+    /// Slug the post got
+    pub slug: Option<String>,
+    /// Number the post got
+    #[serde(rename = "postNumber")]
+    pub number: Option<i32>,
+    /// Original publish date, kept as the post's creation date
+    #[serde(rename = "publishedAt")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// Whether the post was created or skipped
+    pub action: MappingAction,
+    /// Why the post was skipped
+    pub reason: Option<String>,
+    /// Post created
+    #[serde(rename = "postId")]
+    pub post_id: Option<Uuid>,
+    /// Number of inline images stored as files and served from the CDN
+    pub images: usize,
+    /// Problems that did not stop the post from being created, such as an
+    /// image that could not be downloaded and still points to its source
+    pub warnings: Vec<String>,
+}
+
+/// Counts of a feed import report
+#[derive(Debug, Clone, Copy, Default, Serialize, ToSchema)]
+pub struct FeedImportSummary {
+    /// Posts created
+    #[serde(rename = "postsCreated")]
+    pub posts_created: usize,
+    /// Posts skipped
+    #[serde(rename = "postsSkipped")]
+    pub posts_skipped: usize,
+    /// Inline images stored as files
+    #[serde(rename = "imagesImported")]
+    pub images_imported: usize,
+    /// Inline images left pointing to their source
+    #[serde(rename = "imagesFailed")]
+    pub images_failed: usize,
+}
+
+/// Report of a Substack or RSS/Atom import, stored as the job result
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "seriesId": "b2c3d4e5-6f78-9012-bcde-f12345678901",
+    "source": "substack",
+    "summary": {"postsCreated": 1, "postsSkipped": 0, "imagesImported": 2, "imagesFailed": 1},
+    "posts": [{
+        "sourceId": "139887236.welcome", "title": "Welcome", "slug": "welcome",
+        "postNumber": 1, "publishedAt": "2023-05-01T14:00:00Z", "action": "create",
+        "reason": null, "postId": "d290f1ee-6c54-4b01-90e6-d701748f0851", "images": 2,
+        "warnings": ["Image https://example.com/gone.png was not imported: HTTP 404"]
+    }]
+}))]
+pub struct FeedImportReport {
+    /// Series the posts were imported into
+    #[serde(rename = "seriesId")]
+    pub series_id: Uuid,
+    /// Format the upload was read as
+    pub source: FeedSource,
+    /// Counts of the mappings below
+    pub summary: FeedImportSummary,
+    /// Post mappings, oldest first
+    pub posts: Vec<FeedPostMapping>,
+}
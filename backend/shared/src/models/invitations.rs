@@ -0,0 +1,203 @@
+use crate::models::tiers::Tier;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// State of an invitation, stored in `invitations.status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    /// Waiting for the invitee to join
+    Pending,
+    /// The invitee joined
+    Accepted,
+    /// Withdrawn by the creator
+    Revoked,
+}
+
+impl InvitationStatus {
+    /// Name stored in `invitations.status`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Revoked => "revoked",
+        }
+    }
+}
+
+impl From<String> for InvitationStatus {
+    #[inline]
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "accepted" => Self::Accepted,
+            "revoked" => Self::Revoked,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl From<InvitationStatus> for String {
+    #[inline]
+    fn from(status: InvitationStatus) -> Self {
+        status.as_str().to_owned()
+    }
+}
+
+/// Database model for `invitations` table
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::invitations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Invitation {
+    /// Unique invitation identifier
+    pub id: Uuid,
+    /// Creator the invitee is invited to support
+    pub creator_id: Uuid,
+    /// Tier the invitee is offered
+    pub tier_id: Option<Uuid>,
+    /// Address the invitation is for
+    pub email: String,
+    /// Invitee's name, if known
+    pub name: Option<String>,
+    /// State, see [`InvitationStatus`]
+    pub status: String,
+    /// Where the invitee came from, e.g. `patreon`
+    pub source: String,
+    /// Invitee's identifier on the platform they came from
+    pub external_id: Option<String>,
+    /// Source-specific details such as the previous pledge
+    pub metadata: Option<serde_json::Value>,
+    /// When the invitation was created
+    pub created_at: NaiveDateTime,
+    /// When the invitation was last updated
+    pub updated_at: NaiveDateTime,
+}
+
+/// Invitation as shown to the creator who made it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "6b8d0f2a-4c6e-4a8b-9d1f-3e5a7c9b1d2f",
+    "tierId": "3f1c2b7e-9a4d-4e8b-b6a1-0c2d4e6f8a9b",
+    "email": "reader@example.com",
+    "name": "Jane Reader",
+    "status": "pending",
+    "source": "patreon",
+    "metadata": {"patronStatus": "Active patron", "pledgeAmount": "5.00"},
+    "createdAt": "2025-11-03T09:00:00Z"
+}))]
+pub struct InvitationResponse {
+    /// Invitation identifier
+    pub id: Uuid,
+    /// Tier the invitee is offered
+    #[serde(rename = "tierId")]
+    pub tier_id: Option<Uuid>,
+    /// Address the invitation is for
+    pub email: String,
+    /// Invitee's name, if known
+    pub name: Option<String>,
+    /// State of the invitation
+    pub status: InvitationStatus,
+    /// Where the invitee came from
+    pub source: String,
+    /// Source-specific details
+    pub metadata: Option<serde_json::Value>,
+    /// When the invitation was created
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(invitation: Invitation) -> Self {
+        Self {
+            id: invitation.id,
+            tier_id: invitation.tier_id,
+            email: invitation.email,
+            name: invitation.name,
+            status: InvitationStatus::from(invitation.status),
+            source: invitation.source,
+            metadata: invitation.metadata,
+            created_at: invitation.created_at.and_utc(),
+        }
+    }
+}
+
+/// List of a creator's invitations, newest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvitationsListResponse(
+    /// The invitations
+    pub Vec<InvitationResponse>,
+);
+
+impl From<Vec<InvitationResponse>> for InvitationsListResponse {
+    fn from(invitations: Vec<InvitationResponse>) -> Self {
+        Self(invitations)
+    }
+}
+
+/// Membership of the signed-in reader, as shown to the reader and the apps they authorize
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "creatorId": "a1b2c3d4-5e6f-7890-abcd-ef1234567890",
+    "tierId": "3f1c2b7e-9a4d-4e8b-b6a1-0c2d4e6f8a9b",
+    "tierName": "Gold",
+    "amountCents": 500,
+    "status": "accepted",
+    "since": "2025-11-03T09:00:00Z"
+}))]
+pub struct MembershipResponse {
+    /// Creator the reader supports
+    #[serde(rename = "creatorId")]
+    pub creator_id: Uuid,
+    /// Tier the reader is a member of
+    #[serde(rename = "tierId")]
+    pub tier_id: Option<Uuid>,
+    /// Name of the tier
+    #[serde(rename = "tierName")]
+    pub tier_name: Option<String>,
+    /// Monthly price of the tier in cents
+    #[serde(rename = "amountCents")]
+    pub amount_cents: Option<i32>,
+    /// Whether the reader has joined or is only invited
+    pub status: InvitationStatus,
+    /// When the reader was invited
+    pub since: DateTime<Utc>,
+}
+
+impl MembershipResponse {
+    /// Build a membership from an invitation and the tier it offers
+    #[must_use]
+    pub fn new(invitation: Invitation, tier: Option<Tier>) -> Self {
+        let (tier_name, amount_cents) = tier.map(|t| (t.name, t.amount_cents)).unzip();
+        Self {
+            creator_id: invitation.creator_id,
+            tier_id: invitation.tier_id,
+            tier_name,
+            amount_cents,
+            status: InvitationStatus::from(invitation.status),
+            since: invitation.created_at.and_utc(),
+        }
+    }
+}
+
+/// List of the signed-in reader's memberships, newest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MembershipsListResponse(
+    /// The memberships
+    pub Vec<MembershipResponse>,
+);
+
+impl From<Vec<MembershipResponse>> for MembershipsListResponse {
+    fn from(memberships: Vec<MembershipResponse>) -> Self {
+        Self(memberships)
+    }
+}
+
+/// Query parameters for listing the signed-in reader's memberships
+#[derive(Debug, Clone, Copy, Deserialize, IntoParams)]
+pub struct MembershipsQuery {
+    /// Only return memberships with this creator
+    pub creator_id: Option<Uuid>,
+}
@@ -0,0 +1,189 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Kind of work a background job does, stored in `background_jobs.kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Archive of everything stored about a user, emailed as a download link
+    DataExport,
+    /// Posts, tiers and members brought over from a Patreon export
+    PatreonImport,
+    /// Posts brought over from a Substack export or an RSS/Atom feed
+    FeedImport,
+    /// Series, or a range of its posts, compiled into an EPUB stored as a file
+    EpubExport,
+    /// Pages of a series' posts packaged into comic book archives stored as files
+    CbzExport,
+}
+
+impl JobKind {
+    /// Name stored in `background_jobs.kind`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::DataExport => "data_export",
+            Self::PatreonImport => "patreon_import",
+            Self::FeedImport => "feed_import",
+            Self::EpubExport => "epub_export",
+            Self::CbzExport => "cbz_export",
+        }
+    }
+
+    /// Kind named by `background_jobs.kind`, if it is one this server knows
+    #[must_use]
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "data_export" => Some(Self::DataExport),
+            "patreon_import" => Some(Self::PatreonImport),
+            "feed_import" => Some(Self::FeedImport),
+            "epub_export" => Some(Self::EpubExport),
+            "cbz_export" => Some(Self::CbzExport),
+            _ => None,
+        }
+    }
+}
+
+/// Progress of a background job, stored in `background_jobs.status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for the worker
+    Pending,
+    /// Picked up by the worker
+    Running,
+    /// Finished successfully
+    Completed,
+    /// Finished with an error
+    Failed,
+}
+
+impl JobStatus {
+    /// Name stored in `background_jobs.status`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl From<String> for JobStatus {
+    #[inline]
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "running" => Self::Running,
+            "completed" => Self::Completed,
+            "failed" => Self::Failed,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl From<JobStatus> for String {
+    #[inline]
+    fn from(status: JobStatus) -> Self {
+        status.as_str().to_owned()
+    }
+}
+
+/// Database model for `background_jobs` table
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::background_jobs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BackgroundJob {
+    /// Unique job identifier
+    pub id: Uuid,
+    /// User the job works for
+    pub user_id: Uuid,
+    /// Kind of work, see [`JobKind::as_str`]
+    pub kind: String,
+    /// Progress, see [`JobStatus`]
+    pub status: String,
+    /// Kind-specific input
+    pub payload: serde_json::Value,
+    /// Kind-specific summary of the outcome, set once completed
+    pub result: Option<serde_json::Value>,
+    /// Why the job failed
+    pub error: Option<String>,
+    /// How many times the worker picked the job up
+    pub attempts: i32,
+    /// Bucket key of a temporary object the job produced or consumes
+    pub output_key: Option<String>,
+    /// When the temporary object is removed from the bucket
+    pub output_expires_at: Option<NaiveDateTime>,
+    /// When the job was queued
+    pub created_at: NaiveDateTime,
+    /// When the worker last picked the job up
+    pub started_at: Option<NaiveDateTime>,
+    /// When the job completed or failed
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Background job as shown to the user who queued it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "0d6f2b8a-3c1e-4f5a-9b7d-2e8c4a6f1b3d",
+    "kind": "data_export",
+    "status": "completed",
+    "result": {"series": 2, "posts": 48, "files": 31, "size": 73_400_320},
+    "error": null,
+    "createdAt": "2025-11-02T10:00:00Z",
+    "startedAt": "2025-11-02T10:00:02Z",
+    "finishedAt": "2025-11-02T10:01:15Z"
+}))]
+pub struct JobResponse {
+    /// Job identifier
+    pub id: Uuid,
+    /// Kind of work, e.g. `data_export`
+    pub kind: String,
+    /// Progress
+    pub status: JobStatus,
+    /// Summary of the outcome, set once completed
+    pub result: Option<serde_json::Value>,
+    /// Why the job failed
+    pub error: Option<String>,
+    /// When the job was queued
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the worker last picked the job up
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the job completed or failed
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<BackgroundJob> for JobResponse {
+    fn from(job: BackgroundJob) -> Self {
+        Self {
+            id: job.id,
+            kind: job.kind,
+            status: JobStatus::from(job.status),
+            result: job.result,
+            error: job.error,
+            created_at: job.created_at.and_utc(),
+            started_at: job.started_at.map(|dt| dt.and_utc()),
+            finished_at: job.finished_at.map(|dt| dt.and_utc()),
+        }
+    }
+}
+
+/// List of a user's background jobs, newest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobsListResponse(
+    /// The jobs
+    pub Vec<JobResponse>,
+);
+
+impl From<Vec<JobResponse>> for JobsListResponse {
+    fn from(jobs: Vec<JobResponse>) -> Self {
+        Self(jobs)
+    }
+}
@@ -10,8 +10,50 @@ pub mod series;
 /// Posts data models.
 pub mod posts;
 
+/// Post page data models.
+pub mod post_pages;
+
+/// Post attachment data models.
+pub mod post_attachments;
+
 /// API keys data models.
 pub mod api_keys;
 
 /// Series length data models.
 pub mod series_length;
+
+/// Session inventory data models.
+pub mod sessions;
+
+/// Linked external identity data models.
+pub mod identities;
+
+/// `OAuth` authorization server data models.
+pub mod oauth;
+
+/// Native client refresh token data models.
+pub mod tokens;
+
+/// Security audit log data models.
+pub mod audit;
+
+/// Known sign-in device data models.
+pub mod devices;
+
+/// Admin moderation data models.
+pub mod admin;
+
+/// Account deletion data models.
+pub mod account_deletion;
+
+/// Background job data models.
+pub mod jobs;
+
+/// Post import report data models.
+pub mod imports;
+
+/// Membership tier data models.
+pub mod tiers;
+
+/// Member invitation data models.
+pub mod invitations;
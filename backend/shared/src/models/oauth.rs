@@ -0,0 +1,673 @@
+use actix_web::http::Method;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Prefix of access tokens issued to `OAuth` clients
+pub const ACCESS_TOKEN_PREFIX: &str = "pat_";
+/// Prefix of refresh tokens issued to `OAuth` clients
+pub const REFRESH_TOKEN_PREFIX: &str = "prt_";
+/// Prefix of client secrets
+pub const CLIENT_SECRET_PREFIX: &str = "pcs_";
+
+/// Permission a client can request on behalf of a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum OAuthScope {
+    /// Read the user's ID, display name and avatar
+    #[serde(rename = "identity")]
+    Identity,
+    /// Read the user's email address
+    #[serde(rename = "email")]
+    Email,
+    /// Read which creators the user supports and at which tier
+    #[serde(rename = "memberships:read")]
+    MembershipsRead,
+    /// Read the user's series and posts
+    #[serde(rename = "posts:read")]
+    PostsRead,
+}
+
+impl OAuthScope {
+    /// Every scope, in the order they are shown on the consent screen
+    pub const ALL: [Self; 4] = [
+        Self::Identity,
+        Self::Email,
+        Self::MembershipsRead,
+        Self::PostsRead,
+    ];
+
+    /// Name of the scope as used in `scope` parameters
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Identity => "identity",
+            Self::Email => "email",
+            Self::MembershipsRead => "memberships:read",
+            Self::PostsRead => "posts:read",
+        }
+    }
+
+    /// Explanation of the scope shown on the consent screen
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Identity => "See your Patron ID, display name and avatar",
+            Self::Email => "See your email address",
+            Self::MembershipsRead => "See which creators you support and at which tier",
+            Self::PostsRead => "Read your series and posts",
+        }
+    }
+
+    /// Look up a scope by name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == name)
+    }
+
+    /// Parse a space-separated `scope` parameter
+    ///
+    /// # Errors
+    /// Returns the first unknown scope name
+    pub fn parse_list(scopes: &str) -> Result<Vec<Self>, String> {
+        let mut parsed = Vec::new();
+        for name in scopes.split_whitespace() {
+            let scope = Self::from_name(name).ok_or_else(|| name.to_owned())?;
+            if !parsed.contains(&scope) {
+                parsed.push(scope);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Format scopes as a space-separated `scope` parameter
+    #[must_use]
+    pub fn format_list(scopes: &[Self]) -> String {
+        scopes
+            .iter()
+            .map(|scope| scope.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Scope an access token needs to call an endpoint
+    ///
+    /// Tokens issued to third-party clients are read-only and limited to the
+    /// endpoints listed here; `None` means the endpoint cannot be called with an
+    /// `OAuth` token at all. The `email` scope has no endpoint of its own; it
+    /// adds the address to the user info response.
+    #[must_use]
+    pub fn required_for(method: &Method, path: &str) -> Option<Self> {
+        if method != Method::GET {
+            return None;
+        }
+
+        let trimmed = path.trim_end_matches('/');
+        let under = |prefix: &str| {
+            trimmed == prefix
+                || trimmed
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        };
+
+        if trimmed == "/api/oauth/userinfo" {
+            Some(Self::Identity)
+        } else if trimmed == "/api/oauth/memberships" {
+            Some(Self::MembershipsRead)
+        } else if under("/api/posts") || under("/api/series") {
+            Some(Self::PostsRead)
+        } else {
+            None
+        }
+    }
+}
+
+/// Scopes granted to the `OAuth` access token that authenticated a request
+///
+/// Inserted into the request extensions by the `User` extractor.
+#[derive(Debug, Clone)]
+pub struct OAuthGrant {
+    /// Client the token was issued to
+    pub client_id: Uuid,
+    /// Scopes the user consented to
+    pub scopes: Vec<OAuthScope>,
+}
+
+/// Database model for `oauth_clients` table
+#[derive(Debug, Clone, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::oauth_clients)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OAuthClient {
+    /// Client ID
+    pub id: Uuid,
+    /// User who registered the client
+    pub owner_id: Uuid,
+    /// Application name shown on the consent screen
+    pub name: String,
+    /// Hash of the client secret; `None` for public clients
+    pub client_secret_hash: Option<String>,
+    /// Redirect URIs authorization responses may be sent to
+    pub redirect_uris: Vec<Option<String>>,
+    /// Space-separated scopes the client may request
+    pub allowed_scopes: String,
+    /// Timestamp when the client was registered
+    pub created_at: NaiveDateTime,
+    /// Timestamp when the client was last updated
+    pub updated_at: NaiveDateTime,
+}
+
+impl OAuthClient {
+    /// Redirect URIs registered for the client
+    pub fn redirect_uris(&self) -> impl Iterator<Item = &str> {
+        self.redirect_uris.iter().flatten().map(String::as_str)
+    }
+
+    /// Whether a redirect URI is registered for the client, compared exactly
+    #[must_use]
+    pub fn has_redirect_uri(&self, uri: &str) -> bool {
+        self.redirect_uris().any(|registered| registered == uri)
+    }
+}
+
+/// Compute the PKCE `S256` code challenge of a code verifier (RFC 7636 section 4.2)
+#[must_use]
+pub fn pkce_challenge(verifier: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(verifier.as_bytes());
+    URL_SAFE_NO_PAD.encode(hasher.finalize())
+}
+
+/// Database model for `oauth_authorization_codes` table
+#[derive(Debug, Clone, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::oauth_authorization_codes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OAuthAuthorizationCode {
+    /// Unique code identifier
+    pub id: Uuid,
+    /// SHA-256 hash of the code
+    pub code_hash: String,
+    /// Client the code was issued to
+    pub client_id: Uuid,
+    /// User who approved the request
+    pub user_id: Uuid,
+    /// Redirect URI the code was sent to
+    pub redirect_uri: String,
+    /// Space-separated scopes the user approved
+    pub scope: String,
+    /// PKCE `S256` code challenge
+    pub code_challenge: String,
+    /// When the code expires
+    pub expires_at: NaiveDateTime,
+    /// When the code was issued
+    pub created_at: NaiveDateTime,
+}
+
+impl OAuthAuthorizationCode {
+    /// Whether the code can be exchanged by a client with this redirect URI and verifier
+    ///
+    /// The redirect URI must be the one the code was sent to, and the verifier
+    /// must be 43 to 128 characters long and hash to the stored challenge.
+    #[must_use]
+    pub fn is_redeemable(
+        &self,
+        client_id: Uuid,
+        redirect_uri: &str,
+        verifier: &str,
+        now: NaiveDateTime,
+    ) -> bool {
+        self.client_id == client_id
+            && self.expires_at > now
+            && self.redirect_uri == redirect_uri
+            && (43..=128).contains(&verifier.len())
+            && pkce_challenge(verifier) == self.code_challenge
+    }
+}
+
+/// Database model for `oauth_tokens` table
+#[derive(Debug, Clone, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::oauth_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OAuthToken {
+    /// Unique token pair identifier
+    pub id: Uuid,
+    /// Client the tokens were issued to
+    pub client_id: Uuid,
+    /// User the tokens act on behalf of
+    pub user_id: Uuid,
+    /// SHA-256 hash of the access token
+    pub access_token_hash: String,
+    /// SHA-256 hash of the refresh token
+    pub refresh_token_hash: Option<String>,
+    /// Space-separated scopes granted
+    pub scope: String,
+    /// When the access token expires
+    pub access_expires_at: NaiveDateTime,
+    /// When the refresh token expires
+    pub refresh_expires_at: Option<NaiveDateTime>,
+    /// When the pair was revoked, if it has been
+    pub revoked_at: Option<NaiveDateTime>,
+    /// When the pair was issued
+    pub created_at: NaiveDateTime,
+    /// When the access token was last used
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+/// Request body for registering an `OAuth` client
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "name": "Reader Discord Bot",
+    "redirectUris": ["https://bot.example.com/callback"],
+    "scopes": ["identity", "memberships:read"],
+    "confidential": true
+}))]
+pub struct CreateOAuthClientRequest {
+    /// Application name shown on the consent screen
+    pub name: String,
+    /// Redirect URIs authorization responses may be sent to
+    #[serde(rename = "redirectUris")]
+    pub redirect_uris: Vec<String>,
+    /// Scopes the client may request (default: all)
+    pub scopes: Option<Vec<OAuthScope>>,
+    /// Whether the client can keep a secret (server-side apps); public clients rely on PKCE alone
+    #[serde(default)]
+    pub confidential: bool,
+}
+
+/// API response model for an `OAuth` client
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "clientId": "0f8fad5b-d9cb-469f-a165-70867728950e",
+    "name": "Reader Discord Bot",
+    "redirectUris": ["https://bot.example.com/callback"],
+    "scopes": ["identity", "memberships:read"],
+    "confidential": true,
+    "createdAt": "2023-01-01T00:00:00Z"
+}))]
+pub struct OAuthClientResponse {
+    /// Client ID used in authorization and token requests
+    #[serde(rename = "clientId")]
+    pub client_id: Uuid,
+    /// Application name
+    pub name: String,
+    /// Registered redirect URIs
+    #[serde(rename = "redirectUris")]
+    pub redirect_uris: Vec<String>,
+    /// Scopes the client may request
+    pub scopes: Vec<OAuthScope>,
+    /// Whether the client authenticates with a secret
+    pub confidential: bool,
+    /// When the client was registered
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<OAuthClient> for OAuthClientResponse {
+    fn from(client: OAuthClient) -> Self {
+        Self {
+            client_id: client.id,
+            redirect_uris: client.redirect_uris().map(ToOwned::to_owned).collect(),
+            scopes: OAuthScope::parse_list(&client.allowed_scopes).unwrap_or_default(),
+            confidential: client.client_secret_hash.is_some(),
+            created_at: client.created_at.and_utc(),
+            name: client.name,
+        }
+    }
+}
+
+/// Response for client registration, including the secret shown only once
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateOAuthClientResponse {
+    /// Registered client
+    pub client: OAuthClientResponse,
+    /// Client secret for confidential clients; store it now, it cannot be retrieved later
+    #[serde(rename = "clientSecret")]
+    #[schema(example = "pcs_3q2x7wVYo8bLrZpKcZx0dXH1n6bB2gWm")]
+    pub client_secret: Option<String>,
+}
+
+/// Response type for the client listing endpoint
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(description = "OAuth clients registered by the authenticated user")]
+pub struct OAuthClientsListResponse(
+    /// List of clients
+    pub Vec<OAuthClientResponse>,
+);
+
+impl From<Vec<OAuthClientResponse>> for OAuthClientsListResponse {
+    fn from(clients: Vec<OAuthClientResponse>) -> Self {
+        Self(clients)
+    }
+}
+
+/// Authorization request parameters (RFC 6749 section 4.1.1 with PKCE)
+#[derive(Debug, Clone, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct AuthorizeParams {
+    /// Must be `code`
+    #[schema(example = "code")]
+    pub response_type: String,
+    /// Client ID
+    pub client_id: Uuid,
+    /// Registered redirect URI to send the result to
+    #[schema(example = "https://bot.example.com/callback")]
+    pub redirect_uri: String,
+    /// Space-separated scopes (default: `identity`)
+    #[schema(example = "identity memberships:read")]
+    pub scope: Option<String>,
+    /// Opaque value returned unchanged to the client
+    pub state: Option<String>,
+    /// PKCE code challenge
+    #[schema(example = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM")]
+    pub code_challenge: String,
+    /// PKCE challenge method; only `S256` is supported
+    #[schema(example = "S256")]
+    pub code_challenge_method: String,
+}
+
+/// Scope shown on the consent screen
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScopeDescription {
+    /// Scope name
+    pub name: OAuthScope,
+    /// What the scope allows
+    pub description: String,
+}
+
+/// Client shown on the consent screen
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConsentClient {
+    /// Client ID
+    #[serde(rename = "clientId")]
+    pub client_id: Uuid,
+    /// Application name
+    pub name: String,
+}
+
+/// Data the frontend needs to render the consent screen
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "client": {
+        "clientId": "0f8fad5b-d9cb-469f-a165-70867728950e",
+        "name": "Reader Discord Bot"
+    },
+    "scopes": [{
+        "name": "identity",
+        "description": "See your Patron ID, display name and avatar"
+    }],
+    "redirectUri": "https://bot.example.com/callback"
+}))]
+pub struct ConsentResponse {
+    /// Client requesting access
+    pub client: ConsentClient,
+    /// Scopes requested
+    pub scopes: Vec<ScopeDescription>,
+    /// Where the user is sent after deciding
+    #[serde(rename = "redirectUri")]
+    pub redirect_uri: String,
+}
+
+/// Request body recording the user's consent decision
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConsentDecisionRequest {
+    /// The authorization request being answered
+    #[serde(flatten)]
+    pub params: AuthorizeParams,
+    /// Whether the user approved the request
+    pub approve: bool,
+}
+
+/// Where to send the user after the consent decision
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "redirectTo": "https://bot.example.com/callback?code=4f0c9b2e&state=xyz"
+}))]
+pub struct ConsentDecisionResponse {
+    /// Redirect URI with the authorization code or error appended
+    #[serde(rename = "redirectTo")]
+    pub redirect_to: String,
+}
+
+/// Token endpoint request (`application/x-www-form-urlencoded`)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TokenRequest {
+    /// `authorization_code` or `refresh_token`
+    #[schema(example = "authorization_code")]
+    pub grant_type: String,
+    /// Authorization code, for `authorization_code`
+    pub code: Option<String>,
+    /// Redirect URI used in the authorization request, for `authorization_code`
+    pub redirect_uri: Option<String>,
+    /// PKCE code verifier, for `authorization_code`
+    pub code_verifier: Option<String>,
+    /// Refresh token, for `refresh_token`
+    pub refresh_token: Option<String>,
+    /// Client ID, unless sent with HTTP Basic authentication
+    pub client_id: Option<Uuid>,
+    /// Client secret for confidential clients, unless sent with HTTP Basic authentication
+    pub client_secret: Option<String>,
+}
+
+/// Token endpoint response (RFC 6749 section 5.1)
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "access_token": "pat_3q2x7wVYo8bLrZpKcZx0dXH1n6bB2gWm",
+    "token_type": "Bearer",
+    "expires_in": 3600,
+    "refresh_token": "prt_K0s1Qy8WbXq6mZ2nT4vR7uL9pA3cE5dF",
+    "scope": "identity memberships:read"
+}))]
+pub struct TokenResponse {
+    /// Access token
+    pub access_token: String,
+    /// Always `Bearer`
+    pub token_type: String,
+    /// Lifetime of the access token in seconds
+    pub expires_in: i64,
+    /// Refresh token used to obtain a new access token
+    pub refresh_token: String,
+    /// Space-separated scopes granted
+    pub scope: String,
+}
+
+/// `OAuth` error response (RFC 6749 section 5.2)
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "error": "invalid_grant",
+    "error_description": "Authorization code is invalid or expired"
+}))]
+pub struct OAuthErrorResponse {
+    /// Error code
+    pub error: String,
+    /// Human readable explanation
+    pub error_description: String,
+}
+
+/// Token revocation and introspection request (`application/x-www-form-urlencoded`)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TokenLookupRequest {
+    /// Access or refresh token
+    pub token: String,
+    /// Client ID, unless sent with HTTP Basic authentication
+    pub client_id: Option<Uuid>,
+    /// Client secret for confidential clients, unless sent with HTTP Basic authentication
+    pub client_secret: Option<String>,
+}
+
+/// Token introspection response (RFC 7662 section 2.2)
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "active": true,
+    "scope": "identity",
+    "client_id": "0f8fad5b-d9cb-469f-a165-70867728950e",
+    "sub": "a1b2c3d4-5e6f-7890-abcd-ef1234567890",
+    "exp": 1_700_003_600
+}))]
+pub struct IntrospectionResponse {
+    /// Whether the token is currently valid
+    pub active: bool,
+    /// Space-separated scopes granted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Client the token was issued to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<Uuid>,
+    /// User the token acts on behalf of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<Uuid>,
+    /// Expiry as a Unix timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+}
+
+/// Claims about the user returned to `OAuth` clients with the `identity` scope
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "sub": "a1b2c3d4-5e6f-7890-abcd-ef1234567890",
+    "name": "John Doe",
+    "picture": "https://example.com/avatar.jpg",
+    "email": "john@example.com",
+    "email_verified": true
+}))]
+pub struct OAuthUserInfoResponse {
+    /// User ID
+    pub sub: Uuid,
+    /// Display name
+    pub name: Option<String>,
+    /// Avatar URL
+    pub picture: Option<String>,
+    /// Email address; only with the `email` scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Whether the email address is verified; only with the `email` scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::services::tokens::hash_token;
+    use chrono::Duration;
+
+    /// Verifier and challenge from RFC 7636 appendix B
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+    const REDIRECT_URI: &str = "https://app.example.com/callback";
+
+    fn authorization(client_id: Uuid, now: NaiveDateTime) -> OAuthAuthorizationCode {
+        OAuthAuthorizationCode {
+            id: Uuid::new_v4(),
+            code_hash: hash_token("code"),
+            client_id,
+            user_id: Uuid::new_v4(),
+            redirect_uri: REDIRECT_URI.to_owned(),
+            scope: "identity".to_owned(),
+            code_challenge: CHALLENGE.to_owned(),
+            expires_at: now.checked_add_signed(Duration::minutes(10)).unwrap(),
+            created_at: now,
+        }
+    }
+
+    #[test]
+    fn test_required_for_matches_whole_path_segments() {
+        let get = Method::GET;
+        assert_eq!(
+            OAuthScope::required_for(&get, "/api/oauth/userinfo"),
+            Some(OAuthScope::Identity)
+        );
+        assert_eq!(
+            OAuthScope::required_for(&get, "/api/oauth/memberships"),
+            Some(OAuthScope::MembershipsRead)
+        );
+        assert_eq!(
+            OAuthScope::required_for(&get, "/api/series/"),
+            Some(OAuthScope::PostsRead)
+        );
+        assert_eq!(
+            OAuthScope::required_for(&get, "/api/posts/a1b2c3d4/pages"),
+            Some(OAuthScope::PostsRead)
+        );
+        assert_eq!(OAuthScope::required_for(&get, "/api/postsecret"), None);
+        assert_eq!(OAuthScope::required_for(&get, "/api/auth/me/devices"), None);
+        assert_eq!(OAuthScope::required_for(&get, "/api/files"), None);
+    }
+
+    #[test]
+    fn test_required_for_keeps_account_and_creator_data_out_of_reach() {
+        let get = Method::GET;
+        assert_eq!(OAuthScope::required_for(&get, "/api/auth/me"), None);
+        assert_eq!(OAuthScope::required_for(&get, "/api/tiers"), None);
+        assert_eq!(OAuthScope::required_for(&get, "/api/invitations"), None);
+        assert_eq!(OAuthScope::required_for(&get, "/api/oauth/clients"), None);
+    }
+
+    #[test]
+    fn test_required_for_rejects_writes() {
+        assert_eq!(OAuthScope::required_for(&Method::POST, "/api/series"), None);
+        assert_eq!(
+            OAuthScope::required_for(&Method::DELETE, "/api/posts/a1b2c3d4"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pkce_challenge_matches_rfc_example() {
+        assert_eq!(pkce_challenge(VERIFIER), CHALLENGE);
+    }
+
+    #[test]
+    fn test_authorization_code_is_redeemed_with_matching_verifier() {
+        let client_id = Uuid::new_v4();
+        let now = Utc::now().naive_utc();
+        let code = authorization(client_id, now);
+
+        assert!(code.is_redeemable(client_id, REDIRECT_URI, VERIFIER, now));
+        assert!(!code.is_redeemable(client_id, REDIRECT_URI, &VERIFIER.replace('d', "e"), now));
+        assert!(!code.is_redeemable(Uuid::new_v4(), REDIRECT_URI, VERIFIER, now));
+        assert!(!code.is_redeemable(
+            client_id,
+            REDIRECT_URI,
+            VERIFIER,
+            now.checked_add_signed(Duration::minutes(11)).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_authorization_code_needs_exact_redirect_uri() {
+        let client_id = Uuid::new_v4();
+        let now = Utc::now().naive_utc();
+        let code = authorization(client_id, now);
+
+        for redirect_uri in [
+            "https://app.example.com/callback/",
+            "https://app.example.com/callback?next=/",
+            "https://APP.example.com/callback",
+            "http://app.example.com/callback",
+        ] {
+            assert!(!code.is_redeemable(client_id, redirect_uri, VERIFIER, now));
+        }
+    }
+
+    #[test]
+    fn test_client_redirect_uri_is_compared_exactly() {
+        let now = Utc::now().naive_utc();
+        let client = OAuthClient {
+            id: Uuid::new_v4(),
+            owner_id: Uuid::new_v4(),
+            name: "Reader".to_owned(),
+            client_secret_hash: None,
+            redirect_uris: vec![Some(REDIRECT_URI.to_owned()), None],
+            allowed_scopes: "identity".to_owned(),
+            created_at: now,
+            updated_at: now,
+        };
+
+        assert!(client.has_redirect_uri(REDIRECT_URI));
+        assert!(!client.has_redirect_uri("https://app.example.com/callback/"));
+        assert!(!client.has_redirect_uri("https://app.example.com/callback.evil.com"));
+        assert!(!client.has_redirect_uri("https://app.example.com"));
+    }
+}
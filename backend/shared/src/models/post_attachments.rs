@@ -0,0 +1,159 @@
+use crate::models::user_files::UserFile;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Database model for `post_attachments` table
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::post_attachments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PostAttachment {
+    /// Unique attachment identifier
+    pub id: Uuid,
+    /// Post the file is attached to
+    pub post_id: Uuid,
+    /// Attached file
+    pub file_id: Uuid,
+    /// Position of the attachment in the post, starting at 1
+    pub position: i32,
+    /// Text shown with the attachment
+    pub caption: Option<String>,
+    /// Cheapest tier that can download the attachment, absent for public ones
+    pub min_tier_id: Option<Uuid>,
+    /// When the file was attached
+    pub created_at: NaiveDateTime,
+    /// When the attachment was last updated
+    pub updated_at: NaiveDateTime,
+}
+
+/// Request model for attaching a file to a post
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "fileId": "c3d4e5f6-7890-1234-cdef-123456789012",
+    "caption": "Stems for the opening theme",
+    "minTierId": "3f1c2b7e-9a4d-4e8b-b6a1-0c2d4e6f8a9b"
+}))]
+pub struct CreatePostAttachmentRequest {
+    /// File of the signed-in user to attach
+    #[serde(rename = "fileId")]
+    pub file_id: Uuid,
+    /// Text shown with the attachment
+    pub caption: Option<String>,
+    /// Cheapest tier that can download the attachment; omit for a public one
+    #[serde(rename = "minTierId")]
+    pub min_tier_id: Option<Uuid>,
+    /// Position of the attachment; omit to add it after the others
+    pub position: Option<i32>,
+}
+
+/// Request model for updating an attachment; omitted fields are left as they are
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "caption": "Stems for the opening theme, in WAV",
+    "minTierId": null,
+    "position": 2
+}))]
+pub struct UpdatePostAttachmentRequest {
+    /// Text shown with the attachment; an empty caption removes it
+    pub caption: Option<String>,
+    /// Cheapest tier that can download the attachment; null makes it public
+    #[allow(clippy::option_option)]
+    #[schema(value_type = Option<Uuid>)]
+    #[serde(
+        rename = "minTierId",
+        default,
+        deserialize_with = "deserialize_present"
+    )]
+    pub min_tier_id: Option<Option<Uuid>>,
+    /// New position of the attachment, starting at 1
+    pub position: Option<i32>,
+}
+
+/// Attachment of a post with the details of its file
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "id": "e5f6a7b8-9012-3456-ef01-345678901234",
+    "fileId": "c3d4e5f6-7890-1234-cdef-123456789012",
+    "filename": "opening-theme-stems.zip",
+    "mimeType": "application/zip",
+    "fileSize": 48_234_496,
+    "url": "/api/cdn/files/c3d4e5f6-7890-1234-cdef-123456789012",
+    "caption": "Stems for the opening theme",
+    "position": 1,
+    "minTierId": "3f1c2b7e-9a4d-4e8b-b6a1-0c2d4e6f8a9b",
+    "createdAt": "2025-11-05T09:00:00Z",
+    "updatedAt": "2025-11-05T09:00:00Z"
+}))]
+pub struct PostAttachmentResponse {
+    /// Attachment identifier
+    pub id: Uuid,
+    /// Attached file
+    #[serde(rename = "fileId")]
+    pub file_id: Uuid,
+    /// Name of the file as uploaded
+    pub filename: String,
+    /// MIME type of the file
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// Size of the file in bytes
+    #[serde(rename = "fileSize")]
+    pub file_size: i64,
+    /// CDN URL of the file
+    pub url: String,
+    /// Text shown with the attachment
+    pub caption: Option<String>,
+    /// Position of the attachment in the post, starting at 1
+    pub position: i32,
+    /// Cheapest tier that can download the attachment, absent for public ones
+    #[serde(rename = "minTierId")]
+    pub min_tier_id: Option<Uuid>,
+    /// When the file was attached
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the attachment was last updated
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<(PostAttachment, UserFile)> for PostAttachmentResponse {
+    fn from((attachment, file): (PostAttachment, UserFile)) -> Self {
+        Self {
+            id: attachment.id,
+            file_id: file.id,
+            filename: file.original_filename,
+            mime_type: file.mime_type,
+            file_size: file.file_size,
+            url: format!("/api/cdn/files/{}", file.id),
+            caption: attachment.caption,
+            position: attachment.position,
+            min_tier_id: attachment.min_tier_id,
+            created_at: attachment.created_at.and_utc(),
+            updated_at: attachment.updated_at.and_utc(),
+        }
+    }
+}
+
+/// Attachments of a post in order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PostAttachmentsResponse(
+    /// Attachments in order
+    pub Vec<PostAttachmentResponse>,
+);
+
+impl From<Vec<PostAttachmentResponse>> for PostAttachmentsResponse {
+    fn from(attachments: Vec<PostAttachmentResponse>) -> Self {
+        Self(attachments)
+    }
+}
+
+/// Helper function to tell a field given as null apart from a missing one
+#[allow(clippy::option_option)]
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
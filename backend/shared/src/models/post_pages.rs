@@ -0,0 +1,99 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Database model for `post_pages` table
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::post_pages)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PostPage {
+    /// Unique page identifier
+    pub id: Uuid,
+    /// Post the page belongs to
+    pub post_id: Uuid,
+    /// Image file shown on the page
+    pub file_id: Uuid,
+    /// Position of the page in the post, starting at 1
+    pub position: i32,
+    /// Description of the image for screen readers
+    pub alt_text: Option<String>,
+    /// When the page was added
+    pub created_at: NaiveDateTime,
+}
+
+/// Page of a post as given when setting the pages
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PostPageInput {
+    /// Image file of the signed-in user to show on the page
+    #[schema(example = "c3d4e5f6-7890-1234-cdef-123456789012")]
+    #[serde(rename = "fileId")]
+    pub file_id: Uuid,
+    /// Description of the image for screen readers
+    #[schema(example = "Mira opens the door to the lighthouse")]
+    #[serde(rename = "altText")]
+    pub alt_text: Option<String>,
+}
+
+/// Request model for replacing the pages of a post
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "pages": [
+        {
+            "fileId": "c3d4e5f6-7890-1234-cdef-123456789012",
+            "altText": "Mira opens the door to the lighthouse"
+        },
+        {
+            "fileId": "d4e5f6a7-8901-2345-def0-234567890123",
+            "altText": null
+        }
+    ]
+}))]
+pub struct UpdatePostPagesRequest {
+    /// Pages in reading order; an empty list removes every page
+    pub pages: Vec<PostPageInput>,
+}
+
+/// Page of a post as shown to readers
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PostPageResponse {
+    /// Position of the page in the post, starting at 1
+    #[schema(example = 1)]
+    pub position: i32,
+    /// Image file shown on the page
+    #[schema(example = "c3d4e5f6-7890-1234-cdef-123456789012")]
+    #[serde(rename = "fileId")]
+    pub file_id: Uuid,
+    /// CDN URL of the image
+    #[schema(example = "/api/cdn/files/c3d4e5f6-7890-1234-cdef-123456789012")]
+    pub url: String,
+    /// MIME type of the image
+    #[schema(example = "image/png")]
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// Description of the image for screen readers
+    #[schema(example = "Mira opens the door to the lighthouse")]
+    #[serde(rename = "altText")]
+    pub alt_text: Option<String>,
+}
+
+/// Pages of a post in reading order
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "postId": "d290f1ee-6c54-4b01-90e6-d701748f0851",
+    "pages": [{
+        "position": 1,
+        "fileId": "c3d4e5f6-7890-1234-cdef-123456789012",
+        "url": "/api/cdn/files/c3d4e5f6-7890-1234-cdef-123456789012",
+        "mimeType": "image/png",
+        "altText": "Mira opens the door to the lighthouse"
+    }]
+}))]
+pub struct PostPagesResponse {
+    /// Post the pages belong to
+    #[serde(rename = "postId")]
+    pub post_id: Uuid,
+    /// Pages in reading order
+    pub pages: Vec<PostPageResponse>,
+}
@@ -1,3 +1,4 @@
+use crate::models::post_attachments::PostAttachmentResponse;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -44,6 +45,9 @@ pub struct Post {
     /// Timestamp when the post was soft deleted (None if not deleted)
     #[serde(rename = "deletedAt")]
     pub deleted_at: Option<NaiveDateTime>,
+    /// Cheapest tier that can read the post (None if open to everyone)
+    #[serde(rename = "minTierId")]
+    pub min_tier_id: Option<Uuid>,
 }
 
 /// API response model for posts
@@ -60,7 +64,8 @@ pub struct Post {
     "audioFileId": "c3d4e5f6-7890-1234-cdef-123456789012",
     "videoFileId": "d4e5f6a7-8901-2345-def0-234567890123",
     "createdAt": "2023-01-01T00:00:00Z",
-    "updatedAt": "2023-01-01T12:00:00Z"
+    "updatedAt": "2023-01-01T12:00:00Z",
+    "attachments": []
 }))]
 pub struct PostResponse {
     /// Post's unique identifier
@@ -107,6 +112,12 @@ pub struct PostResponse {
     #[schema(example = "2023-01-01T12:00:00Z")]
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<DateTime<Utc>>,
+    /// Cheapest tier that can read the post, absent for posts open to everyone
+    #[schema(example = json!(null))]
+    #[serde(rename = "minTierId")]
+    pub min_tier_id: Option<Uuid>,
+    /// Files attached to the post, in order
+    pub attachments: Vec<PostAttachmentResponse>,
 }
 
 impl From<Post> for PostResponse {
@@ -124,10 +135,21 @@ impl From<Post> for PostResponse {
             video_file_id: post.video_file_id,
             created_at: post.created_at.map(|dt| dt.and_utc()),
             updated_at: post.updated_at.map(|dt| dt.and_utc()),
+            min_tier_id: post.min_tier_id,
+            attachments: Vec::new(),
         }
     }
 }
 
+impl PostResponse {
+    /// Create a `PostResponse` with attachments populated
+    #[must_use]
+    pub fn with_attachments(mut self, attachments: Vec<PostAttachmentResponse>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+}
+
 /// Request model for creating a new post
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[schema(
@@ -200,3 +200,88 @@ impl From<Vec<SeriesResponse>> for SeriesListResponse {
         Self(series)
     }
 }
+
+/// Request model for exporting a series, or a range of its posts, as a book
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "fromNumber": 1,
+    "toNumber": 20
+}))]
+pub struct SeriesExportRequest {
+    /// Number of the first post to include (optional, defaults to the first)
+    #[schema(example = 1)]
+    #[serde(rename = "fromNumber")]
+    pub from_number: Option<i32>,
+    /// Number of the last post to include (optional, defaults to the last)
+    #[schema(example = 20)]
+    #[serde(rename = "toNumber")]
+    pub to_number: Option<i32>,
+}
+
+/// Result of an EPUB export job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "fileId": "a1b2c3d4-e5f6-7890-abcd-ef1234567890",
+    "filename": "my-awesome-podcast-1-20.epub",
+    "fileSize": 482_133,
+    "chapters": 20,
+    "images": 7,
+    "warnings": ["Image https://example.com/gone.png in post 4 was left out: HTTP 404"]
+}))]
+pub struct EpubExportResult {
+    /// File the EPUB was stored as
+    #[serde(rename = "fileId")]
+    pub file_id: Uuid,
+    /// Name of the stored file
+    pub filename: String,
+    /// Size of the EPUB in bytes
+    #[serde(rename = "fileSize")]
+    pub file_size: i64,
+    /// Number of posts included as chapters
+    pub chapters: usize,
+    /// Number of images embedded, including the cover
+    pub images: usize,
+    /// Images that could not be embedded, replaced by their alt text
+    pub warnings: Vec<String>,
+}
+
+/// Comic book archive of one post, made by a CBZ export job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CbzExportFile {
+    /// Post the archive holds the pages of
+    #[serde(rename = "postId")]
+    pub post_id: Uuid,
+    /// Number of the post
+    #[serde(rename = "postNumber")]
+    pub post_number: i32,
+    /// File the archive was stored as
+    #[serde(rename = "fileId")]
+    pub file_id: Uuid,
+    /// Name of the stored file
+    pub filename: String,
+    /// Size of the archive in bytes
+    #[serde(rename = "fileSize")]
+    pub file_size: i64,
+    /// Number of pages in the archive
+    pub pages: usize,
+}
+
+/// Result of a CBZ export job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "files": [{
+        "postId": "d290f1ee-6c54-4b01-90e6-d701748f0851",
+        "postNumber": 1,
+        "fileId": "a1b2c3d4-e5f6-7890-abcd-ef1234567890",
+        "filename": "lighthouse-keepers-001.cbz",
+        "fileSize": 8_412_733,
+        "pages": 24
+    }],
+    "skipped": [2]
+}))]
+pub struct CbzExportResult {
+    /// One archive per post with pages, in number order
+    pub files: Vec<CbzExportFile>,
+    /// Numbers of the posts in the range that have no pages
+    pub skipped: Vec<i32>,
+}
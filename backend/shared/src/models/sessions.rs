@@ -0,0 +1,93 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Per-device session record kept in Redis alongside the cookie session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// Unique session identifier (stored in the cookie session under `sid`)
+    pub id: String,
+    /// ID of the user who owns this session
+    pub user_id: Uuid,
+    /// Timestamp when the session was created
+    pub created_at: NaiveDateTime,
+    /// Timestamp of the last authenticated request made with this session
+    pub last_seen_at: NaiveDateTime,
+    /// Client IP address that created the session
+    pub ip_address: Option<String>,
+    /// User agent of the client that created the session
+    pub user_agent: Option<String>,
+}
+
+/// API response model for an active session
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "9b2f6a64-0d4e-4a6e-8f57-3f1f1c7b2a10",
+    "createdAt": "2023-01-01T00:00:00Z",
+    "lastSeenAt": "2023-01-02T12:00:00Z",
+    "ipAddress": "203.0.113.42",
+    "userAgent": "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/605.1.15",
+    "current": true
+}))]
+pub struct SessionResponse {
+    /// Session identifier used to revoke this session
+    #[schema(example = "9b2f6a64-0d4e-4a6e-8f57-3f1f1c7b2a10")]
+    pub id: String,
+    /// When the session was created
+    #[schema(example = "2023-01-01T00:00:00Z")]
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the session was last used
+    #[schema(example = "2023-01-02T12:00:00Z")]
+    #[serde(rename = "lastSeenAt")]
+    pub last_seen_at: DateTime<Utc>,
+    /// IP address the session was created from
+    #[schema(example = "203.0.113.42")]
+    #[serde(rename = "ipAddress")]
+    pub ip_address: Option<String>,
+    /// User agent the session was created from
+    #[schema(example = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/605.1.15")]
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    /// Whether this is the session making the request
+    #[schema(example = true)]
+    pub current: bool,
+}
+
+impl SessionResponse {
+    /// Build a response from a stored record, flagging whether it is the caller's session
+    #[must_use]
+    pub fn from_record(record: SessionRecord, current_session_id: Option<&str>) -> Self {
+        let current = current_session_id == Some(record.id.as_str());
+        Self {
+            id: record.id,
+            created_at: record.created_at.and_utc(),
+            last_seen_at: record.last_seen_at.and_utc(),
+            ip_address: record.ip_address,
+            user_agent: record.user_agent,
+            current,
+        }
+    }
+}
+
+/// Response type for the session listing endpoint
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(description = "Active sessions for the authenticated user", example = json!([{
+    "id": "9b2f6a64-0d4e-4a6e-8f57-3f1f1c7b2a10",
+    "createdAt": "2023-01-01T00:00:00Z",
+    "lastSeenAt": "2023-01-02T12:00:00Z",
+    "ipAddress": "203.0.113.42",
+    "userAgent": "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) AppleWebKit/605.1.15",
+    "current": true
+}]))]
+pub struct SessionsListResponse(
+    /// List of sessions
+    pub Vec<SessionResponse>,
+);
+
+impl From<Vec<SessionResponse>> for SessionsListResponse {
+    fn from(sessions: Vec<SessionResponse>) -> Self {
+        Self(sessions)
+    }
+}
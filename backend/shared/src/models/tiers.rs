@@ -0,0 +1,80 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Database model for `tiers` table
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable, Selectable)]
+#[diesel(table_name = crate::schema::tiers)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Tier {
+    /// Unique tier identifier
+    pub id: Uuid,
+    /// Creator who offers the tier
+    pub user_id: Uuid,
+    /// Name shown to members
+    pub name: String,
+    /// What members of the tier get
+    pub description: Option<String>,
+    /// Monthly price in cents, which also orders tiers from cheapest to dearest
+    pub amount_cents: i32,
+    /// When the tier was created
+    pub created_at: NaiveDateTime,
+    /// When the tier was last updated
+    pub updated_at: NaiveDateTime,
+}
+
+/// Membership tier as shown to the creator who offers it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(example = json!({
+    "id": "3f1c2b7e-9a4d-4e8b-b6a1-0c2d4e6f8a9b",
+    "name": "Gold",
+    "description": "Early access to every chapter",
+    "amountCents": 500,
+    "createdAt": "2025-11-03T09:00:00Z",
+    "updatedAt": "2025-11-03T09:00:00Z"
+}))]
+pub struct TierResponse {
+    /// Tier identifier
+    pub id: Uuid,
+    /// Name shown to members
+    pub name: String,
+    /// What members of the tier get
+    pub description: Option<String>,
+    /// Monthly price in cents
+    #[serde(rename = "amountCents")]
+    pub amount_cents: i32,
+    /// When the tier was created
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    /// When the tier was last updated
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Tier> for TierResponse {
+    fn from(tier: Tier) -> Self {
+        Self {
+            id: tier.id,
+            name: tier.name,
+            description: tier.description,
+            amount_cents: tier.amount_cents,
+            created_at: tier.created_at.and_utc(),
+            updated_at: tier.updated_at.and_utc(),
+        }
+    }
+}
+
+/// List of a creator's tiers, cheapest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TiersListResponse(
+    /// The tiers
+    pub Vec<TierResponse>,
+);
+
+impl From<Vec<TierResponse>> for TiersListResponse {
+    fn from(tiers: Vec<TierResponse>) -> Self {
+        Self(tiers)
+    }
+}
@@ -19,17 +19,172 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    audit_events (id) {
+        id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        actor_id -> Nullable<Uuid>,
+        #[max_length = 64]
+        event_type -> Varchar,
+        ip_address -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        metadata -> Jsonb,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    background_jobs (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 32]
+        kind -> Varchar,
+        #[max_length = 16]
+        status -> Varchar,
+        payload -> Jsonb,
+        result -> Nullable<Jsonb>,
+        error -> Nullable<Text>,
+        attempts -> Int4,
+        output_key -> Nullable<Text>,
+        output_expires_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        started_at -> Nullable<Timestamp>,
+        finished_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     email_verification_tokens (id) {
         id -> Uuid,
         user_id -> Uuid,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        created_at -> Timestamp,
+        new_email -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    invitations (id) {
+        id -> Uuid,
+        creator_id -> Uuid,
+        tier_id -> Nullable<Uuid>,
+        #[max_length = 255]
+        email -> Varchar,
         #[max_length = 255]
-        token -> Varchar,
+        name -> Nullable<Varchar>,
+        #[max_length = 16]
+        status -> Varchar,
+        #[max_length = 32]
+        source -> Varchar,
+        #[max_length = 64]
+        external_id -> Nullable<Varchar>,
+        metadata -> Nullable<Jsonb>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    known_devices (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 64]
+        fingerprint -> Varchar,
+        user_agent -> Nullable<Text>,
+        ip_address -> Nullable<Text>,
+        #[max_length = 64]
+        deny_token_hash -> Nullable<Varchar>,
+        deny_token_expires_at -> Nullable<Timestamp>,
+        first_seen_at -> Timestamp,
+        last_seen_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    magic_link_tokens (id) {
+        id -> Uuid,
+        email -> Text,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    oauth_authorization_codes (id) {
+        id -> Uuid,
+        #[max_length = 64]
+        code_hash -> Varchar,
+        client_id -> Uuid,
+        user_id -> Uuid,
+        redirect_uri -> Text,
+        scope -> Text,
+        code_challenge -> Text,
         expires_at -> Timestamp,
         created_at -> Timestamp,
     }
 }
 
+diesel::table! {
+    oauth_clients (id) {
+        id -> Uuid,
+        owner_id -> Uuid,
+        name -> Text,
+        #[max_length = 64]
+        client_secret_hash -> Nullable<Varchar>,
+        redirect_uris -> Array<Nullable<Text>>,
+        allowed_scopes -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    oauth_tokens (id) {
+        id -> Uuid,
+        client_id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 64]
+        access_token_hash -> Varchar,
+        #[max_length = 64]
+        refresh_token_hash -> Nullable<Varchar>,
+        scope -> Text,
+        access_expires_at -> Timestamp,
+        refresh_expires_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        last_used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    post_attachments (id) {
+        id -> Uuid,
+        post_id -> Uuid,
+        file_id -> Uuid,
+        position -> Int4,
+        caption -> Nullable<Text>,
+        min_tier_id -> Nullable<Uuid>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    post_pages (id) {
+        id -> Uuid,
+        post_id -> Uuid,
+        file_id -> Uuid,
+        position -> Int4,
+        alt_text -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     posts (id) {
         id -> Uuid,
@@ -47,6 +202,23 @@ diesel::table! {
         created_at -> Nullable<Timestamp>,
         updated_at -> Nullable<Timestamp>,
         deleted_at -> Nullable<Timestamp>,
+        min_tier_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    refresh_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        family_id -> Uuid,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        session_version -> Int4,
+        device_name -> Nullable<Text>,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
     }
 }
 
@@ -77,6 +249,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    tiers (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 255]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        amount_cents -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     user_files (id) {
         id -> Uuid,
@@ -95,6 +280,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    user_identities (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 50]
+        provider -> Varchar,
+        subject -> Text,
+        email -> Nullable<Text>,
+        created_at -> Timestamp,
+        last_used_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Uuid,
@@ -105,28 +303,64 @@ diesel::table! {
         #[max_length = 255]
         display_name -> Nullable<Varchar>,
         avatar_url -> Nullable<Text>,
-        #[max_length = 50]
-        auth_provider -> Varchar,
         email_verified -> Bool,
         last_login -> Nullable<Timestamp>,
         description -> Nullable<Text>,
         banner -> Nullable<Text>,
+        session_version -> Int4,
+        #[max_length = 16]
+        role -> Varchar,
+        suspended_at -> Nullable<Timestamp>,
+        suspension_reason -> Nullable<Text>,
+        suspended_until -> Nullable<Timestamp>,
+        deletion_scheduled_for -> Nullable<Timestamp>,
     }
 }
 
 diesel::joinable!(api_keys -> users (user_id));
+diesel::joinable!(background_jobs -> users (user_id));
 diesel::joinable!(email_verification_tokens -> users (user_id));
+diesel::joinable!(invitations -> tiers (tier_id));
+diesel::joinable!(invitations -> users (creator_id));
+diesel::joinable!(known_devices -> users (user_id));
+diesel::joinable!(oauth_authorization_codes -> oauth_clients (client_id));
+diesel::joinable!(oauth_authorization_codes -> users (user_id));
+diesel::joinable!(oauth_clients -> users (owner_id));
+diesel::joinable!(oauth_tokens -> oauth_clients (client_id));
+diesel::joinable!(oauth_tokens -> users (user_id));
+diesel::joinable!(post_attachments -> posts (post_id));
+diesel::joinable!(post_attachments -> tiers (min_tier_id));
+diesel::joinable!(post_attachments -> user_files (file_id));
+diesel::joinable!(post_pages -> posts (post_id));
+diesel::joinable!(post_pages -> user_files (file_id));
 diesel::joinable!(posts -> series (series_id));
+diesel::joinable!(posts -> tiers (min_tier_id));
+diesel::joinable!(refresh_tokens -> users (user_id));
 diesel::joinable!(series -> users (user_id));
 diesel::joinable!(series_length -> series (series_id));
+diesel::joinable!(tiers -> users (user_id));
 diesel::joinable!(user_files -> users (user_id));
+diesel::joinable!(user_identities -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     api_keys,
+    audit_events,
+    background_jobs,
     email_verification_tokens,
+    invitations,
+    known_devices,
+    magic_link_tokens,
+    oauth_authorization_codes,
+    oauth_clients,
+    oauth_tokens,
+    post_attachments,
+    post_pages,
     posts,
+    refresh_tokens,
     series,
     series_length,
+    tiers,
     user_files,
+    user_identities,
     users,
 );
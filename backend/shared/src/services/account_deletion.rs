@@ -0,0 +1,260 @@
+use crate::errors::ServiceError;
+use crate::models::audit::AuditEventType;
+use crate::models::auth::User;
+use crate::services::audit::record_system_event;
+use crate::services::db::DbService;
+use crate::services::email::{EmailService, HtmlEmailContent};
+use crate::services::s3::S3Service;
+use crate::services::sessions::SessionService;
+use crate::services::user_cache::UserCacheService;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use serde_json::json;
+use uuid::Uuid;
+
+/// How long a requested account deletion can be canceled before the account is purged
+pub const ACCOUNT_DELETION_GRACE_DAYS: i64 = 14;
+
+/// Purge every account whose deletion grace period has passed
+///
+/// Accounts that fail to purge are logged and retried on the next run.
+/// Returns the number of accounts purged.
+///
+/// # Errors
+/// Returns an error if the due accounts cannot be loaded.
+pub async fn purge_due_accounts(
+    db_service: &DbService,
+    s3_service: &S3Service,
+    session_service: &SessionService,
+    user_cache: &UserCacheService,
+    email_service: &EmailService,
+) -> Result<usize, ServiceError> {
+    use crate::schema::users::dsl as users_dsl;
+
+    let due: Vec<User> = {
+        let pool = db_service.pool();
+        let mut conn = pool.get().await.map_err(ServiceError::from)?;
+        users_dsl::users
+            .filter(users_dsl::deletion_scheduled_for.le(Utc::now().naive_utc()))
+            .load(&mut conn)
+            .await?
+    };
+
+    let mut purged: usize = 0;
+    for user in due {
+        match purge_account(db_service, s3_service, session_service, user_cache, &user).await {
+            Ok(true) => {
+                purged = purged.saturating_add(1);
+                send_account_deleted_email(email_service, &user).await;
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to purge account {}: {}", user.id, e),
+        }
+    }
+
+    Ok(purged)
+}
+
+/// Delete an account, its stored files and everything it owns
+///
+/// Everything happens in one transaction that starts by locking the user row,
+/// still due for deletion, so canceling the deletion waits for the purge or
+/// wins outright. File objects are removed from S3 before any row, so a
+/// failure rolls the rows back for the next run to retry; the account's
+/// audit history is kept but pseudonymized, and its sessions are then dropped
+/// from Redis. Returns `false` when the deletion was
+/// canceled in the meantime.
+async fn purge_account(
+    db_service: &DbService,
+    s3_service: &S3Service,
+    session_service: &SessionService,
+    user_cache: &UserCacheService,
+    user: &User,
+) -> Result<bool, ServiceError> {
+    use crate::schema::users::dsl as users_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let user_id = user.id;
+    let deleted_files = conn
+        .transaction::<_, ServiceError, _>(|tx| {
+            async move {
+                let now = Utc::now().naive_utc();
+                let still_due = users_dsl::users
+                    .filter(users_dsl::id.eq(user_id))
+                    .filter(users_dsl::deletion_scheduled_for.le(now))
+                    .select(users_dsl::id)
+                    .for_update()
+                    .first::<Uuid>(tx)
+                    .await
+                    .optional()?;
+                if still_due.is_none() {
+                    return Ok(None);
+                }
+
+                let file_count = delete_stored_objects(tx, s3_service, user_id).await?;
+                delete_owned_rows(tx, user_id).await?;
+                pseudonymize_audit_events(tx, user_id).await?;
+
+                // Identities, devices, OAuth apps, jobs and remaining tokens cascade
+                let deleted = diesel::delete(
+                    users_dsl::users
+                        .filter(users_dsl::id.eq(user_id))
+                        .filter(users_dsl::deletion_scheduled_for.le(now)),
+                )
+                .execute(tx)
+                .await?;
+                if deleted == 0 {
+                    return Err(ServiceError::Conflict(
+                        "Account deletion was canceled during the purge".to_owned(),
+                    ));
+                }
+                Ok(Some(file_count))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let Some(file_count) = deleted_files else {
+        return Ok(false);
+    };
+
+    let _ = session_service.revoke_all_except(user.id, None).await?;
+    user_cache.invalidate(user.id).await?;
+
+    record_system_event(
+        db_service,
+        Some(user.id),
+        AuditEventType::AccountDeleted,
+        json!({ "files": file_count }),
+    )
+    .await;
+
+    Ok(true)
+}
+
+/// Helper function to delete the S3 objects of a user's files and job outputs
+///
+/// Returns the number of files deleted.
+async fn delete_stored_objects(
+    conn: &mut AsyncPgConnection,
+    s3_service: &S3Service,
+    user_id: Uuid,
+) -> Result<usize, ServiceError> {
+    use crate::schema::background_jobs::dsl as jobs_dsl;
+    use crate::schema::user_files::dsl as files_dsl;
+
+    let file_paths: Vec<String> = files_dsl::user_files
+        .filter(files_dsl::user_id.eq(user_id))
+        .select(files_dsl::file_path)
+        .load(conn)
+        .await?;
+    for file_path in &file_paths {
+        s3_service.delete_object(file_path).await?;
+    }
+    // Job rows cascade with the user, so their outputs go now or never
+    let output_keys: Vec<Option<String>> = jobs_dsl::background_jobs
+        .filter(jobs_dsl::user_id.eq(user_id))
+        .filter(jobs_dsl::output_key.is_not_null())
+        .select(jobs_dsl::output_key)
+        .load(conn)
+        .await?;
+    for output_key in output_keys.iter().flatten() {
+        s3_service.delete_object(output_key).await?;
+    }
+
+    Ok(file_paths.len())
+}
+
+/// Helper function to delete the rows a user owns that do not cascade with the user
+async fn delete_owned_rows(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+) -> Result<(), ServiceError> {
+    use crate::schema::{
+        api_keys::dsl as keys_dsl, oauth_tokens::dsl as oauth_tokens_dsl, posts::dsl as posts_dsl,
+        refresh_tokens::dsl as refresh_dsl, series::dsl as series_dsl,
+        user_files::dsl as files_dsl,
+    };
+
+    // Posts go before the user, since their tier locks keep the user's
+    // tiers from being deleted along with the account
+    let series_ids = series_dsl::series
+        .filter(series_dsl::user_id.eq(user_id))
+        .select(series_dsl::id);
+    let _ = diesel::delete(posts_dsl::posts.filter(posts_dsl::series_id.eq_any(series_ids)))
+        .execute(conn)
+        .await?;
+    let _ = diesel::delete(series_dsl::series.filter(series_dsl::user_id.eq(user_id)))
+        .execute(conn)
+        .await?;
+    let _ = diesel::delete(files_dsl::user_files.filter(files_dsl::user_id.eq(user_id)))
+        .execute(conn)
+        .await?;
+    let _ = diesel::delete(keys_dsl::api_keys.filter(keys_dsl::user_id.eq(user_id)))
+        .execute(conn)
+        .await?;
+    let _ = diesel::delete(refresh_dsl::refresh_tokens.filter(refresh_dsl::user_id.eq(user_id)))
+        .execute(conn)
+        .await?;
+    let _ = diesel::delete(
+        oauth_tokens_dsl::oauth_tokens.filter(oauth_tokens_dsl::user_id.eq(user_id)),
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Helper function to strip personal data from the audit events about a user
+///
+/// The events stay, keyed by the now unused user ID, but lose the client IP
+/// address, the user agent and the email addresses recorded in their
+/// metadata. The append-only trigger lets exactly this through once the
+/// transaction sets `audit.pseudonymize`.
+async fn pseudonymize_audit_events(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+) -> Result<(), ServiceError> {
+    use crate::schema::audit_events::dsl as audit_dsl;
+
+    let _ = diesel::sql_query("SET LOCAL audit.pseudonymize = 'on'")
+        .execute(conn)
+        .await?;
+    let _ = diesel::update(audit_dsl::audit_events.filter(audit_dsl::user_id.eq(user_id)))
+        .set((
+            audit_dsl::ip_address.eq(None::<String>),
+            audit_dsl::user_agent.eq(None::<String>),
+            audit_dsl::metadata.eq(audit_dsl::metadata
+                .remove(vec!["email", "newEmail", "oldEmail"])
+                .remove_by_path(vec!["before", "email"])
+                .remove_by_path(vec!["after", "email"])),
+        ))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Helper function to confirm to a former user that their account is gone
+///
+/// Failures are logged rather than returned since the account no longer exists.
+async fn send_account_deleted_email(email_service: &EmailService, user: &User) {
+    let email_body = "<h1>Your account has been deleted</h1>
+        <p>As you requested, your Patron account has been deleted together with its series, posts, files and API keys.</p>
+        <p>Thank you for having been part of Patron. You are welcome to create a new account at any time.</p>";
+
+    if let Err(e) = email_service
+        .send_html_email(HtmlEmailContent {
+            to: &user.email,
+            subject: "Your Patron account has been deleted",
+            html_body: email_body,
+            text_body: None,
+            from: None,
+        })
+        .await
+    {
+        tracing::warn!("Failed to send account deletion confirmation: {}", e);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::services::db::DbService;
+use crate::services::sessions::{client_ip, user_agent};
+use actix_web::HttpRequest;
+use chrono::Utc;
+use diesel_async::RunQueryDsl;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Domain of an email address, lowercased, for events that must not store the address
+///
+/// Failed sign-ins for unknown accounts keep only the domain, which still shows
+/// credential stuffing against one provider without logging typos of other
+/// people's addresses.
+#[must_use]
+pub fn email_domain(email: &str) -> Option<String> {
+    email
+        .trim()
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.to_lowercase())
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Append an event to the security audit log
+///
+/// The client IP address and user agent are taken from the request. Failures
+/// are logged instead of returned so a broken audit log never blocks sign-in
+/// or account recovery.
+pub async fn record_event(
+    db_service: &DbService,
+    req: &HttpRequest,
+    user_id: Option<Uuid>,
+    event_type: AuditEventType,
+    metadata: Value,
+) {
+    insert_event(db_service, Some(req), user_id, None, event_type, metadata).await;
+}
+
+/// Append an action an admin took to the security audit log
+///
+/// The admin is stored as the actor and `subject_id` as the user the action
+/// concerns, if any. Failures are logged like [`record_event`].
+pub async fn record_admin_action(
+    db_service: &DbService,
+    req: &HttpRequest,
+    admin_id: Uuid,
+    subject_id: Option<Uuid>,
+    event_type: AuditEventType,
+    metadata: Value,
+) {
+    insert_event(
+        db_service,
+        Some(req),
+        subject_id,
+        Some(admin_id),
+        event_type,
+        metadata,
+    )
+    .await;
+}
+
+/// Append an event caused by a background job rather than a request
+///
+/// No IP address or user agent is stored. Failures are logged like
+/// [`record_event`].
+pub async fn record_system_event(
+    db_service: &DbService,
+    user_id: Option<Uuid>,
+    event_type: AuditEventType,
+    metadata: Value,
+) {
+    insert_event(db_service, None, user_id, None, event_type, metadata).await;
+}
+
+/// Helper function to insert an audit event, logging failures
+async fn insert_event(
+    db_service: &DbService,
+    req: Option<&HttpRequest>,
+    user_id: Option<Uuid>,
+    actor_id: Option<Uuid>,
+    event_type: AuditEventType,
+    metadata: Value,
+) {
+    use crate::schema::audit_events::dsl as audit_dsl;
+
+    let event = AuditEvent {
+        id: Uuid::new_v4(),
+        user_id,
+        actor_id,
+        event_type: event_type.as_str().to_owned(),
+        ip_address: req.and_then(client_ip),
+        user_agent: req.and_then(user_agent),
+        metadata,
+        created_at: Utc::now().naive_utc(),
+    };
+
+    let pool = db_service.pool();
+    let result = match pool.get().await {
+        Ok(mut conn) => diesel::insert_into(audit_dsl::audit_events)
+            .values(&event)
+            .execute(&mut conn)
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    if let Err(e) = result {
+        tracing::warn!(
+            "Failed to record {} audit event: {}",
+            event_type.as_str(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_domain_drops_the_local_part() {
+        assert_eq!(
+            email_domain(" Jane.Reader@Example.COM "),
+            Some("example.com".to_owned())
+        );
+        assert_eq!(email_domain("not-an-address"), None);
+        assert_eq!(email_domain("jane@"), None);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::errors::ServiceError;
+use crate::models::jobs::BackgroundJob;
+use crate::models::posts::Post;
+use crate::models::series::{CbzExportFile, CbzExportResult, Series};
+use crate::services::epub_export::{escape_xml, InMemoryZip};
+use crate::services::feed_import::sniff_image;
+use crate::services::jobs::{JobContext, JobOutcome};
+use crate::services::user_files::store_job_file;
+use chrono::{Datelike, NaiveDateTime};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// MIME type of comic book archives
+pub const CBZ_MIME_TYPE: &str = "application/vnd.comicbook+zip";
+
+/// Input of a CBZ export job
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CbzExportPayload {
+    /// Series to export
+    #[serde(rename = "seriesId")]
+    pub series_id: Uuid,
+    /// Number of the first post to include
+    #[serde(rename = "fromNumber")]
+    pub from_number: Option<i32>,
+    /// Number of the last post to include
+    #[serde(rename = "toNumber")]
+    pub to_number: Option<i32>,
+}
+
+/// Metadata written to the `ComicInfo.xml` of an archive
+#[derive(Debug, Clone)]
+pub struct ComicInfo<'info> {
+    /// Series title
+    pub series: &'info str,
+    /// Post number
+    pub number: i32,
+    /// Post title
+    pub title: &'info str,
+    /// Creator's display name
+    pub writer: Option<&'info str>,
+    /// Series description
+    pub summary: Option<&'info str>,
+    /// When the post was published
+    pub published: Option<NaiveDateTime>,
+}
+
+/// Package the pages of a series' posts into one comic book archive per post
+///
+/// Published posts with pages become archives of their page images in order,
+/// with a `ComicInfo.xml` describing the episode, stored as files of the
+/// creator. Posts without pages are skipped.
+///
+/// # Errors
+/// Returns an error if the series is gone, none of its published posts in the
+/// range has pages, or storage or database operations fail.
+#[allow(clippy::too_many_lines)]
+pub async fn run_cbz_export(
+    ctx: &JobContext,
+    job: &BackgroundJob,
+) -> Result<JobOutcome, ServiceError> {
+    use crate::schema::{
+        post_pages::dsl as pages_dsl, posts::dsl as posts_dsl, series::dsl as series_dsl,
+        user_files::dsl as files_dsl, users::dsl as users_dsl,
+    };
+
+    let payload: CbzExportPayload = serde_json::from_value(job.payload.clone())?;
+
+    let pool = ctx.db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let series: Series = series_dsl::series
+        .filter(series_dsl::id.eq(payload.series_id))
+        .filter(series_dsl::user_id.eq(job.user_id))
+        .filter(series_dsl::deleted_at.is_null())
+        .select(Series::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| ServiceError::NotFound("Series not found".to_owned()))?;
+    let display_name: Option<String> = users_dsl::users
+        .find(job.user_id)
+        .select(users_dsl::display_name)
+        .first(&mut conn)
+        .await?;
+
+    let mut query = posts_dsl::posts
+        .filter(posts_dsl::series_id.eq(series.id))
+        .filter(posts_dsl::is_published.eq(true))
+        .filter(posts_dsl::deleted_at.is_null())
+        .into_boxed();
+    if let Some(from_number) = payload.from_number {
+        query = query.filter(posts_dsl::number.ge(from_number));
+    }
+    if let Some(to_number) = payload.to_number {
+        query = query.filter(posts_dsl::number.le(to_number));
+    }
+    let posts: Vec<Post> = query
+        .order(posts_dsl::number.asc())
+        .select(Post::as_select())
+        .load(&mut conn)
+        .await?;
+
+    let post_ids: Vec<Uuid> = posts.iter().map(|post| post.id).collect();
+    let page_rows: Vec<(Uuid, String)> = pages_dsl::post_pages
+        .inner_join(files_dsl::user_files)
+        .filter(pages_dsl::post_id.eq_any(&post_ids))
+        .filter(files_dsl::deleted_at.is_null())
+        .order((pages_dsl::post_id.asc(), pages_dsl::position.asc()))
+        .select((pages_dsl::post_id, files_dsl::file_path))
+        .load(&mut conn)
+        .await?;
+    let mut pages_by_post: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (post_id, file_path) in page_rows {
+        pages_by_post.entry(post_id).or_default().push(file_path);
+    }
+
+    let mut result = CbzExportResult {
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for post in &posts {
+        let Some(page_paths) = pages_by_post.get(&post.id) else {
+            result.skipped.push(post.number);
+            continue;
+        };
+
+        let mut pages = Vec::with_capacity(page_paths.len());
+        for file_path in page_paths {
+            let mut stream = ctx.s3_service.get_object_stream(file_path).await?;
+            let mut bytes = Vec::new();
+            while let Some(chunk) = stream.next().await {
+                bytes.extend_from_slice(&chunk?);
+            }
+            pages.push(bytes);
+        }
+
+        let info = ComicInfo {
+            series: &series.title,
+            number: post.number,
+            title: &post.title,
+            writer: display_name
+                .as_deref()
+                .filter(|name| !name.trim().is_empty()),
+            summary: series.description.as_deref(),
+            published: post.created_at,
+        };
+        let archive = build_cbz(&info, &pages)?;
+        let filename = format!("{}-{:03}.cbz", series.slug, post.number);
+        let file = store_job_file(
+            ctx,
+            &mut conn,
+            job.user_id,
+            &filename,
+            CBZ_MIME_TYPE,
+            archive,
+        )
+        .await?;
+
+        result.files.push(CbzExportFile {
+            post_id: post.id,
+            post_number: post.number,
+            file_id: file.id,
+            filename: file.original_filename,
+            file_size: file.file_size,
+            pages: pages.len(),
+        });
+    }
+
+    if result.files.is_empty() {
+        return Err(ServiceError::Config(
+            "None of the published posts in this range has pages".to_owned(),
+        ));
+    }
+
+    Ok(JobOutcome {
+        result: serde_json::to_value(&result)?,
+        output_key: None,
+        output_expires_at: None,
+    })
+}
+
+/// Build a comic book archive of page images
+///
+/// Pages are named by their zero-padded position, so readers that sort by
+/// name show them in order, and stored uncompressed since images already are.
+///
+/// # Errors
+/// Returns an error if the archive cannot be written.
+pub fn build_cbz(info: &ComicInfo<'_>, pages: &[Vec<u8>]) -> Result<Vec<u8>, ServiceError> {
+    let width = pages.len().to_string().len().max(3);
+    let mut writer = InMemoryZip::default();
+    for (index, bytes) in pages.iter().enumerate() {
+        let extension = sniff_image(bytes).map_or("img", |(_, extension)| extension);
+        let name = format!("{:0width$}.{extension}", index.saturating_add(1));
+        writer.add_stored(&name, bytes)?;
+    }
+    writer.add_file("ComicInfo.xml", comic_info_xml(info, pages).as_bytes())?;
+    writer.finish()
+}
+
+/// Helper function to describe an episode in the `ComicInfo` format comic readers use
+fn comic_info_xml(info: &ComicInfo<'_>, pages: &[Vec<u8>]) -> String {
+    let mut fields = vec![
+        format!("<Title>{}</Title>", escape_xml(info.title)),
+        format!("<Series>{}</Series>", escape_xml(info.series)),
+        format!("<Number>{}</Number>", info.number),
+    ];
+    if let Some(summary) = info.summary.filter(|text| !text.trim().is_empty()) {
+        fields.push(format!("<Summary>{}</Summary>", escape_xml(summary)));
+    }
+    if let Some(published) = info.published {
+        fields.push(format!("<Year>{}</Year>", published.year()));
+        fields.push(format!("<Month>{}</Month>", published.month()));
+        fields.push(format!("<Day>{}</Day>", published.day()));
+    }
+    if let Some(writer) = info.writer {
+        fields.push(format!("<Writer>{}</Writer>", escape_xml(writer)));
+    }
+    fields.push(format!("<PageCount>{}</PageCount>", pages.len()));
+
+    let page_entries: Vec<String> = pages
+        .iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            let kind = if index == 0 {
+                " Type=\"FrontCover\""
+            } else {
+                ""
+            };
+            format!(
+                "<Page Image=\"{index}\"{kind} ImageSize=\"{}\"/>",
+                bytes.len()
+            )
+        })
+        .collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<ComicInfo xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">\n  {}\n  <Pages>\n    {}\n  </Pages>\n</ComicInfo>\n",
+        fields.join("\n  "),
+        page_entries.join("\n    ")
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::services::post_import::read_zip;
+
+    #[test]
+    fn builds_comic_book_archive() {
+        let info = ComicInfo {
+            series: "Lighthouse Keepers",
+            number: 7,
+            title: "Storm & Signal",
+            writer: Some("Ada"),
+            summary: None,
+            published: chrono::NaiveDate::from_ymd_opt(2025, 11, 4)
+                .and_then(|date| date.and_hms_opt(9, 0, 0)),
+        };
+        let pages = vec![
+            b"\x89PNG\r\n\x1a\nfirst".to_vec(),
+            b"\xFF\xD8\xFFsecond".to_vec(),
+        ];
+
+        let entries = read_zip(&build_cbz(&info, &pages).unwrap(), usize::MAX).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["001.png", "002.jpg", "ComicInfo.xml"]);
+        assert_eq!(entries[1].contents, pages[1]);
+
+        let comic_info = String::from_utf8(entries[2].contents.clone()).unwrap();
+        assert!(comic_info.contains("<Title>Storm &amp; Signal</Title>"));
+        assert!(comic_info.contains("<Number>7</Number>"));
+        assert!(comic_info.contains("<Month>11</Month>"));
+        assert!(comic_info.contains("<PageCount>2</PageCount>"));
+        assert!(comic_info.contains("<Page Image=\"0\" Type=\"FrontCover\" ImageSize=\"13\"/>"));
+        assert!(!comic_info.contains("<Summary>"));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::errors::ServiceError;
+use crate::services::db::DbService;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// Delete expired and consumed single-use tokens
+///
+/// Handlers reject expired tokens on their own; this only keeps the tables
+/// from growing and limits what a database leak exposes. Refresh tokens are
+/// kept until they expire, even once used, so reuse can still be detected.
+/// Expired "this wasn't me" tokens are cleared from known devices, which are
+/// themselves kept.
+///
+/// Returns the number of rows deleted or cleared.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+pub async fn purge_expired_tokens(db_service: &DbService) -> Result<usize, ServiceError> {
+    use crate::schema::{
+        email_verification_tokens::dsl as verification_dsl, known_devices::dsl as devices_dsl,
+        magic_link_tokens::dsl as magic_dsl, oauth_authorization_codes::dsl as codes_dsl,
+        oauth_tokens::dsl as oauth_tokens_dsl, refresh_tokens::dsl as refresh_dsl,
+    };
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    let now = Utc::now().naive_utc();
+
+    let verification_tokens = diesel::delete(
+        verification_dsl::email_verification_tokens.filter(verification_dsl::expires_at.le(now)),
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let magic_links = diesel::delete(
+        magic_dsl::magic_link_tokens.filter(
+            magic_dsl::expires_at
+                .le(now)
+                .or(magic_dsl::used_at.is_not_null()),
+        ),
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let authorization_codes =
+        diesel::delete(codes_dsl::oauth_authorization_codes.filter(codes_dsl::expires_at.le(now)))
+            .execute(&mut conn)
+            .await?;
+
+    let oauth_tokens = diesel::delete(
+        oauth_tokens_dsl::oauth_tokens.filter(
+            oauth_tokens_dsl::revoked_at
+                .is_not_null()
+                .or(oauth_tokens_dsl::refresh_expires_at.le(now))
+                .or(oauth_tokens_dsl::refresh_expires_at
+                    .is_null()
+                    .and(oauth_tokens_dsl::access_expires_at.le(now))),
+        ),
+    )
+    .execute(&mut conn)
+    .await?;
+
+    let refresh_tokens =
+        diesel::delete(refresh_dsl::refresh_tokens.filter(refresh_dsl::expires_at.le(now)))
+            .execute(&mut conn)
+            .await?;
+
+    let deny_tokens = diesel::update(
+        devices_dsl::known_devices.filter(devices_dsl::deny_token_expires_at.le(now)),
+    )
+    .set((
+        devices_dsl::deny_token_hash.eq(None::<String>),
+        devices_dsl::deny_token_expires_at.eq(None::<chrono::NaiveDateTime>),
+    ))
+    .execute(&mut conn)
+    .await?;
+
+    Ok([
+        verification_tokens,
+        magic_links,
+        authorization_codes,
+        oauth_tokens,
+        refresh_tokens,
+        deny_tokens,
+    ]
+    .iter()
+    .sum())
+}
@@ -1,4 +1,5 @@
 use crate::errors::ServiceError;
+use ipnet::IpNet;
 use std::env;
 
 /// Configuration service for managing application settings
@@ -12,12 +13,26 @@ pub struct ConfigService {
     pub aws_config: Option<AwsConfig>,
     /// Google `OAuth` configuration
     pub google_oauth_config: Option<GoogleOAuthConfig>,
+    /// External `OpenID Connect` providers users can sign in with, including Google
+    pub oidc_providers: Vec<OidcProviderConfig>,
     /// SMTP configuration for email services
     pub smtp_config: Option<SmtpConfig>,
     /// Redis configuration for caching
     pub redis_config: Option<RedisConfig>,
     /// Cookie secure flag for HTTPS
     pub cookie_secure: bool,
+    /// Rate limits for authentication endpoints
+    pub rate_limit_config: RateLimitConfig,
+    /// Minimum zxcvbn score (0-4) a new password must reach
+    pub min_password_score: u8,
+    /// Signing key and lifetimes of access and refresh tokens for native clients
+    pub token_config: Option<TokenConfig>,
+    /// Origins allowed to make credentialed cross-origin requests, e.g. `https://patron.com`
+    pub cors_allowed_origins: Vec<String>,
+    /// Reverse proxies whose `X-Forwarded-For` header is believed, as addresses or CIDR ranges
+    pub trusted_proxies: Vec<IpNet>,
+    /// Address suspended users can write to to appeal a suspension
+    pub appeals_email: Option<String>,
 }
 
 /// Configuration for AWS services
@@ -52,6 +67,119 @@ pub struct GoogleOAuthConfig {
     pub backend_url: String,
 }
 
+/// Configuration for an `OpenID Connect` sign-in provider
+#[derive(Clone, Debug)]
+pub struct OidcProviderConfig {
+    /// Identifier used in URLs and stored on linked identities (e.g. `google`, `keycloak`)
+    pub name: String,
+    /// Human readable name shown on login buttons
+    pub display_name: String,
+    /// Issuer URL; `{issuer}/.well-known/openid-configuration` is the discovery document
+    pub issuer: String,
+    /// Other spellings of the issuer the provider puts in ID tokens, such as
+    /// Google's legacy `accounts.google.com`
+    pub issuer_aliases: Vec<String>,
+    /// `OAuth` client ID
+    pub client_id: String,
+    /// `OAuth` client secret
+    pub client_secret: String,
+    /// Redirect URL registered with the provider
+    pub redirect_url: String,
+    /// Space separated scopes to request
+    pub scopes: String,
+    /// Whether an email address the provider verified may sign in to an
+    /// existing account with that address; otherwise the identity has to be
+    /// linked from the signed-in account
+    pub trust_email: bool,
+    /// Endpoints that replace the discovery document, for providers without one
+    pub endpoint_overrides: OidcEndpointOverrides,
+}
+
+/// Manually configured endpoints for providers that do not publish a discovery document
+#[derive(Clone, Debug, Default)]
+pub struct OidcEndpointOverrides {
+    /// Authorization endpoint URL
+    pub authorization: Option<String>,
+    /// Token endpoint URL
+    pub token: Option<String>,
+    /// Userinfo endpoint URL
+    pub userinfo: Option<String>,
+    /// Endpoint listing the user's email addresses and whether each is
+    /// verified, for providers such as `GitHub` whose userinfo does not say
+    pub emails: Option<String>,
+}
+
+impl OidcProviderConfig {
+    /// Build the provider list: Google from the `GOOGLE_*` variables, then every
+    /// provider named in `OIDC_PROVIDERS` from its `OIDC_<NAME>_*` variables
+    ///
+    /// Providers missing a client ID, client secret or issuer are skipped.
+    fn from_env(google_oauth_config: Option<&GoogleOAuthConfig>) -> Vec<Self> {
+        let mut providers = Vec::new();
+
+        if let Some(google) = google_oauth_config {
+            providers.push(Self {
+                name: "google".to_owned(),
+                display_name: "Google".to_owned(),
+                issuer: "https://accounts.google.com".to_owned(),
+                issuer_aliases: vec!["accounts.google.com".to_owned()],
+                client_id: google.client_id.clone(),
+                client_secret: google.client_secret.clone(),
+                redirect_url: google.redirect_url.clone(),
+                scopes: "openid email profile".to_owned(),
+                trust_email: true,
+                endpoint_overrides: OidcEndpointOverrides::default(),
+            });
+        }
+
+        let backend_url = env::var("APPLICATION_BACKEND_URL")
+            .unwrap_or_else(|_| "http://localhost:8080".to_owned());
+        let names = env::var("OIDC_PROVIDERS").unwrap_or_default();
+        for name in names
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty() && name != "google")
+        {
+            let prefix = format!("OIDC_{}", name.to_uppercase().replace('-', "_"));
+            let var = |suffix: &str| env::var(format!("{prefix}_{suffix}")).ok();
+
+            let (Some(issuer), Some(client_id), Some(client_secret)) =
+                (var("ISSUER"), var("CLIENT_ID"), var("CLIENT_SECRET"))
+            else {
+                tracing::warn!("Skipping OIDC provider {}: incomplete configuration", name);
+                continue;
+            };
+
+            providers.push(Self {
+                display_name: var("DISPLAY_NAME").unwrap_or_else(|| name.clone()),
+                issuer: issuer.trim_end_matches('/').to_owned(),
+                issuer_aliases: var("ISSUER_ALIASES")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|alias| !alias.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect(),
+                client_id,
+                client_secret,
+                redirect_url: var("REDIRECT_URL")
+                    .unwrap_or_else(|| format!("{backend_url}/api/auth/oidc/{name}/callback")),
+                scopes: var("SCOPES").unwrap_or_else(|| "openid email profile".to_owned()),
+                trust_email: var("TRUST_EMAIL").is_some_and(|value| value == "true"),
+                endpoint_overrides: OidcEndpointOverrides {
+                    authorization: var("AUTHORIZATION_ENDPOINT"),
+                    token: var("TOKEN_ENDPOINT"),
+                    userinfo: var("USERINFO_ENDPOINT"),
+                    emails: var("EMAILS_ENDPOINT"),
+                },
+                name,
+            });
+        }
+
+        providers
+    }
+}
+
 /// Configuration for SMTP email service
 #[derive(Clone, Debug)]
 pub struct SmtpConfig {
@@ -76,6 +204,196 @@ pub struct RedisConfig {
     pub max_connections: u32,
 }
 
+/// Configuration for first-party access and refresh tokens
+#[derive(Clone, Debug)]
+pub struct TokenConfig {
+    /// Secret used to sign access tokens with `HS256`
+    pub signing_secret: String,
+    /// Value of the `iss` claim, the public backend URL
+    pub issuer: String,
+    /// Access token lifetime in seconds
+    pub access_ttl_seconds: i64,
+    /// Refresh token lifetime in days
+    pub refresh_ttl_days: i64,
+}
+
+impl TokenConfig {
+    /// Shortest `JWT_SECRET` accepted, the output size of `HS256`
+    pub const MIN_SECRET_BYTES: usize = 32;
+
+    /// Load token settings from `JWT_*` environment variables
+    ///
+    /// Returns `None` if `JWT_SECRET` is unset, shorter than
+    /// [`Self::MIN_SECRET_BYTES`] or the same as `AUTH_SECRET_KEY`; anyone who
+    /// knows the secret can mint tokens for any user, so there is no default.
+    fn from_env() -> Option<Self> {
+        let signing_secret = env::var("JWT_SECRET")
+            .ok()
+            .filter(|secret| secret.len() >= Self::MIN_SECRET_BYTES)
+            .filter(|secret| env::var("AUTH_SECRET_KEY").ok().as_ref() != Some(secret))?;
+
+        Some(Self {
+            signing_secret,
+            issuer: env::var("APPLICATION_BACKEND_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_owned()),
+            access_ttl_seconds: env::var("JWT_ACCESS_TTL_SECONDS")
+                .ok()
+                .and_then(|ttl| ttl.parse::<i64>().ok())
+                .filter(|ttl| *ttl > 0)
+                .unwrap_or(900),
+            refresh_ttl_days: env::var("JWT_REFRESH_TTL_DAYS")
+                .ok()
+                .and_then(|ttl| ttl.parse::<i64>().ok())
+                .filter(|ttl| *ttl > 0)
+                .unwrap_or(30),
+        })
+    }
+}
+
+/// Maximum number of requests allowed within a sliding window
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    /// Requests allowed per window
+    pub max_requests: u64,
+    /// Window length in seconds
+    pub window_seconds: u64,
+}
+
+impl RateLimit {
+    /// Read a limit from an environment variable in `<requests>/<seconds>` form
+    ///
+    /// Falls back to `default` if the variable is unset or malformed.
+    fn from_env(name: &str, default: Self) -> Self {
+        env::var(name)
+            .ok()
+            .and_then(|value| {
+                let (max_requests, window_seconds) = value.split_once('/')?;
+                Some(Self {
+                    max_requests: max_requests.trim().parse().ok()?,
+                    window_seconds: window_seconds.trim().parse().ok()?,
+                })
+            })
+            .filter(|limit| limit.max_requests > 0 && limit.window_seconds > 0)
+            .unwrap_or(default)
+    }
+}
+
+/// Rate limits applied to authentication and account recovery endpoints
+///
+/// Each limit is enforced separately per client IP, per email address and per API key.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitConfig {
+    /// Limit for `POST /api/auth/login`
+    pub login: RateLimit,
+    /// Limit for `POST /api/auth/register`
+    pub register: RateLimit,
+    /// Limit for `POST /api/auth/forgot-password`
+    pub forgot_password: RateLimit,
+    /// Limit for `POST /api/auth/check-email`
+    pub check_email: RateLimit,
+    /// Limit for `POST /api/auth/resend-verification`
+    pub resend_verification: RateLimit,
+    /// Limit for `POST /api/auth/change-email`
+    pub change_email: RateLimit,
+    /// Limit for `POST /api/auth/magic-link`
+    pub magic_link: RateLimit,
+}
+
+impl RateLimitConfig {
+    /// Load rate limits from `RATE_LIMIT_*` environment variables, using defaults for unset ones
+    fn from_env() -> Self {
+        Self {
+            login: RateLimit::from_env(
+                "RATE_LIMIT_LOGIN",
+                RateLimit {
+                    max_requests: 10,
+                    window_seconds: 900,
+                },
+            ),
+            register: RateLimit::from_env(
+                "RATE_LIMIT_REGISTER",
+                RateLimit {
+                    max_requests: 5,
+                    window_seconds: 3600,
+                },
+            ),
+            forgot_password: RateLimit::from_env(
+                "RATE_LIMIT_FORGOT_PASSWORD",
+                RateLimit {
+                    max_requests: 5,
+                    window_seconds: 3600,
+                },
+            ),
+            check_email: RateLimit::from_env(
+                "RATE_LIMIT_CHECK_EMAIL",
+                RateLimit {
+                    max_requests: 20,
+                    window_seconds: 600,
+                },
+            ),
+            resend_verification: RateLimit::from_env(
+                "RATE_LIMIT_RESEND_VERIFICATION",
+                RateLimit {
+                    max_requests: 3,
+                    window_seconds: 3600,
+                },
+            ),
+            change_email: RateLimit::from_env(
+                "RATE_LIMIT_CHANGE_EMAIL",
+                RateLimit {
+                    max_requests: 5,
+                    window_seconds: 3600,
+                },
+            ),
+            magic_link: RateLimit::from_env(
+                "RATE_LIMIT_MAGIC_LINK",
+                RateLimit {
+                    max_requests: 5,
+                    window_seconds: 3600,
+                },
+            ),
+        }
+    }
+}
+
+/// Read `CORS_ALLOWED_ORIGINS`, a comma-separated list of origins
+///
+/// Defaults to the frontend URL. Wildcards are ignored: the session cookie is
+/// sent cross-origin, so every allowed origin must be named explicitly.
+fn cors_allowed_origins_from_env() -> Vec<String> {
+    let configured = env::var("CORS_ALLOWED_ORIGINS")
+        .or_else(|_| env::var("APPLICATION_FRONTEND_URL"))
+        .unwrap_or_else(|_| "http://localhost:5173".to_owned());
+
+    configured
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/'))
+        .filter(|origin| !origin.is_empty() && *origin != "*")
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Helper function to read the trusted reverse proxies from `TRUSTED_PROXIES`
+///
+/// Entries are addresses or CIDR ranges; invalid entries are logged and skipped.
+fn trusted_proxies_from_env() -> Vec<IpNet> {
+    env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .parse::<IpNet>()
+                .or_else(|_| entry.parse::<std::net::IpAddr>().map(IpNet::from));
+            if parsed.is_err() {
+                tracing::warn!("Ignoring invalid TRUSTED_PROXIES entry {entry}");
+            }
+            parsed.ok()
+        })
+        .collect()
+}
+
 impl ConfigService {
     /// Load configuration from environment variables
     ///
@@ -84,6 +402,7 @@ impl ConfigService {
     /// This function will panic if required environment variables for Google `OAuth` (`GOOGLE_CLIENT_ID`, `GOOGLE_CLIENT_SECRET`, `OAUTH_REDIRECT_URL`) are set but their values are invalid or missing.
     /// It may also panic if `SMTP_PORT` is set but cannot be parsed as a `u16`.
     #[must_use]
+    #[allow(clippy::too_many_lines)]
     pub fn from_env() -> Self {
         let google_oauth_config = (env::var("GOOGLE_CLIENT_ID").is_ok()
             && env::var("GOOGLE_CLIENT_SECRET").is_ok()
@@ -151,6 +470,9 @@ impl ConfigService {
                 None
             };
 
+        let rate_limit_config = RateLimitConfig::from_env();
+        let oidc_providers = OidcProviderConfig::from_env(google_oauth_config.as_ref());
+
         Self {
             database_url: env::var("DATABASE_URL").ok(),
             s3_bucket: env::var("AWS_S3_BUCKET")
@@ -159,12 +481,26 @@ impl ConfigService {
                 .or_else(|| Some("latentsync-video-clips".to_owned())),
             aws_config,
             google_oauth_config,
+            oidc_providers,
             smtp_config,
             redis_config,
             cookie_secure: env::var("COOKIE_SECURE")
                 .unwrap_or_else(|_| "false".to_owned())
                 .parse()
                 .unwrap_or(false),
+            rate_limit_config,
+            min_password_score: env::var("MIN_PASSWORD_SCORE")
+                .ok()
+                .and_then(|score| score.parse::<u8>().ok())
+                .unwrap_or(3)
+                .min(4),
+            token_config: TokenConfig::from_env(),
+            cors_allowed_origins: cors_allowed_origins_from_env(),
+            trusted_proxies: trusted_proxies_from_env(),
+            appeals_email: env::var("APPEALS_EMAIL")
+                .or_else(|_| env::var("SMTP_FROM_ADDRESS"))
+                .ok()
+                .filter(|address| !address.trim().is_empty()),
         }
     }
 
@@ -233,6 +569,20 @@ impl ConfigService {
         Ok(config)
     }
 
+    /// Get the access token configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ServiceError::Config`] if `JWT_SECRET` is not set, too short or reuses `AUTH_SECRET_KEY`.
+    pub fn token_config(&self) -> Result<TokenConfig, ServiceError> {
+        self.token_config.clone().ok_or_else(|| {
+            ServiceError::Config(format!(
+                "JWT_SECRET environment variable not set; it must be a random secret of at least {} bytes, different from AUTH_SECRET_KEY",
+                TokenConfig::MIN_SECRET_BYTES
+            ))
+        })
+    }
+
     /// Get the AWS configuration
     ///
     /// # Errors
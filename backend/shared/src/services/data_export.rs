@@ -0,0 +1,344 @@
+use crate::errors::ServiceError;
+use crate::models::api_keys::{ApiKey, ApiKeyResponse};
+use crate::models::auth::{User, UserInfo};
+use crate::models::jobs::BackgroundJob;
+use crate::models::posts::{Post, PostResponse};
+use crate::models::series::{Series, SeriesResponse};
+use crate::models::user_files::{UserFile, UserFileInfo};
+use crate::services::email::HtmlEmailContent;
+use crate::services::front_matter::PostFrontMatter;
+use crate::services::jobs::{JobContext, JobOutcome};
+use crate::services::post_attachments::load_post_attachments;
+use bytes::Bytes;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use serde_json::json;
+use std::fs::File;
+use std::io::Write;
+use tempfile::NamedTempFile;
+use tokio::sync::mpsc;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// How long the download link of a data export stays valid, and the archive kept
+///
+/// Seven days is the longest a presigned link can be valid for.
+pub const EXPORT_LINK_TTL_SECONDS: u64 = 604_800;
+
+/// Build an archive of everything stored about the job's user and email a download link
+///
+/// The archive holds the profile, series, posts as Markdown with front matter,
+/// API key metadata, a file manifest and the original files. It is stored in
+/// the bucket until the link expires.
+///
+/// # Errors
+/// Returns an error if loading the data, fetching files, storing the archive
+/// or sending the email fails.
+#[allow(clippy::too_many_lines)]
+pub async fn run_data_export(
+    ctx: &JobContext,
+    job: &BackgroundJob,
+) -> Result<JobOutcome, ServiceError> {
+    use crate::schema::{
+        api_keys::dsl as keys_dsl, posts::dsl as posts_dsl, series::dsl as series_dsl,
+        user_files::dsl as files_dsl, users::dsl as users_dsl,
+    };
+
+    let (user, series, posts, mut attachments, api_keys, files) = {
+        let pool = ctx.db_service.pool();
+        let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+        let user: User = users_dsl::users
+            .find(job.user_id)
+            .first(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| ServiceError::NotFound("User not found".to_owned()))?;
+        let series: Vec<Series> = series_dsl::series
+            .filter(series_dsl::user_id.eq(user.id))
+            .filter(series_dsl::deleted_at.is_null())
+            .order(series_dsl::created_at.asc())
+            .select(Series::as_select())
+            .load(&mut conn)
+            .await?;
+        let series_ids: Vec<_> = series.iter().map(|s| s.id).collect();
+        let posts: Vec<Post> = posts_dsl::posts
+            .filter(posts_dsl::series_id.eq_any(&series_ids))
+            .filter(posts_dsl::deleted_at.is_null())
+            .order((posts_dsl::series_id, posts_dsl::number.asc()))
+            .select(Post::as_select())
+            .load(&mut conn)
+            .await?;
+        let post_ids: Vec<_> = posts.iter().map(|p| p.id).collect();
+        let attachments = load_post_attachments(&mut conn, &post_ids).await?;
+        let api_keys: Vec<ApiKey> = keys_dsl::api_keys
+            .filter(keys_dsl::user_id.eq(user.id))
+            .order(keys_dsl::created_at.asc())
+            .load(&mut conn)
+            .await?;
+        let files: Vec<UserFile> = files_dsl::user_files
+            .filter(files_dsl::user_id.eq(user.id))
+            .filter(files_dsl::deleted_at.is_null())
+            .order(files_dsl::created_at.asc())
+            .load(&mut conn)
+            .await?;
+        (user, series, posts, attachments, api_keys, files)
+    };
+
+    let mut archive = ExportArchive::new()?;
+    archive
+        .add_json("profile.json", &UserInfo::from(user.clone()))
+        .await?;
+    let key_responses: Vec<ApiKeyResponse> = api_keys.into_iter().map(Into::into).collect();
+    archive.add_json("api_keys.json", &key_responses).await?;
+
+    let post_count = posts.len();
+    for series_item in &series {
+        let series_posts: Vec<&Post> = posts
+            .iter()
+            .filter(|p| p.series_id == series_item.id)
+            .collect();
+        let length = i32::try_from(series_posts.len()).ok();
+        let directory = format!("series/{}", series_item.slug);
+        let response = SeriesResponse::from(series_item.clone()).with_length(length);
+        archive
+            .add_json(&format!("{directory}/series.json"), &response)
+            .await?;
+
+        for post in series_posts {
+            let front_matter = PostFrontMatter {
+                title: Some(post.title.clone()),
+                slug: Some(post.slug.clone()),
+                number: Some(post.number),
+                published: post.is_published,
+                date: post.created_at,
+            };
+            archive
+                .add_bytes(
+                    &format!("{directory}/posts/{:04}-{}.md", post.number, post.slug),
+                    front_matter.render(&post.content).into_bytes(),
+                )
+                .await?;
+            // Fields that have no place in front matter, such as media links
+            archive
+                .add_json(
+                    &format!("{directory}/posts/{:04}-{}.json", post.number, post.slug),
+                    &PostResponse::from(post.clone())
+                        .with_attachments(attachments.remove(&post.id).unwrap_or_default()),
+                )
+                .await?;
+        }
+    }
+
+    let manifest: Vec<UserFileInfo> = files.iter().cloned().map(Into::into).collect();
+    archive.add_json("files/manifest.json", &manifest).await?;
+    for file in &files {
+        let stream = ctx.s3_service.get_object_stream(&file.file_path).await?;
+        archive
+            .add_stream(
+                &format!(
+                    "files/{}/{}",
+                    file.id,
+                    archive_file_name(&file.original_filename)
+                ),
+                &file.mime_type,
+                stream,
+            )
+            .await?;
+    }
+
+    let archive_file = archive.finish().await?;
+    let size = archive_file.as_file().metadata()?.len();
+    let output_key = format!("exports/{}/{}.zip", user.id, job.id);
+    ctx.s3_service
+        .put_file(&output_key, archive_file.path())
+        .await?;
+
+    let download_url = ctx
+        .s3_service
+        .get_presigned_url(&output_key, EXPORT_LINK_TTL_SECONDS)
+        .await?;
+    let expires_at = Utc::now().naive_utc().checked_add_signed(Duration::seconds(
+        i64::try_from(EXPORT_LINK_TTL_SECONDS).unwrap_or(i64::MAX),
+    ));
+    send_export_ready_email(ctx, &user, &download_url).await?;
+
+    Ok(JobOutcome {
+        result: json!({
+            "series": series.len(),
+            "posts": post_count,
+            "files": files.len(),
+            "size": size,
+        }),
+        output_key: Some(output_key),
+        output_expires_at: expires_at,
+    })
+}
+
+/// ZIP archive written to a temporary file, so an export never sits in memory
+///
+/// The `zip` crate writes synchronously, so every entry is written on the
+/// blocking thread pool, with the writer moved there and back.
+struct ExportArchive {
+    file: NamedTempFile,
+    writer: Option<ZipWriter<File>>,
+}
+
+impl ExportArchive {
+    /// Start an empty archive in a new temporary file
+    fn new() -> Result<Self, ServiceError> {
+        let file = NamedTempFile::new()?;
+        let writer = ZipWriter::new(file.reopen()?);
+        Ok(Self {
+            file,
+            writer: Some(writer),
+        })
+    }
+
+    /// Add a value as pretty-printed JSON
+    async fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<(), ServiceError> {
+        self.add_bytes(name, serde_json::to_vec_pretty(value)?)
+            .await
+    }
+
+    /// Add a file held in memory, deflated
+    async fn add_bytes(&mut self, name: &str, contents: Vec<u8>) -> Result<(), ServiceError> {
+        let entry_name = name.to_owned();
+        self.with_writer(move |writer| {
+            writer.start_file(
+                entry_name,
+                SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+            )?;
+            writer.write_all(&contents)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Add a file as it streams in, one chunk at a time
+    async fn add_stream(
+        &mut self,
+        name: &str,
+        mime_type: &str,
+        mut stream: impl Stream<Item = Result<Bytes, ServiceError>> + Unpin,
+    ) -> Result<(), ServiceError> {
+        let (sender, mut receiver) = mpsc::channel::<Bytes>(4);
+        let entry_name = name.to_owned();
+        let options = entry_options(mime_type);
+        let write = self.with_writer(move |writer| {
+            writer.start_file(entry_name, options)?;
+            while let Some(chunk) = receiver.blocking_recv() {
+                writer.write_all(&chunk)?;
+            }
+            Ok(())
+        });
+        let download = async move {
+            while let Some(chunk) = stream.next().await {
+                // A closed channel means the write failed, which `write` reports
+                if sender.send(chunk?).await.is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        };
+        let ((), ()) = futures_util::try_join!(write, download)?;
+        Ok(())
+    }
+
+    /// Write the central directory and return the finished temporary file
+    async fn finish(mut self) -> Result<NamedTempFile, ServiceError> {
+        let writer = self.take_writer()?;
+        let _ = tokio::task::spawn_blocking(move || writer.finish())
+            .await
+            .map_err(|e| ServiceError::Unknown(format!("Export archive write failed: {e}")))??;
+        Ok(self.file)
+    }
+
+    /// Helper function to run a write on the blocking thread pool
+    async fn with_writer<T, F>(&mut self, write: F) -> Result<T, ServiceError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut ZipWriter<File>) -> Result<T, ServiceError> + Send + 'static,
+    {
+        let mut writer = self.take_writer()?;
+        let (returned, result) = tokio::task::spawn_blocking(move || {
+            let result = write(&mut writer);
+            (writer, result)
+        })
+        .await
+        .map_err(|e| ServiceError::Unknown(format!("Export archive write failed: {e}")))?;
+        self.writer = Some(returned);
+        result
+    }
+
+    /// Helper function to take the writer, which is gone if a write failed midway
+    fn take_writer(&mut self) -> Result<ZipWriter<File>, ServiceError> {
+        self.writer.take().ok_or_else(|| {
+            ServiceError::Unknown("Export archive is unusable after a failed write".to_owned())
+        })
+    }
+}
+
+/// Helper function to pick how an entry is compressed
+///
+/// Images, audio and video are already compressed, so they are stored as they are.
+fn entry_options(mime_type: &str) -> SimpleFileOptions {
+    let method = if ["image/", "audio/", "video/"]
+        .iter()
+        .any(|prefix| mime_type.starts_with(prefix))
+    {
+        CompressionMethod::Stored
+    } else {
+        CompressionMethod::Deflated
+    };
+    SimpleFileOptions::default().compression_method(method)
+}
+
+/// Helper function to turn a user-supplied file name into a safe archive entry name
+fn archive_file_name(original: &str) -> String {
+    let replaced: String = original
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | ':') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let name = replaced.trim().trim_start_matches('.');
+    if name.is_empty() {
+        "file".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Helper function to email the download link of a finished export
+async fn send_export_ready_email(
+    ctx: &JobContext,
+    user: &User,
+    download_url: &str,
+) -> Result<(), ServiceError> {
+    let email_body = format!(
+        "<h1>Your data export is ready</h1>
+        <p>The archive of your Patron account, with your profile, series, posts, API keys and uploaded files, is ready to download.</p>
+        <p><a href=\"{download_url}\">Download your data</a></p>
+        <p>This link expires in 7 days, after which the archive is deleted. You can request a new export from your <a href=\"{}/settings\">account settings</a> at any time.</p>
+        <p>If you did not request this export, please change your password.</p>",
+        ctx.frontend_url
+    );
+
+    ctx.email_service
+        .send_html_email(HtmlEmailContent {
+            to: &user.email,
+            subject: "Your Patron data export is ready",
+            html_body: &email_body,
+            text_body: None,
+            from: None,
+        })
+        .await
+}
@@ -0,0 +1,147 @@
+use crate::errors::ServiceError;
+use crate::models::devices::KnownDevice;
+use crate::services::db::DbService;
+use crate::services::sessions::{client_ip, user_agent};
+use crate::services::tokens::hash_token;
+use actix_web::HttpRequest;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// How long the "this wasn't me" link in a new-device alert stays valid
+pub const DENY_LOGIN_TTL_DAYS: i64 = 7;
+
+/// Network a client address belongs to: the /24 for `IPv4`, the /64 for `IPv6`
+///
+/// Addresses that do not parse are used as they are.
+#[must_use]
+pub fn ip_network(ip_address: &str) -> String {
+    match ip_address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{a}.{b}.{c}.0/24")
+        }
+        Ok(IpAddr::V6(ip)) => {
+            let [a, b, c, d, ..] = ip.segments();
+            format!("{a:x}:{b:x}:{c:x}:{d:x}::/64")
+        }
+        Err(_) => ip_address.to_owned(),
+    }
+}
+
+/// Fingerprint of the device a request comes from
+///
+/// Hashes the user agent with the client network so a device keeps its
+/// fingerprint while its address changes within the same network.
+#[must_use]
+pub fn device_fingerprint(user_agent: Option<&str>, ip_address: Option<&str>) -> String {
+    let network = ip_address.map(ip_network).unwrap_or_default();
+    let digest = Sha256::digest(format!("{}\n{network}", user_agent.unwrap_or_default()));
+    format!("{digest:x}")
+}
+
+/// Remember the device a successful sign-in came from
+///
+/// Returns a "this wasn't me" token when the device was not seen before and
+/// the user has signed in from another device already; the first device of
+/// an account is trusted without an alert.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+pub async fn register_login_device(
+    db_service: &DbService,
+    req: &HttpRequest,
+    user_id: Uuid,
+) -> Result<Option<String>, ServiceError> {
+    use crate::schema::known_devices::dsl as devices_dsl;
+
+    let agent = user_agent(req);
+    let ip_address = client_ip(req);
+    let fingerprint = device_fingerprint(agent.as_deref(), ip_address.as_deref());
+    let now = Utc::now().naive_utc();
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let seen = diesel::update(
+        devices_dsl::known_devices
+            .filter(devices_dsl::user_id.eq(user_id))
+            .filter(devices_dsl::fingerprint.eq(&fingerprint)),
+    )
+    .set(devices_dsl::last_seen_at.eq(now))
+    .execute(&mut conn)
+    .await?;
+    if seen > 0 {
+        return Ok(None);
+    }
+
+    let has_devices = diesel::select(diesel::dsl::exists(
+        devices_dsl::known_devices.filter(devices_dsl::user_id.eq(user_id)),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await?;
+
+    let deny_token = has_devices.then(|| Uuid::new_v4().to_string());
+    let deny_token_expires_at = if deny_token.is_some() {
+        Some(
+            now.checked_add_signed(Duration::days(DENY_LOGIN_TTL_DAYS))
+                .ok_or_else(|| {
+                    ServiceError::Unknown("Failed to compute expiration time".to_owned())
+                })?,
+        )
+    } else {
+        None
+    };
+
+    // A concurrent sign-in from the same device may have inserted it first
+    let inserted = diesel::insert_into(devices_dsl::known_devices)
+        .values(&KnownDevice {
+            id: Uuid::new_v4(),
+            user_id,
+            fingerprint,
+            user_agent: agent,
+            ip_address,
+            deny_token_hash: deny_token.as_deref().map(hash_token),
+            deny_token_expires_at,
+            first_seen_at: now,
+            last_seen_at: now,
+        })
+        .on_conflict((devices_dsl::user_id, devices_dsl::fingerprint))
+        .do_nothing()
+        .execute(&mut conn)
+        .await?;
+
+    Ok(deny_token.filter(|_| inserted > 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ip_network_groups_addresses() {
+        assert_eq!(ip_network("203.0.113.42"), "203.0.113.0/24");
+        assert_eq!(ip_network("2001:db8:1:2:3:4:5:6"), "2001:db8:1:2::/64");
+        assert_eq!(ip_network("not-an-ip"), "not-an-ip");
+    }
+
+    #[test]
+    fn fingerprint_ignores_host_part_of_address() {
+        let agent = Some("Mozilla/5.0");
+        assert_eq!(
+            device_fingerprint(agent, Some("203.0.113.42")),
+            device_fingerprint(agent, Some("203.0.113.7"))
+        );
+        assert_ne!(
+            device_fingerprint(agent, Some("203.0.113.42")),
+            device_fingerprint(agent, Some("203.0.114.42"))
+        );
+        assert_ne!(
+            device_fingerprint(agent, Some("203.0.113.42")),
+            device_fingerprint(Some("curl/8.0"), Some("203.0.113.42"))
+        );
+    }
+}
@@ -8,14 +8,13 @@ use std::env;
 ///
 /// # Usage
 ///
-/// ```rust
-/// use shared::services::email::EmailService;
+/// ```rust,no_run
+/// use shared::services::email::{EmailService, HtmlEmailContent};
 ///
-/// #[tokio::main]
-/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// async fn send() -> Result<(), Box<dyn std::error::Error>> {
 ///     // Create email service from environment variables
 ///     let email_service = EmailService::from_env();
-///     
+///
 ///     // Send a plain text email
 ///     email_service.send_text_email(
 ///         "recipient@example.com",
@@ -23,16 +22,16 @@ use std::env;
 ///         "Hello, this is a test email!",
 ///         None, // Use default from address
 ///     ).await?;
-///     
+///
 ///     // Send an HTML email with text fallback
-///     email_service.send_html_email(
-///         "recipient@example.com",
-///         "HTML Email",
-///         "<h1>Hello</h1><p>This is an HTML email!</p>",
-///         Some("Hello\n\nThis is an HTML email!"), // Text fallback
-///         None, // Use default from address
-///     ).await?;
-///     
+///     email_service.send_html_email(HtmlEmailContent {
+///         to: "recipient@example.com",
+///         subject: "HTML Email",
+///         html_body: "<h1>Hello</h1><p>This is an HTML email!</p>",
+///         text_body: Some("Hello\n\nThis is an HTML email!"), // Text fallback
+///         from: None, // Use default from address
+///     }).await?;
+///
 ///     Ok(())
 /// }
 /// ```
@@ -80,6 +79,19 @@ pub struct HtmlEmailContent<'email_content> {
     pub from: Option<&'email_content str>,
 }
 
+/// Escape text interpolated into an HTML email body
+///
+/// Anything that came from a request, such as an email address or a user
+/// agent, must go through this before it is put in a message.
+#[must_use]
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
 impl EmailService {
     /// Create a new `EmailService` from environment variables
     #[must_use]
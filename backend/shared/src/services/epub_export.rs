@@ -0,0 +1,776 @@
+use crate::errors::ServiceError;
+use crate::models::jobs::BackgroundJob;
+use crate::models::posts::Post;
+use crate::models::series::{EpubExportResult, Series};
+use crate::services::feed_import::{download_image, sniff_image, MAX_IMAGE_SIZE};
+use crate::services::jobs::{JobContext, JobOutcome};
+use crate::services::user_files::store_job_file;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use futures_util::StreamExt;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Most images a single EPUB embeds besides its cover
+pub const MAX_EPUB_IMAGES: usize = 500;
+
+/// Largest total size of the images a single EPUB embeds
+pub const MAX_EPUB_IMAGES_SIZE: usize = 0xC80_0000;
+
+/// Path the CDN serves user files from
+const CDN_FILES_PATH: &str = "/api/cdn/files/";
+
+/// Author shown when the creator has no display name
+const UNKNOWN_AUTHOR: &str = "Anonymous";
+
+/// Container document pointing readers to the package document
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// Stylesheet shared by every page of the book
+const STYLE_CSS: &str = "body { margin: 0 5%; line-height: 1.5; }
+h1 { margin: 1.5em 0 1em; text-align: center; }
+img { max-width: 100%; height: auto; }
+pre { white-space: pre-wrap; }
+blockquote { margin: 1em 1.5em; font-style: italic; }
+.cover { margin: 0; padding: 0; text-align: center; }
+.cover img { max-height: 100%; }
+.title-page { margin-top: 30%; text-align: center; }
+";
+
+/// Input of an EPUB export job
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EpubExportPayload {
+    /// Series to export
+    #[serde(rename = "seriesId")]
+    pub series_id: Uuid,
+    /// Number of the first post to include
+    #[serde(rename = "fromNumber")]
+    pub from_number: Option<i32>,
+    /// Number of the last post to include
+    #[serde(rename = "toNumber")]
+    pub to_number: Option<i32>,
+}
+
+/// Image embedded in an EPUB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpubImage {
+    /// Path inside the book, relative to the package document
+    pub path: String,
+    /// MIME type of the image
+    pub mime_type: &'static str,
+    /// Image contents
+    pub bytes: Vec<u8>,
+}
+
+/// Chapter of an EPUB, made from a post
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpubChapter {
+    /// Post number
+    pub number: i32,
+    /// Post title
+    pub title: String,
+    /// Post content as XHTML, see [`render_chapter`]
+    pub body: String,
+}
+
+/// Everything an EPUB is built from
+#[derive(Debug, Clone)]
+pub struct EpubBook {
+    /// Unique identifier of this edition
+    pub identifier: Uuid,
+    /// Book title
+    pub title: String,
+    /// Author name
+    pub creator: String,
+    /// Blurb shown on the title page
+    pub description: Option<String>,
+    /// When the book was built
+    pub modified: NaiveDateTime,
+    /// Cover image
+    pub cover: Option<EpubImage>,
+    /// Chapters in reading order
+    pub chapters: Vec<EpubChapter>,
+    /// Images the chapters embed
+    pub images: Vec<EpubImage>,
+}
+
+/// Compile a series, or a range of its posts, into an EPUB stored as a file of the creator
+///
+/// Published posts become chapters in number order. The series cover and the
+/// images of the posts are embedded; images that cannot be fetched are
+/// replaced by their alt text and reported as warnings.
+///
+/// # Errors
+/// Returns an error if the series is gone or has no published posts in the
+/// range, or storage or database operations fail.
+#[allow(clippy::too_many_lines)]
+pub async fn run_epub_export(
+    ctx: &JobContext,
+    job: &BackgroundJob,
+) -> Result<JobOutcome, ServiceError> {
+    use crate::schema::{
+        posts::dsl as posts_dsl, series::dsl as series_dsl, users::dsl as users_dsl,
+    };
+
+    let payload: EpubExportPayload = serde_json::from_value(job.payload.clone())?;
+
+    let pool = ctx.db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let series: Series = series_dsl::series
+        .filter(series_dsl::id.eq(payload.series_id))
+        .filter(series_dsl::user_id.eq(job.user_id))
+        .filter(series_dsl::deleted_at.is_null())
+        .select(Series::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| ServiceError::NotFound("Series not found".to_owned()))?;
+    let display_name: Option<String> = users_dsl::users
+        .find(job.user_id)
+        .select(users_dsl::display_name)
+        .first(&mut conn)
+        .await?;
+
+    let mut query = posts_dsl::posts
+        .filter(posts_dsl::series_id.eq(series.id))
+        .filter(posts_dsl::is_published.eq(true))
+        .filter(posts_dsl::deleted_at.is_null())
+        .into_boxed();
+    if let Some(from_number) = payload.from_number {
+        query = query.filter(posts_dsl::number.ge(from_number));
+    }
+    if let Some(to_number) = payload.to_number {
+        query = query.filter(posts_dsl::number.le(to_number));
+    }
+    let posts: Vec<Post> = query
+        .order(posts_dsl::number.asc())
+        .select(Post::as_select())
+        .load(&mut conn)
+        .await?;
+    let (Some(first), Some(last)) = (<[Post]>::first(&posts), <[Post]>::last(&posts)) else {
+        return Err(ServiceError::Config(
+            "The series has no published posts in this range".to_owned(),
+        ));
+    };
+    let is_range = payload.from_number.is_some() || payload.to_number.is_some();
+    let (title, filename) = if is_range {
+        (
+            format!("{} ({}\u{2013}{})", series.title, first.number, last.number),
+            format!("{}-{}-{}.epub", series.slug, first.number, last.number),
+        )
+    } else {
+        (series.title.clone(), format!("{}.epub", series.slug))
+    };
+
+    let mut images = ImageCollector {
+        by_source: HashMap::new(),
+        images: Vec::new(),
+        total_size: 0,
+    };
+    let mut warnings = Vec::new();
+
+    let mut cover = None;
+    if let Some(ref cover_url) = series.cover_image_url {
+        match fetch_image(ctx, &mut conn, cover_url).await {
+            Ok((mime_type, extension, bytes)) => {
+                images.total_size = bytes.len();
+                cover = Some(EpubImage {
+                    path: format!("images/cover.{extension}"),
+                    mime_type,
+                    bytes,
+                });
+            }
+            Err(reason) => warnings.push(format!("Cover was left out: {reason}")),
+        }
+    }
+
+    let mut chapters = Vec::with_capacity(posts.len());
+    for post in &posts {
+        let mut paths: HashMap<String, String> = HashMap::new();
+        for src in image_sources(&post.content) {
+            if paths.contains_key(&src) {
+                continue;
+            }
+            match images.collect(ctx, &mut conn, &src).await {
+                Ok(path) => {
+                    let _ = paths.insert(src, path);
+                }
+                Err(reason) => warnings.push(format!(
+                    "Image {src} in post {} was left out: {reason}",
+                    post.number
+                )),
+            }
+        }
+        chapters.push(EpubChapter {
+            number: post.number,
+            title: post.title.clone(),
+            body: render_chapter(&post.content, |src| paths.get(src).cloned()),
+        });
+    }
+
+    let chapter_count = chapters.len();
+    let image_count = images
+        .images
+        .len()
+        .saturating_add(usize::from(cover.is_some()));
+    let book = EpubBook {
+        identifier: Uuid::new_v4(),
+        title,
+        creator: display_name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| UNKNOWN_AUTHOR.to_owned()),
+        description: series.description.filter(|text| !text.trim().is_empty()),
+        modified: Utc::now().naive_utc(),
+        cover,
+        chapters,
+        images: images.images,
+    };
+    let epub = build_epub(&book)?;
+
+    let file = store_job_file(
+        ctx,
+        &mut conn,
+        job.user_id,
+        &filename,
+        "application/epub+zip",
+        epub,
+    )
+    .await?;
+
+    let result = EpubExportResult {
+        file_id: file.id,
+        filename: file.original_filename,
+        file_size: file.file_size,
+        chapters: chapter_count,
+        images: image_count,
+        warnings,
+    };
+    Ok(JobOutcome {
+        result: serde_json::to_value(&result)?,
+        output_key: None,
+        output_expires_at: None,
+    })
+}
+
+/// Sources of the images a post's Markdown embeds, in order
+#[must_use]
+pub fn image_sources(markdown: &str) -> Vec<String> {
+    Parser::new_ext(markdown, markdown_options())
+        .filter_map(|event| match event {
+            Event::Start(Tag::Image { dest_url, .. }) => Some(dest_url.into_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Render a post's Markdown as the XHTML body of a chapter
+///
+/// `image_path` gets each image source and returns its path inside the book;
+/// images it returns `None` for are replaced by their alt text. Raw HTML is
+/// shown as text, since it is not guaranteed to be well-formed XML.
+pub fn render_chapter<F>(markdown: &str, mut image_path: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    // Whether each open image is kept, so its end tag is dropped with it
+    let mut open_images: Vec<bool> = Vec::new();
+    let events = Parser::new_ext(markdown, markdown_options()).filter_map(|event| match event {
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => {
+            let path = image_path(&dest_url);
+            open_images.push(path.is_some());
+            path.map(|local| {
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url: local.into(),
+                    title,
+                    id,
+                })
+            })
+        }
+        Event::End(TagEnd::Image) => open_images
+            .pop()
+            .unwrap_or_default()
+            .then_some(Event::End(TagEnd::Image)),
+        Event::Html(html) | Event::InlineHtml(html) => Some(Event::Text(html)),
+        other => Some(other),
+    });
+
+    let mut body = String::with_capacity(markdown.len().saturating_mul(2));
+    pulldown_cmark::html::push_html(&mut body, events);
+    body
+}
+
+/// Build an EPUB 3 package
+///
+/// The book opens with the cover, when there is one, and a title page,
+/// followed by the table of contents and one page per chapter.
+///
+/// # Errors
+/// Returns an error if the archive cannot be written.
+pub fn build_epub(book: &EpubBook) -> Result<Vec<u8>, ServiceError> {
+    let mut writer = InMemoryZip::default();
+    // Readers identify the format by this first, uncompressed entry
+    writer.add_stored("mimetype", b"application/epub+zip")?;
+    writer.add_file("META-INF/container.xml", CONTAINER_XML.as_bytes())?;
+    writer.add_file("OEBPS/content.opf", package_document(book).as_bytes())?;
+    writer.add_file("OEBPS/style.css", STYLE_CSS.as_bytes())?;
+    writer.add_file("OEBPS/nav.xhtml", navigation_document(book).as_bytes())?;
+
+    if let Some(ref cover) = book.cover {
+        let page = xhtml_document(
+            "Cover",
+            &format!(
+                "<section class=\"cover\" epub:type=\"cover\"><img src=\"{}\" alt=\"{}\"/></section>",
+                escape_xml(&cover.path),
+                escape_xml(&book.title)
+            ),
+        );
+        writer.add_file("OEBPS/cover.xhtml", page.as_bytes())?;
+        writer.add_stored(&format!("OEBPS/{}", cover.path), &cover.bytes)?;
+    }
+
+    let description = book
+        .description
+        .as_deref()
+        .map(|text| format!("<p>{}</p>", escape_xml(text)))
+        .unwrap_or_default();
+    let title_page = xhtml_document(
+        &book.title,
+        &format!(
+            "<section class=\"title-page\" epub:type=\"titlepage\"><h1>{}</h1><p>{}</p>{description}</section>",
+            escape_xml(&book.title),
+            escape_xml(&book.creator)
+        ),
+    );
+    writer.add_file("OEBPS/title.xhtml", title_page.as_bytes())?;
+
+    for (index, chapter) in book.chapters.iter().enumerate() {
+        let page = xhtml_document(
+            &chapter.title,
+            &format!(
+                "<section epub:type=\"chapter\"><h1>{}</h1>\n{}</section>",
+                escape_xml(&chapter.title),
+                chapter.body
+            ),
+        );
+        writer.add_file(&format!("OEBPS/{}", chapter_path(index)), page.as_bytes())?;
+    }
+    for image in &book.images {
+        writer.add_stored(&format!("OEBPS/{}", image.path), &image.bytes)?;
+    }
+
+    writer.finish()
+}
+
+/// ZIP archive built in memory, for packages small enough to hold there
+#[derive(Debug)]
+pub struct InMemoryZip {
+    writer: ZipWriter<Cursor<Vec<u8>>>,
+}
+
+impl Default for InMemoryZip {
+    fn default() -> Self {
+        Self {
+            writer: ZipWriter::new(Cursor::new(Vec::new())),
+        }
+    }
+}
+
+impl InMemoryZip {
+    /// Add a deflated file
+    ///
+    /// # Errors
+    /// Returns an error if the entry cannot be written.
+    pub fn add_file(&mut self, name: &str, contents: &[u8]) -> Result<(), ServiceError> {
+        self.add(name, contents, CompressionMethod::Deflated)
+    }
+
+    /// Add a file without compressing it
+    ///
+    /// Used for images, which compression does not shrink, and for entries
+    /// readers expect uncompressed, such as the `mimetype` that opens an EPUB.
+    ///
+    /// # Errors
+    /// Returns an error if the entry cannot be written.
+    pub fn add_stored(&mut self, name: &str, contents: &[u8]) -> Result<(), ServiceError> {
+        self.add(name, contents, CompressionMethod::Stored)
+    }
+
+    /// Write the central directory and return the finished archive
+    ///
+    /// # Errors
+    /// Returns an error if the archive cannot be finished.
+    pub fn finish(self) -> Result<Vec<u8>, ServiceError> {
+        Ok(self.writer.finish()?.into_inner())
+    }
+
+    /// Helper function to write one entry
+    fn add(
+        &mut self,
+        name: &str,
+        contents: &[u8],
+        method: CompressionMethod,
+    ) -> Result<(), ServiceError> {
+        self.writer.start_file(
+            name,
+            SimpleFileOptions::default().compression_method(method),
+        )?;
+        self.writer.write_all(contents)?;
+        Ok(())
+    }
+}
+
+/// Helper function to choose the Markdown extensions posts are rendered with
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_FOOTNOTES
+}
+
+/// Helper function to name the page of the chapter at `index`
+fn chapter_path(index: usize) -> String {
+    format!("chapter-{}.xhtml", index.saturating_add(1))
+}
+
+/// Helper function to build the package document listing the book's metadata and files
+fn package_document(book: &EpubBook) -> String {
+    let mut manifest = vec![
+        "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>".to_owned(),
+        "<item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>".to_owned(),
+        "<item id=\"title\" href=\"title.xhtml\" media-type=\"application/xhtml+xml\"/>".to_owned(),
+    ];
+    let mut spine = Vec::new();
+    if let Some(ref cover) = book.cover {
+        manifest.push(format!(
+            "<item id=\"cover-image\" href=\"{}\" media-type=\"{}\" properties=\"cover-image\"/>",
+            escape_xml(&cover.path),
+            cover.mime_type
+        ));
+        manifest.push(
+            "<item id=\"cover\" href=\"cover.xhtml\" media-type=\"application/xhtml+xml\"/>"
+                .to_owned(),
+        );
+        spine.push("<itemref idref=\"cover\"/>".to_owned());
+    }
+    spine.push("<itemref idref=\"title\"/>".to_owned());
+    spine.push("<itemref idref=\"nav\"/>".to_owned());
+    for index in 0..book.chapters.len() {
+        let number = index.saturating_add(1);
+        manifest.push(format!(
+            "<item id=\"chapter-{number}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>",
+            chapter_path(index)
+        ));
+        spine.push(format!("<itemref idref=\"chapter-{number}\"/>"));
+    }
+    for (index, image) in book.images.iter().enumerate() {
+        manifest.push(format!(
+            "<item id=\"image-{}\" href=\"{}\" media-type=\"{}\"/>",
+            index.saturating_add(1),
+            escape_xml(&image.path),
+            image.mime_type
+        ));
+    }
+
+    let description = book
+        .description
+        .as_deref()
+        .map(|text| {
+            format!(
+                "\n    <dc:description>{}</dc:description>",
+                escape_xml(text)
+            )
+        })
+        .unwrap_or_default();
+    let cover_meta = if book.cover.is_some() {
+        "\n    <meta name=\"cover\" content=\"cover-image\"/>"
+    } else {
+        ""
+    };
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="en">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">urn:uuid:{identifier}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:creator>{creator}</dc:creator>
+    <dc:language>en</dc:language>{description}
+    <meta property="dcterms:modified">{modified}</meta>{cover_meta}
+  </metadata>
+  <manifest>
+    {manifest}
+  </manifest>
+  <spine>
+    {spine}
+  </spine>
+</package>
+"#,
+        identifier = book.identifier,
+        title = escape_xml(&book.title),
+        creator = escape_xml(&book.creator),
+        modified = book.modified.format("%Y-%m-%dT%H:%M:%SZ"),
+        manifest = manifest.join("\n    "),
+        spine = spine.join("\n    "),
+    )
+}
+
+/// Helper function to build the table of contents from post numbers and titles
+fn navigation_document(book: &EpubBook) -> String {
+    let entries: Vec<String> = book
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| {
+            format!(
+                "<li><a href=\"{}\">{}. {}</a></li>",
+                chapter_path(index),
+                chapter.number,
+                escape_xml(&chapter.title)
+            )
+        })
+        .collect();
+    xhtml_document(
+        "Contents",
+        &format!(
+            "<nav epub:type=\"toc\" id=\"toc\"><h1>Contents</h1><ol>\n{}\n</ol></nav>",
+            entries.join("\n")
+        ),
+    )
+}
+
+/// Helper function to wrap a body in an XHTML page of the book
+fn xhtml_document(title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="en" lang="en">
+<head>
+<meta charset="UTF-8"/>
+<title>{}</title>
+<link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        escape_xml(title)
+    )
+}
+
+/// Escape text for XML content and attributes
+#[must_use]
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Helper function to find the user file a CDN link points to
+fn cdn_file_id(src: &str) -> Option<Uuid> {
+    let path = if src.starts_with(CDN_FILES_PATH) {
+        src.to_owned()
+    } else {
+        Url::parse(src).ok()?.path().to_owned()
+    };
+    let rest = path.strip_prefix(CDN_FILES_PATH)?;
+    let id = rest.split(['?', '#', '/']).next().unwrap_or_default();
+    Uuid::parse_str(id).ok()
+}
+
+/// Helper function to fetch an image for the book, from the bucket or the web
+///
+/// Returns the MIME type, file extension and contents, or why the image
+/// cannot be embedded.
+async fn fetch_image(
+    ctx: &JobContext,
+    conn: &mut AsyncPgConnection,
+    src: &str,
+) -> Result<(&'static str, &'static str, Vec<u8>), String> {
+    use crate::schema::user_files::dsl as files_dsl;
+
+    let bytes = if let Some(file_id) = cdn_file_id(src) {
+        let file_path: String = files_dsl::user_files
+            .filter(files_dsl::id.eq(file_id))
+            .filter(files_dsl::deleted_at.is_null())
+            .select(files_dsl::file_path)
+            .first(conn)
+            .await
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "file not found".to_owned())?;
+        let mut stream = ctx
+            .s3_service
+            .get_object_stream(&file_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut contents = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            contents.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+            if contents.len() > MAX_IMAGE_SIZE {
+                return Err("image is too large".to_owned());
+            }
+        }
+        contents
+    } else {
+        let url = Url::parse(src)
+            .ok()
+            .ok_or_else(|| "not a web address or a file of this site".to_owned())?;
+        download_image(url).await?
+    };
+
+    let (mime_type, extension) =
+        sniff_image(&bytes).ok_or_else(|| "not a PNG, JPEG, GIF or WebP image".to_owned())?;
+    // AVIF is not among the image formats every EPUB reader has to support
+    if extension == "avif" {
+        return Err("AVIF images are not supported in EPUBs".to_owned());
+    }
+    Ok((mime_type, extension, bytes))
+}
+
+/// Helper struct for the images embedded in one book
+struct ImageCollector {
+    /// Path of each source already embedded, or why it was not
+    by_source: HashMap<String, Result<String, String>>,
+    images: Vec<EpubImage>,
+    total_size: usize,
+}
+
+impl ImageCollector {
+    /// Embed the image at `src`, returning its path inside the book
+    async fn collect(
+        &mut self,
+        ctx: &JobContext,
+        conn: &mut AsyncPgConnection,
+        src: &str,
+    ) -> Result<String, String> {
+        if let Some(previous) = self.by_source.get(src) {
+            return previous.clone();
+        }
+        if self.images.len() >= MAX_EPUB_IMAGES {
+            return Err(format!("an EPUB embeds at most {MAX_EPUB_IMAGES} images"));
+        }
+
+        let outcome = match fetch_image(ctx, conn, src).await {
+            Ok((mime_type, extension, bytes)) => {
+                let total_size = self.total_size.saturating_add(bytes.len());
+                if total_size > MAX_EPUB_IMAGES_SIZE {
+                    Err("the book's images are too large in total".to_owned())
+                } else {
+                    self.total_size = total_size;
+                    let path =
+                        format!("images/{}.{extension}", self.images.len().saturating_add(1));
+                    self.images.push(EpubImage {
+                        path: path.clone(),
+                        mime_type,
+                        bytes,
+                    });
+                    Ok(path)
+                }
+            }
+            Err(reason) => Err(reason),
+        };
+        let _ = self.by_source.insert(src.to_owned(), outcome.clone());
+        outcome
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::services::post_import::read_zip;
+
+    #[test]
+    fn renders_chapters_as_xhtml() {
+        let markdown = "Hello <b>there</b>\n\n![A map](/api/cdn/files/1) ![Gone *away*](https://example.com/x.png)\n\n---";
+        let body = render_chapter(markdown, |src| {
+            (src == "/api/cdn/files/1").then(|| "images/1.png".to_owned())
+        });
+        assert_eq!(
+            body,
+            "<p>Hello &lt;b&gt;there&lt;/b&gt;</p>\n<p><img src=\"images/1.png\" alt=\"A map\" /> Gone <em>away</em></p>\n<hr />\n"
+        );
+        assert_eq!(
+            image_sources(markdown),
+            vec![
+                "/api/cdn/files/1".to_owned(),
+                "https://example.com/x.png".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_cdn_files() {
+        let id = Uuid::new_v4();
+        assert_eq!(cdn_file_id(&format!("/api/cdn/files/{id}")), Some(id));
+        assert_eq!(
+            cdn_file_id(&format!("https://api.example.com/api/cdn/files/{id}?v=2")),
+            Some(id)
+        );
+        assert_eq!(cdn_file_id("https://example.com/cover.png"), None);
+    }
+
+    #[test]
+    fn builds_epub_package() {
+        let book = EpubBook {
+            identifier: Uuid::new_v4(),
+            title: "Tales & Legends".to_owned(),
+            creator: "Ada".to_owned(),
+            description: None,
+            modified: Utc::now().naive_utc(),
+            cover: Some(EpubImage {
+                path: "images/cover.png".to_owned(),
+                mime_type: "image/png",
+                bytes: b"\x89PNG\r\n\x1a\n".to_vec(),
+            }),
+            chapters: vec![EpubChapter {
+                number: 3,
+                title: "The <End>".to_owned(),
+                body: "<p>Fin</p>\n".to_owned(),
+            }],
+            images: Vec::new(),
+        };
+        let entries = read_zip(&build_epub(&book).unwrap(), usize::MAX).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "mimetype",
+                "META-INF/container.xml",
+                "OEBPS/content.opf",
+                "OEBPS/style.css",
+                "OEBPS/nav.xhtml",
+                "OEBPS/cover.xhtml",
+                "OEBPS/images/cover.png",
+                "OEBPS/title.xhtml",
+                "OEBPS/chapter-1.xhtml",
+            ]
+        );
+
+        let package = String::from_utf8(entries[2].contents.clone()).unwrap();
+        assert!(package.contains("<dc:title>Tales &amp; Legends</dc:title>"));
+        assert!(package.contains("properties=\"cover-image\""));
+        let nav = String::from_utf8(entries[4].contents.clone()).unwrap();
+        assert!(nav.contains("<a href=\"chapter-1.xhtml\">3. The &lt;End&gt;</a>"));
+    }
+}
@@ -0,0 +1,1270 @@
+use crate::errors::ServiceError;
+use crate::models::imports::{
+    FeedImportReport, FeedImportSummary, FeedPostMapping, FeedSource, MappingAction,
+};
+use crate::models::jobs::BackgroundJob;
+use crate::models::posts::Post;
+use crate::models::user_files::{FileStatus, UserFile};
+use crate::services::jobs::{JobContext, JobOutcome};
+use crate::services::post_import::{
+    is_valid_slug, read_zip, truncate_field, unique_slug, MAX_IMPORT_ARCHIVE_SIZE, MAX_IMPORT_POSTS,
+};
+use crate::services::series_length::refresh_series_length;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use futures_util::StreamExt;
+use reqwest::{header, redirect::Policy, Client, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Largest inline image an import stores
+pub const MAX_IMAGE_SIZE: usize = 0x140_0000;
+
+/// Most inline images a single import stores; later ones keep their source URL
+pub const MAX_IMPORT_IMAGES: usize = 2000;
+
+/// How long an image download may take
+const IMAGE_TIMEOUT_SECONDS: u64 = 30;
+
+/// How many redirects an image download follows
+const MAX_REDIRECTS: usize = 5;
+
+/// Name of the posts list in a Substack export
+const SUBSTACK_POSTS_ENTRY: &str = "posts.csv";
+
+/// Input of a feed import job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedImportPayload {
+    /// Series the posts go into
+    #[serde(rename = "seriesId")]
+    pub series_id: Uuid,
+    /// Bucket key of the uploaded archive or feed
+    #[serde(rename = "inputKey")]
+    pub input_key: String,
+}
+
+/// Post read from a Substack export or a feed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedItem {
+    /// Substack post ID or feed entry ID
+    pub source_id: String,
+    /// Post title
+    pub title: String,
+    /// Post body as HTML
+    pub html: String,
+    /// When the post was published
+    pub published_at: Option<NaiveDateTime>,
+    /// Whether the post was published, rather than a draft
+    pub is_published: bool,
+    /// Slug the post had at its source, if it is a valid one
+    pub slug: Option<String>,
+    /// Whether only paying subscribers could read the post
+    pub paid: bool,
+    /// Archive directory relative image paths are resolved from
+    pub base_dir: String,
+    /// Web address of the post, relative image URLs are resolved from it
+    pub link: Option<String>,
+}
+
+/// Contents of a feed import upload
+#[derive(Debug)]
+pub struct FeedUpload {
+    /// Format the posts were read from
+    pub source: FeedSource,
+    /// Posts, in the order of the upload
+    pub items: Vec<FeedItem>,
+    /// Files of the archive by path, for images referenced by relative paths
+    pub files: HashMap<String, Vec<u8>>,
+}
+
+/// Posts a feed import creates, with the report describing them
+#[derive(Debug)]
+pub struct FeedImportPlan {
+    /// Posts to create, with the HTML their content is converted from
+    pub posts: Vec<PlannedPost>,
+    /// Mapping report
+    pub report: FeedImportReport,
+}
+
+/// Post a feed import creates
+#[derive(Debug)]
+pub struct PlannedPost {
+    /// Post to insert; its content is filled in from `item` once images are stored
+    pub post: Post,
+    /// Source the post is built from
+    pub item: FeedItem,
+    /// Index of the post's mapping in the report
+    pub mapping: usize,
+}
+
+/// Run a Substack or RSS/Atom import job
+///
+/// Reads the upload, plans the posts, stores their inline images as files of
+/// the creator and creates the posts in one transaction. Images that cannot
+/// be stored keep their source URL and are reported as warnings.
+///
+/// # Errors
+/// Returns an error if the upload cannot be read or has no posts, the series
+/// is gone, or storage or database operations fail.
+#[allow(clippy::too_many_lines)]
+pub async fn run_feed_import(
+    ctx: &JobContext,
+    job: &BackgroundJob,
+) -> Result<JobOutcome, ServiceError> {
+    use crate::schema::{
+        posts::dsl as posts_dsl, series::dsl as series_dsl, tiers::dsl as tiers_dsl,
+    };
+
+    let payload: FeedImportPayload = serde_json::from_value(job.payload.clone())?;
+
+    let mut stream = ctx.s3_service.get_object_stream(&payload.input_key).await?;
+    let mut upload = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        upload.extend_from_slice(&chunk?);
+    }
+    let FeedUpload {
+        source,
+        items,
+        files,
+    } = read_feed_upload(&upload)?;
+    if items.is_empty() {
+        return Err(ServiceError::Config("The upload has no posts".to_owned()));
+    }
+    if items.len() > MAX_IMPORT_POSTS {
+        return Err(ServiceError::Config(format!(
+            "An import can create at most {MAX_IMPORT_POSTS} posts"
+        )));
+    }
+
+    let pool = ctx.db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let owns_series = diesel::select(diesel::dsl::exists(
+        series_dsl::series
+            .filter(series_dsl::id.eq(payload.series_id))
+            .filter(series_dsl::user_id.eq(job.user_id))
+            .filter(series_dsl::deleted_at.is_null()),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await?;
+    if !owns_series {
+        return Err(ServiceError::NotFound("Series not found".to_owned()));
+    }
+
+    // Deleted posts keep their slug and number, so they are taken too
+    let existing: Vec<(String, i32, String, Option<NaiveDateTime>)> = posts_dsl::posts
+        .filter(posts_dsl::series_id.eq(payload.series_id))
+        .select((
+            posts_dsl::slug,
+            posts_dsl::number,
+            posts_dsl::title,
+            posts_dsl::created_at,
+        ))
+        .load(&mut conn)
+        .await?;
+    let paid_tier: Option<Uuid> = tiers_dsl::tiers
+        .filter(tiers_dsl::user_id.eq(job.user_id))
+        .filter(tiers_dsl::amount_cents.gt(0_i32))
+        .order(tiers_dsl::amount_cents.asc())
+        .select(tiers_dsl::id)
+        .first(&mut conn)
+        .await
+        .optional()?;
+
+    let mut plan = plan_feed_import(payload.series_id, source, items, &existing, paid_tier);
+
+    let mut images = ImageImporter {
+        files: &files,
+        stored: HashMap::new(),
+    };
+    for planned in &mut plan.posts {
+        let mut sources = Vec::new();
+        let _ = rewrite_images(&planned.item.html, |src| {
+            sources.push(src.to_owned());
+            None
+        });
+
+        let mut replacements: HashMap<String, Uuid> = HashMap::new();
+        let mut warnings = Vec::new();
+        for src in sources {
+            if replacements.contains_key(&src) {
+                continue;
+            }
+            match images
+                .import(ctx, &mut conn, job.user_id, &planned.item, &src)
+                .await
+            {
+                Ok(Some(file_id)) => {
+                    let _ = replacements.insert(src, file_id);
+                }
+                Ok(None) => {}
+                Err(reason) => warnings.push(format!("Image {src} was not imported: {reason}")),
+            }
+        }
+
+        let html = rewrite_images(&planned.item.html, |src| {
+            replacements
+                .get(src)
+                .map(|file_id| format!("/api/cdn/files/{file_id}"))
+        });
+        html2md::parse_html(&html)
+            .trim()
+            .clone_into(&mut planned.post.content);
+
+        if let Some(mapping) = plan.report.posts.get_mut(planned.mapping) {
+            mapping.images = replacements.len();
+            plan.report.summary.images_imported = plan
+                .report
+                .summary
+                .images_imported
+                .saturating_add(replacements.len());
+            plan.report.summary.images_failed = plan
+                .report
+                .summary
+                .images_failed
+                .saturating_add(warnings.len());
+            mapping.warnings.extend(warnings);
+        }
+    }
+
+    let posts: Vec<Post> = plan.posts.into_iter().map(|planned| planned.post).collect();
+    let series_id = payload.series_id;
+    conn.transaction::<_, ServiceError, _>(|tx| {
+        async move {
+            let _ = diesel::insert_into(posts_dsl::posts)
+                .values(&posts)
+                .execute(tx)
+                .await?;
+            refresh_series_length(tx, series_id).await
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    if let Err(e) = ctx.s3_service.delete_object(&payload.input_key).await {
+        tracing::warn!("Failed to delete input of feed import {}: {}", job.id, e);
+    }
+
+    Ok(JobOutcome {
+        result: serde_json::to_value(&plan.report)?,
+        output_key: None,
+        output_expires_at: None,
+    })
+}
+
+/// Read an uploaded Substack export, feed, or archive holding a feed
+///
+/// A ZIP with a `posts.csv` is read as a Substack export; otherwise its first
+/// `.xml`, `.rss`, `.atom` or `.json` file is read as a feed. Anything else is
+/// read as a feed document itself.
+///
+/// # Errors
+/// Returns a `ServiceError::Config` if the upload is none of these.
+pub fn read_feed_upload(upload: &[u8]) -> Result<FeedUpload, ServiceError> {
+    if !upload.starts_with(b"PK\x03\x04") {
+        let (source, items) = parse_feed(upload, "")?;
+        return Ok(FeedUpload {
+            source,
+            items,
+            files: HashMap::new(),
+        });
+    }
+
+    let entries = read_zip(upload, MAX_IMPORT_ARCHIVE_SIZE)?;
+    let files: HashMap<String, Vec<u8>> = entries
+        .into_iter()
+        .filter(|entry| !entry.name.starts_with("__MACOSX/"))
+        .map(|entry| (entry.name, entry.contents))
+        .collect();
+
+    if let Some(posts_csv) = files
+        .keys()
+        .filter(|name| file_name(name) == SUBSTACK_POSTS_ENTRY)
+        .min_by_key(|name| name.len())
+    {
+        let items = parse_substack_export(posts_csv, &files)?;
+        return Ok(FeedUpload {
+            source: FeedSource::Substack,
+            items,
+            files,
+        });
+    }
+
+    let Some(feed_name) = files
+        .keys()
+        .filter(|name| {
+            Path::new(name).extension().is_some_and(|extension| {
+                ["xml", "rss", "atom", "json"]
+                    .iter()
+                    .any(|known| extension.eq_ignore_ascii_case(known))
+            })
+        })
+        .min()
+    else {
+        return Err(ServiceError::Config(
+            "Archive has neither a Substack posts.csv nor an RSS or Atom feed".to_owned(),
+        ));
+    };
+    let contents = files.get(feed_name).map(Vec::as_slice).unwrap_or_default();
+    let (source, items) = parse_feed(contents, parent_dir(feed_name))?;
+    Ok(FeedUpload {
+        source,
+        items,
+        files,
+    })
+}
+
+/// Read the posts of a Substack export
+///
+/// `posts.csv` lists the posts; each one's body is in `posts/{post_id}.html`
+/// next to it. Posts without a body, such as threads, are left out.
+///
+/// # Errors
+/// Returns a `ServiceError::Config` if `posts.csv` cannot be read or has no
+/// `post_id` column.
+#[allow(clippy::implicit_hasher)]
+pub fn parse_substack_export(
+    posts_csv: &str,
+    files: &HashMap<String, Vec<u8>>,
+) -> Result<Vec<FeedItem>, ServiceError> {
+    let root = parent_dir(posts_csv);
+    let contents = files.get(posts_csv).map(Vec::as_slice).unwrap_or_default();
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents));
+
+    let headers = reader
+        .headers()
+        .map_err(|e| ServiceError::Config(format!("posts.csv cannot be read: {e}")))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|heading| heading.eq_ignore_ascii_case(name))
+    };
+    let id_column = column("post_id")
+        .ok_or_else(|| ServiceError::Config("posts.csv has no post_id column".to_owned()))?;
+    let date_column = column("post_date");
+    let published_column = column("is_published");
+    let audience_column = column("audience");
+    let title_column = column("title");
+    let subtitle_column = column("subtitle");
+
+    let mut items = Vec::new();
+    for row in reader.records() {
+        let record =
+            row.map_err(|e| ServiceError::Config(format!("posts.csv cannot be read: {e}")))?;
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|i| record.get(i))
+                .filter(|value| !value.is_empty())
+        };
+        let Some(post_id) = field(Some(id_column)) else {
+            continue;
+        };
+        let html_path = format!("{root}posts/{post_id}.html");
+        let Some(body) = files.get(&html_path) else {
+            continue;
+        };
+
+        let mut html = String::new();
+        if let Some(subtitle) = field(subtitle_column) {
+            // Writing to a String is infallible
+            #[allow(clippy::let_underscore_must_use)]
+            let _ = write!(html, "<p><em>{}</em></p>", escape_html(subtitle));
+        }
+        html.push_str(&String::from_utf8_lossy(body));
+
+        items.push(FeedItem {
+            source_id: post_id.to_owned(),
+            title: field(title_column).unwrap_or_default().to_owned(),
+            html,
+            published_at: field(date_column).and_then(|date| {
+                DateTime::parse_from_rfc3339(date)
+                    .ok()
+                    .map(|dt| dt.naive_utc())
+            }),
+            is_published: field(published_column)
+                .is_some_and(|value| value.eq_ignore_ascii_case("true")),
+            slug: post_id
+                .split_once('.')
+                .map(|(_, slug)| slug.to_owned())
+                .filter(|slug| is_valid_slug(slug)),
+            paid: field(audience_column)
+                .is_some_and(|audience| audience == "only_paid" || audience == "founding"),
+            base_dir: parent_dir(&html_path).to_owned(),
+            link: None,
+        });
+    }
+
+    Ok(items)
+}
+
+/// Read the entries of an RSS, Atom or JSON feed
+///
+/// Entries keep their full content when the feed has it, or their summary
+/// otherwise. Every entry counts as published.
+///
+/// # Errors
+/// Returns a `ServiceError::Config` if the document is not a feed.
+pub fn parse_feed(
+    contents: &[u8],
+    base_dir: &str,
+) -> Result<(FeedSource, Vec<FeedItem>), ServiceError> {
+    let feed = feed_rs::parser::parse(contents)
+        .map_err(|e| ServiceError::Config(format!("Upload is not an RSS or Atom feed: {e}")))?;
+    let source = match feed.feed_type {
+        feed_rs::model::FeedType::Atom => FeedSource::Atom,
+        feed_rs::model::FeedType::JSON => FeedSource::Json,
+        feed_rs::model::FeedType::RSS0
+        | feed_rs::model::FeedType::RSS1
+        | feed_rs::model::FeedType::RSS2 => FeedSource::Rss,
+    };
+
+    let items = feed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let link = entry.links.into_iter().next().map(|link| link.href);
+            FeedItem {
+                title: entry.title.map(|title| title.content).unwrap_or_default(),
+                html: entry
+                    .content
+                    .and_then(|content| content.body)
+                    .or_else(|| entry.summary.map(|summary| summary.content))
+                    .unwrap_or_default(),
+                published_at: entry.published.or(entry.updated).map(|dt| dt.naive_utc()),
+                is_published: true,
+                slug: link.as_deref().and_then(slug_from_link),
+                paid: false,
+                base_dir: base_dir.to_owned(),
+                link,
+                source_id: entry.id,
+            }
+        })
+        .collect();
+
+    Ok((source, items))
+}
+
+/// Number and slug the posts of an import, skipping those already imported
+///
+/// Posts are numbered after the series' last post in publish order and keep
+/// their publish date. A post already in the series with the same title and
+/// date is skipped. Paid posts are locked to `paid_tier`, the creator's
+/// cheapest paid tier, or imported as drafts when there is none. `existing`
+/// lists the slug, number, title and creation date of every post of the series.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn plan_feed_import(
+    series_id: Uuid,
+    source: FeedSource,
+    mut items: Vec<FeedItem>,
+    existing: &[(String, i32, String, Option<NaiveDateTime>)],
+    paid_tier: Option<Uuid>,
+) -> FeedImportPlan {
+    items.sort_by(|a, b| {
+        (a.published_at.is_none(), a.published_at, &a.source_id).cmp(&(
+            b.published_at.is_none(),
+            b.published_at,
+            &b.source_id,
+        ))
+    });
+
+    let now = Utc::now().naive_utc();
+    let mut taken_slugs: HashSet<String> = existing.iter().map(|p| p.0.clone()).collect();
+    let imported: HashSet<(String, Option<NaiveDateTime>)> =
+        existing.iter().map(|p| (p.2.clone(), p.3)).collect();
+    let mut next_number = existing.iter().map(|p| p.1).max().unwrap_or(0_i32);
+
+    let mut summary = FeedImportSummary::default();
+    let mut mappings = Vec::with_capacity(items.len());
+    let mut posts = Vec::new();
+    for item in items {
+        let trimmed = truncate_field(item.title.trim());
+        let title = if trimmed.is_empty() {
+            "Untitled post".to_owned()
+        } else {
+            trimmed
+        };
+        let mut mapping = FeedPostMapping {
+            source_id: item.source_id.clone(),
+            title: title.clone(),
+            slug: None,
+            number: None,
+            published_at: item.published_at.map(|dt| dt.and_utc()),
+            action: MappingAction::Skip,
+            reason: None,
+            post_id: None,
+            images: 0,
+            warnings: Vec::new(),
+        };
+
+        if imported.contains(&(title.clone(), item.published_at)) {
+            mapping.reason = Some("Already imported".to_owned());
+            summary.posts_skipped = summary.posts_skipped.saturating_add(1);
+            mappings.push(mapping);
+            continue;
+        }
+
+        let slug = match item.slug {
+            Some(ref wanted) if taken_slugs.insert(wanted.clone()) => wanted.clone(),
+            _ => unique_slug(&title, &item.source_id, &mut taken_slugs),
+        };
+        next_number = next_number.saturating_add(1);
+        let id = Uuid::new_v4();
+        let date = item.published_at.unwrap_or(now);
+
+        let mut is_published = item.is_published;
+        let min_tier_id = if item.paid { paid_tier } else { None };
+        if item.paid && paid_tier.is_none() && is_published {
+            is_published = false;
+            mapping
+                .warnings
+                .push("Paid post imported as a draft: add a paid tier to lock it".to_owned());
+        }
+
+        mapping.slug = Some(slug.clone());
+        mapping.number = Some(next_number);
+        mapping.action = MappingAction::Create;
+        mapping.post_id = Some(id);
+        summary.posts_created = summary.posts_created.saturating_add(1);
+        posts.push(PlannedPost {
+            post: Post {
+                id,
+                series_id,
+                title,
+                content: String::new(),
+                slug,
+                number: next_number,
+                is_published: Some(is_published),
+                thumbnail_url: None,
+                audio_file_id: None,
+                video_file_id: None,
+                created_at: Some(date),
+                updated_at: Some(date),
+                deleted_at: None,
+                min_tier_id,
+            },
+            item,
+            mapping: mappings.len(),
+        });
+        mappings.push(mapping);
+    }
+
+    FeedImportPlan {
+        posts,
+        report: FeedImportReport {
+            series_id,
+            source,
+            summary,
+            posts: mappings,
+        },
+    }
+}
+
+/// Rewrite the `src` of every `<img>` tag of an HTML document
+///
+/// `replace` gets each source, unescaped, and returns the URL to use instead,
+/// or `None` to keep it. Rewritten tags drop `srcset` and `sizes`, since those
+/// point to copies of the image that are not imported.
+pub fn rewrite_images<F>(html: &str, mut replace: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    // ASCII lowercasing keeps byte offsets, so matches index into `html`
+    let lowercase = html.to_ascii_lowercase();
+    let mut output = String::with_capacity(html.len());
+    let mut copied = 0;
+
+    let mut search_from = 0;
+    while let Some(found) = lowercase
+        .get(search_from..)
+        .and_then(|rest| rest.find("<img"))
+    {
+        let start = search_from.saturating_add(found);
+        let attributes_start = start.saturating_add(4);
+        search_from = attributes_start;
+        let is_img_tag = html
+            .get(attributes_start..)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| c.is_ascii_whitespace() || c == '/' || c == '>');
+        if !is_img_tag {
+            continue;
+        }
+        let Some(end) = tag_end(html, attributes_start) else {
+            break;
+        };
+        let attributes = parse_attributes(html.get(attributes_start..end).unwrap_or_default());
+        search_from = end.saturating_add(1);
+
+        let Some(new_src) = attributes
+            .iter()
+            .find(|attribute| attribute.0 == "src")
+            .and_then(|attribute| attribute.1.as_deref())
+            .and_then(&mut replace)
+        else {
+            continue;
+        };
+
+        output.push_str(html.get(copied..start).unwrap_or_default());
+        output.push_str("<img");
+        for (name, value) in attributes {
+            if name == "srcset" || name == "sizes" {
+                continue;
+            }
+            let written = if name == "src" {
+                Some(new_src.clone())
+            } else {
+                value
+            };
+            match written {
+                Some(text) => {
+                    #[allow(clippy::let_underscore_must_use)]
+                    let _ = write!(output, " {name}=\"{}\"", escape_html(&text));
+                }
+                None => {
+                    #[allow(clippy::let_underscore_must_use)]
+                    let _ = write!(output, " {name}");
+                }
+            }
+        }
+        output.push('>');
+        copied = search_from;
+    }
+
+    output.push_str(html.get(copied..).unwrap_or_default());
+    output
+}
+
+/// Helper function to find the `>` closing a tag, skipping quoted values
+fn tag_end(html: &str, from: usize) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (offset, c) in html.get(from..)?.char_indices() {
+        match (quote, c) {
+            (Some(open), _) => {
+                if c == open {
+                    quote = None;
+                }
+            }
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(from.saturating_add(offset)),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+/// Helper function to read the attributes of a tag, names lowercased and values unescaped
+fn parse_attributes(text: &str) -> Vec<(String, Option<String>)> {
+    let mut attributes = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            // A stray `=`; skip it
+            rest = rest.get(1..).unwrap_or_default();
+            continue;
+        }
+        let name = rest
+            .get(..name_end)
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        rest = rest.get(name_end..).unwrap_or_default().trim_start();
+
+        let Some(after_equals) = rest.strip_prefix('=') else {
+            attributes.push((name, None));
+            continue;
+        };
+        let value_text = after_equals.trim_start();
+        let quote = value_text
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'');
+        let (value, remainder) = quote.map_or_else(
+            || {
+                let close = value_text
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(value_text.len());
+                (
+                    value_text.get(..close).unwrap_or_default(),
+                    value_text.get(close..).unwrap_or_default(),
+                )
+            },
+            |open| {
+                let inner = value_text.get(1..).unwrap_or_default();
+                let close = inner.find(open).unwrap_or(inner.len());
+                (
+                    inner.get(..close).unwrap_or_default(),
+                    inner.get(close.saturating_add(1)..).unwrap_or_default(),
+                )
+            },
+        );
+        attributes.push((name, Some(unescape_html(value))));
+        rest = remainder;
+    }
+    attributes
+}
+
+/// Helper function to escape text for an HTML attribute or element
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Helper function to unescape the entities URLs in attributes commonly carry
+fn unescape_html(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Helper function to take the last segment of an archive path
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Helper function to take the directory of an archive path, with a trailing slash
+fn parent_dir(path: &str) -> &str {
+    path.rfind('/')
+        .and_then(|slash| path.get(..=slash))
+        .unwrap_or_default()
+}
+
+/// Helper function to use the last segment of a post's web address as its slug
+fn slug_from_link(link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    let segment = url.path_segments()?.rfind(|s| !s.is_empty())?;
+    let stem = segment
+        .split('.')
+        .next()
+        .unwrap_or(segment)
+        .to_ascii_lowercase();
+    Some(stem).filter(|slug| is_valid_slug(slug))
+}
+
+/// Helper function to resolve an image path relative to an archive directory
+///
+/// Returns `None` for URLs with a scheme or host, which are not in the archive.
+fn resolve_archive_path(base_dir: &str, src: &str) -> Option<String> {
+    if src.contains("://") || src.starts_with("//") || src.starts_with("data:") {
+        return None;
+    }
+    let path = src.split(['?', '#']).next().unwrap_or_default();
+    let decoded = urlencoding::decode(path).ok()?;
+    let mut segments: Vec<&str> = if decoded.starts_with('/') {
+        Vec::new()
+    } else {
+        base_dir.split('/').filter(|s| !s.is_empty()).collect()
+    };
+    for segment in decoded.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                let _ = segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/")).filter(|resolved| !resolved.is_empty())
+}
+
+/// Tell image formats apart by their first bytes
+///
+/// Returns the MIME type and file extension. SVG is left out on purpose: it
+/// can carry scripts, and files are served from the API's own origin.
+#[must_use]
+pub fn sniff_image(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some(("image/png", "png"));
+    }
+    if bytes.starts_with(b"\xFF\xD8\xFF") {
+        return Some(("image/jpeg", "jpg"));
+    }
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some(("image/gif", "gif"));
+    }
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP".as_slice()) {
+        return Some(("image/webp", "webp"));
+    }
+    if bytes.get(4..12) == Some(b"ftypavif".as_slice()) {
+        return Some(("image/avif", "avif"));
+    }
+    None
+}
+
+/// Helper function to tell addresses on the public internet from internal ones
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [first, second, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                || first == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && (second & 0xC0) == 64)
+                // Benchmarking, 198.18.0.0/15
+                || (first == 198 && (second & 0xFE) == 18)
+                // Reserved, 240.0.0.0/4, which includes broadcast
+                || first >= 240)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(mapped) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let segments = v6.segments();
+            let [first, second, ..] = segments;
+            // NAT64, 64:ff9b::/96, and 6to4, 2002::/16, reach the IPv4
+            // address they embed, so that address decides
+            if segments[..6] == [0x64, 0xFF9B, 0, 0, 0, 0] {
+                return is_public_ip(IpAddr::V4(embedded_ipv4(segments[6], segments[7])));
+            }
+            if first == 0x2002 {
+                return is_public_ip(IpAddr::V4(embedded_ipv4(second, segments[2])));
+            }
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // Local-use NAT64, 64:ff9b:1::/48
+                || (first == 0x64 && second == 0xFF9B)
+                // Unique local, fc00::/7, and link-local, fe80::/10
+                || (first & 0xFE00) == 0xFC00
+                || (first & 0xFFC0) == 0xFE80)
+        }
+    }
+}
+
+/// Helper function to join two 16-bit address segments into the 32-bit address they hold
+const fn embedded_ipv4(high: u16, low: u16) -> Ipv4Addr {
+    let [a, b] = high.to_be_bytes();
+    let [c, d] = low.to_be_bytes();
+    Ipv4Addr::new(a, b, c, d)
+}
+
+/// Helper struct for the images stored during one import
+struct ImageImporter<'files> {
+    files: &'files HashMap<String, Vec<u8>>,
+    /// File stored for each archive path or URL, so repeats are stored once
+    stored: HashMap<String, Result<Uuid, String>>,
+}
+
+impl ImageImporter<'_> {
+    /// Store the image an `<img>` of a post points to
+    ///
+    /// Returns the file, `None` for inline `data:` images, which are kept as
+    /// they are, or why the image was not stored.
+    async fn import(
+        &mut self,
+        ctx: &JobContext,
+        conn: &mut AsyncPgConnection,
+        user_id: Uuid,
+        item: &FeedItem,
+        src: &str,
+    ) -> Result<Option<Uuid>, String> {
+        if src.starts_with("data:") {
+            return Ok(None);
+        }
+
+        let in_archive =
+            resolve_archive_path(&item.base_dir, src).filter(|path| self.files.contains_key(path));
+        let key = if let Some(ref path) = in_archive {
+            path.clone()
+        } else {
+            let base = item.link.as_deref().and_then(|link| Url::parse(link).ok());
+            let url = Url::options()
+                .base_url(base.as_ref())
+                .parse(src)
+                .ok()
+                .ok_or_else(|| "not in the archive and not a web address".to_owned())?;
+            url.to_string()
+        };
+        if let Some(previous) = self.stored.get(&key) {
+            return previous.clone().map(Some);
+        }
+        let successful = self.stored.values().filter(|stored| stored.is_ok()).count();
+        if successful >= MAX_IMPORT_IMAGES {
+            return Err(format!(
+                "an import stores at most {MAX_IMPORT_IMAGES} images"
+            ));
+        }
+
+        let bytes = if let Some(ref path) = in_archive {
+            self.files.get(path).cloned().unwrap_or_default()
+        } else {
+            let url = Url::parse(&key)
+                .ok()
+                .ok_or_else(|| "invalid URL".to_owned())?;
+            download_image(url).await?
+        };
+        let outcome = store_image(ctx, conn, user_id, &key, &bytes).await;
+        let _ = self.stored.insert(key, outcome.clone());
+        outcome.map(Some)
+    }
+}
+
+/// Helper function to build an HTTP client that connects to `host` only at `addresses`
+///
+/// Pinning the addresses that were checked keeps a host from resolving to an
+/// internal address between the check and the connection.
+fn pinned_client(host: &str, addresses: &[SocketAddr]) -> Result<Client, String> {
+    Client::builder()
+        .redirect(Policy::none())
+        .no_proxy()
+        .resolve_to_addrs(host, addresses)
+        .timeout(std::time::Duration::from_secs(IMAGE_TIMEOUT_SECONDS))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Download an image from the public internet
+///
+/// Redirects are followed by hand so every host on the way is checked, and
+/// each request goes to the addresses that passed the check, which keeps
+/// imports from reaching the server's own network.
+///
+/// # Errors
+/// Returns why the image was not downloaded: the host is internal or cannot
+/// be reached, the response is not a success, or the image is too large.
+pub async fn download_image(url: Url) -> Result<Vec<u8>, String> {
+    let mut current = url;
+    for _ in 0..=MAX_REDIRECTS {
+        if current.scheme() != "http" && current.scheme() != "https" {
+            return Err("only HTTP and HTTPS images are downloaded".to_owned());
+        }
+        let host = current
+            .host_str()
+            .ok_or_else(|| "URL has no host".to_owned())?
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let port = current.port_or_known_default().unwrap_or(443);
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("host cannot be resolved: {e}"))?
+            .collect();
+        if addresses.is_empty() || !addresses.iter().all(|address| is_public_ip(address.ip())) {
+            return Err("host is not on the public internet".to_owned());
+        }
+
+        let client = pinned_client(host, &addresses)?;
+        let response = client
+            .get(current.clone())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| "redirect without a location".to_owned())?;
+            current = current.join(location).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status().as_u16()));
+        }
+        if response
+            .content_length()
+            .is_some_and(|length| usize::try_from(length).map_or(true, |l| l > MAX_IMAGE_SIZE))
+        {
+            return Err("image is too large".to_owned());
+        }
+
+        let mut bytes = Vec::new();
+        let mut body = response.bytes_stream();
+        while let Some(chunk) = body.next().await {
+            bytes.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+            if bytes.len() > MAX_IMAGE_SIZE {
+                return Err("image is too large".to_owned());
+            }
+        }
+        return Ok(bytes);
+    }
+    Err("too many redirects".to_owned())
+}
+
+/// Helper function to store an image as a file of the user, reusing an identical one
+#[allow(clippy::too_many_lines)]
+async fn store_image(
+    ctx: &JobContext,
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    source: &str,
+    bytes: &[u8],
+) -> Result<Uuid, String> {
+    use crate::schema::user_files::dsl as files_dsl;
+
+    if bytes.len() > MAX_IMAGE_SIZE {
+        return Err("image is too large".to_owned());
+    }
+    let (mime_type, extension) =
+        sniff_image(bytes).ok_or_else(|| "not a PNG, JPEG, GIF, WebP or AVIF image".to_owned())?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    let file_hash = format!("{:x}", hasher.finalize());
+
+    let find_existing = files_dsl::user_files
+        .filter(files_dsl::user_id.eq(user_id))
+        .filter(files_dsl::file_hash.eq(&file_hash))
+        .filter(files_dsl::deleted_at.is_null())
+        .select(files_dsl::id);
+    if let Some(existing) = find_existing
+        .first::<Uuid>(conn)
+        .await
+        .optional()
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(existing);
+    }
+
+    let file_id = Uuid::new_v4();
+    let s3_key = format!("user_files/{user_id}/{file_id}.{extension}");
+    let s3_url = ctx
+        .s3_service
+        .put_object(&s3_key, bytes.to_vec())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let stem = source
+        .split(['?', '#'])
+        .next()
+        .map(file_name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("image");
+    let original_name = if Path::new(stem).extension().is_some() {
+        truncate_field(stem)
+    } else {
+        truncate_field(&format!("{stem}.{extension}"))
+    };
+    let now = Utc::now().naive_utc();
+    let new_file = UserFile {
+        id: file_id,
+        user_id,
+        filename: original_name.clone(),
+        original_filename: original_name,
+        file_path: s3_key.clone(),
+        file_size: i64::try_from(bytes.len()).unwrap_or(i64::MAX),
+        mime_type: mime_type.to_owned(),
+        file_hash: file_hash.clone(),
+        status: FileStatus::Uploaded.into(),
+        metadata: Some(serde_json::json!({
+            "s3_url": s3_url,
+            "s3_key": s3_key,
+            "source_url": source,
+        })),
+        created_at: Some(now),
+        updated_at: Some(now),
+        deleted_at: None,
+    };
+
+    match diesel::insert_into(files_dsl::user_files)
+        .values(&new_file)
+        .execute(conn)
+        .await
+    {
+        Ok(_) => Ok(file_id),
+        // Stored meanwhile by an upload of the same image
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => {
+            if let Err(e) = ctx.s3_service.delete_object(&new_file.file_path).await {
+                tracing::warn!(
+                    "Failed to delete duplicate image {}: {}",
+                    new_file.file_path,
+                    e
+                );
+            }
+            find_existing
+                .first::<Uuid>(conn)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_image_sources_only() {
+        let html = r#"<p>Hi</p><img src="a.png?x=1&amp;y=2" srcset="a@2x.png 2x" alt='A "cat"'><image-gallery src="b.png"><IMG SRC=c.png>"#;
+        let rewritten = rewrite_images(html, |src| match src {
+            "a.png?x=1&y=2" => Some("/api/cdn/files/1".to_owned()),
+            _ => None,
+        });
+        assert_eq!(
+            rewritten,
+            r#"<p>Hi</p><img src="/api/cdn/files/1" alt="A &quot;cat&quot;"><image-gallery src="b.png"><IMG SRC=c.png>"#
+        );
+
+        let mut seen = Vec::new();
+        let _ = rewrite_images(html, |src| {
+            seen.push(src.to_owned());
+            None
+        });
+        assert_eq!(seen, vec!["a.png?x=1&y=2".to_owned(), "c.png".to_owned()]);
+    }
+
+    #[test]
+    fn tells_public_addresses_from_internal_ones() {
+        for public in [
+            "93.184.216.34",
+            "2606:2800:220:1::1",
+            "64:ff9b::5db8:d822",
+            "2002:5db8:d822::1",
+        ] {
+            assert!(is_public_ip(public.parse().unwrap()), "{public}");
+        }
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "169.254.169.254",
+            "100.64.0.1",
+            "198.18.0.1",
+            "198.19.255.255",
+            "240.0.0.1",
+            "255.255.255.255",
+            "224.0.0.1",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::7f00:1",
+            "64:ff9b::a9fe:a9fe",
+            "64:ff9b:1::1",
+            "2002:7f00:1::1",
+            "2002:c0a8:101::1",
+        ] {
+            assert!(!is_public_ip(internal.parse().unwrap()), "{internal}");
+        }
+    }
+
+    #[test]
+    fn resolves_relative_archive_paths() {
+        assert_eq!(
+            resolve_archive_path("export/posts/", "../images/a%20b.png?w=10"),
+            Some("export/images/a b.png".to_owned())
+        );
+        assert_eq!(
+            resolve_archive_path("export/posts/", "/cover.jpg"),
+            Some("cover.jpg".to_owned())
+        );
+        assert_eq!(
+            resolve_archive_path("export/", "https://example.com/a.png"),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_substack_export() {
+        let mut files = HashMap::new();
+        let _ = files.insert(
+            "export/posts.csv".to_owned(),
+            b"post_id,post_date,is_published,type,audience,title,subtitle\n\
+              2.second,2023-02-01T10:00:00.000Z,true,newsletter,only_paid,Second,\n\
+              1.first,2023-01-01T10:00:00.000Z,true,newsletter,everyone,First,A <b>start</b>\n\
+              3.thread,2023-03-01T10:00:00.000Z,true,thread,everyone,Thread,\n"
+                .to_vec(),
+        );
+        let _ = files.insert(
+            "export/posts/1.first.html".to_owned(),
+            b"<p>One</p>".to_vec(),
+        );
+        let _ = files.insert(
+            "export/posts/2.second.html".to_owned(),
+            b"<p>Two</p>".to_vec(),
+        );
+
+        let items = parse_substack_export("export/posts.csv", &files).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].slug.as_deref(), Some("first"));
+        assert_eq!(
+            items[1].html,
+            "<p><em>A &lt;b&gt;start&lt;/b&gt;</em></p><p>One</p>"
+        );
+        assert!(items[0].paid);
+        assert_eq!(items[0].base_dir, "export/posts/");
+
+        let existing = vec![("first".to_owned(), 1_i32, "Prologue".to_owned(), None)];
+        let plan = plan_feed_import(Uuid::new_v4(), FeedSource::Substack, items, &existing, None);
+        let posts: Vec<(&str, &str, i32, Option<bool>)> = plan
+            .posts
+            .iter()
+            .map(|p| {
+                (
+                    p.post.title.as_str(),
+                    p.post.slug.as_str(),
+                    p.post.number,
+                    p.post.is_published,
+                )
+            })
+            .collect();
+        assert_eq!(
+            posts,
+            vec![
+                ("First", "first-2", 2_i32, Some(true)),
+                ("Second", "second", 3_i32, Some(false)),
+            ]
+        );
+        assert_eq!(plan.report.posts[1].warnings.len(), 1);
+    }
+
+    #[test]
+    fn reads_rss_feed() {
+        let rss = br#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/"><channel>
+            <title>Blog</title><link>https://blog.example.com/</link><description>d</description>
+            <item><title>Hello &amp; welcome</title><link>https://blog.example.com/posts/hello-world.html</link>
+            <guid>post-1</guid><pubDate>Mon, 02 Jan 2023 10:00:00 GMT</pubDate>
+            <description>Short</description>
+            <content:encoded><![CDATA[<p>Full <img src="/img/a.png"></p>]]></content:encoded></item>
+            </channel></rss>"#;
+        let (source, items) = parse_feed(rss, "").unwrap();
+        assert_eq!(source, FeedSource::Rss);
+        assert_eq!(items[0].title, "Hello & welcome");
+        assert_eq!(items[0].slug.as_deref(), Some("hello-world"));
+        assert!(items[0].html.contains("<img src=\"/img/a.png\">"));
+        assert_eq!(
+            items[0].published_at,
+            chrono::NaiveDate::from_ymd_opt(2023, 1, 2).and_then(|d| d.and_hms_opt(10, 0, 0))
+        );
+    }
+
+    #[test]
+    fn only_public_addresses_are_downloaded_from() {
+        assert!(is_public_ip("93.184.216.34".parse().unwrap()));
+        assert!(!is_public_ip("127.0.0.1".parse().unwrap()));
+        assert!(!is_public_ip("10.1.2.3".parse().unwrap()));
+        assert!(!is_public_ip("169.254.169.254".parse().unwrap()));
+        assert!(!is_public_ip("100.100.1.1".parse().unwrap()));
+        assert!(!is_public_ip("::ffff:192.168.0.1".parse().unwrap()));
+        assert!(!is_public_ip("fd00::1".parse().unwrap()));
+    }
+}
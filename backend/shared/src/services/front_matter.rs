@@ -0,0 +1,234 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use serde_yaml::Value;
+
+/// Post fields carried in the YAML front matter of a Markdown file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostFrontMatter {
+    /// Post title
+    pub title: Option<String>,
+    /// URL-friendly slug
+    pub slug: Option<String>,
+    /// Position within the series
+    pub number: Option<i32>,
+    /// Whether the post is visible to readers
+    pub published: Option<bool>,
+    /// When the post was published
+    pub date: Option<NaiveDateTime>,
+}
+
+impl PostFrontMatter {
+    /// Render a Markdown document with these fields as front matter
+    ///
+    /// Strings are written as double-quoted scalars, which YAML reads the
+    /// same way as JSON, so any title survives the round trip.
+    #[must_use]
+    pub fn render(&self, body: &str) -> String {
+        let mut lines = vec!["---".to_owned()];
+        if let Some(ref title) = self.title {
+            lines.push(format!("title: {}", quoted(title)));
+        }
+        if let Some(ref slug) = self.slug {
+            lines.push(format!("slug: {}", quoted(slug)));
+        }
+        if let Some(number) = self.number {
+            lines.push(format!("number: {number}"));
+        }
+        if let Some(published) = self.published {
+            lines.push(format!("published: {published}"));
+        }
+        if let Some(date) = self.date {
+            lines.push(format!("date: {}", date.and_utc().to_rfc3339()));
+        }
+        lines.push("---".to_owned());
+
+        format!("{}\n\n{body}\n", lines.join("\n"))
+    }
+
+    /// Split a Markdown document into its front matter and body
+    ///
+    /// The front matter is read as YAML, with unknown keys ignored. Titles and
+    /// slugs may be any scalar, so `title: 1984` is a title. A document without
+    /// a leading `---` line has no front matter and is all body.
+    ///
+    /// # Errors
+    /// Returns a message describing the problem if the front matter is not
+    /// closed, is not valid YAML, or a value has the wrong type.
+    pub fn parse(document: &str) -> Result<(Self, String), String> {
+        let text = document.strip_prefix('\u{feff}').unwrap_or(document);
+        let mut lines = text.lines();
+        if lines.next().map(str::trim_end) != Some("---") {
+            return Ok((Self::default(), text.trim().to_owned()));
+        }
+
+        let mut yaml_lines = Vec::new();
+        let mut closed = false;
+        for line in lines.by_ref() {
+            if matches!(line.trim_end(), "---" | "...") {
+                closed = true;
+                break;
+            }
+            yaml_lines.push(line);
+        }
+        if !closed {
+            return Err("Front matter is not closed with `---`".to_owned());
+        }
+
+        let yaml = yaml_lines.join("\n");
+        let raw: RawFrontMatter = if yaml.trim().is_empty() {
+            RawFrontMatter::default()
+        } else {
+            serde_yaml::from_str(&yaml).map_err(|e| format!("Invalid front matter: {e}"))?
+        };
+        let date = raw
+            .date
+            .map(|value| {
+                parse_date(&value).ok_or_else(|| {
+                    "date must look like 2025-01-31 or 2025-01-31T09:00:00Z".to_owned()
+                })
+            })
+            .transpose()?;
+        let front_matter = Self {
+            title: raw.title,
+            slug: raw.slug,
+            number: raw.number,
+            published: raw.published,
+            date,
+        };
+
+        let body: Vec<&str> = lines.collect();
+        Ok((front_matter, body.join("\n").trim().to_owned()))
+    }
+}
+
+/// Helper struct for the front matter as written, before dates are read
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawFrontMatter {
+    #[serde(deserialize_with = "text")]
+    title: Option<String>,
+    #[serde(deserialize_with = "text")]
+    slug: Option<String>,
+    number: Option<i32>,
+    #[serde(deserialize_with = "boolean")]
+    published: Option<bool>,
+    #[serde(alias = "publish_date", deserialize_with = "text")]
+    date: Option<String>,
+}
+
+/// Helper function to read any YAML scalar as text
+fn text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::String(value) => Ok(Some(value)),
+        Value::Number(value) => Ok(Some(value.to_string())),
+        Value::Bool(value) => Ok(Some(value.to_string())),
+        Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => {
+            Err(D::Error::custom("expected text, not a list or mapping"))
+        }
+    }
+}
+
+/// Helper function to read a YAML boolean
+///
+/// YAML 1.1 also spells booleans `yes`, `no`, `on` and `off`, which static site
+/// generators still write, so those are accepted too.
+fn boolean<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<bool>, D::Error> {
+    let invalid = || D::Error::custom("published must be true or false");
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::Bool(value) => Ok(Some(value)),
+        Value::String(value) => match value.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" => Ok(Some(true)),
+            "false" | "no" | "off" => Ok(Some(false)),
+            _ => Err(invalid()),
+        },
+        Value::Number(_) | Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => {
+            Err(invalid())
+        }
+    }
+}
+
+/// Helper function to read a date with or without a time, taken as UTC without an offset
+fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.naive_utc())
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").ok())
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// Helper function to write a string as a double-quoted YAML scalar
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value.replace('"', "'")))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendered_front_matter_parses_back() {
+        let front_matter = PostFrontMatter {
+            title: Some("Chapter 1: \"Hello\", she said".to_owned()),
+            slug: Some("chapter-1".to_owned()),
+            number: Some(1_i32),
+            published: Some(true),
+            date: NaiveDate::from_ymd_opt(2024, 5, 17).and_then(|d| d.and_hms_opt(8, 30, 0)),
+        };
+        let document = front_matter.render("# Hello\n\nBody text.");
+
+        let (parsed, body) = PostFrontMatter::parse(&document).unwrap();
+        assert_eq!(parsed, front_matter);
+        assert_eq!(body, "# Hello\n\nBody text.");
+    }
+
+    #[test]
+    fn parses_hand_written_yaml() {
+        let document = "---\ntitle: It's a plain title # comment\nslug: 'it''s'\nnumber: 12\npublished: no\ndate: 2023-01-31\nauthor: ignored\n---\nBody";
+
+        let (parsed, body) = PostFrontMatter::parse(document).unwrap();
+        assert_eq!(parsed.title.as_deref(), Some("It's a plain title"));
+        assert_eq!(parsed.slug.as_deref(), Some("it's"));
+        assert_eq!(parsed.number, Some(12_i32));
+        assert_eq!(parsed.published, Some(false));
+        assert_eq!(
+            parsed.date,
+            NaiveDate::from_ymd_opt(2023, 1, 31).and_then(|d| d.and_hms_opt(0, 0, 0))
+        );
+        assert_eq!(body, "Body");
+    }
+
+    #[test]
+    fn parses_full_yaml_syntax() {
+        let document = "---\ntitle: >-\n  A folded\n  title\nslug: 1984\ntags: [one, two]\nextra:\n  nested: true\n---\nBody";
+
+        let (parsed, _) = PostFrontMatter::parse(document).unwrap();
+        assert_eq!(parsed.title.as_deref(), Some("A folded title"));
+        assert_eq!(parsed.slug.as_deref(), Some("1984"));
+    }
+
+    #[test]
+    fn rejects_unclosed_front_matter_and_bad_values() {
+        let unclosed = PostFrontMatter::parse("---\ntitle: x\n").unwrap_err();
+        assert!(unclosed.contains("not closed"));
+        let bad_number = PostFrontMatter::parse("---\nnumber: one\n---\n").unwrap_err();
+        assert!(bad_number.starts_with("Invalid front matter: number: invalid type"));
+        let bad_date = PostFrontMatter::parse("---\ndate: soon\n---\n").unwrap_err();
+        assert!(bad_date.starts_with("date must look like"));
+    }
+
+    #[test]
+    fn document_without_front_matter_is_all_body() {
+        let (parsed, body) = PostFrontMatter::parse("Just text\n").unwrap();
+        assert_eq!(parsed, PostFrontMatter::default());
+        assert_eq!(body, "Just text");
+    }
+}
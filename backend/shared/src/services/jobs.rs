@@ -0,0 +1,279 @@
+use crate::errors::ServiceError;
+use crate::models::jobs::{BackgroundJob, JobKind, JobStatus};
+use crate::services::cbz_export::run_cbz_export;
+use crate::services::data_export::run_data_export;
+use crate::services::db::DbService;
+use crate::services::email::EmailService;
+use crate::services::epub_export::run_epub_export;
+use crate::services::feed_import::run_feed_import;
+use crate::services::patreon_import::run_patreon_import;
+use crate::services::s3::S3Service;
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// How long a job may run before the worker assumes it died and retries it
+pub const JOB_TIMEOUT_MINUTES: i64 = 60;
+
+/// How many times a job is picked up before it is given up on
+pub const MAX_JOB_ATTEMPTS: i32 = 3;
+
+/// Services background jobs run with
+#[derive(Clone, Debug)]
+pub struct JobContext {
+    /// Database service
+    pub db_service: DbService,
+    /// File storage
+    pub s3_service: S3Service,
+    /// Email delivery
+    pub email_service: EmailService,
+    /// Frontend application URL, for links in emails
+    pub frontend_url: String,
+}
+
+/// What a finished job reports back to the queue
+#[derive(Debug, Default)]
+pub struct JobOutcome {
+    /// Kind-specific summary shown to the user
+    pub result: Value,
+    /// Bucket key of a temporary object the job produced
+    pub output_key: Option<String>,
+    /// When the temporary object should be removed
+    pub output_expires_at: Option<NaiveDateTime>,
+}
+
+/// Queue a job for the worker
+///
+/// `input` is the bucket key of an object the job reads, such as an upload,
+/// and when it should be removed if the job never gets to it.
+///
+/// # Errors
+/// Returns an error if database operations fail.
+pub async fn enqueue_job(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    kind: JobKind,
+    payload: Value,
+    input: Option<(String, NaiveDateTime)>,
+) -> Result<BackgroundJob, ServiceError> {
+    use crate::schema::background_jobs::dsl as jobs_dsl;
+
+    let (output_key, output_expires_at) = input.unzip();
+    let job = BackgroundJob {
+        id: Uuid::new_v4(),
+        user_id,
+        kind: kind.as_str().to_owned(),
+        status: JobStatus::Pending.into(),
+        payload,
+        result: None,
+        error: None,
+        attempts: 0,
+        output_key,
+        output_expires_at,
+        created_at: Utc::now().naive_utc(),
+        started_at: None,
+        finished_at: None,
+    };
+
+    Ok(diesel::insert_into(jobs_dsl::background_jobs)
+        .values(&job)
+        .get_result(conn)
+        .await?)
+}
+
+/// Whether the user already has a job of this kind queued or running
+///
+/// # Errors
+/// Returns an error if database operations fail.
+pub async fn has_active_job(
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    kind: JobKind,
+) -> Result<bool, ServiceError> {
+    use crate::schema::background_jobs::dsl as jobs_dsl;
+
+    Ok(diesel::select(diesel::dsl::exists(
+        jobs_dsl::background_jobs
+            .filter(jobs_dsl::user_id.eq(user_id))
+            .filter(jobs_dsl::kind.eq(kind.as_str()))
+            .filter(
+                jobs_dsl::status.eq_any([JobStatus::Pending.as_str(), JobStatus::Running.as_str()]),
+            ),
+    ))
+    .get_result(conn)
+    .await?)
+}
+
+/// Take the oldest job that is waiting, or whose run timed out, off the queue
+///
+/// The row is locked while it is claimed so several workers never pick up
+/// the same job.
+async fn claim_next_job(db_service: &DbService) -> Result<Option<BackgroundJob>, ServiceError> {
+    use crate::schema::background_jobs::dsl as jobs_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    let now = Utc::now().naive_utc();
+    let timed_out = now
+        .checked_sub_signed(Duration::minutes(JOB_TIMEOUT_MINUTES))
+        .unwrap_or(now);
+
+    conn.transaction::<_, ServiceError, _>(|tx| {
+        async move {
+            let Some(job) = jobs_dsl::background_jobs
+                .filter(
+                    jobs_dsl::status
+                        .eq(JobStatus::Pending.as_str())
+                        .or(jobs_dsl::status
+                            .eq(JobStatus::Running.as_str())
+                            .and(jobs_dsl::started_at.lt(timed_out))),
+                )
+                .filter(jobs_dsl::attempts.lt(MAX_JOB_ATTEMPTS))
+                .order(jobs_dsl::created_at.asc())
+                .for_update()
+                .skip_locked()
+                .first::<BackgroundJob>(tx)
+                .await
+                .optional()?
+            else {
+                return Ok(None);
+            };
+
+            let claimed = diesel::update(jobs_dsl::background_jobs.find(job.id))
+                .set((
+                    jobs_dsl::status.eq(JobStatus::Running.as_str()),
+                    jobs_dsl::attempts.eq(job.attempts.saturating_add(1)),
+                    jobs_dsl::started_at.eq(Some(now)),
+                ))
+                .get_result::<BackgroundJob>(tx)
+                .await?;
+            Ok(Some(claimed))
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Run the next queued job, if any
+///
+/// Returns whether a job was run, so the worker can keep going while the
+/// queue is not empty. Job failures are stored on the job rather than returned.
+///
+/// # Errors
+/// Returns an error if the queue cannot be read or updated.
+pub async fn run_next_job(ctx: &JobContext) -> Result<bool, ServiceError> {
+    use crate::schema::background_jobs::dsl as jobs_dsl;
+
+    let Some(job) = claim_next_job(&ctx.db_service).await? else {
+        return Ok(false);
+    };
+
+    let result = match JobKind::parse(&job.kind) {
+        Some(JobKind::DataExport) => run_data_export(ctx, &job).await,
+        Some(JobKind::PatreonImport) => run_patreon_import(ctx, &job).await,
+        Some(JobKind::FeedImport) => run_feed_import(ctx, &job).await,
+        Some(JobKind::EpubExport) => run_epub_export(ctx, &job).await,
+        Some(JobKind::CbzExport) => run_cbz_export(ctx, &job).await,
+        None => Err(ServiceError::Config(format!(
+            "Unknown job kind {}",
+            job.kind
+        ))),
+    };
+
+    let pool = ctx.db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    let now = Utc::now().naive_utc();
+
+    match result {
+        Ok(outcome) => {
+            let _ = diesel::update(jobs_dsl::background_jobs.find(job.id))
+                .set((
+                    jobs_dsl::status.eq(JobStatus::Completed.as_str()),
+                    jobs_dsl::result.eq(Some(outcome.result)),
+                    jobs_dsl::error.eq(None::<String>),
+                    jobs_dsl::output_key.eq(outcome.output_key),
+                    jobs_dsl::output_expires_at.eq(outcome.output_expires_at),
+                    jobs_dsl::finished_at.eq(Some(now)),
+                ))
+                .execute(&mut conn)
+                .await?;
+        }
+        Err(e) => {
+            tracing::warn!("Background job {} ({}) failed: {}", job.id, job.kind, e);
+            let _ = diesel::update(jobs_dsl::background_jobs.find(job.id))
+                .set((
+                    jobs_dsl::status.eq(JobStatus::Failed.as_str()),
+                    jobs_dsl::error.eq(Some(e.to_string())),
+                    jobs_dsl::finished_at.eq(Some(now)),
+                ))
+                .execute(&mut conn)
+                .await?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Remove expired job outputs from the bucket and give up on abandoned jobs
+///
+/// Jobs that timed out on their last attempt are marked failed. Returns the
+/// number of outputs removed.
+///
+/// # Errors
+/// Returns an error if database operations fail. Objects that cannot be
+/// deleted are logged and retried on the next run.
+pub async fn purge_expired_job_outputs(
+    db_service: &DbService,
+    s3_service: &S3Service,
+) -> Result<usize, ServiceError> {
+    use crate::schema::background_jobs::dsl as jobs_dsl;
+
+    let pool = db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+    let now = Utc::now().naive_utc();
+    let timed_out = now
+        .checked_sub_signed(Duration::minutes(JOB_TIMEOUT_MINUTES))
+        .unwrap_or(now);
+
+    let _ = diesel::update(
+        jobs_dsl::background_jobs
+            .filter(jobs_dsl::status.eq(JobStatus::Running.as_str()))
+            .filter(jobs_dsl::started_at.lt(timed_out))
+            .filter(jobs_dsl::attempts.ge(MAX_JOB_ATTEMPTS)),
+    )
+    .set((
+        jobs_dsl::status.eq(JobStatus::Failed.as_str()),
+        jobs_dsl::error.eq(Some("Job timed out")),
+        jobs_dsl::finished_at.eq(Some(now)),
+    ))
+    .execute(&mut conn)
+    .await?;
+
+    let expired: Vec<(Uuid, Option<String>)> = jobs_dsl::background_jobs
+        .filter(jobs_dsl::output_key.is_not_null())
+        .filter(jobs_dsl::output_expires_at.le(now))
+        .select((jobs_dsl::id, jobs_dsl::output_key))
+        .load(&mut conn)
+        .await?;
+
+    let mut purged: usize = 0;
+    for (job_id, key) in expired {
+        let Some(output_key) = key else {
+            continue;
+        };
+        if let Err(e) = s3_service.delete_object(&output_key).await {
+            tracing::warn!("Failed to delete output of job {}: {}", job_id, e);
+            continue;
+        }
+        let _ = diesel::update(jobs_dsl::background_jobs.find(job_id))
+            .set(jobs_dsl::output_key.eq(None::<String>))
+            .execute(&mut conn)
+            .await?;
+        purged = purged.saturating_add(1);
+    }
+
+    Ok(purged)
+}
@@ -1,10 +1,50 @@
+/// Grace-period account deletion and purging
+pub mod account_deletion;
+/// Security audit log writer
+pub mod audit;
 /// Authentication service for handling user login and registration
 pub mod auth;
+/// Comic book archives of posts with pages
+pub mod cbz_export;
+/// Scheduled purging of expired tokens
+pub mod cleanup;
 /// Configuration service for managing application settings
 pub mod config;
+/// Personal data export archives
+pub mod data_export;
 /// Database service for interacting with the database
 pub mod db;
+/// Sign-in device fingerprinting for new-device alerts
+pub mod devices;
 /// Email service for sending verification and password reset emails
 pub mod email;
+/// EPUB compilation of series
+pub mod epub_export;
+/// Substack export and RSS/Atom feed importer
+pub mod feed_import;
+/// Markdown front matter for imported and exported posts
+pub mod front_matter;
+/// Database-backed background job queue
+pub mod jobs;
+/// `OpenID Connect` client for external sign-in providers
+pub mod oidc;
+/// Patreon posts and members importer
+pub mod patreon_import;
+/// Loading of the files attached to posts
+pub mod post_attachments;
+/// Validation of post imports from Markdown archives
+pub mod post_import;
+/// Redis-backed sliding window rate limiting
+pub mod rate_limit;
 /// Amazon S3 file storage service
 pub mod s3;
+/// Cached post counts of series
+pub mod series_length;
+/// Redis-backed session inventory service
+pub mod sessions;
+/// Signed access tokens, refresh tokens and hashing of single-use tokens
+pub mod tokens;
+/// Redis cache of user rows used by request authentication
+pub mod user_cache;
+/// Storage of files made by background jobs
+pub mod user_files;
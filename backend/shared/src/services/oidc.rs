@@ -0,0 +1,889 @@
+use crate::{
+    errors::ServiceError,
+    services::config::{OidcEndpointOverrides, OidcProviderConfig},
+};
+use chrono::Utc;
+use jsonwebtoken::{
+    errors::ErrorKind,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm},
+    Algorithm, DecodingKey, Validation,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::instrument;
+
+/// How long a fetched key set is trusted before it is fetched again
+const JWKS_TTL: Duration = Duration::from_secs(3600);
+/// Minimum time between refetches triggered by an unknown key ID
+const JWKS_MIN_REFRESH: Duration = Duration::from_secs(60);
+/// Clock skew tolerated when checking `exp` and `iat`
+const CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// Subset of an `OpenID Connect` discovery document used by the login flow
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscoveryDocument {
+    /// Issuer identifier
+    pub issuer: String,
+    /// URL users are sent to for authorization
+    pub authorization_endpoint: String,
+    /// URL authorization codes are exchanged at
+    pub token_endpoint: String,
+    /// URL returning claims about the authenticated user
+    pub userinfo_endpoint: Option<String>,
+    /// URL of the provider's JSON Web Key Set
+    pub jwks_uri: Option<String>,
+}
+
+/// Token endpoint response
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OidcTokenResponse {
+    /// Access token used to call the userinfo endpoint
+    pub access_token: String,
+    /// Type of the token issued
+    pub token_type: Option<String>,
+    /// Lifetime in seconds of the access token
+    pub expires_in: Option<i64>,
+    /// Refresh token, if provided
+    pub refresh_token: Option<String>,
+    /// Scopes granted by the access token
+    pub scope: Option<String>,
+    /// ID token, if the provider issued one
+    pub id_token: Option<String>,
+}
+
+/// Normalized claims about a user returned by a provider
+#[derive(Debug, Clone)]
+pub struct OidcUserInfo {
+    /// Stable identifier of the user at the provider
+    pub subject: String,
+    /// Email address, if shared
+    pub email: Option<String>,
+    /// Whether the provider has verified the email address
+    pub email_verified: bool,
+    /// Display name
+    pub name: Option<String>,
+    /// Profile picture URL
+    pub picture: Option<String>,
+}
+
+impl OidcUserInfo {
+    /// Map userinfo claims to a `OidcUserInfo`
+    ///
+    /// Standard claims are preferred; the fallbacks cover providers such as
+    /// `GitHub` and `Discord` whose user endpoints use their own field names.
+    fn from_claims(claims: &Value) -> Option<Self> {
+        let string_claim = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                let value = claims.get(*name)?;
+                value
+                    .as_str()
+                    .filter(|text| !text.is_empty())
+                    .map(ToOwned::to_owned)
+                    .or_else(|| value.is_number().then(|| value.to_string()))
+            })
+        };
+        let bool_claim = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                let value = claims.get(*name)?;
+                value
+                    .as_bool()
+                    .or_else(|| value.as_str().map(|text| text == "true"))
+            })
+        };
+
+        Some(Self {
+            subject: string_claim(&["sub", "id"])?,
+            email: string_claim(&["email"]),
+            email_verified: bool_claim(&["email_verified", "verified"]).unwrap_or(false),
+            name: string_claim(&[
+                "name",
+                "global_name",
+                "preferred_username",
+                "username",
+                "login",
+            ]),
+            picture: string_claim(&["picture", "avatar_url"]),
+        })
+    }
+}
+
+/// Entry of a provider's email address list, such as `GitHub`'s `/user/emails`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderEmail {
+    /// Email address
+    pub email: String,
+    /// Whether the provider has verified the address
+    #[serde(default)]
+    pub verified: bool,
+    /// Whether this is the user's primary address
+    #[serde(default)]
+    pub primary: bool,
+}
+
+/// Pick the address to sign in with from a provider's email address list
+///
+/// Only verified addresses are considered; the primary one is preferred.
+#[must_use]
+pub fn verified_email(emails: &[ProviderEmail]) -> Option<&str> {
+    let mut verified = emails.iter().filter(|entry| entry.verified);
+    verified
+        .clone()
+        .find(|entry| entry.primary)
+        .or_else(|| verified.next())
+        .map(|entry| entry.email.as_str())
+}
+
+/// Values an ID token must carry to be accepted
+#[derive(Debug, Clone)]
+pub struct IdTokenExpectations<'exp> {
+    /// Issuer from the provider's discovery document
+    pub issuer: &'exp str,
+    /// Other spellings of the issuer configured for the provider
+    pub issuer_aliases: &'exp [String],
+    /// Our client ID, which must be an audience of the token
+    pub client_id: &'exp str,
+    /// Nonce sent with the authorization request
+    pub nonce: &'exp str,
+    /// Current Unix timestamp
+    pub now: i64,
+}
+
+/// Algorithm a provider key verifies signatures with
+///
+/// Only `RS256` keys and `ES256` keys on P-256 are accepted; a key that
+/// names another algorithm in `alg` is not used at all.
+fn signing_algorithm(key: &Jwk) -> Option<Algorithm> {
+    let algorithm = match key.algorithm {
+        AlgorithmParameters::RSA(_) => Algorithm::RS256,
+        AlgorithmParameters::EllipticCurve(ref params) if params.curve == EllipticCurve::P256 => {
+            Algorithm::ES256
+        }
+        _ => return None,
+    };
+    let matches_key_alg = key.common.key_algorithm.map_or(true, |key_algorithm| {
+        matches!(
+            (algorithm, key_algorithm),
+            (Algorithm::RS256, KeyAlgorithm::RS256) | (Algorithm::ES256, KeyAlgorithm::ES256)
+        )
+    });
+    matches_key_alg.then_some(algorithm)
+}
+
+/// Validate an ID token's signature and claims, returning the claims
+///
+/// The signing key is picked by `kid`; a token without one is only accepted
+/// when the key set holds a single key. Besides the discovered issuer, only
+/// the aliases configured for the provider are accepted as `iss`, such as the
+/// scheme-less form Google still issues some tokens with.
+///
+/// # Errors
+/// Returns `ServiceError::Unauthorized` if no key matches, the signature is
+/// invalid, or any of `iss`, `aud`, `azp`, `exp`, `iat` or `nonce` is wrong.
+pub fn validate_id_token(
+    token: &str,
+    keys: &[Jwk],
+    expected: &IdTokenExpectations<'_>,
+) -> Result<Value, ServiceError> {
+    let header = jsonwebtoken::decode_header(token)
+        .map_err(|_decode_err| invalid_id_token("malformed token"))?;
+    let key = match header.kid.as_deref() {
+        Some(kid) => keys
+            .iter()
+            .find(|key| key.common.key_id.as_deref() == Some(kid)),
+        None if keys.len() == 1 => keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| invalid_id_token("no matching signing key"))?;
+    let algorithm =
+        signing_algorithm(key).ok_or_else(|| invalid_id_token("unsupported signing key"))?;
+    let decoding_key =
+        DecodingKey::from_jwk(key).map_err(|_key_err| invalid_id_token("unusable signing key"))?;
+
+    let mut validation = Validation::new(algorithm);
+    validation.set_required_spec_claims(&["iss", "aud", "exp"]);
+    // Expiry and issue time are checked below against `expected.now`
+    validation.validate_exp = false;
+    let mut issuers = vec![expected.issuer];
+    issuers.extend(expected.issuer_aliases.iter().map(String::as_str));
+    validation.set_issuer(&issuers);
+    validation.set_audience(&[expected.client_id]);
+
+    let claims = jsonwebtoken::decode::<Value>(token, &decoding_key, &validation)
+        .map_err(|e| match *e.kind() {
+            ErrorKind::InvalidIssuer => invalid_id_token("unexpected issuer"),
+            ErrorKind::InvalidAudience => invalid_id_token("unexpected audience"),
+            ErrorKind::MissingRequiredClaim(ref claim) => {
+                invalid_id_token(&format!("missing {claim} claim"))
+            }
+            ErrorKind::InvalidSignature | ErrorKind::InvalidAlgorithm => {
+                invalid_id_token("invalid signature")
+            }
+            _ => invalid_id_token("malformed token"),
+        })?
+        .claims;
+
+    let audience = claims.get("aud");
+    let audiences: Vec<&str> = audience.and_then(Value::as_array).map_or_else(
+        || audience.and_then(Value::as_str).into_iter().collect(),
+        |values| values.iter().filter_map(Value::as_str).collect(),
+    );
+    let authorized_party = claims.get("azp").and_then(Value::as_str);
+    if (audiences.len() > 1 || authorized_party.is_some())
+        && authorized_party != Some(expected.client_id)
+    {
+        return Err(invalid_id_token("unexpected authorized party"));
+    }
+
+    let expires_at = claims.get("exp").and_then(Value::as_i64).unwrap_or(0);
+    if expires_at.saturating_add(CLOCK_SKEW_SECONDS) < expected.now {
+        return Err(invalid_id_token("token has expired"));
+    }
+    if claims
+        .get("iat")
+        .and_then(Value::as_i64)
+        .is_some_and(|issued_at| issued_at.saturating_sub(CLOCK_SKEW_SECONDS) > expected.now)
+    {
+        return Err(invalid_id_token("token was issued in the future"));
+    }
+
+    if claims.get("nonce").and_then(Value::as_str) != Some(expected.nonce) {
+        return Err(invalid_id_token("nonce mismatch"));
+    }
+
+    Ok(claims)
+}
+
+fn invalid_id_token(reason: &str) -> ServiceError {
+    ServiceError::Unauthorized(format!("Invalid ID token: {reason}"))
+}
+
+/// Derive the nonce sent with an authorization request from its `OAuth` state
+///
+/// The state is kept in the session, so binding the nonce to it ties the ID
+/// token to the browser session that started the flow without storing more.
+#[must_use]
+pub fn nonce_for_state(state: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"oidc-nonce:");
+    hasher.update(state.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Key set fetched from a provider's `jwks_uri`
+#[derive(Debug, Clone)]
+struct CachedJwks {
+    keys: Vec<Jwk>,
+    fetched_at: Instant,
+}
+
+/// A configured sign-in provider
+#[derive(Clone, Debug)]
+pub struct OidcProvider {
+    name: String,
+    display_name: String,
+    issuer: String,
+    issuer_aliases: Vec<String>,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    scopes: String,
+    trust_email: bool,
+    endpoint_overrides: OidcEndpointOverrides,
+}
+
+impl OidcProvider {
+    /// Identifier used in URLs and on linked identities
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Human readable name
+    #[must_use]
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Whether a verified email address from this provider may sign in to an
+    /// existing account with that address
+    #[must_use]
+    pub const fn trusts_email(&self) -> bool {
+        self.trust_email
+    }
+}
+
+impl From<OidcProviderConfig> for OidcProvider {
+    fn from(config: OidcProviderConfig) -> Self {
+        Self {
+            name: config.name,
+            display_name: config.display_name,
+            issuer: config.issuer,
+            issuer_aliases: config.issuer_aliases,
+            client_id: config.client_id,
+            client_secret: config.client_secret,
+            redirect_url: config.redirect_url,
+            scopes: config.scopes,
+            trust_email: config.trust_email,
+            endpoint_overrides: config.endpoint_overrides,
+        }
+    }
+}
+
+/// `OpenID Connect` client for every configured sign-in provider
+///
+/// Endpoints are read from each provider's discovery document, which is fetched
+/// on first use and cached for the lifetime of the process. Providers without a
+/// discovery document can have their endpoints configured directly.
+///
+/// Providers that publish a `jwks_uri` are treated as full `OpenID Connect`
+/// providers: their ID token is verified locally against the cached key set and
+/// its claims are used directly. Other providers fall back to the userinfo endpoint.
+#[derive(Clone, Debug)]
+pub struct OidcService {
+    client: Client,
+    providers: Arc<[OidcProvider]>,
+    discovery_cache: Arc<RwLock<HashMap<String, DiscoveryDocument>>>,
+    jwks_cache: Arc<RwLock<HashMap<String, CachedJwks>>>,
+}
+
+impl OidcService {
+    /// Create a new `OidcService` for the configured providers
+    #[must_use]
+    pub fn new(providers: Vec<OidcProviderConfig>) -> Self {
+        Self {
+            client: Client::new(),
+            providers: providers.into_iter().map(OidcProvider::from).collect(),
+            discovery_cache: Arc::new(RwLock::new(HashMap::new())),
+            jwks_cache: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// All configured providers
+    #[must_use]
+    pub fn providers(&self) -> &[OidcProvider] {
+        &self.providers
+    }
+
+    /// Look up a provider by name
+    ///
+    /// # Errors
+    /// Returns `ServiceError::NotFound` if no provider with that name is configured
+    pub fn provider(&self, name: &str) -> Result<&OidcProvider, ServiceError> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name == name)
+            .ok_or_else(|| ServiceError::NotFound(format!("Unknown sign-in provider: {name}")))
+    }
+
+    /// Fetch (or return the cached) discovery document of a provider, applying endpoint overrides
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the discovery document cannot be fetched or parsed
+    #[instrument(skip(self, provider), fields(provider = %provider.name))]
+    pub async fn discovery(
+        &self,
+        provider: &OidcProvider,
+    ) -> Result<DiscoveryDocument, ServiceError> {
+        if let Some(document) = self
+            .discovery_cache
+            .read()
+            .map_err(|_poisoned| ServiceError::Unknown("OIDC discovery cache poisoned".to_owned()))?
+            .get(&provider.name)
+        {
+            return Ok(document.clone());
+        }
+
+        let overrides = &provider.endpoint_overrides;
+        let document = if let (Some(authorization_endpoint), Some(token_endpoint)) =
+            (overrides.authorization.clone(), overrides.token.clone())
+        {
+            DiscoveryDocument {
+                issuer: provider.issuer.clone(),
+                authorization_endpoint,
+                token_endpoint,
+                userinfo_endpoint: overrides.userinfo.clone(),
+                jwks_uri: None,
+            }
+        } else {
+            let url = format!("{}/.well-known/openid-configuration", provider.issuer);
+            let resp = self.client.get(&url).send().await?;
+            if !resp.status().is_success() {
+                return Err(ServiceError::Unknown(format!(
+                    "OIDC discovery for {} failed: {}",
+                    provider.name,
+                    resp.status()
+                )));
+            }
+            let mut document = resp.json::<DiscoveryDocument>().await?;
+            if let Some(ref userinfo_endpoint) = overrides.userinfo {
+                document.userinfo_endpoint = Some(userinfo_endpoint.clone());
+            }
+            document
+        };
+
+        let _ = self
+            .discovery_cache
+            .write()
+            .map_err(|_poisoned| ServiceError::Unknown("OIDC discovery cache poisoned".to_owned()))?
+            .insert(provider.name.clone(), document.clone());
+
+        Ok(document)
+    }
+
+    /// Build the URL the user is redirected to for authorization
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the discovery document cannot be fetched
+    pub async fn authorization_url(
+        &self,
+        provider: &OidcProvider,
+        state: &str,
+    ) -> Result<String, ServiceError> {
+        let document = self.discovery(provider).await?;
+        let separator = if document.authorization_endpoint.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+
+        let mut url = format!(
+            "{}{separator}client_id={}&redirect_uri={}&response_type=code&scope={}",
+            document.authorization_endpoint,
+            urlencoding::encode(&provider.client_id),
+            urlencoding::encode(&provider.redirect_url),
+            urlencoding::encode(&provider.scopes),
+        );
+        // Writing to a String is infallible
+        #[allow(clippy::let_underscore_must_use)]
+        let _ = write!(url, "&state={}", urlencoding::encode(state));
+        if document.jwks_uri.is_some() {
+            #[allow(clippy::let_underscore_must_use)]
+            let _ = write!(url, "&nonce={}", nonce_for_state(state));
+        }
+
+        Ok(url)
+    }
+
+    /// Exchange an authorization code and return the claims of the user who signed in
+    ///
+    /// `state` must already have been checked against the session; it is used to
+    /// recompute the nonce the ID token has to carry.
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the exchange fails, an `OpenID Connect`
+    /// provider returns no ID token or an invalid one, or userinfo cannot be fetched
+    pub async fn authenticate(
+        &self,
+        provider: &OidcProvider,
+        code: &str,
+        state: &str,
+    ) -> Result<OidcUserInfo, ServiceError> {
+        let document = self.discovery(provider).await?;
+        let token_response = self.exchange_code(provider, code).await?;
+
+        if document.jwks_uri.is_none() {
+            return self.user_info(provider, &token_response.access_token).await;
+        }
+
+        let Some(id_token) = token_response.id_token else {
+            return Err(ServiceError::Unauthorized(format!(
+                "{} did not return an ID token",
+                provider.display_name
+            )));
+        };
+        self.verify_id_token(provider, &id_token, &nonce_for_state(state))
+            .await
+    }
+
+    /// Verify an ID token against the provider's cached key set and return its claims
+    ///
+    /// # Errors
+    /// Returns `ServiceError::Unauthorized` if the token is invalid, or another
+    /// `ServiceError` if the key set cannot be fetched
+    #[instrument(skip(self, provider, id_token, nonce), fields(provider = %provider.name))]
+    pub async fn verify_id_token(
+        &self,
+        provider: &OidcProvider,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<OidcUserInfo, ServiceError> {
+        let document = self.discovery(provider).await?;
+        let Some(ref jwks_uri) = document.jwks_uri else {
+            return Err(ServiceError::Config(format!(
+                "{} has no JWKS URI",
+                provider.display_name
+            )));
+        };
+
+        let header = jsonwebtoken::decode_header(id_token)
+            .map_err(|_decode_err| invalid_id_token("malformed token"))?;
+        let keys = self
+            .signing_keys(provider, jwks_uri, header.kid.as_deref())
+            .await?;
+        let claims = validate_id_token(
+            id_token,
+            &keys,
+            &IdTokenExpectations {
+                issuer: &document.issuer,
+                issuer_aliases: &provider.issuer_aliases,
+                client_id: &provider.client_id,
+                nonce,
+                now: Utc::now().timestamp(),
+            },
+        )?;
+
+        OidcUserInfo::from_claims(&claims).ok_or_else(|| invalid_id_token("token has no subject"))
+    }
+
+    /// Return the provider's signing keys, refetching when stale or when `kid` is unknown
+    ///
+    /// Unknown key IDs usually mean the provider rotated its keys, but refetches
+    /// they trigger are limited so forged tokens cannot make us hammer the provider.
+    async fn signing_keys(
+        &self,
+        provider: &OidcProvider,
+        jwks_uri: &str,
+        kid: Option<&str>,
+    ) -> Result<Vec<Jwk>, ServiceError> {
+        let cached = self
+            .jwks_cache
+            .read()
+            .map_err(|_poisoned| ServiceError::Unknown("JWKS cache poisoned".to_owned()))?
+            .get(&provider.name)
+            .cloned();
+
+        if let Some(jwks) = cached {
+            let age = jwks.fetched_at.elapsed();
+            let has_key = kid.map_or(true, |id| {
+                jwks.keys
+                    .iter()
+                    .any(|key| key.common.key_id.as_deref() == Some(id))
+            });
+            if age < JWKS_TTL && (has_key || age < JWKS_MIN_REFRESH) {
+                return Ok(jwks.keys);
+            }
+        }
+
+        let resp = self.client.get(jwks_uri).send().await?;
+        if !resp.status().is_success() {
+            return Err(ServiceError::Unknown(format!(
+                "{} JWKS fetch failed: {}",
+                provider.display_name,
+                resp.status()
+            )));
+        }
+        let key_set = resp.json::<JwkSet>().await?;
+
+        let _ = self
+            .jwks_cache
+            .write()
+            .map_err(|_poisoned| ServiceError::Unknown("JWKS cache poisoned".to_owned()))?
+            .insert(
+                provider.name.clone(),
+                CachedJwks {
+                    keys: key_set.keys.clone(),
+                    fetched_at: Instant::now(),
+                },
+            );
+
+        Ok(key_set.keys)
+    }
+
+    /// Exchange an authorization code for tokens
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the HTTP request fails or the provider returns an error
+    #[instrument(skip(self, provider, code), fields(provider = %provider.name))]
+    pub async fn exchange_code(
+        &self,
+        provider: &OidcProvider,
+        code: &str,
+    ) -> Result<OidcTokenResponse, ServiceError> {
+        let document = self.discovery(provider).await?;
+        let params = [
+            ("code", code),
+            ("client_id", &provider.client_id),
+            ("client_secret", &provider.client_secret),
+            ("redirect_uri", &provider.redirect_url),
+            ("grant_type", "authorization_code"),
+        ];
+
+        let resp = self
+            .client
+            .post(&document.token_endpoint)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&params)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ServiceError::Unknown(format!(
+                "{} token exchange failed: {}",
+                provider.display_name,
+                resp.status()
+            )));
+        }
+
+        Ok(resp.json::<OidcTokenResponse>().await?)
+    }
+
+    /// Retrieve claims about the user from the provider's userinfo endpoint
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the provider has no userinfo endpoint, the
+    /// request fails, or the response lacks a subject
+    #[instrument(skip(self, provider, access_token), fields(provider = %provider.name))]
+    pub async fn user_info(
+        &self,
+        provider: &OidcProvider,
+        access_token: &str,
+    ) -> Result<OidcUserInfo, ServiceError> {
+        let document = self.discovery(provider).await?;
+        let Some(userinfo_endpoint) = document.userinfo_endpoint else {
+            return Err(ServiceError::Config(format!(
+                "{} has no userinfo endpoint",
+                provider.display_name
+            )));
+        };
+
+        let resp = self
+            .client
+            .get(&userinfo_endpoint)
+            .bearer_auth(access_token)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .header(reqwest::header::USER_AGENT, "patron-backend")
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ServiceError::Unknown(format!(
+                "{} userinfo fetch failed: {}",
+                provider.display_name,
+                resp.status()
+            )));
+        }
+
+        let claims = resp.json::<Value>().await?;
+        let mut user_info = OidcUserInfo::from_claims(&claims).ok_or_else(|| {
+            ServiceError::Unknown(format!(
+                "{} userinfo response has no subject",
+                provider.display_name
+            ))
+        })?;
+
+        if let Some(ref emails_endpoint) = provider.endpoint_overrides.emails {
+            let emails = self
+                .provider_emails(provider, emails_endpoint, access_token)
+                .await?;
+            user_info.email = verified_email(&emails).map(ToOwned::to_owned);
+            user_info.email_verified = user_info.email.is_some();
+        }
+
+        Ok(user_info)
+    }
+
+    /// Retrieve the user's email addresses from the provider's email list endpoint
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the request fails or the response is not a list of addresses
+    async fn provider_emails(
+        &self,
+        provider: &OidcProvider,
+        emails_endpoint: &str,
+        access_token: &str,
+    ) -> Result<Vec<ProviderEmail>, ServiceError> {
+        let resp = self
+            .client
+            .get(emails_endpoint)
+            .bearer_auth(access_token)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .header(reqwest::header::USER_AGENT, "patron-backend")
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(ServiceError::Unknown(format!(
+                "{} email list fetch failed: {}",
+                provider.display_name,
+                resp.status()
+            )));
+        }
+
+        Ok(resp.json::<Vec<ProviderEmail>>().await?)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use jsonwebtoken::{EncodingKey, Header};
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use serde_json::json;
+
+    const ISSUER: &str = "https://accounts.google.com";
+    const CLIENT_ID: &str = "patron-client";
+    const NOW: i64 = 1_700_000_000;
+
+    fn signing_key() -> (EncodingKey, Jwk) {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                .unwrap();
+        let point = key_pair.public_key().as_ref();
+        let jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "kid": "test-key",
+            "alg": "ES256",
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+        }))
+        .unwrap();
+        (EncodingKey::from_ec_der(pkcs8.as_ref()), jwk)
+    }
+
+    fn sign(key: &EncodingKey, claims: &Value) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("test-key".to_owned());
+        jsonwebtoken::encode(&header, claims, key).unwrap()
+    }
+
+    fn claims(nonce: &str) -> Value {
+        json!({
+            "iss": "accounts.google.com",
+            "aud": CLIENT_ID,
+            "sub": "110169484474386276334",
+            "email": "user@gmail.com",
+            "email_verified": true,
+            "iat": NOW,
+            "exp": NOW + 3600,
+            "nonce": nonce,
+        })
+    }
+
+    fn expectations<'exp>(
+        nonce: &'exp str,
+        issuer_aliases: &'exp [String],
+    ) -> IdTokenExpectations<'exp> {
+        IdTokenExpectations {
+            issuer: ISSUER,
+            issuer_aliases,
+            client_id: CLIENT_ID,
+            nonce,
+            now: NOW,
+        }
+    }
+
+    fn google_aliases() -> Vec<String> {
+        vec!["accounts.google.com".to_owned()]
+    }
+
+    #[test]
+    fn test_valid_id_token_is_accepted() {
+        let (key, jwk) = signing_key();
+        let nonce = nonce_for_state("state");
+        let token = sign(&key, &claims(&nonce));
+
+        let claims =
+            validate_id_token(&token, &[jwk], &expectations(&nonce, &google_aliases())).unwrap();
+        let user_info = OidcUserInfo::from_claims(&claims).unwrap();
+        assert_eq!(user_info.subject, "110169484474386276334");
+        assert!(user_info.email_verified);
+    }
+
+    #[test]
+    fn test_scheme_less_issuer_needs_an_alias() {
+        let (key, jwk) = signing_key();
+        let nonce = nonce_for_state("state");
+        let token = sign(&key, &claims(&nonce));
+
+        assert!(matches!(
+            validate_id_token(&token, &[jwk], &expectations(&nonce, &[])),
+            Err(ServiceError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_id_token_with_other_nonce_is_rejected() {
+        let (key, jwk) = signing_key();
+        let token = sign(&key, &claims(&nonce_for_state("other-state")));
+        let nonce = nonce_for_state("state");
+
+        assert!(matches!(
+            validate_id_token(&token, &[jwk], &expectations(&nonce, &google_aliases())),
+            Err(ServiceError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_id_token_for_other_audience_is_rejected() {
+        let (key, jwk) = signing_key();
+        let nonce = nonce_for_state("state");
+        let mut token_claims = claims(&nonce);
+        token_claims["aud"] = json!("someone-else");
+        let token = sign(&key, &token_claims);
+
+        assert!(matches!(
+            validate_id_token(&token, &[jwk], &expectations(&nonce, &google_aliases())),
+            Err(ServiceError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_expired_id_token_is_rejected() {
+        let (key, jwk) = signing_key();
+        let nonce = nonce_for_state("state");
+        let mut token_claims = claims(&nonce);
+        token_claims["exp"] = json!(NOW - 3600);
+        let token = sign(&key, &token_claims);
+
+        assert!(matches!(
+            validate_id_token(&token, &[jwk], &expectations(&nonce, &google_aliases())),
+            Err(ServiceError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_verified_email_prefers_primary_verified_address() {
+        let emails: Vec<ProviderEmail> = serde_json::from_value(json!([
+            { "email": "old@example.com", "verified": true, "primary": false },
+            { "email": "unverified@example.com", "verified": false, "primary": false },
+            { "email": "main@example.com", "verified": true, "primary": true },
+        ]))
+        .unwrap();
+        assert_eq!(verified_email(&emails), Some("main@example.com"));
+
+        let unverified: Vec<ProviderEmail> = serde_json::from_value(json!([
+            { "email": "main@example.com", "verified": false, "primary": true },
+        ]))
+        .unwrap();
+        assert_eq!(verified_email(&unverified), None);
+    }
+
+    #[test]
+    fn test_id_token_signed_with_unknown_key_is_rejected() {
+        let (key, _) = signing_key();
+        let (_, other_jwk) = signing_key();
+        let nonce = nonce_for_state("state");
+        let token = sign(&key, &claims(&nonce));
+
+        assert!(matches!(
+            validate_id_token(
+                &token,
+                &[other_jwk],
+                &expectations(&nonce, &google_aliases())
+            ),
+            Err(ServiceError::Unauthorized(_))
+        ));
+    }
+}
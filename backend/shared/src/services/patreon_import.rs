@@ -0,0 +1,912 @@
+use crate::errors::ServiceError;
+use crate::models::imports::{
+    MappingAction, MemberMapping, PatreonImportReport, PatreonImportSummary, PostMapping,
+    TierMapping,
+};
+use crate::models::invitations::{Invitation, InvitationStatus};
+use crate::models::jobs::BackgroundJob;
+use crate::models::posts::Post;
+use crate::models::tiers::Tier;
+use crate::services::jobs::{JobContext, JobOutcome};
+use crate::services::post_import::{
+    read_zip, truncate_field, unique_slug, MAX_IMPORT_ARCHIVE_SIZE,
+};
+use crate::services::series_length::refresh_series_length;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Name of the Patreon posts export inside an import's input archive
+pub const POSTS_ENTRY: &str = "posts.json";
+
+/// Name of the Patreon members CSV inside an import's input archive
+pub const MEMBERS_ENTRY: &str = "members.csv";
+
+/// How long the uploaded export is kept after a dry run, to run the import for real
+pub const IMPORT_INPUT_TTL_HOURS: i64 = 72;
+
+/// Value of `invitations.source` for members brought over from Patreon
+const INVITATION_SOURCE: &str = "patreon";
+
+/// Longest email address the database accepts
+const MAX_EMAIL_LENGTH: usize = 255;
+
+/// Input of a Patreon import job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatreonImportPayload {
+    /// Series the posts go into; required when the export has posts
+    #[serde(rename = "seriesId")]
+    pub series_id: Option<Uuid>,
+    /// Only report what would be imported
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+    /// Bucket key of the archive holding the uploaded export
+    #[serde(rename = "inputKey")]
+    pub input_key: String,
+}
+
+/// Tier read from a Patreon posts export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatreonTier {
+    /// Patreon tier ID
+    pub id: String,
+    /// Tier name
+    pub title: String,
+    /// Monthly price in cents
+    pub amount_cents: i32,
+    /// Tier description
+    pub description: Option<String>,
+}
+
+/// Post read from a Patreon posts export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatreonPost {
+    /// Patreon post ID
+    pub id: String,
+    /// Post title
+    pub title: String,
+    /// Post body as HTML
+    pub content: String,
+    /// When the post was published; drafts have none
+    pub published_at: Option<NaiveDateTime>,
+    /// Whether anyone can read the post
+    pub is_public: bool,
+    /// Patreon IDs of the tiers the post is open to
+    pub tier_ids: Vec<String>,
+    /// Smallest pledge that unlocks the post, in cents
+    pub min_cents: Option<i32>,
+}
+
+/// Posts and tiers read from a Patreon posts export
+#[derive(Debug, Clone, Default)]
+pub struct PatreonExport {
+    /// Posts, in export order
+    pub posts: Vec<PatreonPost>,
+    /// Tiers the posts refer to
+    pub tiers: Vec<PatreonTier>,
+}
+
+/// Row of a Patreon members CSV
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatreonMember {
+    /// Member's name
+    pub name: Option<String>,
+    /// Member's email address
+    pub email: String,
+    /// Name of the member's tier
+    pub tier: Option<String>,
+    /// Patreon's status, e.g. `Active patron` or `Former patron`
+    pub patron_status: Option<String>,
+    /// Current pledge, as written in the CSV
+    pub pledge_amount: Option<String>,
+    /// Total pledged, as written in the CSV
+    pub lifetime_amount: Option<String>,
+    /// Patreon user ID
+    pub user_id: Option<String>,
+    /// When the member started pledging, as written in the CSV
+    pub patronage_since: Option<String>,
+}
+
+/// What the creator already has that an import must not duplicate
+#[derive(Debug, Clone, Default)]
+pub struct ImportTarget {
+    /// Creator importing
+    pub user_id: Uuid,
+    /// Series the posts go into
+    pub series_id: Option<Uuid>,
+    /// Creator's tiers
+    pub tiers: Vec<Tier>,
+    /// Slug, number, title and creation date of every post of the series
+    pub posts: Vec<(String, i32, String, Option<NaiveDateTime>)>,
+    /// Addresses the creator has already invited, lowercased
+    pub invited_emails: HashSet<String>,
+}
+
+/// Helper struct for a tier the export needs, before it is matched or created
+#[derive(Debug)]
+struct WantedTier<'export> {
+    patreon_id: Option<&'export str>,
+    name: String,
+    amount_cents: Option<i32>,
+    description: Option<String>,
+}
+
+/// Rows a Patreon import creates, with the report describing them
+#[derive(Debug)]
+pub struct PatreonImportPlan {
+    /// Tiers to create
+    pub tiers: Vec<Tier>,
+    /// Posts to create
+    pub posts: Vec<Post>,
+    /// Invitations to create
+    pub invitations: Vec<Invitation>,
+    /// Mapping report
+    pub report: PatreonImportReport,
+}
+
+/// Run a Patreon import job
+///
+/// Reads the uploaded export, maps it onto the creator's tiers, series and
+/// invitations and, unless it is a dry run, creates everything in one
+/// transaction. A dry run keeps the upload so the import can be run for real
+/// without uploading it again.
+///
+/// # Errors
+/// Returns an error if the upload cannot be read or parsed, the series is
+/// gone, or database operations fail.
+#[allow(clippy::too_many_lines)]
+pub async fn run_patreon_import(
+    ctx: &JobContext,
+    job: &BackgroundJob,
+) -> Result<JobOutcome, ServiceError> {
+    use crate::schema::{
+        invitations::dsl as invitations_dsl, posts::dsl as posts_dsl, series::dsl as series_dsl,
+        tiers::dsl as tiers_dsl,
+    };
+
+    let payload: PatreonImportPayload = serde_json::from_value(job.payload.clone())?;
+
+    let mut stream = ctx.s3_service.get_object_stream(&payload.input_key).await?;
+    let mut archive = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        archive.extend_from_slice(&chunk?);
+    }
+    let mut export = PatreonExport::default();
+    let mut members = Vec::new();
+    for entry in read_zip(&archive, MAX_IMPORT_ARCHIVE_SIZE)? {
+        match entry.name.as_str() {
+            POSTS_ENTRY => export = parse_posts_export(&entry.contents)?,
+            MEMBERS_ENTRY => members = parse_members_csv(&entry.contents)?,
+            _ => {}
+        }
+    }
+
+    let pool = ctx.db_service.pool();
+    let mut conn = pool.get().await.map_err(ServiceError::from)?;
+
+    let mut target = ImportTarget {
+        user_id: job.user_id,
+        series_id: payload.series_id,
+        ..ImportTarget::default()
+    };
+    if let Some(series_id) = payload.series_id {
+        let owns_series = diesel::select(diesel::dsl::exists(
+            series_dsl::series
+                .filter(series_dsl::id.eq(series_id))
+                .filter(series_dsl::user_id.eq(job.user_id))
+                .filter(series_dsl::deleted_at.is_null()),
+        ))
+        .get_result::<bool>(&mut conn)
+        .await?;
+        if !owns_series {
+            return Err(ServiceError::NotFound("Series not found".to_owned()));
+        }
+        // Deleted posts keep their slug and number, so they are taken too
+        target.posts = posts_dsl::posts
+            .filter(posts_dsl::series_id.eq(series_id))
+            .select((
+                posts_dsl::slug,
+                posts_dsl::number,
+                posts_dsl::title,
+                posts_dsl::created_at,
+            ))
+            .load(&mut conn)
+            .await?;
+    }
+    if payload.series_id.is_none() && !export.posts.is_empty() {
+        return Err(ServiceError::Config(
+            "A series is required to import posts".to_owned(),
+        ));
+    }
+    target.tiers = tiers_dsl::tiers
+        .filter(tiers_dsl::user_id.eq(job.user_id))
+        .load(&mut conn)
+        .await?;
+    target.invited_emails = invitations_dsl::invitations
+        .filter(invitations_dsl::creator_id.eq(job.user_id))
+        .select(invitations_dsl::email)
+        .load::<String>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|email| email.to_lowercase())
+        .collect();
+
+    let plan = plan_patreon_import(&export, &members, &target, payload.dry_run);
+    let result = serde_json::to_value(&plan.report)?;
+
+    if payload.dry_run {
+        let expires_at = Utc::now()
+            .naive_utc()
+            .checked_add_signed(Duration::hours(IMPORT_INPUT_TTL_HOURS));
+        return Ok(JobOutcome {
+            result,
+            output_key: Some(payload.input_key),
+            output_expires_at: expires_at,
+        });
+    }
+
+    let target_series = payload.series_id;
+    conn.transaction::<_, ServiceError, _>(|tx| {
+        async move {
+            let _ = diesel::insert_into(tiers_dsl::tiers)
+                .values(&plan.tiers)
+                .execute(tx)
+                .await?;
+            let _ = diesel::insert_into(posts_dsl::posts)
+                .values(&plan.posts)
+                .execute(tx)
+                .await?;
+            let _ = diesel::insert_into(invitations_dsl::invitations)
+                .values(&plan.invitations)
+                .on_conflict_do_nothing()
+                .execute(tx)
+                .await?;
+            if let Some(series_id) = target_series {
+                refresh_series_length(tx, series_id).await?;
+            }
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    if let Err(e) = ctx.s3_service.delete_object(&payload.input_key).await {
+        tracing::warn!("Failed to delete input of Patreon import {}: {}", job.id, e);
+    }
+
+    Ok(JobOutcome {
+        result,
+        output_key: None,
+        output_expires_at: None,
+    })
+}
+
+/// Read a Patreon posts export
+///
+/// The export is the JSON:API document Patreon's posts API returns: posts in
+/// `data`, with their tiers either listed directly under
+/// `relationships.tiers` or through `access_rules`, and the tiers themselves
+/// (typed `tier` or `reward`) in `included`. A bare array of posts is accepted too.
+///
+/// # Errors
+/// Returns a `ServiceError::Config` if the file is not such a document.
+#[allow(clippy::too_many_lines)]
+pub fn parse_posts_export(contents: &[u8]) -> Result<PatreonExport, ServiceError> {
+    let document: Value = serde_json::from_slice(contents)
+        .map_err(|e| ServiceError::Config(format!("{POSTS_ENTRY} is not valid JSON: {e}")))?;
+    let (data, included) = match document {
+        Value::Array(data) => (data, Vec::new()),
+        Value::Object(mut object) => {
+            let Some(Value::Array(data)) = object.remove("data") else {
+                return Err(ServiceError::Config(format!(
+                    "{POSTS_ENTRY} has no data array of posts"
+                )));
+            };
+            let included = match object.remove("included") {
+                Some(Value::Array(included)) => included,
+                _ => Vec::new(),
+            };
+            (data, included)
+        }
+        _ => {
+            return Err(ServiceError::Config(format!(
+                "{POSTS_ENTRY} is not a Patreon posts export"
+            )))
+        }
+    };
+
+    let mut tiers = Vec::new();
+    let mut access_rule_tiers: HashMap<String, String> = HashMap::new();
+    for resource in &included {
+        let Some(id) = resource_id(resource) else {
+            continue;
+        };
+        match resource.get("type").and_then(Value::as_str) {
+            Some("tier" | "reward") => {
+                let attributes = resource.get("attributes").unwrap_or(&Value::Null);
+                let title = text(attributes, "title").unwrap_or_default();
+                // Patreon lists "Everyone" and "Patrons only" pseudo-tiers without an amount
+                if title.is_empty() || id == "-1" || id == "0" {
+                    continue;
+                }
+                tiers.push(PatreonTier {
+                    id,
+                    title,
+                    amount_cents: cents(attributes, "amount_cents").unwrap_or(0_i32),
+                    description: text(attributes, "description"),
+                });
+            }
+            Some("access-rule" | "access_rule") => {
+                if let Some(tier_id) = resource
+                    .pointer("/relationships/tier/data")
+                    .and_then(resource_id)
+                {
+                    let _ = access_rule_tiers.insert(id, tier_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut posts = Vec::with_capacity(data.len());
+    for resource in &data {
+        let Some(id) = resource_id(resource) else {
+            continue;
+        };
+        let attributes = resource.get("attributes").unwrap_or(&Value::Null);
+
+        let mut tier_ids: Vec<String> = related_ids(resource, "tiers");
+        tier_ids.extend(
+            related_ids(resource, "access_rules")
+                .iter()
+                .filter_map(|rule| access_rule_tiers.get(rule).cloned()),
+        );
+
+        posts.push(PatreonPost {
+            id,
+            title: text(attributes, "title").unwrap_or_default(),
+            content: text(attributes, "content").unwrap_or_default(),
+            published_at: text(attributes, "published_at").and_then(|date| {
+                DateTime::parse_from_rfc3339(&date)
+                    .ok()
+                    .map(|dt| dt.naive_utc())
+            }),
+            is_public: attributes
+                .get("is_public")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            tier_ids,
+            min_cents: cents(attributes, "min_cents_pledged_to_view").filter(|c| *c > 0_i32),
+        });
+    }
+
+    Ok(PatreonExport { posts, tiers })
+}
+
+/// Read a Patreon members CSV, as exported from the relationship manager
+///
+/// Columns are found by header name, so their order and any extra columns
+/// do not matter. Only `Email` is required.
+///
+/// # Errors
+/// Returns a `ServiceError::Config` if the CSV cannot be read or has no
+/// `Email` column.
+pub fn parse_members_csv(contents: &[u8]) -> Result<Vec<PatreonMember>, ServiceError> {
+    let without_bom = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(without_bom);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| ServiceError::Config(format!("{MEMBERS_ENTRY} cannot be read: {e}")))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+    };
+    let email_column = column("Email")
+        .ok_or_else(|| ServiceError::Config(format!("{MEMBERS_ENTRY} has no Email column")))?;
+    let name_column = column("Name");
+    let tier_column = column("Tier");
+    let status_column = column("Patron Status");
+    let pledge_column = column("Pledge Amount");
+    let lifetime_column = column("Lifetime Amount");
+    let user_id_column = column("User ID");
+    let since_column = column("Patronage Since Date");
+
+    let mut members = Vec::new();
+    for row in reader.records() {
+        let record =
+            row.map_err(|e| ServiceError::Config(format!("{MEMBERS_ENTRY} cannot be read: {e}")))?;
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|i| record.get(i))
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+        };
+        members.push(PatreonMember {
+            name: field(name_column),
+            email: field(Some(email_column)).unwrap_or_default(),
+            tier: field(tier_column),
+            patron_status: field(status_column),
+            pledge_amount: field(pledge_column),
+            lifetime_amount: field(lifetime_column),
+            user_id: field(user_id_column),
+            patronage_since: field(since_column),
+        });
+    }
+
+    Ok(members)
+}
+
+/// Map a Patreon export onto the creator's tiers, series and invitations
+///
+/// Tiers are matched to existing ones by name, or created. Posts are numbered
+/// after the series' last post in publish order, keep their publish date and
+/// are locked to the cheapest tier that could read them on Patreon. Posts
+/// already imported, recognized by title and publish date, are skipped.
+/// Active patrons get a pending invitation to their tier; nobody is emailed.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn plan_patreon_import(
+    export: &PatreonExport,
+    members: &[PatreonMember],
+    target: &ImportTarget,
+    dry_run: bool,
+) -> PatreonImportPlan {
+    let now = Utc::now().naive_utc();
+    let mut summary = PatreonImportSummary::default();
+
+    // Tiers, keyed by lowercased name; Patreon IDs point into the same map
+    let mut tiers_by_name: HashMap<String, (Uuid, String, i32)> = target
+        .tiers
+        .iter()
+        .map(|tier| {
+            (
+                tier.name.to_lowercase(),
+                (tier.id, tier.name.clone(), tier.amount_cents),
+            )
+        })
+        .collect();
+    let mut tier_names_by_patreon_id: HashMap<&str, String> = HashMap::new();
+    let mut new_tiers = Vec::new();
+    let mut tier_mappings = Vec::new();
+
+    let mut wanted: Vec<WantedTier<'_>> = export
+        .tiers
+        .iter()
+        .map(|tier| WantedTier {
+            patreon_id: Some(tier.id.as_str()),
+            name: truncate_field(tier.title.trim()),
+            amount_cents: Some(tier.amount_cents),
+            description: tier.description.clone(),
+        })
+        .collect();
+    for member in members {
+        let Some(ref tier_name) = member.tier else {
+            continue;
+        };
+        let pledge = member.pledge_amount.as_deref().and_then(parse_amount);
+        if let Some(existing) = wanted
+            .iter_mut()
+            .find(|w| w.name.eq_ignore_ascii_case(tier_name))
+        {
+            // A tier only named in the CSV costs what its cheapest member pledges
+            if existing.patreon_id.is_none() {
+                existing.amount_cents = match (existing.amount_cents, pledge) {
+                    (Some(current), Some(amount)) => Some(current.min(amount)),
+                    (current, amount) => current.or(amount),
+                };
+            }
+        } else {
+            wanted.push(WantedTier {
+                patreon_id: None,
+                name: truncate_field(tier_name),
+                amount_cents: pledge,
+                description: None,
+            });
+        }
+    }
+
+    for wanted_tier in wanted {
+        let WantedTier {
+            patreon_id,
+            name,
+            description,
+            ..
+        } = wanted_tier;
+        let amount_cents = wanted_tier.amount_cents.unwrap_or(0_i32);
+        let key = name.to_lowercase();
+        let (action, tier_id) = if let Some(&(id, _, _)) = tiers_by_name.get(&key) {
+            (MappingAction::Match, id)
+        } else {
+            let id = Uuid::new_v4();
+            new_tiers.push(Tier {
+                id,
+                user_id: target.user_id,
+                name: name.clone(),
+                description,
+                amount_cents,
+                created_at: now,
+                updated_at: now,
+            });
+            let _ = tiers_by_name.insert(key.clone(), (id, name.clone(), amount_cents));
+            (MappingAction::Create, id)
+        };
+        if action == MappingAction::Match {
+            summary.tiers_matched = summary.tiers_matched.saturating_add(1);
+        } else {
+            summary.tiers_created = summary.tiers_created.saturating_add(1);
+        }
+        if let Some(id) = patreon_id {
+            let _ = tier_names_by_patreon_id.insert(id, key);
+        }
+        tier_mappings.push(TierMapping {
+            patreon_id: patreon_id.map(str::to_owned),
+            name,
+            amount_cents,
+            action,
+            tier_id,
+        });
+    }
+
+    let paid_tiers: Vec<&(Uuid, String, i32)> = {
+        let mut tiers: Vec<_> = tiers_by_name.values().collect();
+        tiers.sort_by_key(|tier| tier.2);
+        tiers
+    };
+
+    // Posts, oldest first so numbers follow the original order
+    let mut source_posts: Vec<&PatreonPost> = export.posts.iter().collect();
+    source_posts.sort_by(|a, b| {
+        (a.published_at.is_none(), a.published_at, &a.id).cmp(&(
+            b.published_at.is_none(),
+            b.published_at,
+            &b.id,
+        ))
+    });
+
+    let mut taken_slugs: HashSet<String> = target.posts.iter().map(|p| p.0.clone()).collect();
+    let imported: HashSet<(&str, Option<NaiveDateTime>)> =
+        target.posts.iter().map(|p| (p.2.as_str(), p.3)).collect();
+    let mut next_number = target.posts.iter().map(|p| p.1).max().unwrap_or(0_i32);
+    let mut new_posts = Vec::new();
+    let mut post_mappings = Vec::new();
+
+    for source in source_posts {
+        let trimmed = truncate_field(source.title.trim());
+        let title = if trimmed.is_empty() {
+            "Untitled post".to_owned()
+        } else {
+            trimmed
+        };
+
+        let lock = if source.is_public {
+            None
+        } else {
+            let named: Vec<&(Uuid, String, i32)> = source
+                .tier_ids
+                .iter()
+                .filter_map(|id| tier_names_by_patreon_id.get(id.as_str()))
+                .filter_map(|key| tiers_by_name.get(key))
+                .collect();
+            // Without named tiers, locked by pledge amount or to all paying patrons
+            let floor = source.min_cents.unwrap_or(1_i32);
+            named
+                .iter()
+                .min_by_key(|tier| tier.2)
+                .or_else(|| {
+                    paid_tiers
+                        .iter()
+                        .find(|tier| tier.2 >= floor)
+                        .or_else(|| paid_tiers.last())
+                })
+                .copied()
+        };
+
+        let Some(series_id) = target.series_id else {
+            continue;
+        };
+
+        let mut mapping = PostMapping {
+            patreon_id: source.id.clone(),
+            title: title.clone(),
+            slug: None,
+            number: None,
+            published_at: source.published_at.map(|dt| dt.and_utc()),
+            tier: lock.map(|tier| tier.1.clone()),
+            action: MappingAction::Skip,
+            reason: None,
+            post_id: None,
+        };
+
+        if imported.contains(&(title.as_str(), source.published_at)) {
+            mapping.reason = Some("Already imported".to_owned());
+            summary.posts_skipped = summary.posts_skipped.saturating_add(1);
+            post_mappings.push(mapping);
+            continue;
+        }
+
+        let slug = unique_slug(&title, &source.id, &mut taken_slugs);
+        next_number = next_number.saturating_add(1);
+        let id = Uuid::new_v4();
+        let date = source.published_at.unwrap_or(now);
+
+        new_posts.push(Post {
+            id,
+            series_id,
+            title,
+            content: html2md::parse_html(&source.content).trim().to_owned(),
+            slug: slug.clone(),
+            number: next_number,
+            is_published: Some(source.published_at.is_some()),
+            thumbnail_url: None,
+            audio_file_id: None,
+            video_file_id: None,
+            created_at: Some(date),
+            updated_at: Some(date),
+            deleted_at: None,
+            min_tier_id: lock.map(|tier| tier.0),
+        });
+        mapping.slug = Some(slug);
+        mapping.number = Some(next_number);
+        mapping.action = MappingAction::Create;
+        mapping.post_id = Some(id);
+        summary.posts_created = summary.posts_created.saturating_add(1);
+        post_mappings.push(mapping);
+    }
+
+    // Members, in CSV order
+    let mut seen_emails: HashSet<String> = HashSet::new();
+    let mut invitations = Vec::new();
+    let mut member_mappings = Vec::with_capacity(members.len());
+    for member in members {
+        let email = member.email.trim().to_lowercase();
+        let tier = member
+            .tier
+            .as_deref()
+            .and_then(|name| tiers_by_name.get(&name.to_lowercase()));
+        let mut mapping = MemberMapping {
+            email: member.email.clone(),
+            name: member.name.clone(),
+            tier: tier.map(|t| t.1.clone()),
+            action: MappingAction::Skip,
+            reason: None,
+        };
+
+        let inactive = member
+            .patron_status
+            .as_deref()
+            .filter(|status| !status.eq_ignore_ascii_case("Active patron"));
+        mapping.reason = if !is_plausible_email(&email) {
+            Some("No valid email address".to_owned())
+        } else if let Some(status) = inactive {
+            Some(format!("Not an active patron ({status})"))
+        } else if target.invited_emails.contains(&email) {
+            Some("Already invited".to_owned())
+        } else if !seen_emails.insert(email.clone()) {
+            Some("Listed more than once".to_owned())
+        } else {
+            None
+        };
+
+        if mapping.reason.is_some() {
+            summary.members_skipped = summary.members_skipped.saturating_add(1);
+        } else {
+            invitations.push(Invitation {
+                id: Uuid::new_v4(),
+                creator_id: target.user_id,
+                tier_id: tier.map(|t| t.0),
+                email,
+                name: member.name.as_deref().map(truncate_field),
+                status: InvitationStatus::Pending.into(),
+                source: INVITATION_SOURCE.to_owned(),
+                external_id: member.user_id.clone(),
+                metadata: Some(json!({
+                    "patronStatus": member.patron_status,
+                    "pledgeAmount": member.pledge_amount,
+                    "lifetimeAmount": member.lifetime_amount,
+                    "patronageSince": member.patronage_since,
+                })),
+                created_at: now,
+                updated_at: now,
+            });
+            mapping.action = MappingAction::Create;
+            summary.invitations_created = summary.invitations_created.saturating_add(1);
+        }
+        member_mappings.push(mapping);
+    }
+
+    PatreonImportPlan {
+        tiers: new_tiers,
+        posts: new_posts,
+        invitations,
+        report: PatreonImportReport {
+            dry_run,
+            series_id: target.series_id,
+            summary,
+            tiers: tier_mappings,
+            posts: post_mappings,
+            members: member_mappings,
+        },
+    }
+}
+
+/// Helper function to read the `id` of a JSON:API resource or identifier
+fn resource_id(resource: &Value) -> Option<String> {
+    let id = resource.get("id")?;
+    id.as_str()
+        .map(str::to_owned)
+        .or_else(|| id.as_u64().map(|number| number.to_string()))
+}
+
+/// Helper function to list the IDs of a to-many relationship
+fn related_ids(resource: &Value, relationship: &str) -> Vec<String> {
+    resource
+        .pointer(&format!("/relationships/{relationship}/data"))
+        .and_then(Value::as_array)
+        .map(|data| data.iter().filter_map(resource_id).collect())
+        .unwrap_or_default()
+}
+
+/// Helper function to read a string attribute
+fn text(attributes: &Value, name: &str) -> Option<String> {
+    attributes
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+}
+
+/// Helper function to read an amount of cents
+fn cents(attributes: &Value, name: &str) -> Option<i32> {
+    attributes
+        .get(name)
+        .and_then(Value::as_i64)
+        .and_then(|value| i32::try_from(value).ok())
+}
+
+/// Helper function to read an amount such as `$5.00` or `5` into cents
+fn parse_amount(amount: &str) -> Option<i32> {
+    let digits: String = amount
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let (whole_part, fraction_part) = digits.split_once('.').unwrap_or((&digits, ""));
+    let whole: i32 = whole_part.parse().ok()?;
+    let fraction: i32 = match fraction_part.len() {
+        0 => 0_i32,
+        1 => fraction_part.parse::<i32>().ok()?.checked_mul(10_i32)?,
+        _ => fraction_part.get(..2)?.parse().ok()?,
+    };
+    whole.checked_mul(100_i32)?.checked_add(fraction)
+}
+
+/// Helper function to weed out values that cannot be an email address
+fn is_plausible_email(email: &str) -> bool {
+    email.len() <= MAX_EMAIL_LENGTH
+        && email
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "data": [
+            {"id": "2", "type": "post", "attributes": {"title": "Chapter 2", "content": "<p>Two</p>",
+                "published_at": "2023-02-01T18:00:00.000+00:00", "is_public": false},
+             "relationships": {"access_rules": {"data": [{"id": "r1", "type": "access-rule"}]}}},
+            {"id": "1", "type": "post", "attributes": {"title": "Chapter 1", "content": "<p><b>One</b></p>",
+                "published_at": "2023-01-01T18:00:00.000+00:00", "is_public": true}},
+            {"id": "3", "type": "post", "attributes": {"title": "Chapter 3", "content": "",
+                "published_at": null, "is_public": false, "min_cents_pledged_to_view": 300}}
+        ],
+        "included": [
+            {"id": "10", "type": "reward", "attributes": {"title": "Silver", "amount_cents": 300}},
+            {"id": "11", "type": "reward", "attributes": {"title": "Gold", "amount_cents": 500}},
+            {"id": "r1", "type": "access-rule", "relationships": {"tier": {"data": {"id": "11", "type": "reward"}}}}
+        ]
+    }"#;
+
+    const MEMBERS: &str = "\u{feff}Name,Email,Tier,Patron Status,Pledge Amount\n\
+        Jane Reader,Jane@Example.com,Gold,Active patron,5.00\n\
+        Sam Former,sam@example.com,Silver,Former patron,3.00\n\
+        Jane Again,jane@example.com,Gold,Active patron,5.00\n\
+        Pat Bronze,pat@example.com,Bronze,Active patron,$1.50\n";
+
+    #[test]
+    fn parses_posts_export_with_access_rules() {
+        let export = parse_posts_export(EXPORT.as_bytes()).unwrap();
+        assert_eq!(export.tiers.len(), 2);
+        assert_eq!(export.posts[0].tier_ids, vec!["11".to_owned()]);
+        assert_eq!(export.posts[2].min_cents, Some(300_i32));
+        assert!(export.posts[2].published_at.is_none());
+    }
+
+    #[test]
+    fn plans_posts_tiers_and_invitations() {
+        let export = parse_posts_export(EXPORT.as_bytes()).unwrap();
+        let members = parse_members_csv(MEMBERS.as_bytes()).unwrap();
+        let target = ImportTarget {
+            user_id: Uuid::new_v4(),
+            series_id: Some(Uuid::new_v4()),
+            posts: vec![("chapter-1".to_owned(), 4_i32, "Prologue".to_owned(), None)],
+            ..ImportTarget::default()
+        };
+
+        let plan = plan_patreon_import(&export, &members, &target, true);
+
+        let names: Vec<(&str, i32)> = plan
+            .tiers
+            .iter()
+            .map(|t| (t.name.as_str(), t.amount_cents))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("Silver", 300_i32), ("Gold", 500_i32), ("Bronze", 150_i32)]
+        );
+
+        let posts: Vec<(&str, &str, i32, bool)> = plan
+            .posts
+            .iter()
+            .map(|p| {
+                (
+                    p.title.as_str(),
+                    p.slug.as_str(),
+                    p.number,
+                    p.is_published == Some(true),
+                )
+            })
+            .collect();
+        assert_eq!(
+            posts,
+            vec![
+                ("Chapter 1", "chapter-1-2", 5_i32, true),
+                ("Chapter 2", "chapter-2", 6_i32, true),
+                ("Chapter 3", "chapter-3", 7_i32, false),
+            ]
+        );
+        assert_eq!(plan.posts[0].content, "**One**");
+        assert!(plan.posts[0].min_tier_id.is_none());
+        assert_eq!(plan.report.posts[1].tier.as_deref(), Some("Gold"));
+        assert_eq!(plan.report.posts[2].tier.as_deref(), Some("Silver"));
+
+        let reasons: Vec<Option<&str>> = plan
+            .report
+            .members
+            .iter()
+            .map(|m| m.reason.as_deref())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                None,
+                Some("Not an active patron (Former patron)"),
+                Some("Listed more than once"),
+                None,
+            ]
+        );
+        assert_eq!(plan.invitations[0].email, "jane@example.com");
+        assert_eq!(plan.report.summary.invitations_created, 2);
+    }
+
+    #[test]
+    fn parses_amounts_into_cents() {
+        assert_eq!(parse_amount("$5.00"), Some(500_i32));
+        assert_eq!(parse_amount("12.5"), Some(1250_i32));
+        assert_eq!(parse_amount("7"), Some(700_i32));
+        assert_eq!(parse_amount("free"), None);
+    }
+}
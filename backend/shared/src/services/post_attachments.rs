@@ -0,0 +1,42 @@
+use crate::errors::ServiceError;
+use crate::models::post_attachments::{PostAttachment, PostAttachmentResponse};
+use crate::models::user_files::UserFile;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Load the attachments of posts in order, keyed by post
+///
+/// Attachments whose file has been deleted are left out.
+///
+/// # Errors
+/// Returns an error if the database query fails.
+pub async fn load_post_attachments(
+    conn: &mut AsyncPgConnection,
+    post_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<PostAttachmentResponse>>, ServiceError> {
+    use crate::schema::{post_attachments::dsl as attachments_dsl, user_files::dsl as files_dsl};
+
+    let rows: Vec<(PostAttachment, UserFile)> = attachments_dsl::post_attachments
+        .inner_join(files_dsl::user_files)
+        .filter(attachments_dsl::post_id.eq_any(post_ids))
+        .filter(files_dsl::deleted_at.is_null())
+        .order((
+            attachments_dsl::post_id.asc(),
+            attachments_dsl::position.asc(),
+            attachments_dsl::created_at.asc(),
+        ))
+        .select((PostAttachment::as_select(), UserFile::as_select()))
+        .load(conn)
+        .await?;
+
+    let mut attachments: HashMap<Uuid, Vec<PostAttachmentResponse>> = HashMap::new();
+    for (attachment, file) in rows {
+        attachments
+            .entry(attachment.post_id)
+            .or_default()
+            .push(PostAttachmentResponse::from((attachment, file)));
+    }
+    Ok(attachments)
+}
@@ -0,0 +1,500 @@
+use crate::errors::ServiceError;
+use crate::models::imports::{ImportFileReport, ImportFileStatus};
+use crate::models::posts::Post;
+use crate::services::front_matter::PostFrontMatter;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+use std::path::Path;
+use uuid::Uuid;
+use zip::ZipArchive;
+
+/// Largest total size the files of an import archive may expand to
+pub const MAX_IMPORT_ARCHIVE_SIZE: usize = 0x1000_0000;
+
+/// Most posts a single import may create
+pub const MAX_IMPORT_POSTS: usize = 2000;
+
+/// Longest title or slug the posts table accepts
+const MAX_FIELD_LENGTH: usize = 255;
+
+/// File read from a ZIP archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    /// Path of the file inside the archive
+    pub name: String,
+    /// Uncompressed contents
+    pub contents: Vec<u8>,
+}
+
+/// Posts to create from an archive, with a report for every file
+#[derive(Debug)]
+pub struct ImportPlan {
+    /// Outcome of each file, in archive order
+    pub reports: Vec<ImportFileReport>,
+    /// Posts built from the valid files
+    pub posts: Vec<Post>,
+}
+
+impl ImportPlan {
+    /// Whether every Markdown file is valid, so the posts can be created
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.reports
+            .iter()
+            .all(|r| r.status != ImportFileStatus::Invalid)
+    }
+
+    /// Mark the planned posts as created
+    pub fn mark_imported(&mut self) {
+        for report in &mut self.reports {
+            if report.status == ImportFileStatus::Ready {
+                report.status = ImportFileStatus::Imported;
+            }
+        }
+    }
+}
+
+/// Helper struct for a Markdown file that passed its own checks
+#[derive(Debug)]
+struct Candidate {
+    report_index: usize,
+    front_matter: PostFrontMatter,
+    title: String,
+    slug: String,
+    body: String,
+}
+
+/// Validate the Markdown files of an archive and build the posts they describe
+///
+/// Each `.md` file becomes a post. Titles are required; slugs default to the
+/// slugified title and numbers to the next free ones in file name order.
+/// Slugs and numbers must be unique within the archive and not already used
+/// in the series, including by deleted posts. `existing` lists the slug and
+/// number of every post the series has.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn plan_markdown_import(
+    series_id: Uuid,
+    mut entries: Vec<ZipEntry>,
+    existing: &[(String, i32)],
+) -> ImportPlan {
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut reports = Vec::with_capacity(entries.len());
+    let mut candidates = Vec::new();
+    for entry in entries {
+        if !is_markdown(&entry.name) {
+            reports.push(ImportFileReport::skipped(
+                &entry.name,
+                "Not a Markdown file",
+            ));
+            continue;
+        }
+
+        let mut report = ImportFileReport {
+            file: entry.name,
+            status: ImportFileStatus::Ready,
+            title: None,
+            slug: None,
+            number: None,
+            post_id: None,
+            errors: Vec::new(),
+        };
+        match check_markdown(&entry.contents) {
+            Ok((front_matter, title, slug, body)) => {
+                report.title = Some(title.clone());
+                report.slug = Some(slug.clone());
+                report.number = front_matter.number;
+                candidates.push(Candidate {
+                    report_index: reports.len(),
+                    front_matter,
+                    title,
+                    slug,
+                    body,
+                });
+            }
+            Err(errors) => {
+                report.status = ImportFileStatus::Invalid;
+                report.errors = errors;
+            }
+        }
+        reports.push(report);
+    }
+
+    let mut next_number = existing
+        .iter()
+        .map(|&(_, number)| number)
+        .chain(candidates.iter().filter_map(|c| c.front_matter.number))
+        .max()
+        .unwrap_or(0_i32);
+
+    let taken_slugs: HashSet<&str> = existing.iter().map(|entry| entry.0.as_str()).collect();
+    let taken_numbers: HashSet<i32> = existing.iter().map(|&(_, number)| number).collect();
+    let mut slugs_in_archive: HashMap<String, String> = HashMap::new();
+    let mut numbers_in_archive: HashMap<i32, String> = HashMap::new();
+
+    let now = Utc::now().naive_utc();
+    let mut posts = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let Some(report) = reports.get_mut(candidate.report_index) else {
+            continue;
+        };
+
+        let number = if let Some(number) = candidate.front_matter.number {
+            number
+        } else {
+            next_number = next_number.saturating_add(1);
+            report.number = Some(next_number);
+            next_number
+        };
+
+        if taken_slugs.contains(candidate.slug.as_str()) {
+            report.errors.push(format!(
+                "Slug {} is already used in this series",
+                candidate.slug
+            ));
+        } else if let Some(other) = slugs_in_archive.get(&candidate.slug) {
+            report
+                .errors
+                .push(format!("Slug {} is also used by {other}", candidate.slug));
+        } else {
+            let _ = slugs_in_archive.insert(candidate.slug.clone(), report.file.clone());
+        }
+        if taken_numbers.contains(&number) {
+            report
+                .errors
+                .push(format!("Number {number} is already used in this series"));
+        } else if let Some(other) = numbers_in_archive.get(&number) {
+            report
+                .errors
+                .push(format!("Number {number} is also used by {other}"));
+        } else {
+            let _ = numbers_in_archive.insert(number, report.file.clone());
+        }
+
+        if !report.errors.is_empty() {
+            report.status = ImportFileStatus::Invalid;
+            continue;
+        }
+
+        let id = Uuid::new_v4();
+        report.post_id = Some(id);
+        let date = candidate.front_matter.date.unwrap_or(now);
+        posts.push(Post {
+            id,
+            series_id,
+            title: candidate.title,
+            content: candidate.body,
+            slug: candidate.slug,
+            number,
+            is_published: Some(candidate.front_matter.published.unwrap_or(false)),
+            thumbnail_url: None,
+            audio_file_id: None,
+            video_file_id: None,
+            created_at: Some(date),
+            updated_at: Some(date),
+            deleted_at: None,
+            min_tier_id: None,
+        });
+    }
+
+    ImportPlan { reports, posts }
+}
+
+/// Helper function to validate one Markdown file on its own
+///
+/// Returns the front matter, title, slug and body, or every problem found.
+fn check_markdown(
+    contents: &[u8],
+) -> Result<(PostFrontMatter, String, String, String), Vec<String>> {
+    let text = std::str::from_utf8(contents)
+        .ok()
+        .ok_or_else(|| vec!["File is not valid UTF-8".to_owned()])?;
+    let (front_matter, body) = PostFrontMatter::parse(text).map_err(|e| vec![e])?;
+
+    let mut errors = Vec::new();
+    let title = front_matter
+        .title
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_owned();
+    if title.is_empty() {
+        errors.push("Front matter has no title".to_owned());
+    }
+    if title.chars().count() > MAX_FIELD_LENGTH {
+        errors.push(format!(
+            "Title is longer than {MAX_FIELD_LENGTH} characters"
+        ));
+    }
+
+    let slug = front_matter.slug.clone().unwrap_or_else(|| slugify(&title));
+    // A slug derived from a missing title is already reported as such
+    if !is_valid_slug(&slug) && (front_matter.slug.is_some() || !title.is_empty()) {
+        errors.push(format!(
+            "Slug {slug:?} must be 1 to {MAX_FIELD_LENGTH} lowercase letters, digits and dashes"
+        ));
+    }
+
+    if let Some(number) = front_matter.number {
+        if number < 1_i32 {
+            errors.push("Number must be 1 or more".to_owned());
+        }
+    }
+
+    if errors.is_empty() {
+        Ok((front_matter, title, slug, body))
+    } else {
+        Err(errors)
+    }
+}
+
+/// Read every file of an uploaded ZIP archive
+///
+/// Directory entries are left out. Decompression stops once the files add up
+/// to more than `max_total_size` bytes, whatever sizes the headers claim, so a
+/// small archive cannot expand into an unbounded amount of memory.
+///
+/// # Errors
+/// Returns a `ServiceError::Config` if the archive is malformed, encrypted, or
+/// expands beyond `max_total_size`.
+pub fn read_zip(data: &[u8], max_total_size: usize) -> Result<Vec<ZipEntry>, ServiceError> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let too_large = || {
+        ServiceError::Config(format!(
+            "Archive expands to more than {max_total_size} bytes"
+        ))
+    };
+
+    let mut entries = Vec::with_capacity(archive.len());
+    let mut remaining = max_total_size;
+    for index in 0..archive.len() {
+        let file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_owned();
+        let limit = u64::try_from(remaining.saturating_add(1)).unwrap_or(u64::MAX);
+        let mut contents = Vec::new();
+        let _ = file
+            .take(limit)
+            .read_to_end(&mut contents)
+            .map_err(|e| ServiceError::Config(format!("Invalid ZIP archive: {name}: {e}")))?;
+        remaining = remaining
+            .checked_sub(contents.len())
+            .ok_or_else(too_large)?;
+        entries.push(ZipEntry { name, contents });
+    }
+
+    Ok(entries)
+}
+
+/// Helper function to tell Markdown files from everything else in an archive
+///
+/// Hidden files and the resource forks macOS adds to archives are not posts
+/// even when they carry a Markdown extension.
+fn is_markdown(name: &str) -> bool {
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    !name.starts_with("__MACOSX/")
+        && !file_name.starts_with('.')
+        && Path::new(file_name).extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown")
+        })
+}
+
+/// Turn a title into a URL-friendly slug
+///
+/// Letters and digits are lowercased, everything else collapses into single
+/// dashes. Letters outside ASCII are dropped.
+#[must_use]
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+            continue;
+        }
+        if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let trimmed = slug.trim_end_matches('-');
+    trimmed
+        .char_indices()
+        .nth(MAX_FIELD_LENGTH)
+        .map_or(trimmed, |(end, _)| trimmed.get(..end).unwrap_or(trimmed))
+        .trim_end_matches('-')
+        .to_owned()
+}
+
+/// Pick a slug for a title that is not taken yet, numbering repeats
+///
+/// A title without letters or digits falls back to `post-` followed by the
+/// slugified `fallback`, such as the ID the post had on another platform.
+/// The slug picked is added to `taken`.
+#[allow(clippy::implicit_hasher)]
+pub fn unique_slug(title: &str, fallback: &str, taken: &mut HashSet<String>) -> String {
+    let slugified = Some(slugify(title))
+        .filter(|slug| !slug.is_empty())
+        .unwrap_or_else(|| format!("post-{}", slugify(fallback)));
+    // Room for a numbered suffix
+    let base: String = slugified
+        .chars()
+        .take(MAX_FIELD_LENGTH.saturating_sub(8))
+        .collect();
+
+    let mut slug = base.clone();
+    let mut suffix: u32 = 1;
+    while taken.contains(&slug) {
+        suffix = suffix.saturating_add(1);
+        slug = format!("{base}-{suffix}");
+    }
+    let _ = taken.insert(slug.clone());
+    slug
+}
+
+/// Cut a title or name down to the longest the posts and tiers tables accept
+#[must_use]
+pub fn truncate_field(value: &str) -> String {
+    value.chars().take(MAX_FIELD_LENGTH).collect()
+}
+
+/// Whether a slug is non-empty, short enough, and only lowercase letters, digits and dashes
+#[must_use]
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= MAX_FIELD_LENGTH
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn zip_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for &(name, contents) in files {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_archive_files() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .add_directory("posts/", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .start_file("posts/one.md", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"# One").unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        assert_eq!(
+            read_zip(&data, usize::MAX).unwrap(),
+            vec![ZipEntry {
+                name: "posts/one.md".to_owned(),
+                contents: b"# One".to_vec(),
+            }]
+        );
+    }
+
+    #[test]
+    fn stops_reading_at_size_limit() {
+        let data = zip_of(&[("a.md", &[b'a'; 600]), ("b.md", &[b'b'; 600])]);
+        assert_eq!(read_zip(&data, 1200).unwrap().len(), 2);
+        assert!(matches!(
+            read_zip(&data, 1000),
+            Err(ServiceError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_archives() {
+        assert!(matches!(
+            read_zip(b"not a zip file", usize::MAX),
+            Err(ServiceError::Config(_))
+        ));
+    }
+
+    fn entry(name: &str, contents: &str) -> ZipEntry {
+        ZipEntry {
+            name: name.to_owned(),
+            contents: contents.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn slugify_collapses_punctuation() {
+        assert_eq!(
+            slugify("Chapter 1: The Beginning!"),
+            "chapter-1-the-beginning"
+        );
+        assert_eq!(slugify("  --Édition--  "), "dition");
+    }
+
+    #[test]
+    fn numbers_missing_from_front_matter_follow_the_highest() {
+        let existing = vec![("prologue".to_owned(), 1_i32)];
+        let plan = plan_markdown_import(
+            Uuid::new_v4(),
+            vec![
+                entry("b.md", "---\ntitle: Second\n---\nText"),
+                entry(
+                    "a.md",
+                    "---\ntitle: First\nnumber: 5\npublished: true\n---\nText",
+                ),
+                entry("cover.png", "binary"),
+                entry("__MACOSX/._a.md", "junk"),
+            ],
+            &existing,
+        );
+
+        assert!(plan.is_valid());
+        assert_eq!(plan.posts.len(), 2);
+        assert_eq!(plan.posts[0].slug, "first");
+        assert_eq!(plan.posts[0].number, 5_i32);
+        assert_eq!(plan.posts[0].is_published, Some(true));
+        assert_eq!(plan.posts[1].slug, "second");
+        assert_eq!(plan.posts[1].number, 6_i32);
+        assert_eq!(plan.reports.len(), 4);
+        assert_eq!(plan.reports[0].status, ImportFileStatus::Skipped);
+    }
+
+    #[test]
+    fn conflicts_are_reported_per_file() {
+        let existing = vec![("taken".to_owned(), 1_i32)];
+        let plan = plan_markdown_import(
+            Uuid::new_v4(),
+            vec![
+                entry("1.md", "---\ntitle: Taken\n---\n"),
+                entry("2.md", "---\ntitle: A\nnumber: 2\n---\n"),
+                entry("3.md", "---\ntitle: B\nnumber: 2\n---\n"),
+                entry("4.md", "no front matter"),
+            ],
+            &existing,
+        );
+
+        assert!(!plan.is_valid());
+        let errors: Vec<&Vec<String>> = plan.reports.iter().map(|r| &r.errors).collect();
+        assert_eq!(
+            errors[0],
+            &vec!["Slug taken is already used in this series".to_owned()]
+        );
+        assert!(errors[1].is_empty());
+        assert_eq!(errors[2], &vec!["Number 2 is also used by 2.md".to_owned()]);
+        assert_eq!(errors[3], &vec!["Front matter has no title".to_owned()]);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::errors::ServiceError;
+use crate::services::config::{ConfigService, RateLimit, RateLimitConfig};
+use crate::services::sessions::client_ip;
+use actix_web::{http::header, HttpRequest};
+use chrono::Utc;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Endpoints protected by rate limiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitedAction {
+    /// Password login
+    Login,
+    /// Account registration
+    Register,
+    /// Password reset email request
+    ForgotPassword,
+    /// Email existence check
+    CheckEmail,
+    /// Verification email resend
+    ResendVerification,
+    /// Email address change request
+    ChangeEmail,
+    /// Passwordless login link request
+    MagicLink,
+}
+
+impl RateLimitedAction {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::Register => "register",
+            Self::ForgotPassword => "forgot-password",
+            Self::CheckEmail => "check-email",
+            Self::ResendVerification => "resend-verification",
+            Self::ChangeEmail => "change-email",
+            Self::MagicLink => "magic-link",
+        }
+    }
+}
+
+/// Identity a rate limit is counted against
+#[derive(Debug, Clone, Copy)]
+pub enum RateLimitKey<'key> {
+    /// Client IP address
+    Ip(&'key str),
+    /// Email address the request targets (compared case-insensitively)
+    Email(&'key str),
+    /// Bearer API key presented with the request
+    ApiKey(&'key str),
+}
+
+impl RateLimitKey<'_> {
+    fn redis_suffix(self) -> String {
+        match self {
+            Self::Ip(ip) => format!("ip:{ip}"),
+            Self::Email(email) => format!("email:{}", email.trim().to_lowercase()),
+            Self::ApiKey(key) => {
+                let mut hasher = Sha256::new();
+                hasher.update(key.as_bytes());
+                format!("api-key:{:x}", hasher.finalize())
+            }
+        }
+    }
+}
+
+/// Redis-backed sliding window rate limiter
+///
+/// Every attempt is recorded in the sorted set
+/// `rate-limit:{action}:{kind}:{value}`, scored by its timestamp in
+/// milliseconds. Entries older than the window are dropped on each check, so the
+/// limit applies to any window-length span rather than fixed buckets. If Redis is
+/// unavailable requests are let through rather than locking everyone out.
+#[derive(Clone)]
+pub struct RateLimitService {
+    redis: ConnectionManager,
+    config: RateLimitConfig,
+}
+
+impl std::fmt::Debug for RateLimitService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimitService")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimitService {
+    /// Create a new `RateLimitService` with limits taken from `ConfigService`
+    #[must_use]
+    pub const fn new(redis: ConnectionManager, config: &ConfigService) -> Self {
+        Self {
+            redis,
+            config: config.rate_limit_config,
+        }
+    }
+
+    const fn limit_for(&self, action: RateLimitedAction) -> RateLimit {
+        match action {
+            RateLimitedAction::Login => self.config.login,
+            RateLimitedAction::Register => self.config.register,
+            RateLimitedAction::ForgotPassword => self.config.forgot_password,
+            RateLimitedAction::CheckEmail => self.config.check_email,
+            RateLimitedAction::ResendVerification => self.config.resend_verification,
+            RateLimitedAction::ChangeEmail => self.config.change_email,
+            RateLimitedAction::MagicLink => self.config.magic_link,
+        }
+    }
+
+    /// Record an attempt against the client IP, the optional target email and any bearer API key
+    ///
+    /// # Errors
+    /// Returns `ServiceError::RateLimited` if any of the keys is over its limit
+    pub async fn check_request(
+        &self,
+        action: RateLimitedAction,
+        req: &HttpRequest,
+        email: Option<&str>,
+    ) -> Result<(), ServiceError> {
+        let ip_address = client_ip(req);
+        let bearer_token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(ToOwned::to_owned);
+
+        let mut keys = Vec::with_capacity(3);
+        if let Some(ref ip) = ip_address {
+            keys.push(RateLimitKey::Ip(ip));
+        }
+        if let Some(address) = email.filter(|address| !address.trim().is_empty()) {
+            keys.push(RateLimitKey::Email(address));
+        }
+        if let Some(ref api_key) = bearer_token {
+            keys.push(RateLimitKey::ApiKey(api_key));
+        }
+
+        self.check(action, &keys).await
+    }
+
+    /// Record an attempt against each key, failing on the first key that is over its limit
+    ///
+    /// # Errors
+    /// Returns `ServiceError::RateLimited` with the number of seconds until the oldest
+    /// attempt leaves the window
+    pub async fn check(
+        &self,
+        action: RateLimitedAction,
+        keys: &[RateLimitKey<'_>],
+    ) -> Result<(), ServiceError> {
+        let limit = self.limit_for(action);
+        for key in keys {
+            let redis_key = format!("rate-limit:{}:{}", action.name(), key.redis_suffix());
+            match self.record_attempt(&redis_key, limit).await {
+                Ok(None) => {}
+                Ok(Some(retry_after)) => return Err(ServiceError::RateLimited(retry_after)),
+                Err(e) => tracing::warn!("Rate limit check failed for {}: {}", redis_key, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an attempt to the window, returning the retry delay in seconds if the limit is exceeded
+    async fn record_attempt(
+        &self,
+        redis_key: &str,
+        limit: RateLimit,
+    ) -> Result<Option<u64>, ServiceError> {
+        let now_ms = Utc::now().timestamp_millis();
+        let window_ms =
+            i64::try_from(limit.window_seconds.saturating_mul(1000)).unwrap_or(i64::MAX);
+        let window_seconds = i64::try_from(limit.window_seconds).unwrap_or(i64::MAX);
+        let member = Uuid::new_v4().to_string();
+
+        let mut con = self.redis.clone();
+        let (count, oldest): (u64, Vec<(String, i64)>) = redis::pipe()
+            .atomic()
+            .zrembyscore(redis_key, 0_i64, now_ms.saturating_sub(window_ms))
+            .ignore()
+            .zadd(redis_key, &member, now_ms)
+            .ignore()
+            .zcard(redis_key)
+            .zrange_withscores(redis_key, 0, 0)
+            .expire(redis_key, window_seconds)
+            .ignore()
+            .query_async(&mut con)
+            .await?;
+
+        if count <= limit.max_requests {
+            return Ok(None);
+        }
+
+        // Rejected attempts do not count towards the window, otherwise a client
+        // retrying too eagerly would never get back under the limit.
+        let _: usize = con.zrem(redis_key, &member).await?;
+
+        let oldest_ms = oldest.first().map_or(now_ms, |&(_, score)| score);
+        let remaining_ms = oldest_ms.saturating_add(window_ms).saturating_sub(now_ms);
+        let retry_after = u64::try_from(remaining_ms)
+            .unwrap_or(0)
+            .saturating_add(999)
+            .checked_div(1000)
+            .unwrap_or(0)
+            .max(1);
+
+        Ok(Some(retry_after))
+    }
+}
@@ -2,15 +2,27 @@ use crate::errors::ServiceError;
 use crate::services::config::AwsConfig;
 use aws_config::{BehaviorVersion, Region};
 use aws_credential_types::{provider::SharedCredentialsProvider, Credentials};
-use aws_sdk_s3::{presigning::PresigningConfig, primitives::ByteStream, Client};
+use aws_sdk_s3::{
+    presigning::PresigningConfig,
+    primitives::{ByteStream, Length},
+    types::{CompletedMultipartUpload, CompletedPart},
+    Client,
+};
 use bytes::Bytes;
 use futures_util::stream::{Stream, StreamExt};
+use std::path::Path;
 use std::pin::Pin;
 use tokio_util::io::ReaderStream;
 use tracing::instrument;
 
 type S3ObjectStream = Pin<Box<dyn Stream<Item = Result<Bytes, ServiceError>> + Send>>;
 
+/// Size of the parts large files are uploaded in
+///
+/// Small enough for a part to finish within the attempt timeout, and above the
+/// minimum size S3 sets for every part but the last.
+const UPLOAD_PART_SIZE: u64 = 8 * 1024 * 1024;
+
 /// Service for interacting with AWS S3
 #[derive(Clone, Debug)]
 pub struct S3Service {
@@ -98,6 +110,108 @@ impl S3Service {
         Ok(url)
     }
 
+    /// Upload a local file to S3 without reading it into memory
+    ///
+    /// Files larger than one part are sent as a multipart upload, read from
+    /// disk one part at a time. A failed multipart upload is aborted so its
+    /// parts do not linger in the bucket.
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the file cannot be read or the S3 upload fails
+    #[instrument(skip_all, fields(key = %key, bucket = %self.bucket))]
+    pub async fn put_file(&self, key: &str, path: &Path) -> Result<(), ServiceError> {
+        let size = std::fs::metadata(path)?.len();
+        if size <= UPLOAD_PART_SIZE {
+            let _ = self
+                .client
+                .put_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .body(file_part(path, 0, size).await?)
+                .send()
+                .await?;
+            return Ok(());
+        }
+
+        let upload_id = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await?
+            .upload_id
+            .ok_or_else(|| ServiceError::AwsSdk("Multipart upload has no ID".to_owned()))?;
+
+        match self.upload_parts(key, &upload_id, path, size).await {
+            Ok(parts) => {
+                let _ = self
+                    .client
+                    .complete_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .multipart_upload(
+                        CompletedMultipartUpload::builder()
+                            .set_parts(Some(parts))
+                            .build()
+                    )
+                    .send()
+                    .await?;
+                Ok(())
+            }
+            Err(e) => {
+                if let Err(abort_error) = self
+                    .client
+                    .abort_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .send()
+                    .await
+                {
+                    tracing::warn!("Failed to abort multipart upload of {key}: {abort_error}");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Helper function to upload the parts of a file, in order
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        path: &Path,
+        size: u64,
+    ) -> Result<Vec<CompletedPart>, ServiceError> {
+        let mut parts = Vec::new();
+        let mut offset = 0;
+        let mut part_number = 1_i32;
+        while offset < size {
+            let length = UPLOAD_PART_SIZE.min(size.saturating_sub(offset));
+            let response = self
+                .client
+                .upload_part()
+                .bucket(&self.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(file_part(path, offset, length).await?)
+                .send()
+                .await?;
+            parts.push(
+                CompletedPart::builder()
+                    .set_e_tag(response.e_tag)
+                    .part_number(part_number)
+                    .build()
+            );
+            offset = offset.saturating_add(length);
+            part_number = part_number.saturating_add(1);
+        }
+        Ok(parts)
+    }
+
     /// Upload an audio clip to S3 and return the URL and key
     ///
     /// # Errors
@@ -186,3 +300,14 @@ impl S3Service {
         Ok(())
     }
 }
+
+/// Helper function to stream `length` bytes of a file from `offset` as a request body
+async fn file_part(path: &Path, offset: u64, length: u64) -> Result<ByteStream, ServiceError> {
+    ByteStream::read_from()
+        .path(path)
+        .offset(offset)
+        .length(Length::Exact(length))
+        .build()
+        .await
+        .map_err(|e| ServiceError::AwsSdk(e.to_string()))
+}
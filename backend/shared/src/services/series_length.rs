@@ -0,0 +1,40 @@
+use crate::errors::ServiceError;
+use crate::models::series_length::SeriesLength;
+use crate::schema::series_length::dsl as series_length_dsl;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use uuid::Uuid;
+
+/// Recount the live posts of a series and store the result in `series_length`
+///
+/// # Errors
+/// Returns error if database operations fail
+pub async fn refresh_series_length(
+    conn: &mut AsyncPgConnection,
+    series_id: Uuid,
+) -> Result<(), ServiceError> {
+    use crate::schema::posts::dsl as posts_dsl;
+
+    let post_count: i64 = posts_dsl::posts
+        .filter(posts_dsl::series_id.eq(series_id))
+        .filter(posts_dsl::deleted_at.is_null())
+        .count()
+        .get_result(conn)
+        .await
+        .map_err(|e| ServiceError::Database(e.to_string()))?;
+
+    #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+    let post_count_i32 = post_count as i32;
+    let new_series_length = SeriesLength::new(series_id, post_count_i32);
+
+    let _rows_affected = diesel::insert_into(series_length_dsl::series_length)
+        .values(&new_series_length)
+        .on_conflict(series_length_dsl::series_id)
+        .do_update()
+        .set(series_length_dsl::length.eq(post_count_i32))
+        .execute(conn)
+        .await
+        .map_err(|e| ServiceError::Database(e.to_string()))?;
+
+    Ok(())
+}
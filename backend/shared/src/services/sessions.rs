@@ -0,0 +1,290 @@
+use crate::errors::ServiceError;
+use crate::models::sessions::SessionRecord;
+use crate::services::config::ConfigService;
+use actix_web::{http::header, web, HttpRequest};
+use chrono::Utc;
+use ipnet::IpNet;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Minimum interval between `last_seen_at` writes for a single session
+const LAST_SEEN_RESOLUTION_SECONDS: i64 = 60;
+
+/// Service maintaining a per-user index of cookie sessions in Redis
+///
+/// Each login creates a [`SessionRecord`] stored under `session:{id}` and
+/// referenced from the sorted set `user-sessions:{user_id}` (scored by creation
+/// time). The record ID is kept in the cookie session under the `sid` key; a
+/// session whose record has been removed is treated as revoked.
+#[derive(Clone)]
+pub struct SessionService {
+    redis: ConnectionManager,
+    ttl_seconds: u64,
+}
+
+impl std::fmt::Debug for SessionService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionService")
+            .field("ttl_seconds", &self.ttl_seconds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionService {
+    /// Session key under which the session record ID is stored
+    pub const SESSION_ID_KEY: &'static str = "sid";
+
+    /// Create a new `SessionService` using the given Redis connection and session lifetime
+    #[must_use]
+    pub const fn new(redis: ConnectionManager, session_ttl_days: u64) -> Self {
+        Self {
+            redis,
+            ttl_seconds: session_ttl_days.saturating_mul(86_400),
+        }
+    }
+
+    fn record_key(session_id: &str) -> String {
+        format!("session:{session_id}")
+    }
+
+    fn index_key(user_id: Uuid) -> String {
+        format!("user-sessions:{user_id}")
+    }
+
+    fn index_ttl(&self) -> i64 {
+        i64::try_from(self.ttl_seconds).unwrap_or(i64::MAX)
+    }
+
+    /// Create and index a new session record for a user
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the record cannot be written to Redis
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<SessionRecord, ServiceError> {
+        let now = Utc::now().naive_utc();
+        let record = SessionRecord {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            created_at: now,
+            last_seen_at: now,
+            ip_address,
+            user_agent,
+        };
+
+        let mut con = self.redis.clone();
+        let payload = serde_json::to_string(&record)?;
+        let _: () = con
+            .set_ex(Self::record_key(&record.id), payload, self.ttl_seconds)
+            .await?;
+        let _: usize = con
+            .zadd(
+                Self::index_key(user_id),
+                &record.id,
+                now.and_utc().timestamp(),
+            )
+            .await?;
+        let _: bool = con
+            .expire(Self::index_key(user_id), self.index_ttl())
+            .await?;
+
+        Ok(record)
+    }
+
+    /// Fetch a session record by ID
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if Redis cannot be reached or the record is malformed
+    pub async fn get(&self, session_id: &str) -> Result<Option<SessionRecord>, ServiceError> {
+        let mut con = self.redis.clone();
+        let payload: Option<String> = con.get(Self::record_key(session_id)).await?;
+        payload
+            .map(|p| serde_json::from_str(&p).map_err(ServiceError::from))
+            .transpose()
+    }
+
+    /// Record activity on a session, refreshing its TTL
+    ///
+    /// Writes are skipped if the record was already touched within the last minute.
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the record cannot be written to Redis
+    pub async fn touch(&self, mut record: SessionRecord) -> Result<(), ServiceError> {
+        let now = Utc::now().naive_utc();
+        if now.signed_duration_since(record.last_seen_at).num_seconds()
+            < LAST_SEEN_RESOLUTION_SECONDS
+        {
+            return Ok(());
+        }
+        record.last_seen_at = now;
+
+        let mut con = self.redis.clone();
+        let payload = serde_json::to_string(&record)?;
+        let _: () = con
+            .set_ex(Self::record_key(&record.id), payload, self.ttl_seconds)
+            .await?;
+        let _: bool = con
+            .expire(Self::index_key(record.user_id), self.index_ttl())
+            .await?;
+        Ok(())
+    }
+
+    /// List a user's live sessions, newest first, pruning index entries whose record expired
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if Redis cannot be reached
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<SessionRecord>, ServiceError> {
+        let mut con = self.redis.clone();
+        let ids: Vec<String> = con.zrevrange(Self::index_key(user_id), 0, -1).await?;
+
+        let mut records = Vec::with_capacity(ids.len());
+        let mut stale = Vec::new();
+        for id in ids {
+            match self.get(&id).await? {
+                Some(record) => records.push(record),
+                None => stale.push(id),
+            }
+        }
+
+        if !stale.is_empty() {
+            let _: usize = con.zrem(Self::index_key(user_id), stale).await?;
+        }
+
+        Ok(records)
+    }
+
+    /// Revoke a single session belonging to a user
+    ///
+    /// Returns `false` if the session does not exist or belongs to someone else.
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if Redis cannot be reached
+    pub async fn revoke(&self, user_id: Uuid, session_id: &str) -> Result<bool, ServiceError> {
+        match self.get(session_id).await? {
+            Some(record) if record.user_id == user_id => {}
+            Some(_) | None => return Ok(false),
+        }
+
+        let mut con = self.redis.clone();
+        let _: usize = con.del(Self::record_key(session_id)).await?;
+        let _: usize = con.zrem(Self::index_key(user_id), session_id).await?;
+        Ok(true)
+    }
+
+    /// Revoke every session of a user except `keep`, returning how many were revoked
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if Redis cannot be reached
+    pub async fn revoke_all_except(
+        &self,
+        user_id: Uuid,
+        keep: Option<&str>,
+    ) -> Result<usize, ServiceError> {
+        let mut con = self.redis.clone();
+        let ids: Vec<String> = con.zrange(Self::index_key(user_id), 0, -1).await?;
+
+        let mut revoked = 0_usize;
+        for id in ids.iter().filter(|id| Some(id.as_str()) != keep) {
+            let _: usize = con.del(Self::record_key(id)).await?;
+            let _: usize = con.zrem(Self::index_key(user_id), id).await?;
+            revoked = revoked.saturating_add(1);
+        }
+
+        Ok(revoked)
+    }
+}
+
+/// Client IP address of a request
+///
+/// This is the address of the connection's peer. `X-Forwarded-For` is only
+/// read when the peer is one of the configured trusted proxies, since any
+/// client can send the header.
+#[must_use]
+pub fn client_ip(req: &HttpRequest) -> Option<String> {
+    let peer = req.peer_addr()?.ip();
+    let trusted_proxies = req
+        .app_data::<web::Data<ConfigService>>()
+        .map_or(&[][..], |config| config.trusted_proxies.as_slice());
+    let forwarded_for = req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok());
+    Some(resolve_client_ip(peer, forwarded_for, trusted_proxies).to_string())
+}
+
+/// Resolve the client address from the peer address and `X-Forwarded-For`
+///
+/// The header is walked from the right, skipping the trusted proxies that
+/// appended to it; the first other address is the client. Entries left of it
+/// were written by the client and are ignored.
+#[must_use]
+pub fn resolve_client_ip(
+    peer: IpAddr,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &[IpNet],
+) -> IpAddr {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+    if !is_trusted(&peer) {
+        return peer;
+    }
+
+    let mut client = peer;
+    for entry in forwarded_for.unwrap_or_default().rsplit(',') {
+        let Ok(ip) = entry.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(&ip) {
+            break;
+        }
+    }
+    client
+}
+
+/// User agent header of a request, if present and valid UTF-8
+#[must_use]
+pub fn user_agent(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn ignores_forwarded_for_from_untrusted_peers() {
+        let proxies = vec!["10.0.0.0/8".parse().unwrap()];
+        let client = resolve_client_ip(ip("203.0.113.7"), Some("198.51.100.1"), &proxies);
+        assert_eq!(client, ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn takes_first_untrusted_address_from_the_right() {
+        let proxies = vec!["10.0.0.0/8".parse().unwrap()];
+        let header = Some("1.2.3.4, 198.51.100.1, 10.0.0.2");
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.1"), header, &proxies),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.1"), None, &proxies),
+            ip("10.0.0.1")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.1"), Some("garbage, 10.0.0.3"), &proxies),
+            ip("10.0.0.3")
+        );
+    }
+}
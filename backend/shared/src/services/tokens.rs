@@ -0,0 +1,298 @@
+use crate::errors::ServiceError;
+use crate::models::auth::User;
+use crate::services::config::TokenConfig;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::Utc;
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+/// Prefix of refresh tokens, so they are recognizable in logs and secret scanners
+pub const REFRESH_TOKEN_PREFIX: &str = "prf_";
+
+/// Claims carried by a first-party access token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessTokenClaims {
+    /// Issuer, the public backend URL
+    pub iss: String,
+    /// ID of the user the token was issued to
+    pub sub: Uuid,
+    /// Refresh token family the token belongs to; revoking the family revokes the token
+    pub sid: Uuid,
+    /// `users.session_version` at issue time; bumping it invalidates the token
+    pub sv: i32,
+    /// Issue time as a Unix timestamp
+    pub iat: i64,
+    /// Expiry time as a Unix timestamp
+    pub exp: i64,
+}
+
+impl AccessTokenClaims {
+    /// Whether the token was issued to this user and survived every session
+    /// reset since, i.e. `session_version` has not been bumped
+    #[must_use]
+    pub fn is_current_for(&self, user: &User) -> bool {
+        self.sub == user.id && self.sv == user.session_version
+    }
+}
+
+/// Issues and verifies short-lived `HS256` access tokens for native clients
+///
+/// Access tokens are stateless apart from the revocation check done by the
+/// `User` extractor. Refresh tokens are opaque random strings; only their
+/// SHA-256 hash is stored.
+#[derive(Clone)]
+pub struct AccessTokenService {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    validation: Validation,
+    issuer: String,
+    access_ttl_seconds: i64,
+    refresh_ttl_days: i64,
+}
+
+impl std::fmt::Debug for AccessTokenService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessTokenService")
+            .field("issuer", &self.issuer)
+            .field("access_ttl_seconds", &self.access_ttl_seconds)
+            .field("refresh_ttl_days", &self.refresh_ttl_days)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AccessTokenService {
+    /// Create a new `AccessTokenService` from the token configuration
+    #[must_use]
+    pub fn new(config: &TokenConfig) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+        validation.set_issuer(&[&config.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+
+        Self {
+            encoding_key: EncodingKey::from_secret(config.signing_secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(config.signing_secret.as_bytes()),
+            validation,
+            issuer: config.issuer.clone(),
+            access_ttl_seconds: config.access_ttl_seconds,
+            refresh_ttl_days: config.refresh_ttl_days,
+        }
+    }
+
+    /// Access token lifetime in seconds
+    #[must_use]
+    pub const fn access_ttl_seconds(&self) -> i64 {
+        self.access_ttl_seconds
+    }
+
+    /// Refresh token lifetime in days
+    #[must_use]
+    pub const fn refresh_ttl_days(&self) -> i64 {
+        self.refresh_ttl_days
+    }
+
+    /// Sign an access token for a user within a refresh token family
+    ///
+    /// # Errors
+    /// Returns `ServiceError::Unknown` if the token cannot be encoded
+    pub fn issue_access_token(&self, user: &User, family_id: Uuid) -> Result<String, ServiceError> {
+        let now = Utc::now().timestamp();
+        let claims = AccessTokenClaims {
+            iss: self.issuer.clone(),
+            sub: user.id,
+            sid: family_id,
+            sv: user.session_version,
+            iat: now,
+            exp: now.saturating_add(self.access_ttl_seconds),
+        };
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| ServiceError::Unknown(format!("Failed to sign access token: {e}")))
+    }
+
+    /// Verify an access token's signature, issuer and expiry
+    ///
+    /// # Errors
+    /// Returns `ServiceError::Unauthorized` if the token is malformed, forged, expired or from another issuer
+    pub fn verify_access_token(&self, token: &str) -> Result<AccessTokenClaims, ServiceError> {
+        jsonwebtoken::decode::<AccessTokenClaims>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| {
+                let reason = match *e.kind() {
+                    ErrorKind::ExpiredSignature => "Token has expired",
+                    ErrorKind::InvalidIssuer => "Token was issued by another server",
+                    ErrorKind::InvalidSignature | ErrorKind::InvalidAlgorithm => {
+                        "Invalid token signature"
+                    }
+                    _ => "Malformed token",
+                };
+                ServiceError::Unauthorized(reason.to_owned())
+            })
+    }
+}
+
+/// Whether a bearer token is a JSON Web Token rather than an API key or `OAuth` token
+#[must_use]
+pub fn is_jwt(token: &str) -> bool {
+    token.starts_with("eyJ") && token.split('.').count() == 3
+}
+
+/// Generate a new opaque refresh token with 256 bits of entropy
+///
+/// # Errors
+/// Returns `ServiceError::Unknown` if the system random number generator fails
+pub fn generate_refresh_token() -> Result<String, ServiceError> {
+    let mut bytes = [0_u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_unspecified| ServiceError::Unknown("Failed to generate token".to_owned()))?;
+    Ok(format!(
+        "{REFRESH_TOKEN_PREFIX}{}",
+        URL_SAFE_NO_PAD.encode(bytes)
+    ))
+}
+
+/// Hash a token, login link, authorization code or client secret for storage and lookup
+#[must_use]
+pub fn hash_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Compare a presented token against a stored hash in constant time
+#[must_use]
+pub fn token_matches_hash(token: &str, stored_hash: &str) -> bool {
+    hash_token(token)
+        .as_bytes()
+        .ct_eq(stored_hash.as_bytes())
+        .into()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::models::auth::UserRole;
+
+    fn service(access_ttl_seconds: i64) -> AccessTokenService {
+        AccessTokenService::new(&TokenConfig {
+            signing_secret: "test-signing-secret-of-at-least-32-bytes".to_owned(),
+            issuer: "https://api.example.com".to_owned(),
+            access_ttl_seconds,
+            refresh_ttl_days: 30,
+        })
+    }
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            email: "reader@example.com".to_owned(),
+            password_hash: None,
+            created_at: None,
+            updated_at: None,
+            display_name: None,
+            avatar_url: None,
+            email_verified: true,
+            last_login: None,
+            description: None,
+            banner: None,
+            session_version: 3,
+            role: UserRole::User.into(),
+            suspended_at: None,
+            suspension_reason: None,
+            suspended_until: None,
+            deletion_scheduled_for: None,
+        }
+    }
+
+    #[test]
+    fn test_issued_token_verifies() {
+        let tokens = service(900);
+        let user = user();
+        let family_id = Uuid::new_v4();
+
+        let claims = tokens
+            .verify_access_token(&tokens.issue_access_token(&user, family_id).unwrap())
+            .unwrap();
+        assert_eq!(claims.sub, user.id);
+        assert_eq!(claims.sid, family_id);
+        assert!(claims.is_current_for(&user));
+    }
+
+    #[test]
+    fn test_tampered_token_is_rejected() {
+        let tokens = service(900);
+        let user = user();
+        let token = tokens.issue_access_token(&user, Uuid::new_v4()).unwrap();
+
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let forged_claims = URL_SAFE_NO_PAD.encode(
+            serde_json::json!({
+                "iss": "https://api.example.com",
+                "sub": Uuid::new_v4(),
+                "sid": Uuid::new_v4(),
+                "sv": user.session_version,
+                "iat": Utc::now().timestamp(),
+                "exp": Utc::now().timestamp() + 900,
+            })
+            .to_string()
+            .as_bytes(),
+        );
+        parts[1] = &forged_claims;
+        assert!(matches!(
+            tokens.verify_access_token(&parts.join(".")),
+            Err(ServiceError::Unauthorized(_))
+        ));
+
+        let other_key = AccessTokenService::new(&TokenConfig {
+            signing_secret: "another-signing-secret-of-32-bytes-or-more".to_owned(),
+            issuer: "https://api.example.com".to_owned(),
+            access_ttl_seconds: 900,
+            refresh_ttl_days: 30,
+        });
+        assert!(matches!(
+            other_key.verify_access_token(&token),
+            Err(ServiceError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_expired_token_is_rejected() {
+        let tokens = service(-1);
+        let token = tokens.issue_access_token(&user(), Uuid::new_v4()).unwrap();
+
+        assert!(matches!(
+            tokens.verify_access_token(&token),
+            Err(ServiceError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_token_is_stale_after_session_version_bump() {
+        let tokens = service(900);
+        let mut user = user();
+        let claims = tokens
+            .verify_access_token(&tokens.issue_access_token(&user, Uuid::new_v4()).unwrap())
+            .unwrap();
+
+        user.session_version += 1_i32;
+        assert!(!claims.is_current_for(&user));
+    }
+
+    #[test]
+    fn test_refresh_token_matches_only_its_hash() {
+        let refresh_token = generate_refresh_token().unwrap();
+        let stored_hash = hash_token(&refresh_token);
+
+        assert!(refresh_token.starts_with(REFRESH_TOKEN_PREFIX));
+        assert!(token_matches_hash(&refresh_token, &stored_hash));
+        assert!(!token_matches_hash(
+            &generate_refresh_token().unwrap(),
+            &stored_hash
+        ));
+    }
+}
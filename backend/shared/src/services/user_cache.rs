@@ -0,0 +1,105 @@
+use crate::errors::ServiceError;
+use crate::models::auth::User;
+use crate::services::db::DbService;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use uuid::Uuid;
+
+/// How long a user row stays cached in Redis
+const USER_CACHE_TTL_SECONDS: u64 = 60;
+
+/// Short-lived Redis cache of `users` rows for request authentication
+///
+/// Sessions only carry a user ID, so every authenticated request needs the
+/// current user row. Entries live for a minute; handlers that modify a user
+/// call [`UserCacheService::invalidate`] so changes are visible immediately.
+/// The password hash is never cached, so reading Redis is not enough to
+/// attack a password.
+#[derive(Clone)]
+pub struct UserCacheService {
+    redis: ConnectionManager,
+}
+
+impl std::fmt::Debug for UserCacheService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserCacheService").finish_non_exhaustive()
+    }
+}
+
+impl UserCacheService {
+    /// Create a new `UserCacheService` backed by the given Redis connection
+    #[must_use]
+    pub const fn new(redis: ConnectionManager) -> Self {
+        Self { redis }
+    }
+
+    fn cache_key(user_id: Uuid) -> String {
+        format!("user-cache:{user_id}")
+    }
+
+    /// Load a user by ID, serving from the cache when possible
+    ///
+    /// The user comes without its password hash, whether cached or not, so
+    /// handlers never depend on where it came from. A Redis failure falls back
+    /// to the database rather than failing the request.
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if the database query fails
+    pub async fn get_user(
+        &self,
+        db_service: &DbService,
+        user_id: Uuid,
+    ) -> Result<Option<User>, ServiceError> {
+        use crate::schema::users::dsl as users_dsl;
+
+        let mut con = self.redis.clone();
+        match con.get::<_, Option<String>>(Self::cache_key(user_id)).await {
+            Ok(Some(payload)) => {
+                if let Ok(user) = serde_json::from_str::<User>(&payload) {
+                    // Entries written before hashes were left out may still hold one
+                    return Ok(Some(User {
+                        password_hash: None,
+                        ..user
+                    }));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("User cache read failed: {}", e),
+        }
+
+        let pool = db_service.pool();
+        let mut conn = pool.get().await.map_err(ServiceError::from)?;
+        let user: Option<User> = users_dsl::users
+            .filter(users_dsl::id.eq(user_id))
+            .first(&mut conn)
+            .await
+            .optional()?
+            .map(|found| User {
+                password_hash: None,
+                ..found
+            });
+
+        if let Some(ref found) = user {
+            let payload = serde_json::to_string(found)?;
+            if let Err(e) = con
+                .set_ex::<_, _, ()>(Self::cache_key(user_id), payload, USER_CACHE_TTL_SECONDS)
+                .await
+            {
+                tracing::warn!("User cache write failed: {}", e);
+            }
+        }
+
+        Ok(user)
+    }
+
+    /// Drop a cached user so the next request reloads it from the database
+    ///
+    /// # Errors
+    /// Returns a `ServiceError` if Redis cannot be reached
+    pub async fn invalidate(&self, user_id: Uuid) -> Result<(), ServiceError> {
+        let mut con = self.redis.clone();
+        let _: usize = con.del(Self::cache_key(user_id)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use crate::errors::ServiceError;
+use crate::models::user_files::{FileStatus, UserFile};
+use crate::services::jobs::JobContext;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Store a file a job made, such as an export, as a file of the user
+///
+/// The file is kept under the user's files like an upload, so it shows up in
+/// their file list and can be downloaded or deleted from there. As with
+/// uploads, a file with the same contents is returned instead of a copy.
+///
+/// # Errors
+/// Returns an error if storing the object or the database row fails.
+pub async fn store_job_file(
+    ctx: &JobContext,
+    conn: &mut AsyncPgConnection,
+    user_id: Uuid,
+    filename: &str,
+    mime_type: &str,
+    contents: Vec<u8>,
+) -> Result<UserFile, ServiceError> {
+    use crate::schema::user_files::dsl as files_dsl;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&contents);
+    let file_hash = format!("{:x}", hasher.finalize());
+    let file_size = i64::try_from(contents.len()).unwrap_or(i64::MAX);
+
+    if let Some(existing) = files_dsl::user_files
+        .filter(files_dsl::user_id.eq(user_id))
+        .filter(files_dsl::file_hash.eq(&file_hash))
+        .filter(files_dsl::deleted_at.is_null())
+        .first::<UserFile>(conn)
+        .await
+        .optional()?
+    {
+        return Ok(existing);
+    }
+
+    let file_id = Uuid::new_v4();
+    let s3_key = Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map_or_else(
+            || format!("user_files/{user_id}/{file_id}"),
+            |extension| format!("user_files/{user_id}/{file_id}.{extension}"),
+        );
+    let s3_url = ctx.s3_service.put_object(&s3_key, contents).await?;
+
+    let now = Utc::now().naive_utc();
+    let new_file = UserFile {
+        id: file_id,
+        user_id,
+        filename: filename.to_owned(),
+        original_filename: filename.to_owned(),
+        file_path: s3_key.clone(),
+        file_size,
+        mime_type: mime_type.to_owned(),
+        file_hash,
+        status: FileStatus::Uploaded.into(),
+        metadata: Some(serde_json::json!({
+            "s3_url": s3_url,
+            "s3_key": s3_key,
+        })),
+        created_at: Some(now),
+        updated_at: Some(now),
+        deleted_at: None,
+    };
+
+    Ok(diesel::insert_into(files_dsl::user_files)
+        .values(&new_file)
+        .get_result(conn)
+        .await?)
+}